@@ -1,3 +1,60 @@
+use std::path::Path;
+use std::process::Command;
+
 fn main() {
+    // Short commit hash for get_version_info. Builds from a source archive or
+    // without git installed can pass it in; otherwise it is left empty.
+    println!("cargo:rerun-if-env-changed=DRIVEANALIZER_GIT_HASH");
+    let git_hash = std::env::var("DRIVEANALIZER_GIT_HASH")
+        .ok()
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_default();
+    println!("cargo:rustc-env=DRIVEANALIZER_GIT_HASH={}", git_hash);
+
+    // A missing path would rerun this script on every build
+    let head = Path::new("../.git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed={}", head.display());
+        // New commits move the branch ref, not HEAD itself
+        if let Some(branch) = std::fs::read_to_string(head)
+            .ok()
+            .and_then(|head| head.trim().strip_prefix("ref: ").map(str::to_string))
+        {
+            let branch = Path::new("../.git").join(branch);
+            if branch.exists() {
+                println!("cargo:rerun-if-changed={}", branch.display());
+            }
+        }
+    }
+
+    // Resolved sqlx version from the lock file
+    let sqlx_version = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| {
+            let mut lines = lock.lines();
+            while let Some(line) = lines.next() {
+                if line.trim() == "name = \"sqlx\"" {
+                    return lines
+                        .next()
+                        .and_then(|v| v.trim().strip_prefix("version = "))
+                        .map(|v| v.trim_matches('"').to_string());
+                }
+            }
+            None
+        })
+        .unwrap_or_default();
+    println!(
+        "cargo:rustc-env=DRIVEANALIZER_SQLX_VERSION={}",
+        sqlx_version
+    );
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
     tauri_build::build()
 }
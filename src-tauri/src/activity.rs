@@ -0,0 +1,143 @@
+use crate::db;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// app_settings key holding the maximum LED update rate
+pub const ACTIVITY_LED_HZ_KEY: &str = "activity_led_hz";
+
+pub const DEFAULT_LED_HZ: f64 = 4.0;
+pub const MIN_LED_HZ: f64 = 0.1;
+pub const MAX_LED_HZ: f64 = 20.0;
+
+/// Bytes a tick has to move before the LED lights up (filters background noise)
+const ACTIVITY_THRESHOLD_BYTES: u64 = 64 * 1024;
+
+/// State of the emulated drive LED
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activity {
+    Idle,
+    Read,
+    Write,
+}
+
+/// Tri-state from one tick's deltas; the larger direction wins, writes on a tie
+pub fn classify(read_bytes: u64, write_bytes: u64) -> Activity {
+    if read_bytes.max(write_bytes) < ACTIVITY_THRESHOLD_BYTES {
+        Activity::Idle
+    } else if read_bytes > write_bytes {
+        Activity::Read
+    } else {
+        Activity::Write
+    }
+}
+
+/// Minimum time between LED updates (ms), shared with the settings command
+pub type SharedLedRate = Arc<AtomicU64>;
+
+pub fn create_led_rate() -> SharedLedRate {
+    Arc::new(AtomicU64::new(hz_to_interval_ms(DEFAULT_LED_HZ)))
+}
+
+pub fn hz_to_interval_ms(hz: f64) -> u64 {
+    (1000.0 / hz.clamp(MIN_LED_HZ, MAX_LED_HZ)).round() as u64
+}
+
+pub fn interval_ms_to_hz(interval_ms: u64) -> f64 {
+    1000.0 / interval_ms.max(1) as f64
+}
+
+/// Emits only state changes, at most once per `min_interval`
+#[derive(Debug, Default)]
+pub struct ActivityLed {
+    last_state: Option<Activity>,
+    last_emit: Option<Instant>,
+}
+
+impl ActivityLed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the state to emit, if any; a change held back by the rate
+    /// limit is emitted by a later call
+    pub fn observe(
+        &mut self,
+        state: Activity,
+        now: Instant,
+        min_interval: Duration,
+    ) -> Option<Activity> {
+        if self.last_state == Some(state) {
+            return None;
+        }
+        if self
+            .last_emit
+            .is_some_and(|emitted| now.duration_since(emitted) < min_interval)
+        {
+            return None;
+        }
+        self.last_state = Some(state);
+        self.last_emit = Some(now);
+        Some(state)
+    }
+}
+
+pub async fn load_led_hz(pool: &Pool<Sqlite>) -> Result<f64, sqlx::Error> {
+    Ok(db::get_setting(pool, ACTIVITY_LED_HZ_KEY)
+        .await?
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|hz| hz.is_finite())
+        .map(|hz| hz.clamp(MIN_LED_HZ, MAX_LED_HZ))
+        .unwrap_or(DEFAULT_LED_HZ))
+}
+
+pub async fn save_led_hz(pool: &Pool<Sqlite>, hz: f64) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, ACTIVITY_LED_HZ_KEY, &hz.to_string()).await
+}
+
+pub fn store_led_hz(rate: &SharedLedRate, hz: f64) {
+    rate.store(hz_to_interval_ms(hz), Ordering::Relaxed);
+}
+
+pub fn min_interval(rate: &SharedLedRate) -> Duration {
+    Duration::from_millis(rate.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(0, 100), Activity::Idle);
+        assert_eq!(classify(1 << 20, 0), Activity::Read);
+        assert_eq!(classify(1 << 20, 1 << 20), Activity::Write);
+    }
+
+    #[test]
+    fn test_only_changes_are_emitted_within_rate() {
+        let mut led = ActivityLed::new();
+        let start = Instant::now();
+        let limit = Duration::from_millis(500);
+
+        assert_eq!(
+            led.observe(Activity::Write, start, limit),
+            Some(Activity::Write)
+        );
+        assert_eq!(led.observe(Activity::Write, start + limit, limit), None);
+        // Too soon after the last update: held back, then emitted later
+        let soon = start + limit + Duration::from_millis(100);
+        assert_eq!(
+            led.observe(Activity::Idle, start + Duration::from_millis(100), limit),
+            None
+        );
+        assert_eq!(
+            led.observe(Activity::Idle, soon, limit),
+            Some(Activity::Idle)
+        );
+        assert_eq!(hz_to_interval_ms(100.0), 50);
+    }
+}
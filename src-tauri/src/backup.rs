@@ -0,0 +1,362 @@
+use crate::data_dir;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Sub-directory of the data dir holding backups (moves with migrate_app_data)
+pub const BACKUPS_DIR: &str = "backups";
+
+const BACKUP_PREFIX: &str = "drive_analytics-backup-";
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// How many backups to keep (grandfather-father style).
+///
+/// The newest backup of each of the last `keep_daily` days and of each of the
+/// last `keep_weekly` weeks is kept; everything else is deleted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BackupRotation {
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+}
+
+impl Default for BackupRotation {
+    fn default() -> Self {
+        Self {
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+/// Manifest written next to every backup file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub file: String,
+    pub sha256: String,
+    pub size: u64,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// A backup on disk with its manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub manifest: BackupManifest,
+}
+
+/// Outcome of verify_backup
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupVerification {
+    pub path: String,
+    pub valid: bool,
+    pub expected_sha256: Option<String>,
+    pub actual_sha256: String,
+    /// Result of `PRAGMA integrity_check` ("ok" when healthy)
+    pub integrity: String,
+}
+
+pub fn backups_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(BACKUPS_DIR)
+}
+
+fn manifest_path(backup_path: &Path) -> PathBuf {
+    let mut name = backup_path.as_os_str().to_os_string();
+    name.push(MANIFEST_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Parses the creation time out of a backup file name
+fn backup_timestamp(file_name: &str) -> Option<u64> {
+    file_name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(".db")?
+        .parse()
+        .ok()
+}
+
+/// Creates a consistent snapshot with `VACUUM INTO`, writes its SHA-256
+/// manifest and applies the rotation policy.
+pub async fn create_backup(
+    pool: &Pool<Sqlite>,
+    data_dir: &Path,
+    rotation: BackupRotation,
+) -> Result<BackupInfo, BoxError> {
+    let dir = backups_dir(data_dir);
+    fs::create_dir_all(&dir)?;
+
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let file = format!("{}{}.db", BACKUP_PREFIX, created_at);
+    let path = dir.join(&file);
+    if path.exists() {
+        return Err("A backup was already created this second".into());
+    }
+
+    // VACUUM INTO writes a compacted, transactionally consistent copy while
+    // the monitor keeps writing to the live database.
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_str().ok_or("Backup path is not valid UTF-8")?)
+        .execute(pool)
+        .await?;
+
+    let manifest = BackupManifest {
+        file,
+        sha256: data_dir::sha256_file(&path)?,
+        size: fs::metadata(&path)?.len(),
+        created_at,
+    };
+    fs::write(
+        manifest_path(&path),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    let removed = rotate_backups(&dir, rotation)?;
+    println!(
+        "[Backup] Created {} ({} bytes), rotated out {} old backups",
+        manifest.file, manifest.size, removed
+    );
+
+    Ok(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+/// Lists backups newest first (files without a readable manifest are included
+/// with an empty checksum so they can still be verified or deleted)
+pub fn list_backups(data_dir: &Path) -> Result<Vec<BackupInfo>, BoxError> {
+    let dir = backups_dir(data_dir);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let Some(file) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let Some(created_at) = backup_timestamp(&file) else {
+            continue;
+        };
+
+        let manifest = fs::read_to_string(manifest_path(&path))
+            .ok()
+            .and_then(|content| serde_json::from_str::<BackupManifest>(&content).ok())
+            .unwrap_or_else(|| BackupManifest {
+                file: file.clone(),
+                sha256: String::new(),
+                size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                created_at,
+            });
+
+        backups.push(BackupInfo {
+            path: path.to_string_lossy().to_string(),
+            manifest,
+        });
+    }
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.manifest.created_at));
+    Ok(backups)
+}
+
+/// Returns the creation times to keep under the rotation policy
+pub fn select_backups_to_keep(created_at: &[u64], rotation: BackupRotation) -> HashSet<u64> {
+    let mut sorted: Vec<u64> = created_at.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut keep = HashSet::new();
+    if let Some(newest) = sorted.first() {
+        keep.insert(*newest);
+    }
+
+    let mut days_seen = HashSet::new();
+    let mut weeks_seen = HashSet::new();
+    for ts in sorted {
+        let day = ts / 86400;
+        // 1970-01-01 was a Thursday; shift so weeks start on Monday
+        let week = (day + 3) / 7;
+
+        if !days_seen.contains(&day) && days_seen.len() < rotation.keep_daily as usize {
+            days_seen.insert(day);
+            keep.insert(ts);
+        }
+        if !weeks_seen.contains(&week) && weeks_seen.len() < rotation.keep_weekly as usize {
+            weeks_seen.insert(week);
+            keep.insert(ts);
+        }
+    }
+
+    keep
+}
+
+/// Deletes backups (and manifests) not selected by the rotation policy
+fn rotate_backups(dir: &Path, rotation: BackupRotation) -> Result<usize, BoxError> {
+    let data_dir = dir.parent().ok_or("Backups dir has no parent")?;
+    let backups = list_backups(data_dir)?;
+    let created: Vec<u64> = backups.iter().map(|b| b.manifest.created_at).collect();
+    let keep = select_backups_to_keep(&created, rotation);
+
+    let mut removed = 0;
+    for backup in backups {
+        if keep.contains(&backup.manifest.created_at) {
+            continue;
+        }
+        let path = PathBuf::from(&backup.path);
+        fs::remove_file(&path)?;
+        let _ = fs::remove_file(manifest_path(&path));
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Verifies a backup against its manifest checksum and SQLite's integrity check
+pub async fn verify_backup(path: &Path) -> Result<BackupVerification, BoxError> {
+    if !path.is_file() {
+        return Err(format!("Backup not found: {}", path.display()).into());
+    }
+
+    let expected_sha256 = fs::read_to_string(manifest_path(path))
+        .ok()
+        .and_then(|content| serde_json::from_str::<BackupManifest>(&content).ok())
+        .map(|manifest| manifest.sha256);
+    let actual_sha256 = data_dir::sha256_file(path)?;
+
+    let integrity = match open_read_only(path).await {
+        Ok(pool) => {
+            let result: Result<(String,), sqlx::Error> = sqlx::query_as("PRAGMA integrity_check")
+                .fetch_one(&pool)
+                .await;
+            pool.close().await;
+            match result {
+                Ok((status,)) => status,
+                Err(e) => format!("integrity_check failed: {}", e),
+            }
+        }
+        Err(e) => format!("Cannot open backup: {}", e),
+    };
+
+    let checksum_ok = expected_sha256.as_deref() == Some(actual_sha256.as_str());
+    Ok(BackupVerification {
+        path: path.to_string_lossy().to_string(),
+        valid: checksum_ok && integrity == "ok",
+        expected_sha256,
+        actual_sha256,
+        integrity,
+    })
+}
+
+async fn open_read_only(path: &Path) -> Result<Pool<Sqlite>, BoxError> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    Ok(SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400;
+
+    #[test]
+    fn test_rotation_keeps_newest_per_day() {
+        // Two backups on each of 3 days
+        let base = 100 * DAY;
+        let created = [
+            base,
+            base + 10,
+            base + DAY,
+            base + DAY + 10,
+            base + 2 * DAY,
+            base + 2 * DAY + 10,
+        ];
+        let keep = select_backups_to_keep(
+            &created,
+            BackupRotation {
+                keep_daily: 2,
+                keep_weekly: 0,
+            },
+        );
+        assert_eq!(keep.len(), 2);
+        assert!(keep.contains(&(base + 2 * DAY + 10)));
+        assert!(keep.contains(&(base + DAY + 10)));
+    }
+
+    #[test]
+    fn test_rotation_weekly_reaches_further_back() {
+        let base = 700 * DAY;
+        let created: Vec<u64> = (0..30).map(|d| base + d * DAY).collect();
+        let keep = select_backups_to_keep(
+            &created,
+            BackupRotation {
+                keep_daily: 3,
+                keep_weekly: 4,
+            },
+        );
+        // 3 newest days + newest of up to 4 weeks (the newest week overlaps)
+        assert!(keep.len() >= 5 && keep.len() <= 7);
+        assert!(keep.iter().any(|ts| *ts < base + 20 * DAY));
+    }
+
+    #[test]
+    fn test_rotation_always_keeps_newest() {
+        let keep = select_backups_to_keep(
+            &[5, 10],
+            BackupRotation {
+                keep_daily: 0,
+                keep_weekly: 0,
+            },
+        );
+        assert_eq!(keep, HashSet::from([10]));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_verify_backup() {
+        let data_dir =
+            std::env::temp_dir().join(format!("driveanalizer-backup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(data_dir.join("live.db"))
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let backup = create_backup(&pool, &data_dir, BackupRotation::default())
+            .await
+            .unwrap();
+        assert_eq!(list_backups(&data_dir).unwrap().len(), 1);
+
+        let verification = verify_backup(Path::new(&backup.path)).await.unwrap();
+        assert!(verification.valid, "{:?}", verification);
+
+        // Corrupt the file: checksum no longer matches
+        fs::write(&backup.path, b"garbage").unwrap();
+        let verification = verify_backup(Path::new(&backup.path)).await.unwrap();
+        assert!(!verification.valid);
+
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+}
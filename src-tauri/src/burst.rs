@@ -0,0 +1,205 @@
+use crate::models::{BurstProcess, IoBurst};
+use crate::process_monitor::TickDeltas;
+use crate::sampling;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Timeline event kind used for sustained I/O bursts (details hold the IoBurst JSON)
+pub const BURST_EVENT_KIND: &str = "io_burst";
+
+/// How many contributing processes are kept per burst
+const TOP_PROCESS_COUNT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BurstDirection {
+    Read,
+    Write,
+}
+
+impl BurstDirection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BurstDirection::Read => "read",
+            BurstDirection::Write => "write",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BurstConfig {
+    /// Speed (bytes/sec) a tick must reach to count towards a burst
+    pub threshold_bytes_per_sec: u64,
+    /// Consecutive ticks above the threshold before it counts as a burst
+    pub min_ticks: u32,
+}
+
+/// How long the speed must stay above the threshold
+const MIN_BURST_SECS: u64 = 5;
+
+impl BurstConfig {
+    /// Default threshold with `min_ticks` covering the same duration at this interval
+    pub fn for_interval(interval_ms: u64) -> Self {
+        Self {
+            min_ticks: sampling::ticks_per(
+                std::time::Duration::from_secs(MIN_BURST_SECS),
+                interval_ms,
+            ) as u32,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for BurstConfig {
+    fn default() -> Self {
+        Self {
+            threshold_bytes_per_sec: 50 * 1024 * 1024,
+            min_ticks: 5,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Candidate {
+    start_time: f64,
+    last_time: f64,
+    ticks: u32,
+    peak_speed: u64,
+    total_bytes: u64,
+    /// Bytes per process name in the burst direction
+    processes: HashMap<Arc<str>, u64>,
+}
+
+/// Detects sustained bursts in one direction
+///
+/// Ticks above the threshold accumulate into a candidate; when the speed
+/// drops again the candidate is reported if it lasted `min_ticks` or more.
+#[derive(Debug)]
+pub struct BurstDetector {
+    direction: BurstDirection,
+    config: BurstConfig,
+    candidate: Option<Candidate>,
+}
+
+impl BurstDetector {
+    pub fn new(direction: BurstDirection, config: BurstConfig) -> Self {
+        Self {
+            direction,
+            config,
+            candidate: None,
+        }
+    }
+
+    /// Feeds one tick (speed, bytes moved during the tick, per-process
+    /// (read, write) deltas) and returns a finished burst when one just ended
+    pub fn observe(
+        &mut self,
+        speed: u64,
+        bytes: u64,
+        now: f64,
+        process_deltas: &TickDeltas,
+    ) -> Option<IoBurst> {
+        if speed < self.config.threshold_bytes_per_sec {
+            return self.finish(now);
+        }
+
+        let candidate = self.candidate.get_or_insert_with(|| Candidate {
+            start_time: now,
+            ..Default::default()
+        });
+        candidate.last_time = now;
+        candidate.ticks += 1;
+        candidate.peak_speed = candidate.peak_speed.max(speed);
+        candidate.total_bytes = candidate.total_bytes.saturating_add(bytes);
+        for (name, (read, write)) in process_deltas {
+            let bytes = match self.direction {
+                BurstDirection::Read => *read,
+                BurstDirection::Write => *write,
+            };
+            if bytes > 0 {
+                let entry = candidate.processes.entry(name.clone()).or_insert(0);
+                *entry = entry.saturating_add(bytes);
+            }
+        }
+        None
+    }
+
+    /// Ends the current candidate (e.g. at shutdown); returns it if it qualifies
+    pub fn finish(&mut self, now: f64) -> Option<IoBurst> {
+        let candidate = self.candidate.take()?;
+        if candidate.ticks < self.config.min_ticks {
+            return None;
+        }
+
+        let mut top_processes: Vec<BurstProcess> = candidate
+            .processes
+            .into_iter()
+            .map(|(name, bytes)| BurstProcess {
+                name: name.to_string(),
+                bytes,
+            })
+            .collect();
+        top_processes.sort_by_key(|p| std::cmp::Reverse(p.bytes));
+        top_processes.truncate(TOP_PROCESS_COUNT);
+
+        Some(IoBurst {
+            direction: self.direction.as_str().to_string(),
+            start_time: candidate.start_time,
+            end_time: now.max(candidate.last_time),
+            peak_speed: candidate.peak_speed,
+            total_bytes: candidate.total_bytes,
+            top_processes,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.candidate = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BurstConfig {
+        BurstConfig {
+            threshold_bytes_per_sec: 100,
+            min_ticks: 3,
+        }
+    }
+
+    #[test]
+    fn test_short_spike_is_ignored() {
+        let mut detector = BurstDetector::new(BurstDirection::Write, config());
+        let none = TickDeltas::new();
+        assert!(detector.observe(500, 500, 1.0, &none).is_none());
+        assert!(detector.observe(500, 500, 2.0, &none).is_none());
+        assert!(detector.observe(10, 10, 3.0, &none).is_none());
+    }
+
+    #[test]
+    fn test_sustained_burst_reports_peak_and_processes() {
+        let mut detector = BurstDetector::new(BurstDirection::Write, config());
+        let deltas = TickDeltas::from([
+            ("copy.exe".into(), (0, 150)),
+            ("indexer.exe".into(), (900, 50)),
+        ]);
+        for (i, speed) in [200, 400, 300].into_iter().enumerate() {
+            assert!(detector.observe(speed, speed, i as f64, &deltas).is_none());
+        }
+
+        let burst = detector.observe(0, 0, 3.0, &TickDeltas::new()).unwrap();
+        assert_eq!(burst.direction, "write");
+        assert_eq!(burst.start_time, 0.0);
+        assert_eq!(burst.end_time, 3.0);
+        assert_eq!(burst.peak_speed, 400);
+        assert_eq!(burst.total_bytes, 900);
+        assert_eq!(burst.top_processes[0].name, "copy.exe");
+        assert_eq!(burst.top_processes[0].bytes, 450);
+    }
+
+    #[test]
+    fn test_min_ticks_follow_interval() {
+        assert_eq!(BurstConfig::for_interval(1000).min_ticks, 5);
+        assert_eq!(BurstConfig::for_interval(250).min_ticks, 20);
+    }
+}
@@ -0,0 +1,185 @@
+// Başlangıçta neyin mümkün olduğunu yoklar (SMART, ETW, yetki, PDH) ki arayüz
+// kullanılamayan özellikleri çağrı anında hata almak yerine açıklamasıyla gri gösterebilsin
+
+use crate::elevation;
+use crate::etw;
+use crate::perf_counters::PerfCounterSession;
+use crate::smart;
+use crate::volume_monitor;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Capability {
+    pub available: bool,
+    /// Why it is unavailable (or only partly available)
+    pub reason: Option<String>,
+}
+
+impl Capability {
+    fn from_probe(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self {
+                available: true,
+                reason: None,
+            },
+            Err(reason) => Self {
+                available: false,
+                reason: Some(reason),
+            },
+        }
+    }
+}
+
+/// Whether one UI feature can be used, with the explanation to show if not
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureAvailability {
+    pub feature: String,
+    pub available: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub elevated: bool,
+    pub pdh: Capability,
+    pub etw: Capability,
+    pub smart: Capability,
+    pub features: Vec<FeatureAvailability>,
+}
+
+/// Probed once at startup; None until the probe finished
+pub type SharedCapabilities = Arc<Mutex<Option<Capabilities>>>;
+
+pub fn create_capabilities() -> SharedCapabilities {
+    Arc::new(Mutex::new(None))
+}
+
+/// PDH: bir query açılabiliyor mu
+fn probe_pdh() -> Result<(), String> {
+    PerfCounterSession::open().map(drop)
+}
+
+/// ETW: kısa ömürlü bir oturum başlatılıp durdurulabiliyor mu (yönetici ya da
+/// Performance Log Users üyeliği gerekir)
+fn probe_etw() -> Result<(), String> {
+    let name = etw::session_name("Probe");
+    let limits = etw::SessionLimits {
+        min_buffers: 2,
+        max_buffers: 2,
+        ..etw::SessionLimits::default()
+    };
+    etw::start_session(&name, &limits)?;
+    etw::stop_session(&name)
+}
+
+/// SMART: bağlı birimlerden birinin NVMe log sayfası ya da SMART verisi okunabiliyor mu
+fn probe_smart() -> Result<(), String> {
+    let mut last_error = "No mounted volumes to query".to_string();
+    for mounted in volume_monitor::list_mounted_volumes() {
+        match smart::read_nvme_health_log(&mounted.volume)
+            .or_else(|_| smart::read_attributes(&mounted.volume))
+        {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+pub fn probe() -> Capabilities {
+    let elevated = elevation::is_elevated_safe();
+    let pdh = Capability::from_probe(probe_pdh());
+    let etw = Capability::from_probe(probe_etw());
+    let smart = Capability::from_probe(probe_smart());
+    let features = feature_matrix(elevated, &pdh, &etw, &smart);
+    Capabilities {
+        elevated,
+        pdh,
+        etw,
+        smart,
+        features,
+    }
+}
+
+/// Maps the probed capabilities to the features the UI offers
+pub fn feature_matrix(
+    elevated: bool,
+    pdh: &Capability,
+    etw: &Capability,
+    smart: &Capability,
+) -> Vec<FeatureAvailability> {
+    let needs = |feature: &str, capability: &Capability, explanation: &str| FeatureAvailability {
+        feature: feature.to_string(),
+        available: capability.available,
+        reason: capability
+            .reason
+            .as_ref()
+            .map(|reason| format!("{} ({})", explanation, reason)),
+    };
+
+    vec![
+        needs(
+            "disk_latency",
+            pdh,
+            "Idle time, queue depth, IOPS and latency need Windows performance counters",
+        ),
+        needs(
+            "disk_breakdown",
+            pdh,
+            "The per-disk breakdown needs Windows performance counters",
+        ),
+        needs(
+            "volume_speeds",
+            pdh,
+            "Per-drive speeds need Windows performance counters",
+        ),
+        needs(
+            "temperatures",
+            smart,
+            "Drive temperatures need SMART access to the disks",
+        ),
+        needs("ssd_wear", smart, "SSD wear needs SMART access to the disks"),
+        needs(
+            "etw_tracing",
+            etw,
+            "Event tracing needs administrator rights or Performance Log Users membership",
+        ),
+        FeatureAvailability {
+            feature: "process_io".to_string(),
+            available: true,
+            reason: (!elevated).then(|| {
+                "Without administrator rights, I/O of other users' and system processes may be missing"
+                    .to_string()
+            }),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_matrix_explains_missing_capabilities() {
+        let ok = Capability::from_probe(Ok(()));
+        let denied = Capability::from_probe(Err("Access is denied".to_string()));
+        let features = feature_matrix(false, &ok, &denied, &ok);
+
+        let etw = features
+            .iter()
+            .find(|f| f.feature == "etw_tracing")
+            .unwrap();
+        assert!(!etw.available);
+        assert!(etw.reason.as_deref().unwrap().contains("Access is denied"));
+
+        let temperatures = features
+            .iter()
+            .find(|f| f.feature == "temperatures")
+            .unwrap();
+        assert!(temperatures.available && temperatures.reason.is_none());
+
+        let process_io = features.iter().find(|f| f.feature == "process_io").unwrap();
+        assert!(process_io.available && process_io.reason.is_some());
+    }
+}
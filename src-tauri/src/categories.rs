@@ -0,0 +1,179 @@
+//! User-defined process categories (Browsers, Games, ...) and read/write
+//! totals per category and day, from the per-process time series.
+
+use crate::models::ProcessSample;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Categories offered before the user creates their own
+pub const DEFAULT_CATEGORIES: &[&str] = &["Browsers", "Games", "Dev tools", "System"];
+
+/// Where processes without a category are counted
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Longest category name accepted
+pub const MAX_CATEGORY_LEN: usize = 64;
+
+const SECS_PER_DAY: f64 = 86400.0;
+
+/// Bytes of one category on one (local) day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryDay {
+    /// Unix time of the day's local midnight
+    pub day_start: f64,
+    pub category: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Category assignments and every category name in use or offered
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessCategories {
+    /// The defaults plus any user-created ones, sorted
+    pub categories: Vec<String>,
+    /// Process name -> category
+    pub assignments: HashMap<String, String>,
+}
+
+impl ProcessCategories {
+    pub fn new(assignments: HashMap<String, String>) -> Self {
+        let mut categories: Vec<String> = DEFAULT_CATEGORIES
+            .iter()
+            .map(|c| c.to_string())
+            .chain(assignments.values().cloned())
+            .collect();
+        categories.sort();
+        categories.dedup();
+        Self {
+            categories,
+            assignments,
+        }
+    }
+}
+
+/// Trimmed category name, or an error for empty or overlong names
+pub fn validate(category: &str) -> Result<&str, String> {
+    let category = category.trim();
+    if category.is_empty() {
+        return Err("Category name is empty".to_string());
+    }
+    if category.chars().count() > MAX_CATEGORY_LEN {
+        return Err(format!(
+            "Category name is longer than {} characters",
+            MAX_CATEGORY_LEN
+        ));
+    }
+    Ok(category)
+}
+
+/// Per-day, per-category totals of `samples`, oldest day first and the
+/// busiest category first within a day. `categories` maps process name ->
+/// category; names are matched case-insensitively.
+pub fn daily_totals(
+    samples: &[ProcessSample],
+    categories: &HashMap<String, String>,
+    utc_offset_secs: i64,
+) -> Vec<CategoryDay> {
+    let by_name: HashMap<String, &str> = categories
+        .iter()
+        .map(|(name, category)| (name.to_lowercase(), category.as_str()))
+        .collect();
+
+    let mut days: BTreeMap<i64, HashMap<&str, (u64, u64)>> = BTreeMap::new();
+    for sample in samples {
+        let category = by_name
+            .get(&sample.name.to_lowercase())
+            .copied()
+            .unwrap_or(UNCATEGORIZED);
+        let day = ((sample.timestamp + utc_offset_secs as f64) / SECS_PER_DAY).floor() as i64;
+        let entry = days
+            .entry(day)
+            .or_default()
+            .entry(category)
+            .or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(sample.read_bytes);
+        entry.1 = entry.1.saturating_add(sample.write_bytes);
+    }
+
+    days.into_iter()
+        .flat_map(|(day, totals)| {
+            let mut totals: Vec<CategoryDay> = totals
+                .into_iter()
+                .map(|(category, (read_bytes, write_bytes))| CategoryDay {
+                    day_start: day as f64 * SECS_PER_DAY - utc_offset_secs as f64,
+                    category: category.to_string(),
+                    read_bytes,
+                    write_bytes,
+                })
+                .collect();
+            totals.sort_by(|a, b| {
+                (b.read_bytes + b.write_bytes)
+                    .cmp(&(a.read_bytes + a.write_bytes))
+                    .then_with(|| a.category.cmp(&b.category))
+            });
+            totals
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: f64, name: &str, write_bytes: u64) -> ProcessSample {
+        ProcessSample {
+            timestamp,
+            name: name.to_string(),
+            read_bytes: 0,
+            write_bytes,
+        }
+    }
+
+    #[test]
+    fn test_totals_per_category_and_day() {
+        let midnight = 20_000.0 * SECS_PER_DAY;
+        let categories = HashMap::from([
+            ("chrome.exe".to_string(), "Browsers".to_string()),
+            ("firefox.exe".to_string(), "Browsers".to_string()),
+        ]);
+        let samples = [
+            sample(midnight - 10.0, "chrome.exe", 5),
+            sample(midnight + 10.0, "Chrome.exe", 100),
+            sample(midnight + 20.0, "firefox.exe", 50),
+            sample(midnight + 30.0, "game.exe", 20),
+        ];
+
+        let totals: Vec<(f64, String, u64)> = daily_totals(&samples, &categories, 0)
+            .into_iter()
+            .map(|d| (d.day_start, d.category, d.write_bytes))
+            .collect();
+        assert_eq!(
+            totals,
+            [
+                (midnight - SECS_PER_DAY, "Browsers".to_string(), 5),
+                (midnight, "Browsers".to_string(), 150),
+                (midnight, UNCATEGORIZED.to_string(), 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_user_categories_join_the_defaults() {
+        let assignments = HashMap::from([
+            ("a.exe".to_string(), "Backup".to_string()),
+            ("b.exe".to_string(), "Games".to_string()),
+        ]);
+        let categories = ProcessCategories::new(assignments).categories;
+        assert_eq!(
+            categories,
+            ["Backup", "Browsers", "Dev tools", "Games", "System"]
+        );
+    }
+
+    #[test]
+    fn test_category_names_are_validated() {
+        assert_eq!(validate("  Games "), Ok("Games"));
+        assert!(validate("   ").is_err());
+        assert!(validate(&"x".repeat(MAX_CATEGORY_LEN + 1)).is_err());
+    }
+}
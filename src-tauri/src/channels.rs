@@ -0,0 +1,208 @@
+//! Per-subscriber binary streams over Tauri channels
+//!
+//! Each frontend view opens its own channel. Frames are MessagePack
+//! (decoded by `src/utils/msgpack.ts`). A channel whose send fails (its
+//! window was closed) is dropped, so nothing keeps serializing for a view
+//! that no longer exists.
+
+use crate::db;
+use crate::payload::PayloadEncoder;
+use crate::subscriptions::MetricStream;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::ipc::{Channel, InvokeResponseBody};
+
+/// app_settings key: keep emitting the `disk-metrics` and `top-processes`
+/// window events for older frontends (the key predates top-processes channels)
+pub const LEGACY_DISK_METRICS_EVENT_KEY: &str = "legacy_disk_metrics_event";
+
+#[derive(Default)]
+pub struct LiveChannels {
+    subscribers: HashMap<MetricStream, HashMap<u32, Channel>>,
+    /// Also emit channel streams as window events (compatibility option)
+    pub legacy_disk_metrics_event: bool,
+    /// Bumped on every subscribe, so diff-based streams know to send a full frame
+    generation: u64,
+}
+
+pub type SharedChannels = Arc<Mutex<LiveChannels>>;
+
+pub fn create_channels() -> SharedChannels {
+    Arc::new(Mutex::new(LiveChannels::default()))
+}
+
+/// Registers a channel for `stream`; returns its id for `unsubscribe`
+pub fn subscribe(channels: &SharedChannels, stream: MetricStream, channel: Channel) -> u32 {
+    let id = channel.id();
+    if let Ok(mut channels) = channels.lock() {
+        channels.generation += 1;
+        channels
+            .subscribers
+            .entry(stream)
+            .or_default()
+            .insert(id, channel);
+    }
+    id
+}
+
+/// Removes a channel (each channel belongs to one stream); returns true if it was registered
+pub fn unsubscribe(channels: &SharedChannels, id: u32) -> bool {
+    channels
+        .lock()
+        .map(|mut channels| {
+            channels
+                .subscribers
+                .values_mut()
+                .any(|subscribers| subscribers.remove(&id).is_some())
+        })
+        .unwrap_or(false)
+}
+
+pub fn has_subscribers(channels: &SharedChannels, stream: MetricStream) -> bool {
+    channels
+        .lock()
+        .map(|channels| {
+            channels
+                .subscribers
+                .get(&stream)
+                .is_some_and(|s| !s.is_empty())
+        })
+        .unwrap_or(false)
+}
+
+/// Changes whenever a channel subscribes
+pub fn generation(channels: &SharedChannels) -> u64 {
+    channels
+        .lock()
+        .map(|channels| channels.generation)
+        .unwrap_or(0)
+}
+
+pub fn legacy_disk_metrics_event(channels: &SharedChannels) -> bool {
+    channels
+        .lock()
+        .map(|channels| channels.legacy_disk_metrics_event)
+        .unwrap_or(true)
+}
+
+/// Streams that are delivered over channels; their window events are legacy
+pub fn is_channel_stream(stream: MetricStream) -> bool {
+    matches!(
+        stream,
+        MetricStream::DiskMetrics | MetricStream::TopProcesses | MetricStream::TopProcessesDiff
+    )
+}
+
+pub fn set_legacy_disk_metrics_event(channels: &SharedChannels, enabled: bool) {
+    if let Ok(mut channels) = channels.lock() {
+        channels.legacy_disk_metrics_event = enabled;
+    }
+}
+
+/// Encodes `payload` once and sends it to every subscriber of `stream`
+///
+/// # Returns
+/// Number of channels the frame was delivered to
+pub fn send<T: Serialize>(
+    channels: &SharedChannels,
+    encoder: &mut PayloadEncoder,
+    stream: MetricStream,
+    payload: &T,
+) -> Result<usize, String> {
+    let mut channels = channels.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let Some(subscribers) = channels.subscribers.get_mut(&stream) else {
+        return Ok(0);
+    };
+    if subscribers.is_empty() {
+        return Ok(0);
+    }
+
+    let frame = encoder.msgpack(payload)?;
+    subscribers.retain(
+        |id, channel| match channel.send(InvokeResponseBody::Raw(frame.to_vec())) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("[Channels] Dropping channel {}: {}", id, e);
+                false
+            }
+        },
+    );
+    Ok(subscribers.len())
+}
+
+pub async fn load_legacy_disk_metrics_event(pool: &Pool<Sqlite>) -> Result<bool, sqlx::Error> {
+    Ok(db::get_setting(pool, LEGACY_DISK_METRICS_EVENT_KEY)
+        .await?
+        .is_some_and(|value| value == "true"))
+}
+
+pub async fn save_legacy_disk_metrics_event(
+    pool: &Pool<Sqlite>,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    db::set_setting(
+        pool,
+        LEGACY_DISK_METRICS_EVENT_KEY,
+        if enabled { "true" } else { "false" },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording_channel(frames: Arc<Mutex<Vec<Vec<u8>>>>, fail: bool) -> Channel {
+        Channel::new(move |body| {
+            if fail {
+                return Err(tauri::Error::WebviewNotFound);
+            }
+            if let InvokeResponseBody::Raw(bytes) = body {
+                frames.lock().unwrap().push(bytes);
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_frames_reach_subscribers_and_dead_channels_are_dropped() {
+        let channels = create_channels();
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let before = generation(&channels);
+        let alive = subscribe(
+            &channels,
+            MetricStream::DiskMetrics,
+            recording_channel(Arc::clone(&frames), false),
+        );
+        subscribe(
+            &channels,
+            MetricStream::DiskMetrics,
+            recording_channel(Arc::clone(&frames), true),
+        );
+
+        let mut encoder = PayloadEncoder::new();
+        let sent = send(
+            &channels,
+            &mut encoder,
+            MetricStream::DiskMetrics,
+            &[1u8, 2],
+        )
+        .unwrap();
+        assert_eq!(sent, 1);
+        assert_eq!(frames.lock().unwrap().as_slice(), &[vec![0x92, 0x01, 0x02]]);
+
+        assert_eq!(generation(&channels), before + 2);
+        assert!(is_channel_stream(MetricStream::TopProcesses));
+        assert!(is_channel_stream(MetricStream::TopProcessesDiff));
+        assert!(!is_channel_stream(MetricStream::DiskBreakdown));
+
+        assert!(unsubscribe(&channels, alive));
+        assert!(!has_subscribers(&channels, MetricStream::DiskMetrics));
+        assert_eq!(
+            send(&channels, &mut encoder, MetricStream::TopProcesses, &1u8).unwrap(),
+            0
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Difference between wall-clock and monotonic progress treated as a clock
+/// change (NTP step, manual change) rather than scheduling jitter
+const CLOCK_STEP_TOLERANCE_SECS: f64 = 2.0;
+
+/// One reading of the session clock
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockReading {
+    /// Wall-clock Unix time (what samples are stored and queried by)
+    pub timestamp: f64,
+    /// Monotonic seconds since the monitoring session started
+    pub offset: f64,
+    /// Monotonic time since the previous reading (None for the first one)
+    pub elapsed: Option<Duration>,
+    /// Seconds the wall clock moved beyond the monotonic clock since the
+    /// previous reading, if it did by more than the tolerance
+    pub clock_step: Option<f64>,
+}
+
+/// Pairs wall-clock timestamps with monotonic time so speeds and ordering
+/// don't depend on the system clock being stable
+#[derive(Debug)]
+pub struct SessionClock {
+    started: Instant,
+    previous: Option<(Instant, f64)>,
+}
+
+impl Default for SessionClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionClock {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            previous: None,
+        }
+    }
+
+    pub fn read(&mut self) -> ClockReading {
+        self.read_at(Instant::now(), unix_now())
+    }
+
+    fn read_at(&mut self, instant: Instant, wall: f64) -> ClockReading {
+        let (elapsed, clock_step) = match self.previous {
+            Some((previous_instant, previous_wall)) => {
+                let elapsed = instant.saturating_duration_since(previous_instant);
+                let step = (wall - previous_wall) - elapsed.as_secs_f64();
+                (
+                    Some(elapsed),
+                    (step.abs() > CLOCK_STEP_TOLERANCE_SECS).then_some(step),
+                )
+            }
+            None => (None, None),
+        };
+        self.previous = Some((instant, wall));
+
+        ClockReading {
+            timestamp: wall,
+            offset: instant
+                .saturating_duration_since(self.started)
+                .as_secs_f64(),
+            elapsed,
+            clock_step,
+        }
+    }
+
+    /// Starts the next interval now (after counters were re-baselined, the
+    /// next deltas only cover the time from here)
+    pub fn restart_interval(&mut self) {
+        self.previous = Some((Instant::now(), unix_now()));
+    }
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_and_elapsed_are_monotonic() {
+        let mut clock = SessionClock::new();
+        let start = clock.started;
+
+        let first = clock.read_at(start + Duration::from_secs(1), 1000.0);
+        assert_eq!(first.elapsed, None);
+        assert_eq!(first.offset, 1.0);
+
+        // The wall clock was set back an hour: elapsed and offset are unaffected
+        let second = clock.read_at(start + Duration::from_secs(2), 1001.0 - 3600.0);
+        assert_eq!(second.elapsed, Some(Duration::from_secs(1)));
+        assert_eq!(second.offset, 2.0);
+        assert_eq!(second.clock_step, Some(-3600.0));
+
+        let third = clock.read_at(start + Duration::from_secs(3), 1002.5 - 3600.0);
+        assert_eq!(third.clock_step, None);
+    }
+}
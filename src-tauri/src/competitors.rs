@@ -0,0 +1,289 @@
+// Çakışabilecek diğer izleme araçlarının tespiti (ETW oturumları, sayaçlar, ikinci instance)
+
+use crate::models::CompetingMonitor;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lock file in the default app data dir holding the PID of the running instance
+pub const LOCK_FILE_NAME: &str = "driveanalizer.lock";
+
+/// Known tools: (lowercase process name, display name, guidance)
+const KNOWN_TOOLS: &[(&str, &str, &str)] = &[
+    (
+        "hwinfo64.exe",
+        "HWiNFO",
+        "HWiNFO polls SMART and disk counters; close it or disable its drive sensors if temperatures or counters look wrong.",
+    ),
+    (
+        "hwinfo32.exe",
+        "HWiNFO",
+        "HWiNFO polls SMART and disk counters; close it or disable its drive sensors if temperatures or counters look wrong.",
+    ),
+    (
+        "resmon.exe",
+        "Resource Monitor",
+        "Resource Monitor runs its own kernel trace session; close it while comparing numbers.",
+    ),
+    (
+        "perfmon.exe",
+        "Performance Monitor",
+        "Performance Monitor may hold trace sessions or reset counters; close it while comparing numbers.",
+    ),
+    (
+        "procmon.exe",
+        "Process Monitor",
+        "Process Monitor uses the kernel logger; close it so trace sessions can start.",
+    ),
+    (
+        "procmon64.exe",
+        "Process Monitor",
+        "Process Monitor uses the kernel logger; close it so trace sessions can start.",
+    ),
+    (
+        "systeminformer.exe",
+        "System Informer",
+        "System Informer uses the kernel logger for disk I/O; close it so trace sessions can start.",
+    ),
+    (
+        "processhacker.exe",
+        "Process Hacker",
+        "Process Hacker uses the kernel logger for disk I/O; close it so trace sessions can start.",
+    ),
+    (
+        "diskinfo64.exe",
+        "CrystalDiskInfo",
+        "CrystalDiskInfo queries SMART on the same drives; temperature reads may fail while it refreshes.",
+    ),
+];
+
+/// Trace sessions that conflict with ours: (session name, guidance)
+const KNOWN_SESSIONS: &[(&str, &str)] = &[
+    (
+        "NT Kernel Logger",
+        "The kernel logger can only run once; a tool such as Resource Monitor, Process Monitor or xperf is holding it.",
+    ),
+    (
+        "PROCMON TRACE",
+        "Process Monitor's trace session is active; close Process Monitor.",
+    ),
+];
+
+const INSTANCE_GUIDANCE: &str =
+    "Another DriveAnalizer instance is running; close it so sessions and the database aren't shared.";
+
+/// Matches running processes against the known tools and other instances of this app
+pub fn match_processes<'a>(
+    processes: impl IntoIterator<Item = (u32, &'a str)>,
+    own_pid: u32,
+    own_name: &str,
+) -> Vec<CompetingMonitor> {
+    let own_name = own_name.to_lowercase();
+    let mut found: Vec<CompetingMonitor> = processes
+        .into_iter()
+        .filter(|(pid, _)| *pid != own_pid)
+        .filter_map(|(pid, name)| {
+            let lower = name.to_lowercase();
+            if lower == own_name {
+                return Some(competing(
+                    "instance",
+                    "DriveAnalizer",
+                    Some(pid),
+                    INSTANCE_GUIDANCE,
+                ));
+            }
+            KNOWN_TOOLS
+                .iter()
+                .find(|(process, _, _)| *process == lower)
+                .map(|(_, display, guidance)| competing("process", display, Some(pid), guidance))
+        })
+        .collect();
+    found.sort_by(|a, b| a.name.cmp(&b.name).then(a.pid.cmp(&b.pid)));
+    found
+}
+
+/// Matches active trace session names against the ones known to conflict
+pub fn match_sessions(sessions: &[String]) -> Vec<CompetingMonitor> {
+    sessions
+        .iter()
+        .filter_map(|session| {
+            KNOWN_SESSIONS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(session))
+                .map(|(name, guidance)| competing("trace_session", name, None, guidance))
+        })
+        .collect()
+}
+
+/// Entry for another instance known from the lock file
+pub fn other_instance(pid: u32) -> CompetingMonitor {
+    competing("instance", "DriveAnalizer", Some(pid), INSTANCE_GUIDANCE)
+}
+
+fn competing(kind: &str, name: &str, pid: Option<u32>, guidance: &str) -> CompetingMonitor {
+    CompetingMonitor {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        pid,
+        guidance: guidance.to_string(),
+    }
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Diagnostics::Etw::{QueryAllTracesW, EVENT_TRACE_PROPERTIES};
+
+    /// Sorgulanacak en fazla oturum sayısı (Windows sınırı 64)
+    const MAX_SESSIONS: usize = 64;
+    /// Oturum ve log dosyası adları için ayrılan WCHAR sayısı
+    const NAME_CHARS: usize = 1024;
+
+    /// Aktif ETW oturumlarının adlarını listele
+    pub fn list_trace_sessions() -> Result<Vec<String>, String> {
+        let header = std::mem::size_of::<EVENT_TRACE_PROPERTIES>();
+        let size = header + 2 * NAME_CHARS * std::mem::size_of::<u16>();
+        // u64 hizalaması için
+        let mut buffers: Vec<Vec<u64>> = (0..MAX_SESSIONS)
+            .map(|_| vec![0u64; size.div_ceil(8)])
+            .collect();
+        let mut pointers: Vec<*mut EVENT_TRACE_PROPERTIES> = buffers
+            .iter_mut()
+            .map(|buffer| {
+                let props = buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+                unsafe {
+                    (*props).Wnode.BufferSize = size as u32;
+                    (*props).LoggerNameOffset = header as u32;
+                    (*props).LogFileNameOffset = (header + NAME_CHARS * 2) as u32;
+                }
+                props
+            })
+            .collect();
+
+        let mut count: u32 = 0;
+        let status = unsafe { QueryAllTracesW(&mut pointers, &mut count) };
+        if status != ERROR_SUCCESS {
+            return Err(format!("QueryAllTracesW failed: {}", status.0));
+        }
+
+        let names = pointers
+            .iter()
+            .take(count as usize)
+            .map(|props| unsafe {
+                let offset = (**props).LoggerNameOffset as usize;
+                let start = (*props as *const u8).add(offset) as *const u16;
+                let chars = std::slice::from_raw_parts(start, NAME_CHARS);
+                let len = chars.iter().position(|c| *c == 0).unwrap_or(NAME_CHARS);
+                String::from_utf16_lossy(&chars[..len])
+            })
+            .collect();
+        Ok(names)
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::list_trace_sessions;
+
+/// Windows dışı platformlar için fallback
+#[cfg(not(windows))]
+pub fn list_trace_sessions() -> Result<Vec<String>, String> {
+    Ok(Vec::new())
+}
+
+/// Güvenli wrapper - hata durumunda boş liste
+pub fn list_trace_sessions_safe() -> Vec<String> {
+    list_trace_sessions().unwrap_or_else(|e| {
+        eprintln!("[Competitors] {}", e);
+        Vec::new()
+    })
+}
+
+/// Single-instance marker: the first instance writes its PID, later ones
+/// report the PID they found
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    owned: bool,
+    /// PID recorded by another instance that was still running at startup
+    pub other_pid: Option<u32>,
+}
+
+impl InstanceLock {
+    /// Takes the lock unless `is_running` says the recorded PID is still alive
+    pub fn acquire(dir: &Path, is_running: impl Fn(u32) -> bool) -> Self {
+        let path = dir.join(LOCK_FILE_NAME);
+        let own_pid = std::process::id();
+        let other_pid = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| content.trim().parse::<u32>().ok())
+            .filter(|pid| *pid != own_pid && is_running(*pid));
+
+        let owned = other_pid.is_none()
+            && fs::create_dir_all(dir)
+                .and_then(|_| fs::write(&path, own_pid.to_string()))
+                .map_err(|e| eprintln!("[Competitors] Failed to write lock file: {}", e))
+                .is_ok();
+
+        Self {
+            path,
+            owned,
+            other_pid,
+        }
+    }
+
+    /// Removes the lock file if this instance wrote it
+    pub fn release(&mut self) {
+        if self.owned {
+            let _ = fs::remove_file(&self.path);
+            self.owned = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_processes() {
+        let processes = [
+            (1, "HWiNFO64.EXE"),
+            (2, "driveanalizer.exe"),
+            (3, "driveanalizer.exe"),
+            (4, "explorer.exe"),
+        ];
+        let found = match_processes(processes, 2, "DriveAnalizer.exe");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, "instance");
+        assert_eq!(found[0].pid, Some(3));
+        assert_eq!(found[1].name, "HWiNFO");
+
+        let sessions = match_sessions(&[
+            "NT Kernel Logger".to_string(),
+            "EventLog-System".to_string(),
+        ]);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].kind, "trace_session");
+    }
+
+    #[test]
+    fn test_instance_lock() {
+        let dir = std::env::temp_dir().join(format!("driveanalizer-lock-{}", std::process::id()));
+
+        let mut first = InstanceLock::acquire(&dir, |_| true);
+        assert!(first.owned);
+        assert_eq!(first.other_pid, None);
+
+        // A stale PID from a crashed instance is taken over
+        fs::write(dir.join(LOCK_FILE_NAME), "999999").unwrap();
+        let second = InstanceLock::acquire(&dir, |_| false);
+        assert!(second.owned && second.other_pid.is_none());
+
+        fs::write(dir.join(LOCK_FILE_NAME), "999999").unwrap();
+        let third = InstanceLock::acquire(&dir, |_| true);
+        assert_eq!(third.other_pid, Some(999999));
+        assert!(!third.owned);
+
+        first.release();
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,179 @@
+//! Read/write composition of a time range: how the bytes split between reads
+//! and writes, between large sequential transfers and small ones, and per day.
+
+use crate::models::DiskStat;
+use crate::sampling;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Average transfer size from which a sample counts as a large transfer
+pub const LARGE_TRANSFER_BYTES: f64 = 128.0 * 1024.0;
+
+/// Speed from which a sample counts as a large transfer when IOPS are not
+/// available (older samples, counters missing)
+pub const LARGE_TRANSFER_SPEED: u64 = 32 * 1024 * 1024;
+
+const SECS_PER_DAY: f64 = 86400.0;
+
+/// Bytes of one direction split by transfer size
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransferMix {
+    pub large_bytes: u64,
+    pub small_bytes: u64,
+    /// 0-100
+    pub large_share_percent: f64,
+}
+
+/// Bytes of one (local) day
+#[derive(Debug, Clone, Serialize)]
+pub struct DaySplit {
+    /// Unix time of the day's local midnight
+    pub day_start: f64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IoComposition {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    /// 0-100
+    pub read_share_percent: f64,
+    pub reads: TransferMix,
+    pub writes: TransferMix,
+    /// Oldest day first
+    pub days: Vec<DaySplit>,
+}
+
+fn share_percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+fn is_large(speed: u64, iops: f64) -> bool {
+    if iops > 0.0 {
+        speed as f64 / iops >= LARGE_TRANSFER_BYTES
+    } else {
+        speed >= LARGE_TRANSFER_SPEED
+    }
+}
+
+impl TransferMix {
+    fn add(&mut self, bytes: u64, large: bool) {
+        if large {
+            self.large_bytes = self.large_bytes.saturating_add(bytes);
+        } else {
+            self.small_bytes = self.small_bytes.saturating_add(bytes);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.large_share_percent =
+            share_percent(self.large_bytes, self.large_bytes + self.small_bytes);
+    }
+}
+
+/// Composition of `stats` (ordered by time). Each sample stands for the time
+/// since the previous one, capped at the longest sampling interval so gaps
+/// are not filled with its speed. Days are cut at local midnight of
+/// `utc_offset_secs`.
+pub fn compose(stats: &[DiskStat], utc_offset_secs: i64) -> IoComposition {
+    let max_step = sampling::MAX_INTERVAL_MS as f64 / 1000.0;
+    let mut composition = IoComposition::default();
+    let mut days: BTreeMap<i64, (u64, u64)> = BTreeMap::new();
+    let mut previous: Option<f64> = None;
+
+    for stat in stats {
+        let step = previous
+            .map(|prev| (stat.timestamp - prev).clamp(0.0, max_step))
+            .unwrap_or(sampling::DEFAULT_INTERVAL_MS as f64 / 1000.0);
+        previous = Some(stat.timestamp);
+
+        let read = (stat.read_speed as f64 * step) as u64;
+        let write = (stat.write_speed as f64 * step) as u64;
+        composition.read_bytes = composition.read_bytes.saturating_add(read);
+        composition.write_bytes = composition.write_bytes.saturating_add(write);
+        composition
+            .reads
+            .add(read, is_large(stat.read_speed, stat.read_iops));
+        composition
+            .writes
+            .add(write, is_large(stat.write_speed, stat.write_iops));
+
+        let day = ((stat.timestamp + utc_offset_secs as f64) / SECS_PER_DAY).floor() as i64;
+        let entry = days.entry(day).or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(read);
+        entry.1 = entry.1.saturating_add(write);
+    }
+
+    composition.read_share_percent = share_percent(
+        composition.read_bytes,
+        composition.read_bytes + composition.write_bytes,
+    );
+    composition.reads.finish();
+    composition.writes.finish();
+    composition.days = days
+        .into_iter()
+        .map(|(day, (read_bytes, write_bytes))| DaySplit {
+            day_start: day as f64 * SECS_PER_DAY - utc_offset_secs as f64,
+            read_bytes,
+            write_bytes,
+        })
+        .collect();
+    composition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(timestamp: f64, read_speed: u64, write_speed: u64, write_iops: f64) -> DiskStat {
+        DiskStat {
+            read_speed,
+            write_speed,
+            write_iops,
+            ..DiskStat::idle(timestamp)
+        }
+    }
+
+    #[test]
+    fn test_composition_splits_direction_size_and_day() {
+        let midnight = 20_000.0 * SECS_PER_DAY;
+        let stats = vec![
+            // 1 MB/s in 8 KB writes: small
+            stat(midnight - 1.0, 0, 1_000_000, 125.0),
+            // 1 MB/s in 1 MB writes: large
+            stat(midnight, 3_000_000, 1_000_000, 1.0),
+            // After a gap: counted for at most the longest interval
+            stat(midnight + 60.0, 0, 1_000_000, 1.0),
+        ];
+        let composition = compose(&stats, 0);
+
+        assert_eq!(composition.read_bytes, 3_000_000);
+        assert_eq!(composition.write_bytes, 12_000_000);
+        assert_eq!(composition.read_share_percent, 20.0);
+        assert_eq!(composition.writes.small_bytes, 1_000_000);
+        assert_eq!(composition.writes.large_bytes, 11_000_000);
+        // No IOPS for reads: 3 MB/s is below the speed threshold
+        assert_eq!(composition.reads.small_bytes, 3_000_000);
+
+        let days: Vec<(f64, u64, u64)> = composition
+            .days
+            .iter()
+            .map(|d| (d.day_start, d.read_bytes, d.write_bytes))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (midnight - SECS_PER_DAY, 0, 1_000_000),
+                (midnight, 3_000_000, 11_000_000),
+            ]
+        );
+
+        // An hour east of UTC both samples fall on the same local day
+        assert_eq!(compose(&stats, 3600).days.len(), 1);
+    }
+}
@@ -0,0 +1,511 @@
+//! Core library API: the monitor loop and queries over the recorded data,
+//! independent of Tauri.
+//!
+//! Commands, the soak runner and tests call these functions with plain
+//! handles (a pool, the storage backend, the contention counters) instead of
+//! `tauri::State` wrappers, so every front end runs the same code paths.
+//! Signatures here are kept stable; the modules behind them are free to change.
+
+use crate::categories::{self, CategoryDay, ProcessCategories};
+use crate::composition::{self, IoComposition};
+use crate::coverage::{self, DayCoverage};
+use crate::db;
+use crate::db_reader::{ReadSession, SharedContention};
+use crate::distribution::{self, SpeedDistributions};
+use crate::gaps::{self, DataGap};
+use crate::moment;
+use crate::process_comparison::{self, ProcessComparison};
+use crate::process_monitor::ProcessAccumulators;
+use crate::process_names::NameNormalization;
+use crate::session::{self, SessionSummary};
+use crate::smoothing;
+use crate::storage::{self, SharedStorage, Storage, StorageError};
+use crate::text_summary::{self, SummaryRange, TextSummary};
+use crate::write_coalescer::PendingWrites;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+pub use crate::models::{
+    AllTimeTotals, DiskStat, FreeSpaceSample, Moment, PhysicalDiskStat, ProcessDetails,
+    ProcessHistoryChunk, ProcessSeriesPoint, SeriesBucket, SmoothedSpeeds, SpeedPeak,
+    TemperatureSample, TimelineEvent,
+};
+pub use crate::monitor_loop::{
+    LivePayload, LoopHandles, MonitorEmitter, MonitorEvent, MonitorLoop, NoEmitter,
+};
+
+/// Largest page `process_history_chunk` returns
+pub const MAX_CHUNK_LIMIT: u32 = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CoreError {
+    /// No pool yet, or it is taken out while the data directory is migrated
+    #[error("Database not initialized")]
+    NotInitialized,
+    /// The database file could not be opened or migrated
+    #[error("Database error: {0}")]
+    Open(String),
+    #[error("Lock error: {0}")]
+    Lock(String),
+    /// A caller-supplied value was rejected
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+pub type CoreResult<T> = Result<T, CoreError>;
+
+/// Opens (creating and migrating if needed) the database at `path`
+pub async fn open_database(path: &Path) -> CoreResult<Pool<Sqlite>> {
+    db::open_pool(path)
+        .await
+        .map_err(|e| CoreError::Open(e.to_string()))
+}
+
+/// Deletes every recorded sample, the process history, the all-time peaks
+/// and the timeline
+pub async fn clear_recorded_data(pool: &Pool<Sqlite>) -> CoreResult<()> {
+    Ok(db::clear_recorded_data(pool).await?)
+}
+
+/// The current pool
+pub fn pool(shared: &db::SharedPool) -> CoreResult<Pool<Sqlite>> {
+    let guard = shared.lock().map_err(|e| CoreError::Lock(e.to_string()))?;
+    guard.clone().ok_or(CoreError::NotInitialized)
+}
+
+/// The current time series backend
+pub fn store(shared: &SharedStorage) -> CoreResult<Arc<dyn Storage>> {
+    storage::current(shared).ok_or(CoreError::NotInitialized)
+}
+
+/// Bytes read and written since the database was created
+pub async fn alltime_totals(pool: &Pool<Sqlite>) -> CoreResult<AllTimeTotals> {
+    let (read_bytes, write_bytes) = db::get_alltime_totals(pool).await?;
+    Ok(AllTimeTotals {
+        read_bytes,
+        write_bytes,
+    })
+}
+
+/// All-time (read, write) bytes per process name
+pub async fn process_history(pool: &Pool<Sqlite>) -> CoreResult<HashMap<String, (u64, u64)>> {
+    Ok(db::get_process_history(pool).await?)
+}
+
+/// Merges the recorded history of names that normalize to the same key and
+/// returns how many names were folded away. Without stored exe paths, keying
+/// by path leaves recorded names as they are.
+pub async fn normalize_recorded_names(
+    pool: &Pool<Sqlite>,
+    names: &NameNormalization,
+) -> CoreResult<usize> {
+    if !names.is_enabled() {
+        return Ok(0);
+    }
+    let renames: Vec<(String, String)> = db::get_process_history(pool)
+        .await?
+        .into_keys()
+        .filter_map(|name| {
+            let normalized = names.normalize(&name, None);
+            (normalized != name).then_some((name, normalized))
+        })
+        .collect();
+    db::rename_processes(pool, &renames).await?;
+    Ok(renames.len())
+}
+
+/// One page of the process history (limit is clamped to 1..=MAX_CHUNK_LIMIT)
+pub async fn process_history_chunk(
+    pool: &Pool<Sqlite>,
+    cursor: Option<&str>,
+    limit: u32,
+) -> CoreResult<ProcessHistoryChunk> {
+    Ok(db::get_process_history_chunk(pool, cursor, limit.clamp(1, MAX_CHUNK_LIMIT)).await?)
+}
+
+/// Sum of the process history
+pub async fn process_history_totals(pool: &Pool<Sqlite>) -> CoreResult<AllTimeTotals> {
+    let history = db::get_process_history(pool).await?;
+    Ok(AllTimeTotals {
+        read_bytes: history.values().map(|(r, _)| r).sum(),
+        write_bytes: history.values().map(|(_, w)| w).sum(),
+    })
+}
+
+pub async fn timeline_events(
+    pool: &Pool<Sqlite>,
+    start: f64,
+    end: f64,
+) -> CoreResult<Vec<TimelineEvent>> {
+    Ok(db::get_timeline_events(pool, start, end).await?)
+}
+
+pub async fn all_time_peaks(pool: &Pool<Sqlite>) -> CoreResult<Vec<SpeedPeak>> {
+    Ok(db::get_speed_peaks(pool).await?)
+}
+
+pub async fn disk_stats_history(
+    store: &dyn Storage,
+    start: f64,
+    end: f64,
+) -> CoreResult<Vec<DiskStat>> {
+    Ok(store.disk_stats_history(start, end).await?)
+}
+
+pub async fn physical_disk_stats(
+    store: &dyn Storage,
+    start: f64,
+    end: f64,
+) -> CoreResult<Vec<PhysicalDiskStat>> {
+    Ok(store.physical_disk_stats(start, end).await?)
+}
+
+/// 5s/30s moving averages of the recorded speeds
+pub async fn smoothed_history(
+    store: &dyn Storage,
+    start: f64,
+    end: f64,
+) -> CoreResult<Vec<SmoothedSpeeds>> {
+    let stats = store
+        .disk_stats_history(start - smoothing::WARMUP_SECS, end)
+        .await?;
+    Ok(smoothing::smooth_history(&stats, start))
+}
+
+/// Read/write composition of a range; days are cut at local midnight of `utc_offset_secs`
+pub async fn io_composition(
+    store: &dyn Storage,
+    start: f64,
+    end: f64,
+    utc_offset_secs: i64,
+) -> CoreResult<IoComposition> {
+    let stats = store.disk_stats_history(start, end).await?;
+    Ok(composition::compose(&stats, utc_offset_secs))
+}
+
+/// Speed percentiles and histograms of a range
+pub async fn speed_distribution(
+    store: &dyn Storage,
+    start: f64,
+    end: f64,
+) -> CoreResult<SpeedDistributions> {
+    let stats = store.disk_stats_history(start, end).await?;
+    Ok(distribution::speed_distributions(&stats))
+}
+
+/// Periods of a range without samples, with the reason taken from the timeline
+pub async fn data_gaps(
+    pool: &Pool<Sqlite>,
+    store: &dyn Storage,
+    start: f64,
+    end: f64,
+) -> CoreResult<Vec<DataGap>> {
+    let stats = store.disk_stats_history(start, end).await?;
+    let events = db::get_timeline_events(pool, start, end).await?;
+    Ok(gaps::find_gaps(&stats, &events, start))
+}
+
+/// Category assignments and the category names to offer
+pub async fn process_categories(pool: &Pool<Sqlite>) -> CoreResult<ProcessCategories> {
+    let assignments = db::get_process_categories(pool).await?;
+    Ok(ProcessCategories::new(assignments))
+}
+
+/// Puts the process `name` into `category` (None removes it from its category)
+pub async fn set_process_category(
+    pool: &Pool<Sqlite>,
+    name: &str,
+    category: Option<&str>,
+) -> CoreResult<()> {
+    let category = category
+        .map(categories::validate)
+        .transpose()
+        .map_err(CoreError::InvalidInput)?;
+    db::set_process_category(pool, name, category).await?;
+    Ok(())
+}
+
+/// Read/write totals per category and local day of `start..end`, from the
+/// per-process time series (on a read-only connection)
+pub async fn category_totals(
+    pool: &Pool<Sqlite>,
+    contention: &SharedContention,
+    start: f64,
+    end: f64,
+    utc_offset_secs: i64,
+) -> CoreResult<Vec<CategoryDay>> {
+    let assignments = db::get_process_categories(pool).await?;
+    let mut read = ReadSession::begin(pool, contention).await?;
+    let samples = db::get_process_samples(read.conn(), start, end).await;
+    read.finish(&samples).await;
+    Ok(categories::daily_totals(
+        &samples?,
+        &assignments,
+        utc_offset_secs,
+    ))
+}
+
+/// Read/write bytes of one process name per hour or local day of
+/// `start..end`, from the per-process rollup (on a read-only connection)
+pub async fn process_series(
+    pool: &Pool<Sqlite>,
+    contention: &SharedContention,
+    name: &str,
+    start: f64,
+    end: f64,
+    bucket: SeriesBucket,
+    utc_offset_secs: i64,
+) -> CoreResult<Vec<ProcessSeriesPoint>> {
+    let bucket_secs = bucket.secs();
+    let mut read = ReadSession::begin(pool, contention).await?;
+    let rows =
+        db::get_process_series(read.conn(), name, start, end, bucket_secs, utc_offset_secs).await;
+    read.finish(&rows).await;
+    Ok(rows?
+        .into_iter()
+        .map(|(index, read_bytes, write_bytes)| ProcessSeriesPoint {
+            start: (index * bucket_secs - utc_offset_secs) as f64,
+            read_bytes,
+            write_bytes,
+        })
+        .collect())
+}
+
+/// The recorded side of `ProcessDetails` for `name`: all-time totals and
+/// the per-day history (on a read-only connection). The live fields are
+/// left empty for the caller to fill from the sampler.
+pub async fn process_details(
+    pool: &Pool<Sqlite>,
+    contention: &SharedContention,
+    name: &str,
+    utc_offset_secs: i64,
+) -> CoreResult<ProcessDetails> {
+    let entry = db::get_process_history_entry(pool, name).await?;
+    let days = process_series(
+        pool,
+        contention,
+        name,
+        0.0,
+        f64::MAX,
+        SeriesBucket::Day,
+        utc_offset_secs,
+    )
+    .await?;
+
+    Ok(ProcessDetails {
+        name: name.to_string(),
+        exe_path: None,
+        command_line: entry.as_ref().and_then(|e| e.command_line.clone()),
+        read_bytes: entry.as_ref().map_or(0, |e| e.read_bytes),
+        write_bytes: entry.as_ref().map_or(0, |e| e.write_bytes),
+        first_seen: entry.as_ref().and_then(|e| e.first_seen),
+        last_seen: entry.as_ref().and_then(|e| e.last_seen),
+        days,
+        instances: Vec::new(),
+        extensions: Vec::new(),
+        read_speed: 0,
+        write_speed: 0,
+    })
+}
+
+/// `name`'s session bytes (read, write) next to its all-time totals, today's
+/// bytes and its average recorded day, as of `now`
+pub async fn process_comparison(
+    pool: &Pool<Sqlite>,
+    contention: &SharedContention,
+    name: &str,
+    session: (u64, u64),
+    now: f64,
+    utc_offset_secs: i64,
+) -> CoreResult<ProcessComparison> {
+    let entry = db::get_process_history_entry(pool, name).await?;
+    let days = process_series(
+        pool,
+        contention,
+        name,
+        0.0,
+        now,
+        SeriesBucket::Day,
+        utc_offset_secs,
+    )
+    .await?;
+    let offset = utc_offset_secs as f64;
+    let today_start = ((now + offset) / 86400.0).floor() * 86400.0 - offset;
+    let all_time = entry.map_or((0, 0), |e| (e.read_bytes, e.write_bytes));
+    Ok(process_comparison::compare(
+        name,
+        session,
+        all_time,
+        &days,
+        today_start,
+    ))
+}
+
+/// Collected seconds and coverage percentage per day of `start..end`, as of `now`
+pub async fn monitoring_coverage(
+    pool: &Pool<Sqlite>,
+    start: f64,
+    end: f64,
+    now: f64,
+) -> CoreResult<Vec<DayCoverage>> {
+    let rows = db::get_coverage(pool, coverage::day_of(start), coverage::day_of(end)).await?;
+    Ok(coverage::day_coverage(&rows, now))
+}
+
+/// Free space per volume (volume = None returns every volume), on a read-only connection
+pub async fn free_space_history(
+    pool: &Pool<Sqlite>,
+    contention: &SharedContention,
+    start: f64,
+    end: f64,
+    volume: Option<&str>,
+) -> CoreResult<Vec<FreeSpaceSample>> {
+    let mut read = ReadSession::begin(pool, contention).await?;
+    let result = db::get_free_space_history(read.conn(), start, end, volume).await;
+    read.finish(&result).await;
+    Ok(result?)
+}
+
+/// Drive temperatures (disk = None returns every disk), on a read-only connection
+pub async fn temperature_history(
+    pool: &Pool<Sqlite>,
+    contention: &SharedContention,
+    start: f64,
+    end: f64,
+    disk: Option<&str>,
+) -> CoreResult<Vec<TemperatureSample>> {
+    let mut read = ReadSession::begin(pool, contention).await?;
+    let result = db::get_temperature_history(read.conn(), start, end, disk).await;
+    read.finish(&result).await;
+    Ok(result?)
+}
+
+/// Nearest sample, per-disk counters, active processes and timeline events at an instant
+pub async fn moment_at(
+    pool: &Pool<Sqlite>,
+    store: &dyn Storage,
+    contention: &SharedContention,
+    timestamp: f64,
+    pending: PendingWrites,
+) -> CoreResult<Moment> {
+    let (start, end) = moment::window(timestamp);
+
+    let mut stats = store.disk_stats_history(start, end).await?;
+    stats.extend(pending.disk_stats);
+    let mut disks = store.physical_disk_stats(start, end).await?;
+    disks.extend(pending.physical_disk_stats);
+
+    // The covering interval may end a whole flush period after the instant
+    let mut read = ReadSession::begin(pool, contention).await?;
+    let samples = db::get_covering_process_samples(read.conn(), timestamp).await;
+    read.finish(&samples).await;
+    let samples = samples?;
+
+    let top_processes = if samples.is_empty() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        moment::unflushed_processes(pending.process_deltas, now)
+    } else {
+        moment::covering_processes(samples, timestamp)
+    };
+
+    let events = db::get_timeline_events(pool, timestamp, timestamp).await?;
+
+    Ok(Moment {
+        timestamp,
+        disk_stat: moment::nearest_disk_stat(stats, timestamp),
+        disks: moment::nearest_disks(disks, timestamp),
+        top_processes,
+        events,
+    })
+}
+
+/// Session (read, write) totals of the processes seen so far, grouped by name
+pub fn session_totals_by_name(
+    accumulators: &ProcessAccumulators,
+) -> CoreResult<Vec<(String, u64, u64)>> {
+    let acc = accumulators
+        .lock()
+        .map_err(|e| CoreError::Lock(e.to_string()))?;
+    let mut by_name: HashMap<String, (u64, u64)> = HashMap::new();
+    for process in acc.values() {
+        let entry = by_name.entry(process.name.to_string()).or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(process.read_bytes);
+        entry.1 = entry.1.saturating_add(process.write_bytes);
+    }
+    Ok(by_name
+        .into_iter()
+        .map(|(name, (r, w))| (name, r, w))
+        .collect())
+}
+
+/// Summary recorded when the previous monitoring session ended
+pub async fn last_session_summary(pool: &Pool<Sqlite>) -> CoreResult<Option<SessionSummary>> {
+    let event = db::get_latest_timeline_event(pool, session::SESSION_EVENT_KIND).await?;
+    Ok(event.as_ref().and_then(session::from_event))
+}
+
+/// Natural-language summary of the range ending at `end`
+pub async fn text_summary(
+    store: &dyn Storage,
+    accumulators: &ProcessAccumulators,
+    range: SummaryRange,
+    end: f64,
+) -> CoreResult<TextSummary> {
+    let aggregate = store
+        .range_aggregate(end - range.duration_secs(), end)
+        .await?;
+    let dominant = text_summary::dominant_process(&session_totals_by_name(accumulators)?);
+    Ok(text_summary::summarize(
+        range,
+        &aggregate,
+        dominant.as_ref(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_handles_report_not_initialized() {
+        let pool_slot: db::SharedPool = Default::default();
+        assert!(matches!(pool(&pool_slot), Err(CoreError::NotInitialized)));
+        let storage_slot: SharedStorage = Default::default();
+        assert_eq!(
+            store(&storage_slot).err().unwrap().to_string(),
+            "Database not initialized"
+        );
+    }
+
+    #[test]
+    fn test_session_totals_group_by_name() {
+        let accumulators = crate::process_monitor::create_accumulators();
+        {
+            let mut acc = accumulators.lock().unwrap();
+            for (pid, read) in [(1, 10), (2, 5)] {
+                acc.insert(
+                    pid,
+                    crate::process_monitor::ProcessIOAccumulator {
+                        name: "app.exe".into(),
+                        exe_path: None,
+                        read_bytes: read,
+                        write_bytes: 1,
+                    },
+                );
+            }
+        }
+        assert_eq!(
+            session_totals_by_name(&accumulators).unwrap(),
+            vec![("app.exe".to_string(), 15, 2)]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Monitoring coverage: seconds per (UTC) day the monitor was collecting, so
+//! users can tell how complete their all-time totals are. Recorded per tick
+//! and kept in its own table, so it outlives the time series retention.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub const SECS_PER_DAY: f64 = 86400.0;
+
+/// Coverage of one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayCoverage {
+    /// Unix time of the day's start (UTC midnight)
+    pub day_start: f64,
+    pub collected_secs: f64,
+    /// 0-100, of the part of the day that has passed
+    pub coverage_percent: f64,
+}
+
+/// Day number (days since the Unix epoch) of a timestamp
+pub fn day_of(timestamp: f64) -> i64 {
+    (timestamp / SECS_PER_DAY).floor() as i64
+}
+
+/// Adds the seconds of `start..end` to `days`, split at midnight
+pub fn add_interval(days: &mut HashMap<i64, f64>, start: f64, end: f64) {
+    let mut from = start;
+    while from < end {
+        let day = day_of(from);
+        let to = end.min((day + 1) as f64 * SECS_PER_DAY);
+        *days.entry(day).or_insert(0.0) += to - from;
+        from = to;
+    }
+}
+
+/// Per-day coverage from stored (day, seconds) rows, oldest first; today is
+/// measured against the time elapsed so far
+pub fn day_coverage(rows: &[(i64, f64)], now: f64) -> Vec<DayCoverage> {
+    let mut rows = rows.to_vec();
+    rows.sort_by_key(|(day, _)| *day);
+    rows.into_iter()
+        .map(|(day, collected_secs)| {
+            let day_start = day as f64 * SECS_PER_DAY;
+            let length = (now - day_start).clamp(0.0, SECS_PER_DAY);
+            let coverage_percent = if length > 0.0 {
+                (collected_secs / length * 100.0).min(100.0)
+            } else {
+                0.0
+            };
+            DayCoverage {
+                day_start,
+                collected_secs,
+                coverage_percent,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_is_split_at_midnight() {
+        let midnight = 19_000.0 * SECS_PER_DAY;
+        let mut days = HashMap::new();
+        add_interval(&mut days, midnight - 2.0, midnight + 3.0);
+        add_interval(&mut days, midnight + 3.0, midnight + 4.0);
+        assert_eq!(days.get(&18_999), Some(&2.0));
+        assert_eq!(days.get(&19_000), Some(&4.0));
+    }
+
+    #[test]
+    fn test_coverage_of_today_uses_elapsed_time() {
+        let today = 19_000.0 * SECS_PER_DAY;
+        let rows = [(19_000, 1800.0), (18_999, SECS_PER_DAY / 4.0)];
+        let coverage = day_coverage(&rows, today + 3600.0);
+        assert_eq!(coverage[0].day_start, today - SECS_PER_DAY);
+        assert_eq!(coverage[0].coverage_percent, 25.0);
+        assert_eq!(coverage[1].coverage_percent, 50.0);
+    }
+}
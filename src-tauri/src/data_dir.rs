@@ -0,0 +1,437 @@
+use crate::db::{self, SharedPool};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// SQLite database file name inside the data directory
+pub const DB_FILE_NAME: &str = "drive_analytics.db";
+
+/// Sub-directories that belong to the data directory and move with it
+/// ("parquet" holds the time series when the Parquet storage backend is used)
+pub const DATA_SUBDIRS: &[&str] = &[
+    crate::backup::BACKUPS_DIR,
+    crate::incident::EXPORTS_DIR,
+    "logs",
+    "parquet",
+];
+
+/// Pointer file in the default app data dir that records a relocated data dir
+const LOCATION_FILE_NAME: &str = "data_location.json";
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DataLocation {
+    data_dir: PathBuf,
+}
+
+/// Result of a successful data directory migration
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub old_dir: String,
+    pub new_dir: String,
+    pub files_moved: u64,
+    pub bytes_moved: u64,
+}
+
+/// Returns the directory holding the database, backups and logs.
+///
+/// Defaults to the platform app data dir; `migrate_app_data` can relocate it,
+/// in which case the default dir keeps a small pointer file.
+pub fn resolve_data_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, BoxError> {
+    let default_dir = app_handle.path().app_data_dir()?;
+    Ok(read_location(&default_dir).unwrap_or(default_dir))
+}
+
+/// True when the data dir lives next to the executable (e.g. run from a USB stick)
+pub fn is_portable(app_handle: &tauri::AppHandle) -> bool {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    match (exe_dir, resolve_data_dir(app_handle)) {
+        (Some(exe_dir), Ok(data_dir)) => data_dir.starts_with(exe_dir),
+        _ => false,
+    }
+}
+
+/// Full path of the SQLite database file
+pub fn db_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, BoxError> {
+    Ok(resolve_data_dir(app_handle)?.join(DB_FILE_NAME))
+}
+
+fn read_location(default_dir: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(default_dir.join(LOCATION_FILE_NAME)).ok()?;
+    let location: DataLocation = serde_json::from_str(&content).ok()?;
+    Some(location.data_dir)
+}
+
+/// Writes the pointer file atomically (temp file + rename); removes it when
+/// the data dir is moved back to the default location.
+fn write_location(default_dir: &Path, data_dir: &Path) -> Result<(), BoxError> {
+    let location_path = default_dir.join(LOCATION_FILE_NAME);
+    if data_dir == default_dir {
+        if location_path.exists() {
+            fs::remove_file(location_path)?;
+        }
+        return Ok(());
+    }
+
+    fs::create_dir_all(default_dir)?;
+    let tmp_path = default_dir.join(format!("{}.tmp", LOCATION_FILE_NAME));
+    let content = serde_json::to_string_pretty(&DataLocation {
+        data_dir: data_dir.to_path_buf(),
+    })?;
+    fs::write(&tmp_path, content)?;
+    fs::rename(tmp_path, location_path)?;
+    Ok(())
+}
+
+/// Lists every file that belongs to the data dir, relative to it
+pub(crate) fn collect_data_files(data_dir: &Path) -> Result<Vec<PathBuf>, BoxError> {
+    let mut files = Vec::new();
+
+    for name in [
+        DB_FILE_NAME.to_string(),
+        format!("{}-wal", DB_FILE_NAME),
+        format!("{}-shm", DB_FILE_NAME),
+    ] {
+        if data_dir.join(&name).is_file() {
+            files.push(PathBuf::from(name));
+        }
+    }
+
+    for subdir in DATA_SUBDIRS {
+        collect_recursive(data_dir, Path::new(subdir), &mut files)?;
+    }
+
+    Ok(files)
+}
+
+fn collect_recursive(
+    root: &Path,
+    relative: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), BoxError> {
+    let dir = root.join(relative);
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let child = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_recursive(root, &child, files)?;
+        } else {
+            files.push(child);
+        }
+    }
+    Ok(())
+}
+
+/// SHA-256 of a file as lowercase hex
+pub(crate) fn sha256_file(path: &Path) -> Result<String, BoxError> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// What a copy created in the target directory, so a failed migration can
+/// remove exactly that and leave anything already there alone
+#[derive(Debug, Default)]
+struct CopiedFiles {
+    files: Vec<PathBuf>,
+    dirs: Vec<PathBuf>,
+    bytes: u64,
+}
+
+impl CopiedFiles {
+    /// Best-effort removal of the copies and of the directories made for them
+    fn remove(&self) {
+        for file in &self.files {
+            let _ = fs::remove_file(file);
+        }
+        remove_empty_dirs(self.dirs.clone());
+    }
+}
+
+/// Copies the files to the new dir and verifies each copy by checksum.
+/// Existing files are never overwritten; on error the partial copy is removed.
+fn copy_verified(
+    old_dir: &Path,
+    new_dir: &Path,
+    files: &[PathBuf],
+) -> Result<CopiedFiles, BoxError> {
+    let mut copied = CopiedFiles::default();
+    for relative in files {
+        let result = (|| -> Result<(), BoxError> {
+            let source = old_dir.join(relative);
+            let target = new_dir.join(relative);
+            if target.exists() {
+                return Err(
+                    format!("Target directory already contains {}", relative.display()).into(),
+                );
+            }
+            if let Some(parent) = target.parent() {
+                let missing: Vec<PathBuf> = parent
+                    .ancestors()
+                    .take_while(|dir| !dir.exists())
+                    .map(Path::to_path_buf)
+                    .collect();
+                fs::create_dir_all(parent)?;
+                copied.dirs.extend(missing);
+            }
+            copied.files.push(target.clone());
+            copied.bytes += fs::copy(&source, &target)?;
+            if sha256_file(&source)? != sha256_file(&target)? {
+                return Err(format!("Checksum mismatch for {}", relative.display()).into());
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            copied.remove();
+            return Err(e);
+        }
+    }
+    Ok(copied)
+}
+
+/// Best-effort removal of data files and of the directories they leave empty
+fn remove_files(dir: &Path, files: &[PathBuf]) {
+    let mut parents = Vec::new();
+    for relative in files {
+        let _ = fs::remove_file(dir.join(relative));
+        parents.extend(
+            relative
+                .ancestors()
+                .skip(1)
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map(|parent| dir.join(parent)),
+        );
+    }
+    remove_empty_dirs(parents);
+}
+
+/// Removes the directories that are empty, deepest first
+fn remove_empty_dirs(mut dirs: Vec<PathBuf>) {
+    dirs.sort();
+    dirs.dedup();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    for dir in dirs {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Puts a pool for `db_path` back into the shared slot after a failed migration
+async fn reopen_pool(shared_pool: &SharedPool, db_path: &Path) {
+    match db::open_pool(db_path).await {
+        Ok(pool) => {
+            if let Ok(mut guard) = shared_pool.lock() {
+                *guard = Some(pool);
+            }
+        }
+        Err(e) => eprintln!("[DataDir] Failed to reopen database: {}", e),
+    }
+}
+
+/// Moves the database, backups and logs to `new_dir`.
+///
+/// Steps: take the pool out of the shared slot (the monitor and schedulers
+/// keep buffering while it is gone), checkpoint and close it, copy every file
+/// and verify SHA-256 checksums, switch the pointer file, reopen the pool at
+/// the new location and finally delete the old files. Any failure before the
+/// switch reopens the original database untouched.
+pub async fn migrate_app_data(
+    app_handle: &tauri::AppHandle,
+    shared_pool: &SharedPool,
+    new_dir: &Path,
+) -> Result<MigrationReport, BoxError> {
+    let default_dir = app_handle.path().app_data_dir()?;
+    let old_dir = resolve_data_dir(app_handle)?;
+
+    if !new_dir.is_absolute() {
+        return Err("Target directory must be an absolute path".into());
+    }
+    fs::create_dir_all(new_dir)?;
+    let new_dir = new_dir.canonicalize()?;
+    let old_dir_canonical = old_dir.canonicalize().unwrap_or_else(|_| old_dir.clone());
+    if new_dir == old_dir_canonical {
+        return Err("Target directory is the current data directory".into());
+    }
+    if new_dir.starts_with(&old_dir_canonical) {
+        return Err("Target directory cannot be inside the current data directory".into());
+    }
+    if new_dir.join(DB_FILE_NAME).exists() {
+        return Err("Target directory already contains a database".into());
+    }
+
+    // 1. Stop all database access
+    let old_pool = {
+        let mut guard = shared_pool
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        guard.take().ok_or("Database not initialized")?
+    };
+    let _ = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&old_pool)
+        .await;
+    old_pool.close().await;
+
+    let old_db_path = old_dir.join(DB_FILE_NAME);
+
+    // 2. Copy and verify
+    let files = match collect_data_files(&old_dir) {
+        Ok(files) => files,
+        Err(e) => {
+            reopen_pool(shared_pool, &old_db_path).await;
+            return Err(e);
+        }
+    };
+    let copied = match copy_verified(&old_dir, &new_dir, &files) {
+        Ok(copied) => copied,
+        Err(e) => {
+            reopen_pool(shared_pool, &old_db_path).await;
+            return Err(e);
+        }
+    };
+
+    // 3. Switch
+    if let Err(e) = write_location(&default_dir, &new_dir) {
+        copied.remove();
+        reopen_pool(shared_pool, &old_db_path).await;
+        return Err(e);
+    }
+    let new_pool = match db::open_pool(&new_dir.join(DB_FILE_NAME)).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            let _ = write_location(&default_dir, &old_dir);
+            copied.remove();
+            reopen_pool(shared_pool, &old_db_path).await;
+            return Err(e);
+        }
+    };
+    if let Ok(mut guard) = shared_pool.lock() {
+        *guard = Some(new_pool);
+    }
+
+    // 4. Delete old copies (files written since they were listed stay)
+    remove_files(&old_dir, &files);
+
+    println!(
+        "[DataDir] Migrated {} files ({} bytes) to {}",
+        files.len(),
+        copied.bytes,
+        new_dir.display()
+    );
+
+    Ok(MigrationReport {
+        old_dir: old_dir.to_string_lossy().to_string(),
+        new_dir: new_dir.to_string_lossy().to_string(),
+        files_moved: files.len() as u64,
+        bytes_moved: copied.bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("driveanalizer-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_location_roundtrip() {
+        let default_dir = temp_dir("location");
+        let custom = default_dir.join("elsewhere");
+
+        assert_eq!(read_location(&default_dir), None);
+        write_location(&default_dir, &custom).unwrap();
+        assert_eq!(read_location(&default_dir), Some(custom));
+
+        // Moving back to the default removes the pointer
+        write_location(&default_dir, &default_dir).unwrap();
+        assert_eq!(read_location(&default_dir), None);
+
+        fs::remove_dir_all(default_dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_verified_includes_subdirs() {
+        let old_dir = temp_dir("copy-old");
+        let new_dir = temp_dir("copy-new");
+        fs::write(old_dir.join(DB_FILE_NAME), b"db").unwrap();
+        fs::create_dir_all(old_dir.join("backups")).unwrap();
+        fs::write(old_dir.join("backups").join("b1.db"), b"backup").unwrap();
+
+        let files = collect_data_files(&old_dir).unwrap();
+        assert_eq!(files.len(), 2);
+
+        let copied = copy_verified(&old_dir, &new_dir, &files).unwrap();
+        assert_eq!(copied.bytes, 8);
+        assert_eq!(
+            sha256_file(&old_dir.join("backups").join("b1.db")).unwrap(),
+            sha256_file(&new_dir.join("backups").join("b1.db")).unwrap()
+        );
+
+        fs::remove_dir_all(old_dir).unwrap();
+        fs::remove_dir_all(new_dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_copy_keeps_existing_target_files() {
+        let old_dir = temp_dir("failed-old");
+        let new_dir = temp_dir("failed-new");
+        fs::write(old_dir.join(DB_FILE_NAME), b"db").unwrap();
+        for subdir in ["logs", "backups"] {
+            fs::create_dir_all(old_dir.join(subdir)).unwrap();
+        }
+        fs::write(old_dir.join("logs").join("app.log"), b"log").unwrap();
+        fs::write(old_dir.join("backups").join("b1.db"), b"backup").unwrap();
+        fs::create_dir_all(new_dir.join("logs")).unwrap();
+        fs::write(new_dir.join("logs").join("keep.txt"), b"mine").unwrap();
+
+        // The last file vanished after it was listed
+        let mut files = collect_data_files(&old_dir).unwrap();
+        files.push(PathBuf::from("logs").join("gone.log"));
+        assert!(copy_verified(&old_dir, &new_dir, &files).is_err());
+
+        assert_eq!(
+            fs::read(new_dir.join("logs").join("keep.txt")).unwrap(),
+            b"mine"
+        );
+        assert!(!new_dir.join("logs").join("app.log").exists());
+        assert!(!new_dir.join(DB_FILE_NAME).exists());
+        // Only directories made for the copy are removed
+        assert!(!new_dir.join("backups").exists());
+
+        // A later write to the old dir survives removing the moved files
+        let moved = collect_data_files(&old_dir).unwrap();
+        fs::write(old_dir.join("logs").join("late.log"), b"late").unwrap();
+        remove_files(&old_dir, &moved);
+        assert!(old_dir.join("logs").join("late.log").exists());
+        assert!(!old_dir.join("backups").exists());
+
+        fs::remove_dir_all(old_dir).unwrap();
+        fs::remove_dir_all(new_dir).unwrap();
+    }
+}
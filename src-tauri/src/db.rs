@@ -1,29 +1,50 @@
-use crate::models::DiskStat;
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
+use crate::data_dir;
+use crate::models::{
+    DiskStat, FreeSpaceSample, PhysicalDiskStat, ProcessHistoryChunk, ProcessHistoryEntry,
+    ProcessSample, RangeAggregate, SpeedPeak, TemperatureSample, TimelineEvent,
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
 use std::fs;
-use tauri::Manager;
+use std::sync::{Arc, Mutex};
+
+/// Shared, swappable pool slot. `None` until initialized and while the data
+/// directory is being migrated; long-running tasks re-read it on every use.
+pub type SharedPool = Arc<Mutex<Option<Pool<Sqlite>>>>;
+
+/// Returns a clone of the current pool, if available
+pub fn current_pool(shared: &SharedPool) -> Option<Pool<Sqlite>> {
+    shared.lock().ok().and_then(|guard| guard.clone())
+}
 
 pub async fn init_db(
     app_handle: &tauri::AppHandle,
 ) -> Result<Pool<Sqlite>, Box<dyn std::error::Error + Send + Sync>> {
-    use tauri::Manager;
-
-    let app_data_dir = app_handle.path().app_data_dir()?;
-    if !app_data_dir.exists() {
-        fs::create_dir_all(&app_data_dir)?;
+    let data_dir = data_dir::resolve_data_dir(app_handle)?;
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir)?;
     }
 
-    let db_path = app_data_dir.join("drive_analytics.db");
-    let db_url = format!("sqlite://{}", db_path.to_str().unwrap());
+    let pool = open_pool(&data_dir.join(data_dir::DB_FILE_NAME)).await?;
+
+    println!("[DB] Database initialized successfully.");
+
+    Ok(pool)
+}
 
+/// Opens (creating if needed) the database at `db_path`, applies PRAGMAs and the schema
+pub async fn open_pool(
+    db_path: &std::path::Path,
+) -> Result<Pool<Sqlite>, Box<dyn std::error::Error + Send + Sync>> {
     // Create the DB file if it doesn't exist
     if !db_path.exists() {
-        fs::File::create(&db_path)?;
+        fs::File::create(db_path)?;
     }
 
+    // Options rather than a URL: canonical Windows paths (\\?\C:\...) are not valid URLs
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
-        .connect(&db_url)
+        .connect_with(SqliteConnectOptions::new().filename(db_path))
         .await?;
 
     // Optimize PRAGMA settings for better performance and reliability
@@ -40,12 +61,38 @@ pub async fn init_db(
 
     println!("[DB] PRAGMA settings optimized");
 
+    create_schema(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Bumped whenever create_schema changes (stored in `PRAGMA user_version`)
+pub const SCHEMA_VERSION: i64 = 16;
+
+/// Seconds per slot of the per-process rollup. Every UTC offset in use is a
+/// whole number of quarter hours, so slots add up to exact local days
+pub const ROLLUP_SLOT_SECS: i64 = 900;
+
+/// Creates tables, applies column migrations and builds indexes (idempotent)
+pub(crate) async fn create_schema(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    let (had_hourly,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'process_hourly')",
+    )
+    .fetch_one(pool)
+    .await?;
+    let (had_rollup,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'process_rollup')",
+    )
+    .fetch_one(pool)
+    .await?;
+
     // Create persistent tables
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS process_history (
             name TEXT PRIMARY KEY,
             read_bytes INTEGER NOT NULL DEFAULT 0,
-            write_bytes INTEGER NOT NULL DEFAULT 0
+            write_bytes INTEGER NOT NULL DEFAULT 0,
+            command_line TEXT
          );
          CREATE TABLE IF NOT EXISTS disk_stats (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -53,66 +100,751 @@ pub async fn init_db(
             read_bytes INTEGER NOT NULL,
             write_bytes INTEGER NOT NULL,
             read_speed INTEGER NOT NULL,
-            write_speed INTEGER NOT NULL
+            write_speed INTEGER NOT NULL,
+            read_iops REAL NOT NULL DEFAULT 0,
+            write_iops REAL NOT NULL DEFAULT 0,
+            idle_time REAL NOT NULL DEFAULT 100,
+            queue_depth REAL NOT NULL DEFAULT 0,
+            read_latency_ms REAL NOT NULL DEFAULT 0,
+            write_latency_ms REAL NOT NULL DEFAULT 0,
+            monotonic_offset REAL NOT NULL DEFAULT 0
+         );
+         CREATE TABLE IF NOT EXISTS physical_disk_stats (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp REAL NOT NULL,
+            disk TEXT NOT NULL,
+            read_speed INTEGER NOT NULL,
+            write_speed INTEGER NOT NULL,
+            idle_time REAL NOT NULL,
+            queue_depth REAL NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS timeline_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            start_time REAL NOT NULL,
+            end_time REAL,
+            details TEXT
+         );
+         CREATE TABLE IF NOT EXISTS free_space_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp REAL NOT NULL,
+            volume TEXT NOT NULL,
+            total_bytes INTEGER NOT NULL,
+            free_bytes INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS temperature_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp REAL NOT NULL,
+            disk TEXT NOT NULL,
+            temperature_c REAL NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS process_samples (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp REAL NOT NULL,
+            name TEXT NOT NULL,
+            read_bytes INTEGER NOT NULL,
+            write_bytes INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS process_rollup (
+            slot INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            read_bytes INTEGER NOT NULL DEFAULT 0,
+            write_bytes INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (slot, name)
+         );
+         CREATE TABLE IF NOT EXISTS speed_peaks (
+            direction TEXT PRIMARY KEY,
+            speed INTEGER NOT NULL,
+            timestamp REAL NOT NULL,
+            process TEXT
+         );
+         CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS monitor_coverage (
+            day INTEGER PRIMARY KEY,
+            seconds REAL NOT NULL DEFAULT 0
+         );
+         CREATE TABLE IF NOT EXISTS process_categories (
+            name TEXT PRIMARY KEY COLLATE NOCASE,
+            category TEXT NOT NULL
          );"
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
 
+    // Columns added after the first release (existing databases need ALTER TABLE)
+    add_column_if_missing(pool, "disk_stats", "read_iops", "REAL NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(pool, "disk_stats", "write_iops", "REAL NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(pool, "disk_stats", "idle_time", "REAL NOT NULL DEFAULT 100").await?;
+    add_column_if_missing(pool, "disk_stats", "queue_depth", "REAL NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(
+        pool,
+        "disk_stats",
+        "read_latency_ms",
+        "REAL NOT NULL DEFAULT 0",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "disk_stats",
+        "write_latency_ms",
+        "REAL NOT NULL DEFAULT 0",
+    )
+    .await?;
+    add_column_if_missing(
+        pool,
+        "disk_stats",
+        "monotonic_offset",
+        "REAL NOT NULL DEFAULT 0",
+    )
+    .await?;
+    add_column_if_missing(pool, "process_history", "command_line", "TEXT").await?;
+    if had_hourly {
+        // The first rollup kept whole UTC hours; each becomes its first slot
+        sqlx::query(
+            "INSERT OR IGNORE INTO process_rollup (slot, name, read_bytes, write_bytes)
+             SELECT hour * 3600 / ?, name, read_bytes, write_bytes FROM process_hourly",
+        )
+        .bind(ROLLUP_SLOT_SECS)
+        .execute(pool)
+        .await?;
+        sqlx::query("DROP TABLE process_hourly")
+            .execute(pool)
+            .await?;
+    } else if !had_rollup {
+        // Seed the rollup from the retained per-process time series
+        sqlx::query(
+            "INSERT INTO process_rollup (slot, name, read_bytes, write_bytes)
+             SELECT CAST(timestamp / ? AS INTEGER), name, SUM(read_bytes), SUM(write_bytes)
+             FROM process_samples GROUP BY 1, 2",
+        )
+        .bind(ROLLUP_SLOT_SECS)
+        .execute(pool)
+        .await?;
+    }
+    add_column_if_missing(pool, "process_history", "first_seen", "REAL").await?;
+    if add_column_if_missing(pool, "process_history", "last_seen", "REAL").await? {
+        // Best effort for existing rows: the retained per-process time series
+        sqlx::query(
+            "UPDATE process_history SET
+                first_seen = (SELECT MIN(timestamp) FROM process_samples s WHERE s.name = process_history.name),
+                last_seen = (SELECT MAX(timestamp) FROM process_samples s WHERE s.name = process_history.name)"
+        )
+        .execute(pool)
+        .await?;
+    }
+
     // Create optimized indexes for better query performance
     // Index 1: Timestamp in descending order for recent data queries
     sqlx::query(
         "CREATE INDEX IF NOT EXISTS idx_disk_stats_timestamp ON disk_stats(timestamp DESC);"
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
 
     // Index 2: Process name for process history lookups
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_process_name ON process_history(name);")
+        .execute(pool)
+        .await?;
+
+    // Index 3: Composite index for time-range queries with multiple columns
     sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_process_name ON process_history(name);"
+        "CREATE INDEX IF NOT EXISTS idx_disk_stats_time_range ON disk_stats(timestamp DESC, read_bytes, write_bytes);"
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
 
-    // Index 3: Composite index for time-range queries with multiple columns
+    // Index 4: Per-disk samples by time for range queries
     sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_disk_stats_time_range ON disk_stats(timestamp DESC, read_bytes, write_bytes);"
+        "CREATE INDEX IF NOT EXISTS idx_physical_disk_stats_timestamp ON physical_disk_stats(timestamp);"
     )
-    .execute(&pool)
+    .execute(pool)
+    .await?;
+
+    // Index 5: Timeline events by start time for range queries
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_timeline_events_start ON timeline_events(start_time);",
+    )
+    .execute(pool)
+    .await?;
+
+    // Index 6: Free space samples per volume over time
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_free_space_volume_time ON free_space_history(volume, timestamp);"
+    )
+    .execute(pool)
+    .await?;
+
+    // Index 7: Temperature samples per disk over time
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_temperature_disk_time ON temperature_history(disk, timestamp);"
+    )
+    .execute(pool)
+    .await?;
+
+    // Index 8: Per-process samples by time (moment lookups and range exports)
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_process_samples_time ON process_samples(timestamp);",
+    )
+    .execute(pool)
+    .await?;
+
+    // Index 9: Per-process rollup by name (one process over a range)
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_process_rollup_name ON process_rollup(name, slot);",
+    )
+    .execute(pool)
     .await?;
 
     println!("[DB] Indexes created successfully");
-    println!("[DB] Database initialized successfully.");
 
-    Ok(pool)
+    // PRAGMA values can't be bound as parameters
+    sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Gets recorded disk stats within a time range, oldest first
+pub async fn get_disk_stats_history<'c, E>(
+    executor: E,
+    start: f64,
+    end: f64,
+) -> Result<Vec<DiskStat>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query(
+        "SELECT timestamp, read_bytes, write_bytes, read_speed, write_speed, read_iops, write_iops,
+                idle_time, queue_depth, read_latency_ms, write_latency_ms, monotonic_offset
+         FROM disk_stats WHERE timestamp BETWEEN ? AND ? ORDER BY timestamp",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(executor)
+    .await?;
+
+    rows.iter().map(disk_stat_from_row).collect()
+}
+
+/// Maps a disk_stats row (selected with the column list above) to a DiskStat
+fn disk_stat_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<DiskStat, sqlx::Error> {
+    use sqlx::Row;
+
+    Ok(DiskStat {
+        timestamp: row.try_get("timestamp")?,
+        read_bytes: row.try_get::<i64, _>("read_bytes")? as u64,
+        write_bytes: row.try_get::<i64, _>("write_bytes")? as u64,
+        read_speed: row.try_get::<i64, _>("read_speed")? as u64,
+        write_speed: row.try_get::<i64, _>("write_speed")? as u64,
+        idle_time: row.try_get("idle_time")?,
+        queue_depth: row.try_get("queue_depth")?,
+        read_iops: row.try_get("read_iops")?,
+        write_iops: row.try_get("write_iops")?,
+        read_latency_ms: row.try_get("read_latency_ms")?,
+        write_latency_ms: row.try_get("write_latency_ms")?,
+        monotonic_offset: row.try_get("monotonic_offset")?,
+    })
+}
+
+/// Averages and peaks of the per-second samples within a time range
+pub async fn get_range_aggregate<'c, E>(
+    executor: E,
+    start: f64,
+    end: f64,
+) -> Result<RangeAggregate, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    use sqlx::Row;
+
+    let row = sqlx::query(
+        "SELECT COUNT(*) AS sample_count, AVG(read_speed) AS avg_read, AVG(write_speed) AS avg_write,
+                MAX(read_speed) AS max_read, MAX(write_speed) AS max_write, AVG(idle_time) AS avg_idle
+         FROM disk_stats WHERE timestamp BETWEEN ? AND ?",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(RangeAggregate {
+        sample_count: row.try_get::<i64, _>("sample_count")? as u64,
+        avg_read_speed: row.try_get::<Option<f64>, _>("avg_read")?.unwrap_or(0.0),
+        avg_write_speed: row.try_get::<Option<f64>, _>("avg_write")?.unwrap_or(0.0),
+        max_read_speed: row.try_get::<Option<i64>, _>("max_read")?.unwrap_or(0) as u64,
+        max_write_speed: row.try_get::<Option<i64>, _>("max_write")?.unwrap_or(0) as u64,
+        avg_idle_time: row.try_get::<Option<f64>, _>("avg_idle")?.unwrap_or(100.0),
+    })
 }
 
-pub async fn insert_stats_batch(
+/// Adds a column to an existing table unless it is already present
+async fn add_column_if_missing(
     pool: &Pool<Sqlite>,
-    stats: &[DiskStat],
-) -> Result<(), sqlx::Error> {
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<bool, sqlx::Error> {
+    let columns =
+        sqlx::query_as::<_, (String,)>(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await?;
+
+    if !columns.iter().any(|(name,)| name == column) {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(pool)
+        .await?;
+        println!("[DB] Added column {}.{}", table, column);
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+pub async fn insert_stats_batch<'c, E>(executor: E, stats: &[DiskStat]) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if stats.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO disk_stats (timestamp, read_bytes, write_bytes, read_speed, write_speed, \
+         read_iops, write_iops, idle_time, queue_depth, read_latency_ms, write_latency_ms, monotonic_offset) "
+    );
+
+    query_builder.push_values(stats, |mut b, stat| {
+        b.push_bind(stat.timestamp)
+            .push_bind(stat.read_bytes as i64)
+            .push_bind(stat.write_bytes as i64)
+            .push_bind(stat.read_speed as i64)
+            .push_bind(stat.write_speed as i64)
+            .push_bind(stat.read_iops)
+            .push_bind(stat.write_iops)
+            .push_bind(stat.idle_time)
+            .push_bind(stat.queue_depth)
+            .push_bind(stat.read_latency_ms)
+            .push_bind(stat.write_latency_ms)
+            .push_bind(stat.monotonic_offset);
+    });
+
+    let query = query_builder.build();
+    query.execute(executor).await?;
+
+    Ok(())
+}
+
+pub async fn insert_physical_disk_stats_batch<'c, E>(
+    executor: E,
+    stats: &[PhysicalDiskStat],
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
     if stats.is_empty() {
         return Ok(());
     }
 
     let mut query_builder = sqlx::QueryBuilder::new(
-        "INSERT INTO disk_stats (timestamp, read_bytes, write_bytes, read_speed, write_speed) "
+        "INSERT INTO physical_disk_stats (timestamp, disk, read_speed, write_speed, idle_time, queue_depth) "
     );
 
     query_builder.push_values(stats, |mut b, stat| {
         b.push_bind(stat.timestamp)
-         .push_bind(stat.read_bytes as i64)
-         .push_bind(stat.write_bytes as i64)
-         .push_bind(stat.read_speed as i64)
-         .push_bind(stat.write_speed as i64);
+            .push_bind(&stat.disk)
+            .push_bind(stat.read_speed as i64)
+            .push_bind(stat.write_speed as i64)
+            .push_bind(stat.idle_time)
+            .push_bind(stat.queue_depth);
     });
 
     let query = query_builder.build();
-    query.execute(pool).await?;
+    query.execute(executor).await?;
+
+    Ok(())
+}
+
+/// Gets per physical disk samples within a time range
+pub async fn get_physical_disk_stats<'c, E>(
+    executor: E,
+    start: f64,
+    end: f64,
+) -> Result<Vec<PhysicalDiskStat>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query_as::<_, (f64, String, i64, i64, f64, f64)>(
+        "SELECT timestamp, disk, read_speed, write_speed, idle_time, queue_depth
+         FROM physical_disk_stats WHERE timestamp BETWEEN ? AND ? ORDER BY timestamp",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(timestamp, disk, read_speed, write_speed, idle_time, queue_depth)| PhysicalDiskStat {
+                timestamp,
+                disk,
+                read_speed: read_speed as u64,
+                write_speed: write_speed as u64,
+                idle_time,
+                queue_depth,
+            },
+        )
+        .collect())
+}
+
+pub async fn insert_free_space_samples<'c, E>(
+    executor: E,
+    samples: &[FreeSpaceSample],
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if samples.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO free_space_history (timestamp, volume, total_bytes, free_bytes) ",
+    );
+
+    query_builder.push_values(samples, |mut b, sample| {
+        b.push_bind(sample.timestamp)
+            .push_bind(&sample.volume)
+            .push_bind(sample.total_bytes as i64)
+            .push_bind(sample.free_bytes as i64);
+    });
+
+    query_builder.build().execute(executor).await?;
+
+    Ok(())
+}
+
+/// Gets free space samples within a time range, optionally for a single volume
+pub async fn get_free_space_history<'c, E>(
+    executor: E,
+    start: f64,
+    end: f64,
+    volume: Option<&str>,
+) -> Result<Vec<FreeSpaceSample>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query_as::<_, (f64, String, i64, i64)>(
+        "SELECT timestamp, volume, total_bytes, free_bytes
+         FROM free_space_history
+         WHERE timestamp BETWEEN ? AND ? AND (? IS NULL OR volume = ?)
+         ORDER BY volume, timestamp",
+    )
+    .bind(start)
+    .bind(end)
+    .bind(volume)
+    .bind(volume)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(timestamp, volume, total_bytes, free_bytes)| FreeSpaceSample {
+                timestamp,
+                volume,
+                total_bytes: total_bytes as u64,
+                free_bytes: free_bytes as u64,
+            },
+        )
+        .collect())
+}
+
+pub async fn insert_temperature_samples<'c, E>(
+    executor: E,
+    samples: &[TemperatureSample],
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if samples.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO temperature_history (timestamp, disk, temperature_c) ",
+    );
+
+    query_builder.push_values(samples, |mut b, sample| {
+        b.push_bind(sample.timestamp)
+            .push_bind(&sample.disk)
+            .push_bind(sample.temperature_c);
+    });
+
+    query_builder.build().execute(executor).await?;
+
+    Ok(())
+}
+
+/// Adds collected seconds per day (day = days since the Unix epoch)
+pub async fn add_coverage<'c, E>(
+    executor: E,
+    days: &std::collections::HashMap<i64, f64>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if days.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new("INSERT INTO monitor_coverage (day, seconds) ");
+
+    query_builder.push_values(days, |mut b, (day, seconds)| {
+        b.push_bind(*day).push_bind(*seconds);
+    });
+
+    query_builder.push(
+        " ON CONFLICT(day) DO UPDATE SET seconds = monitor_coverage.seconds + excluded.seconds",
+    );
 
+    query_builder.build().execute(executor).await?;
+
+    Ok(())
+}
+
+/// Collected seconds per day for days in first_day..=last_day
+pub async fn get_coverage(
+    pool: &Pool<Sqlite>,
+    first_day: i64,
+    last_day: i64,
+) -> Result<Vec<(i64, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (i64, f64)>(
+        "SELECT day, seconds FROM monitor_coverage WHERE day BETWEEN ? AND ? ORDER BY day",
+    )
+    .bind(first_day)
+    .bind(last_day)
+    .fetch_all(pool)
+    .await
+}
+
+/// Process name -> user-assigned category
+pub async fn get_process_categories(
+    pool: &Pool<Sqlite>,
+) -> Result<std::collections::HashMap<String, String>, sqlx::Error> {
+    let rows =
+        sqlx::query_as::<_, (String, String)>("SELECT name, category FROM process_categories")
+            .fetch_all(pool)
+            .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Assigns `name` to `category`, or removes its category when None
+pub async fn set_process_category<'c, E>(
+    executor: E,
+    name: &str,
+    category: Option<&str>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    match category {
+        Some(category) => {
+            sqlx::query(
+                "INSERT INTO process_categories (name, category) VALUES (?, ?)
+                 ON CONFLICT(name) DO UPDATE SET category = excluded.category",
+            )
+            .bind(name)
+            .bind(category)
+            .execute(executor)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM process_categories WHERE name = ?")
+                .bind(name)
+                .execute(executor)
+                .await?;
+        }
+    }
     Ok(())
 }
 
+/// Gets temperature samples within a time range, optionally for a single disk
+pub async fn get_temperature_history<'c, E>(
+    executor: E,
+    start: f64,
+    end: f64,
+    disk: Option<&str>,
+) -> Result<Vec<TemperatureSample>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query_as::<_, (f64, String, f64)>(
+        "SELECT timestamp, disk, temperature_c
+         FROM temperature_history
+         WHERE timestamp BETWEEN ? AND ? AND (? IS NULL OR disk = ?)
+         ORDER BY disk, timestamp",
+    )
+    .bind(start)
+    .bind(end)
+    .bind(disk)
+    .bind(disk)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(timestamp, disk, temperature_c)| TemperatureSample {
+            timestamp,
+            disk,
+            temperature_c,
+        })
+        .collect())
+}
+
+/// Deletes free space samples older than `days` (kept much longer than the
+/// per-second stats so trends over weeks and months stay visible)
+pub async fn prune_free_space_history(pool: &Pool<Sqlite>, days: u64) -> Result<u64, sqlx::Error> {
+    let cutoff = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+        - (days as f64 * 24.0 * 3600.0);
+
+    let result = sqlx::query("DELETE FROM free_space_history WHERE timestamp < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Tables that grow with monitoring time
+pub const DATA_TABLES: &[&str] = &[
+    "disk_stats",
+    "physical_disk_stats",
+    "process_history",
+    "timeline_events",
+    "free_space_history",
+    "temperature_history",
+    "process_samples",
+    "process_rollup",
+];
+
+/// Row count per data table
+pub async fn get_table_row_counts(pool: &Pool<Sqlite>) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let mut counts = Vec::with_capacity(DATA_TABLES.len());
+    for table in DATA_TABLES {
+        // Table names come from the constant list above, never from input
+        let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await?;
+        counts.push((table.to_string(), count));
+    }
+    Ok(counts)
+}
+
+/// Timestamp of the oldest per-second sample (None when empty)
+pub async fn get_oldest_disk_stat(pool: &Pool<Sqlite>) -> Result<Option<f64>, sqlx::Error> {
+    let (oldest,): (Option<f64>,) = sqlx::query_as("SELECT MIN(timestamp) FROM disk_stats")
+        .fetch_one(pool)
+        .await?;
+    Ok(oldest)
+}
+
+/// Stores new all-time peaks (a row is only replaced by a higher speed)
+pub async fn upsert_speed_peaks<'c, E>(executor: E, peaks: &[SpeedPeak]) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if peaks.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder =
+        sqlx::QueryBuilder::new("INSERT INTO speed_peaks (direction, speed, timestamp, process) ");
+
+    query_builder.push_values(peaks, |mut b, peak| {
+        b.push_bind(&peak.direction)
+            .push_bind(peak.speed as i64)
+            .push_bind(peak.timestamp)
+            .push_bind(&peak.process);
+    });
+
+    query_builder.push(
+        " ON CONFLICT(direction) DO UPDATE SET
+          speed = excluded.speed,
+          timestamp = excluded.timestamp,
+          process = excluded.process
+          WHERE excluded.speed > speed_peaks.speed",
+    );
+
+    query_builder.build().execute(executor).await?;
+
+    Ok(())
+}
+
+/// All-time peaks, read first
+pub async fn get_speed_peaks(pool: &Pool<Sqlite>) -> Result<Vec<SpeedPeak>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, i64, f64, Option<String>)>(
+        "SELECT direction, speed, timestamp, process FROM speed_peaks ORDER BY direction",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(direction, speed, timestamp, process)| SpeedPeak {
+            direction,
+            speed: speed as u64,
+            timestamp,
+            process,
+        })
+        .collect())
+}
+
+/// Reads a persisted setting (None when never set)
+pub async fn get_setting(pool: &Pool<Sqlite>, key: &str) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM app_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|(value,)| value))
+}
+
+pub async fn set_setting<'c, E>(executor: E, key: &str, value: &str) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    sqlx::query(
+        "INSERT INTO app_settings (key, value) VALUES (?, ?)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Schema version stored in the database file and the linked SQLite version
+pub async fn get_schema_info(pool: &Pool<Sqlite>) -> Result<(i64, String), sqlx::Error> {
+    let (schema_version,): (i64,) = sqlx::query_as("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    let (sqlite_version,): (String,) = sqlx::query_as("SELECT sqlite_version()")
+        .fetch_one(pool)
+        .await?;
+    Ok((schema_version, sqlite_version))
+}
+
 // get_max_session_totals removed as it's no longer used for recovery.
 // We instead rely on periodic delta flushes to process_history.
 
@@ -132,22 +864,15 @@ pub async fn reset_database_with_size(
     app_handle: &tauri::AppHandle,
 ) -> Result<(u64, u64), Box<dyn std::error::Error + Send + Sync>> {
     // Get database path
-    let app_data_dir = app_handle.path().app_data_dir()?;
-    let db_path = app_data_dir.join("drive_analytics.db");
-    let wal_path = app_data_dir.join("drive_analytics.db-wal");
-    let shm_path = app_data_dir.join("drive_analytics.db-shm");
+    let db_path = data_dir::db_path(app_handle)?;
+    let wal_path = db_path.with_extension("db-wal");
+    let shm_path = db_path.with_extension("db-shm");
 
     // Get size before reset
     let size_before = get_db_total_size(&db_path, &wal_path, &shm_path)?;
 
     // Reset the database
-    clear_disk_stats(pool)
-        .await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-    // Reset process history
-    sqlx::query("DELETE FROM process_history")
-        .execute(pool)
+    clear_recorded_data(pool)
         .await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
@@ -178,7 +903,7 @@ pub async fn reset_database_with_size(
 }
 
 /// Gets the total database size including main file and WAL files
-fn get_db_total_size(
+pub(crate) fn get_db_total_size(
     db_path: &std::path::Path,
     wal_path: &std::path::Path,
     shm_path: &std::path::Path,
@@ -207,10 +932,9 @@ fn get_db_total_size(
 pub fn get_database_size(
     app_handle: &tauri::AppHandle,
 ) -> Result<(u64, u64), Box<dyn std::error::Error + Send + Sync>> {
-    let app_data_dir = app_handle.path().app_data_dir()?;
-    let db_path = app_data_dir.join("drive_analytics.db");
-    let wal_path = app_data_dir.join("drive_analytics.db-wal");
-    let shm_path = app_data_dir.join("drive_analytics.db-shm");
+    let db_path = data_dir::db_path(app_handle)?;
+    let wal_path = db_path.with_extension("db-wal");
+    let shm_path = db_path.with_extension("db-shm");
 
     let size = get_db_total_size(&db_path, &wal_path, &shm_path)?;
 
@@ -220,6 +944,38 @@ pub fn get_database_size(
 
 pub async fn clear_disk_stats(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
     sqlx::query("DELETE FROM disk_stats").execute(pool).await?;
+    sqlx::query("DELETE FROM physical_disk_stats")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM free_space_history")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM temperature_history")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM process_samples")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Clears the time series, process history, all-time peaks and the timeline
+/// (settings are kept)
+pub async fn clear_recorded_data(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    clear_disk_stats(pool).await?;
+    sqlx::query("DELETE FROM process_history")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM process_rollup")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM speed_peaks").execute(pool).await?;
+    sqlx::query("DELETE FROM monitor_coverage")
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM timeline_events")
+        .execute(pool)
+        .await?;
     Ok(())
 }
 
@@ -239,36 +995,515 @@ pub async fn get_process_history(
     Ok(map)
 }
 
-pub async fn update_process_history(
+/// Merges the recorded rows of each (old, new) process name pair into the new
+/// name: all-time totals and first/last seen, the time series, the
+/// per-process rollup and the category assignment. No new name may be an old one.
+pub async fn rename_processes(
     pool: &Pool<Sqlite>,
-    stats: std::collections::HashMap<String, (u64, u64)>,
+    renames: &[(String, String)],
 ) -> Result<(), sqlx::Error> {
+    if renames.is_empty() {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "CREATE TEMP TABLE IF NOT EXISTS process_renames (old TEXT PRIMARY KEY, new TEXT NOT NULL)",
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM process_renames")
+        .execute(&mut *tx)
+        .await?;
+    for chunk in renames.chunks(400) {
+        let mut query_builder = sqlx::QueryBuilder::new("INSERT INTO process_renames (old, new) ");
+        query_builder.push_values(chunk, |mut b, (old, new)| {
+            b.push_bind(old).push_bind(new);
+        });
+        query_builder.build().execute(&mut *tx).await?;
+    }
+
+    sqlx::query(
+        "INSERT INTO process_history (name, read_bytes, write_bytes, command_line, first_seen, last_seen)
+         SELECT r.new, SUM(h.read_bytes), SUM(h.write_bytes), CASE WHEN COUNT(*) = 1 THEN MAX(h.command_line) END, MIN(h.first_seen), MAX(h.last_seen)
+         FROM process_history h JOIN process_renames r ON h.name = r.old
+         WHERE true GROUP BY r.new
+         ON CONFLICT(name) DO UPDATE SET
+           read_bytes = read_bytes + excluded.read_bytes,
+           write_bytes = write_bytes + excluded.write_bytes,
+           command_line = COALESCE(command_line, excluded.command_line),
+           first_seen = COALESCE(MIN(first_seen, excluded.first_seen), first_seen, excluded.first_seen),
+           last_seen = COALESCE(MAX(last_seen, excluded.last_seen), last_seen, excluded.last_seen)"
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "INSERT INTO process_rollup (slot, name, read_bytes, write_bytes)
+         SELECT p.slot, r.new, SUM(p.read_bytes), SUM(p.write_bytes)
+         FROM process_rollup p JOIN process_renames r ON p.name = r.old
+         WHERE true GROUP BY p.slot, r.new
+         ON CONFLICT(slot, name) DO UPDATE SET
+           read_bytes = read_bytes + excluded.read_bytes,
+           write_bytes = write_bytes + excluded.write_bytes",
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE process_samples SET name = (SELECT new FROM process_renames WHERE old = process_samples.name)
+         WHERE name IN (SELECT old FROM process_renames)"
+    )
+    .execute(&mut *tx)
+    .await?;
+    // Category names compare case-insensitively, so only real renames move
+    sqlx::query(
+        "INSERT OR IGNORE INTO process_categories (name, category)
+         SELECT r.new, c.category FROM process_categories c JOIN process_renames r ON c.name = r.old
+         WHERE lower(r.old) <> lower(r.new)",
+    )
+    .execute(&mut *tx)
+    .await?;
+    for table in ["process_history", "process_rollup"] {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE name IN (SELECT old FROM process_renames)",
+            table
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query(
+        "DELETE FROM process_categories
+         WHERE name IN (SELECT old FROM process_renames WHERE lower(old) <> lower(new))",
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DROP TABLE process_renames")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// Gets a page of process_history ordered by name.
+///
+/// The cursor is the last name of the previous page (keyset pagination), so
+/// pages stay stable while the monitor keeps inserting and updating rows.
+pub async fn get_process_history_chunk(
+    pool: &Pool<Sqlite>,
+    cursor: Option<&str>,
+    limit: u32,
+) -> Result<ProcessHistoryChunk, sqlx::Error> {
+    // Fetch one extra row to know whether another page exists
+    let rows = sqlx::query_as::<_, (String, Option<String>, i64, i64, Option<f64>, Option<f64>)>(
+        "SELECT name, command_line, read_bytes, write_bytes, first_seen, last_seen FROM process_history
+         WHERE ? IS NULL OR name > ?
+         ORDER BY name
+         LIMIT ?",
+    )
+    .bind(cursor)
+    .bind(cursor)
+    .bind(limit as i64 + 1)
+    .fetch_all(pool)
+    .await?;
+
+    let (total_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM process_history")
+        .fetch_one(pool)
+        .await?;
+
+    let has_more = rows.len() > limit as usize;
+    let rows: Vec<ProcessHistoryEntry> = rows
+        .into_iter()
+        .take(limit as usize)
+        .map(
+            |(name, command_line, read, write, first_seen, last_seen)| ProcessHistoryEntry {
+                name,
+                command_line,
+                read_bytes: read as u64,
+                write_bytes: write as u64,
+                total_bytes: (read as u64).saturating_add(write as u64),
+                first_seen,
+                last_seen,
+            },
+        )
+        .collect();
+
+    let next_cursor = if has_more {
+        rows.last().map(|row| row.name.clone())
+    } else {
+        None
+    };
+
+    Ok(ProcessHistoryChunk {
+        rows,
+        next_cursor,
+        total_count: total_count as u64,
+    })
+}
+
+/// Adds one flush interval of per-process deltas to the all-time totals; names
+/// with I/O get `timestamp` as their last-seen time (and first-seen if new)
+pub async fn update_process_history<'c, E>(
+    executor: E,
+    timestamp: f64,
+    stats: &std::collections::HashMap<String, (u64, u64)>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
     if stats.is_empty() {
         return Ok(());
     }
 
     let mut query_builder = sqlx::QueryBuilder::new(
-        "INSERT INTO process_history (name, read_bytes, write_bytes) "
+        "INSERT INTO process_history (name, read_bytes, write_bytes, first_seen, last_seen) ",
     );
 
     query_builder.push_values(stats.iter(), |mut b, (name, (read, write))| {
+        let seen = (*read > 0 || *write > 0).then_some(timestamp);
         b.push_bind(name)
-         .push_bind(*read as i64)
-         .push_bind(*write as i64);
+            .push_bind(*read as i64)
+            .push_bind(*write as i64)
+            .push_bind(seen)
+            .push_bind(seen);
     });
 
     query_builder.push(
         " ON CONFLICT(name) DO UPDATE SET
           read_bytes = read_bytes + excluded.read_bytes,
-          write_bytes = write_bytes + excluded.write_bytes"
+          write_bytes = write_bytes + excluded.write_bytes,
+          first_seen = COALESCE(first_seen, excluded.first_seen),
+          last_seen = COALESCE(excluded.last_seen, last_seen)",
     );
 
     let query = query_builder.build();
-    query.execute(pool).await?;
+    query.execute(executor).await?;
 
     Ok(())
 }
 
+/// Stores the latest command line per process name (rows are created if the
+/// name has no bytes recorded yet)
+pub async fn update_process_command_lines<'c, E>(
+    executor: E,
+    command_lines: &crate::process_monitor::TickCommandLines,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if command_lines.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder =
+        sqlx::QueryBuilder::new("INSERT INTO process_history (name, command_line) ");
+    query_builder.push_values(command_lines.iter(), |mut b, (name, command_line)| {
+        b.push_bind(&**name).push_bind(&**command_line);
+    });
+    query_builder.push(" ON CONFLICT(name) DO UPDATE SET command_line = excluded.command_line");
+
+    query_builder.build().execute(executor).await?;
+    Ok(())
+}
+
+/// Forgets every stored command line (they are filled in again as processes run)
+pub async fn clear_process_command_lines(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE process_history SET command_line = NULL WHERE command_line IS NOT NULL")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Records one flush interval of per-process deltas (the per-process time series)
+pub async fn insert_process_samples<'c, E>(
+    executor: E,
+    timestamp: f64,
+    deltas: &std::collections::HashMap<String, (u64, u64)>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    if deltas.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO process_samples (timestamp, name, read_bytes, write_bytes) ",
+    );
+
+    query_builder.push_values(deltas.iter(), |mut b, (name, (read, write))| {
+        b.push_bind(timestamp)
+            .push_bind(name)
+            .push_bind(*read as i64)
+            .push_bind(*write as i64);
+    });
+
+    query_builder.build().execute(executor).await?;
+
+    Ok(())
+}
+
+/// The all-time row of one process name
+pub async fn get_process_history_entry(
+    pool: &Pool<Sqlite>,
+    name: &str,
+) -> Result<Option<ProcessHistoryEntry>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String, Option<String>, i64, i64, Option<f64>, Option<f64>)>(
+        "SELECT name, command_line, read_bytes, write_bytes, first_seen, last_seen
+         FROM process_history WHERE name = ?",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(
+        |(name, command_line, read, write, first_seen, last_seen)| ProcessHistoryEntry {
+            name,
+            command_line,
+            read_bytes: read as u64,
+            write_bytes: write as u64,
+            total_bytes: (read as u64).saturating_add(write as u64),
+            first_seen,
+            last_seen,
+        },
+    ))
+}
+
+/// Adds one flush interval of per-process deltas to the rollup slot containing
+/// `timestamp` (slot = `ROLLUP_SLOT_SECS` periods since the Unix epoch)
+pub async fn add_process_rollup<'c, E>(
+    executor: E,
+    timestamp: f64,
+    deltas: &std::collections::HashMap<String, (u64, u64)>,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let active: Vec<(&String, &(u64, u64))> = deltas
+        .iter()
+        .filter(|(_, (r, w))| *r > 0 || *w > 0)
+        .collect();
+    if active.is_empty() {
+        return Ok(());
+    }
+
+    let slot = (timestamp / ROLLUP_SLOT_SECS as f64).floor() as i64;
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO process_rollup (slot, name, read_bytes, write_bytes) ",
+    );
+
+    query_builder.push_values(active, |mut b, (name, (read, write))| {
+        b.push_bind(slot)
+            .push_bind(name)
+            .push_bind(*read as i64)
+            .push_bind(*write as i64);
+    });
+
+    query_builder.push(
+        " ON CONFLICT(slot, name) DO UPDATE SET
+          read_bytes = process_rollup.read_bytes + excluded.read_bytes,
+          write_bytes = process_rollup.write_bytes + excluded.write_bytes",
+    );
+
+    query_builder.build().execute(executor).await?;
+    Ok(())
+}
+
+/// (bucket index, read, write) of one process name in the rollup slots
+/// touching `start..=end`, summed into buckets of `bucket_secs` shifted by
+/// the UTC offset (so day buckets are local days), oldest first
+pub async fn get_process_series<'c, E>(
+    executor: E,
+    name: &str,
+    start: f64,
+    end: f64,
+    bucket_secs: i64,
+    utc_offset_secs: i64,
+) -> Result<Vec<(i64, u64, u64)>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query_as::<_, (i64, i64, i64)>(
+        // Slots are positive, so integer division rounds down
+        "SELECT (slot * ? + ?) / ? AS bucket, SUM(read_bytes), SUM(write_bytes)
+         FROM process_rollup WHERE name = ? AND slot BETWEEN ? AND ?
+         GROUP BY bucket ORDER BY bucket",
+    )
+    .bind(ROLLUP_SLOT_SECS)
+    .bind(utc_offset_secs)
+    .bind(bucket_secs)
+    .bind(name)
+    .bind((start / ROLLUP_SLOT_SECS as f64).floor() as i64)
+    .bind((end / ROLLUP_SLOT_SECS as f64).floor() as i64)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(bucket, read, write)| (bucket, read as u64, write as u64))
+        .collect())
+}
+
+/// Gets per-process samples within a time range, oldest first
+pub async fn get_process_samples<'c, E>(
+    executor: E,
+    start: f64,
+    end: f64,
+) -> Result<Vec<ProcessSample>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query_as::<_, (f64, String, i64, i64)>(
+        "SELECT timestamp, name, read_bytes, write_bytes
+         FROM process_samples WHERE timestamp BETWEEN ? AND ?
+         ORDER BY timestamp, name",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(timestamp, name, read_bytes, write_bytes)| ProcessSample {
+            timestamp,
+            name,
+            read_bytes: read_bytes as u64,
+            write_bytes: write_bytes as u64,
+        })
+        .collect())
+}
+
+/// Samples of the first flush interval ending at or after `timestamp`
+pub async fn get_covering_process_samples<'c, E>(
+    executor: E,
+    timestamp: f64,
+) -> Result<Vec<ProcessSample>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query_as::<_, (f64, String, i64, i64)>(
+        "SELECT timestamp, name, read_bytes, write_bytes
+         FROM process_samples
+         WHERE timestamp = (SELECT MIN(timestamp) FROM process_samples WHERE timestamp >= ?)
+         ORDER BY name",
+    )
+    .bind(timestamp)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(timestamp, name, read_bytes, write_bytes)| ProcessSample {
+            timestamp,
+            name,
+            read_bytes: read_bytes as u64,
+            write_bytes: write_bytes as u64,
+        })
+        .collect())
+}
+
+/// Opens a timeline event and returns its id (end_time stays NULL until closed)
+pub async fn insert_timeline_event(
+    pool: &Pool<Sqlite>,
+    kind: &str,
+    start_time: f64,
+    details: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let result =
+        sqlx::query("INSERT INTO timeline_events (kind, start_time, details) VALUES (?, ?, ?)")
+            .bind(kind)
+            .bind(start_time)
+            .bind(details)
+            .execute(pool)
+            .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Records a timeline event that has already ended and returns its id
+pub async fn insert_completed_timeline_event(
+    pool: &Pool<Sqlite>,
+    kind: &str,
+    start_time: f64,
+    end_time: f64,
+    details: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO timeline_events (kind, start_time, end_time, details) VALUES (?, ?, ?, ?)",
+    )
+    .bind(kind)
+    .bind(start_time)
+    .bind(end_time)
+    .bind(details)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Closes a previously opened timeline event
+pub async fn close_timeline_event(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    end_time: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE timeline_events SET end_time = ? WHERE id = ?")
+        .bind(end_time)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Gets timeline events overlapping the given time range
+pub async fn get_timeline_events(
+    pool: &Pool<Sqlite>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<TimelineEvent>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (i64, String, f64, Option<f64>, Option<String>)>(
+        "SELECT id, kind, start_time, end_time, details FROM timeline_events
+         WHERE start_time <= ? AND (end_time IS NULL OR end_time >= ?)
+         ORDER BY start_time",
+    )
+    .bind(end)
+    .bind(start)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, kind, start_time, end_time, details)| TimelineEvent {
+            id,
+            kind,
+            start_time,
+            end_time,
+            details,
+        })
+        .collect())
+}
+
+/// Most recent timeline event of a kind
+pub async fn get_latest_timeline_event(
+    pool: &Pool<Sqlite>,
+    kind: &str,
+) -> Result<Option<TimelineEvent>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (i64, String, f64, Option<f64>, Option<String>)>(
+        "SELECT id, kind, start_time, end_time, details FROM timeline_events
+         WHERE kind = ? ORDER BY start_time DESC LIMIT 1",
+    )
+    .bind(kind)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(
+        row.map(|(id, kind, start_time, end_time, details)| TimelineEvent {
+            id,
+            kind,
+            start_time,
+            end_time,
+            details,
+        }),
+    )
+}
+
 /// Eski verileri temizle (belirtilen gün sayısından eski)
 /// Varsayılan: 7 gün
 pub async fn cleanup_old_data(pool: &Pool<Sqlite>, days: u64) -> Result<u64, sqlx::Error> {
@@ -283,7 +1518,25 @@ pub async fn cleanup_old_data(pool: &Pool<Sqlite>, days: u64) -> Result<u64, sql
         .execute(pool)
         .await?;
 
-    let deleted = result.rows_affected();
+    let disk_result = sqlx::query("DELETE FROM physical_disk_stats WHERE timestamp < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+    let temperature_result = sqlx::query("DELETE FROM temperature_history WHERE timestamp < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+    let process_result = sqlx::query("DELETE FROM process_samples WHERE timestamp < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+    let deleted = result.rows_affected()
+        + disk_result.rows_affected()
+        + temperature_result.rows_affected()
+        + process_result.rows_affected();
     if deleted > 0 {
         println!(
             "[DB] Cleaned up {} old records (older than {} days)",
@@ -293,3 +1546,383 @@ pub async fn cleanup_old_data(pool: &Pool<Sqlite>, days: u64) -> Result<u64, sql
 
     Ok(deleted)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> Pool<Sqlite> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_schema(&pool).await.unwrap();
+        pool
+    }
+
+    fn stat(timestamp: f64, read_speed: u64) -> DiskStat {
+        DiskStat {
+            read_bytes: read_speed,
+            read_speed,
+            idle_time: 90.0,
+            queue_depth: 0.5,
+            read_iops: 10.0,
+            write_iops: 2.0,
+            read_latency_ms: 1.5,
+            write_latency_ms: 3.0,
+            monotonic_offset: timestamp - 10.0,
+            ..DiskStat::idle(timestamp)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_schema_is_idempotent() {
+        let pool = test_pool().await;
+        create_schema(&pool).await.unwrap();
+
+        let (schema_version, _) = get_schema_info(&pool).await.unwrap();
+        assert_eq!(schema_version, SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_coverage_accumulates_per_day() {
+        let pool = test_pool().await;
+        add_coverage(&pool, &[(5, 10.0), (6, 1.0)].into_iter().collect())
+            .await
+            .unwrap();
+        add_coverage(&pool, &[(5, 2.5)].into_iter().collect())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_coverage(&pool, 0, 10).await.unwrap(),
+            vec![(5, 12.5), (6, 1.0)]
+        );
+        assert_eq!(get_coverage(&pool, 6, 6).await.unwrap(), vec![(6, 1.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_process_category_assignment() {
+        let pool = test_pool().await;
+        set_process_category(&pool, "chrome.exe", Some("Browsers"))
+            .await
+            .unwrap();
+        set_process_category(&pool, "Chrome.EXE", Some("Web"))
+            .await
+            .unwrap();
+        set_process_category(&pool, "game.exe", Some("Games"))
+            .await
+            .unwrap();
+        set_process_category(&pool, "GAME.exe", None).await.unwrap();
+
+        let categories = get_process_categories(&pool).await.unwrap();
+        assert_eq!(categories.len(), 1);
+        assert_eq!(categories["chrome.exe"], "Web");
+    }
+
+    #[tokio::test]
+    async fn test_process_series_buckets_hours_and_local_days() {
+        let pool = test_pool().await;
+        let midnight = 20_000.0 * 86400.0;
+        for (timestamp, write) in [
+            (midnight - 7200.0, 5),
+            (midnight - 1800.0, 10),
+            (midnight - 1700.0, 1),
+            (midnight + 60.0, 20),
+        ] {
+            let deltas = [
+                ("a.exe".to_string(), (0, write)),
+                ("b.exe".to_string(), (0, 0)),
+            ]
+            .into_iter()
+            .collect();
+            add_process_rollup(&pool, timestamp, &deltas).await.unwrap();
+        }
+
+        let hours = get_process_series(&pool, "a.exe", 0.0, f64::MAX, 3600, 0)
+            .await
+            .unwrap();
+        let last_hour = 20_000 * 24 - 1;
+        assert_eq!(
+            hours,
+            [
+                (last_hour - 1, 0, 5),
+                (last_hour, 0, 11),
+                (last_hour + 1, 0, 20)
+            ]
+        );
+
+        let utc = get_process_series(&pool, "a.exe", 0.0, f64::MAX, 86400, 0)
+            .await
+            .unwrap();
+        assert_eq!(utc, [(19_999, 0, 16), (20_000, 0, 20)]);
+        // One hour ahead of UTC, the half past eleven samples are already the next day
+        let ahead = get_process_series(&pool, "a.exe", 0.0, f64::MAX, 86400, 3600)
+            .await
+            .unwrap();
+        assert_eq!(ahead, [(19_999, 0, 5), (20_000, 0, 31)]);
+
+        let only_today = get_process_series(&pool, "a.exe", midnight, midnight + 60.0, 86400, 0)
+            .await
+            .unwrap();
+        assert_eq!(only_today, [(20_000, 0, 20)]);
+        assert!(get_process_series(&pool, "b.exe", 0.0, f64::MAX, 3600, 0)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_process_series_follows_half_hour_offsets() {
+        let pool = test_pool().await;
+        // Local midnight at UTC+5:30 is 18:30 UTC, in the middle of a UTC hour
+        let offset = 5 * 3600 + 1800;
+        let local_midnight = 20_000.0 * 86400.0 - offset as f64;
+        for (timestamp, write) in [(local_midnight - 600.0, 7), (local_midnight + 600.0, 3)] {
+            let deltas = [("a.exe".to_string(), (0, write))].into_iter().collect();
+            add_process_rollup(&pool, timestamp, &deltas).await.unwrap();
+        }
+
+        let days = get_process_series(&pool, "a.exe", 0.0, f64::MAX, 86400, offset)
+            .await
+            .unwrap();
+        assert_eq!(days, [(19_999, 0, 7), (20_000, 0, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_disk_stats_history_roundtrip() {
+        let pool = test_pool().await;
+        insert_stats_batch(&pool, &[stat(10.0, 100), stat(20.0, 200), stat(30.0, 300)])
+            .await
+            .unwrap();
+
+        let history = get_disk_stats_history(&pool, 15.0, 30.0).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].read_speed, 200);
+        assert_eq!(history[1].timestamp, 30.0);
+        assert_eq!(history[1].read_latency_ms, 1.5);
+        assert_eq!(history[1].write_iops, 2.0);
+        assert_eq!(history[1].monotonic_offset, 20.0);
+
+        let aggregate = get_range_aggregate(&pool, 0.0, 100.0).await.unwrap();
+        assert_eq!(aggregate.sample_count, 3);
+        assert_eq!(aggregate.max_read_speed, 300);
+        assert_eq!(aggregate.avg_read_speed, 200.0);
+    }
+
+    #[tokio::test]
+    async fn test_process_history_chunk_pagination() {
+        let pool = test_pool().await;
+        let stats = ["a.exe", "b.exe", "c.exe"]
+            .iter()
+            .map(|name| (name.to_string(), (1, 2)))
+            .collect();
+        update_process_history(&pool, 100.0, &stats).await.unwrap();
+
+        let first = get_process_history_chunk(&pool, None, 2).await.unwrap();
+        assert_eq!(first.total_count, 3);
+        assert_eq!(first.rows.len(), 2);
+        assert_eq!(first.next_cursor.as_deref(), Some("b.exe"));
+
+        let second = get_process_history_chunk(&pool, first.next_cursor.as_deref(), 2)
+            .await
+            .unwrap();
+        assert_eq!(second.rows.len(), 1);
+        assert_eq!(second.rows[0].name, "c.exe");
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_command_line_is_kept_with_process_totals() {
+        let pool = test_pool().await;
+        let stats = [("node.exe".to_string(), (1, 2))].into_iter().collect();
+        update_process_history(&pool, 100.0, &stats).await.unwrap();
+        for command_line in ["node build.js", "node server.js"] {
+            let lines = [("node.exe".into(), command_line.into())]
+                .into_iter()
+                .collect();
+            update_process_command_lines(&pool, &lines).await.unwrap();
+        }
+        update_process_history(&pool, 100.0, &stats).await.unwrap();
+
+        let chunk = get_process_history_chunk(&pool, None, 10).await.unwrap();
+        assert_eq!(chunk.rows.len(), 1);
+        assert_eq!(
+            chunk.rows[0].command_line.as_deref(),
+            Some("node server.js")
+        );
+        assert_eq!(chunk.rows[0].total_bytes, 6);
+    }
+
+    #[tokio::test]
+    async fn test_first_and_last_seen_follow_io() {
+        let pool = test_pool().await;
+        let busy = [("a.exe".to_string(), (1, 0))].into_iter().collect();
+        let idle = [("a.exe".to_string(), (0, 0))].into_iter().collect();
+        update_process_history(&pool, 100.0, &busy).await.unwrap();
+        update_process_history(&pool, 200.0, &busy).await.unwrap();
+        update_process_history(&pool, 300.0, &idle).await.unwrap();
+
+        let row = &get_process_history_chunk(&pool, None, 10)
+            .await
+            .unwrap()
+            .rows[0];
+        assert_eq!((row.first_seen, row.last_seen), (Some(100.0), Some(200.0)));
+    }
+
+    #[tokio::test]
+    async fn test_renamed_processes_merge_into_one_row() {
+        let pool = test_pool().await;
+        for (timestamp, name, write) in [
+            (100.0, "Code.exe", 1),
+            (200.0, "code.exe", 2),
+            (300.0, "app-1.exe", 4),
+        ] {
+            let stats = [(name.to_string(), (0, write))].into_iter().collect();
+            update_process_history(&pool, timestamp, &stats)
+                .await
+                .unwrap();
+            add_process_rollup(&pool, timestamp, &stats).await.unwrap();
+            insert_process_samples(&pool, timestamp, &stats)
+                .await
+                .unwrap();
+        }
+        set_process_category(&pool, "app-1.exe", Some("Games"))
+            .await
+            .unwrap();
+
+        let renames = [
+            ("Code.exe".to_string(), "code.exe".to_string()),
+            ("app-1.exe".to_string(), "app.exe".to_string()),
+        ];
+        rename_processes(&pool, &renames).await.unwrap();
+
+        let history = get_process_history(&pool).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history["code.exe"], (0, 3));
+        let code = get_process_history_entry(&pool, "code.exe")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (code.first_seen, code.last_seen),
+            (Some(100.0), Some(200.0))
+        );
+        let series = get_process_series(&pool, "code.exe", 0.0, 1000.0, 3600, 0)
+            .await
+            .unwrap();
+        assert_eq!(series, vec![(0, 0, 3)]);
+        let samples = get_process_samples(&pool, 0.0, 1000.0).await.unwrap();
+        assert!(samples
+            .iter()
+            .all(|s| s.name == "code.exe" || s.name == "app.exe"));
+        assert_eq!(
+            get_process_categories(&pool).await.unwrap()["app.exe"],
+            "Games"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_temperature_history_roundtrip() {
+        let pool = test_pool().await;
+        let sample = |timestamp: f64, disk: &str, temperature_c: f64| TemperatureSample {
+            timestamp,
+            disk: disk.to_string(),
+            temperature_c,
+        };
+        insert_temperature_samples(
+            &pool,
+            &[
+                sample(10.0, "C:", 41.0),
+                sample(10.0, "D:", 35.0),
+                sample(70.0, "C:", 48.5),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let c = get_temperature_history(&pool, 0.0, 100.0, Some("C:"))
+            .await
+            .unwrap();
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[1].temperature_c, 48.5);
+        assert_eq!(
+            get_temperature_history(&pool, 0.0, 50.0, None)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_speed_peaks_only_grow() {
+        let pool = test_pool().await;
+        let peak = |speed: u64, timestamp: f64| SpeedPeak {
+            direction: "write".to_string(),
+            speed,
+            timestamp,
+            process: Some("copy.exe".to_string()),
+        };
+        upsert_speed_peaks(&pool, &[peak(500, 1.0)]).await.unwrap();
+        upsert_speed_peaks(&pool, &[peak(300, 2.0)]).await.unwrap();
+
+        let peaks = get_speed_peaks(&pool).await.unwrap();
+        assert_eq!(peaks.len(), 1);
+        assert_eq!(peaks[0].speed, 500);
+        assert_eq!(peaks[0].timestamp, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_free_space_history_filters_by_volume() {
+        let pool = test_pool().await;
+        let sample = |timestamp: f64, volume: &str, free_bytes: u64| FreeSpaceSample {
+            timestamp,
+            volume: volume.to_string(),
+            total_bytes: 1000,
+            free_bytes,
+        };
+        insert_free_space_samples(
+            &pool,
+            &[
+                sample(10.0, "C:", 500),
+                sample(10.0, "D:", 900),
+                sample(20.0, "C:", 400),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let all = get_free_space_history(&pool, 0.0, 30.0, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+
+        let c = get_free_space_history(&pool, 0.0, 30.0, Some("C:"))
+            .await
+            .unwrap();
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[1].free_bytes, 400);
+    }
+
+    #[tokio::test]
+    async fn test_settings_roundtrip() {
+        let pool = test_pool().await;
+        assert_eq!(get_setting(&pool, "telemetry_enabled").await.unwrap(), None);
+
+        set_setting(&pool, "telemetry_enabled", "true")
+            .await
+            .unwrap();
+        set_setting(&pool, "telemetry_enabled", "false")
+            .await
+            .unwrap();
+        assert_eq!(
+            get_setting(&pool, "telemetry_enabled")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("false")
+        );
+    }
+}
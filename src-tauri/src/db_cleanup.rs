@@ -1,3 +1,5 @@
+use crate::db;
+use crate::storage::{Storage, StorageResult};
 use sqlx::{Pool, Sqlite};
 
 /// Data retention policy configuration
@@ -37,18 +39,66 @@ impl RetentionPolicy {
     }
 }
 
-/// Cleanup old data from disk_stats table based on retention policy
+/// app_settings key of the number of days of data kept
+pub const RETENTION_DAYS_KEY: &str = "retention_days";
+
+/// Longest retention period accepted (ten years)
+pub const MAX_RETENTION_DAYS: u64 = 3650;
+
+pub fn validate_retention_days(days: u64) -> Result<u64, String> {
+    if (1..=MAX_RETENTION_DAYS).contains(&days) {
+        Ok(days)
+    } else {
+        Err(format!(
+            "Retention must be between 1 and {} days",
+            MAX_RETENTION_DAYS
+        ))
+    }
+}
+
+/// The retention policy in effect: the stored period, or the default when
+/// none was saved (or it can't be read)
+pub async fn load_retention_policy(pool: &Pool<Sqlite>) -> RetentionPolicy {
+    let stored = db::get_setting(pool, RETENTION_DAYS_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse::<u64>().ok())
+        .and_then(|days| validate_retention_days(days).ok());
+    match stored {
+        Some(keep_days) => RetentionPolicy {
+            keep_days,
+            ..RetentionPolicy::default()
+        },
+        None => RetentionPolicy::default(),
+    }
+}
+
+pub async fn save_retention_days(pool: &Pool<Sqlite>, days: u64) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, RETENTION_DAYS_KEY, &days.to_string()).await
+}
+
+/// Drops the time series samples older than the stored retention period (the
+/// monitor's periodic prune; `cleanup_old_data` also clears the other tables)
+pub async fn prune_time_series(pool: &Pool<Sqlite>, store: &dyn Storage) -> StorageResult<u64> {
+    let policy = load_retention_policy(pool).await;
+    store.prune_older_than(policy.keep_days).await
+}
+
+/// Cleanup old data based on retention policy
 ///
 /// # Arguments
 /// * `pool` - SQLite connection pool
+/// * `store` - Backend holding the time series (SQLite or Parquet)
 /// * `policy` - Retention policy configuration
 ///
 /// # Returns
 /// * Number of records deleted
 pub async fn cleanup_old_data(
     pool: &Pool<Sqlite>,
+    store: &dyn Storage,
     policy: &RetentionPolicy,
-) -> Result<u64, sqlx::Error> {
+) -> StorageResult<u64> {
     // Calculate cutoff timestamp (everything older than this will be deleted)
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -57,26 +107,32 @@ pub async fn cleanup_old_data(
 
     let cutoff = now - (policy.keep_days as f64 * 86400.0);
 
-    // Get count of records that will be deleted
-    let count: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM disk_stats WHERE timestamp < ?"
-    )
-    .bind(cutoff)
-    .fetch_one(pool)
-    .await?;
+    // Per-second and per-disk samples live wherever the backend keeps them
+    let mut deleted = store.prune_older_than(policy.keep_days).await?;
 
-    // Delete old records
-    sqlx::query("DELETE FROM disk_stats WHERE timestamp < ?")
+    // Temperatures and the per-process series stay in SQLite with either backend
+    for table in ["temperature_history", "process_samples"] {
+        deleted += sqlx::query(&format!("DELETE FROM {} WHERE timestamp < ?", table))
+            .bind(cutoff)
+            .execute(pool)
+            .await?
+            .rows_affected();
+    }
+
+    // So does the per-process rollup (slots that ended before the cutoff)
+    deleted += sqlx::query("DELETE FROM process_rollup WHERE (slot + 1) * ? <= ?")
+        .bind(db::ROLLUP_SLOT_SECS)
         .bind(cutoff)
         .execute(pool)
-        .await?;
+        .await?
+        .rows_affected();
 
     println!(
         "[Cleanup] Deleted {} records older than {} days",
-        count.0, policy.keep_days
+        deleted, policy.keep_days
     );
 
-    Ok(count.0 as u64)
+    Ok(deleted)
 }
 
 /// Get count of records that would be deleted by cleanup
@@ -123,6 +179,13 @@ pub async fn analyze_database(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_retention_days_are_bounded() {
+        assert_eq!(validate_retention_days(7), Ok(7));
+        assert!(validate_retention_days(0).is_err());
+        assert!(validate_retention_days(MAX_RETENTION_DAYS + 1).is_err());
+    }
+
     #[test]
     fn test_retention_policy_default() {
         let policy = RetentionPolicy::default();
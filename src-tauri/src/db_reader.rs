@@ -0,0 +1,156 @@
+// Ağır analitik sorgular için ayrı, salt-okunur SQLite bağlantısı
+// WAL modunda okuyucular yazıcıyı bloklamaz; uzun süren export ve özetler
+// monitor'ün yazma havuzundaki bağlantıları meşgul etmez.
+
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnection;
+use sqlx::{ConnectOptions, Connection, Pool, Sqlite};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Count and duration of one kind of database work
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryTiming {
+    pub count: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+impl QueryTiming {
+    fn record(&mut self, ms: f64) {
+        self.count += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+}
+
+/// Reader/writer contention metrics
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContentionStats {
+    /// Analytical queries on the read-only connection
+    pub reads: QueryTiming,
+    /// Coalesced write transactions, start to commit
+    pub writes: QueryTiming,
+    /// Time spent waiting to begin a write transaction
+    pub write_waits: QueryTiming,
+    /// SQLITE_BUSY / SQLITE_LOCKED errors seen by either side
+    pub busy_errors: u64,
+}
+
+pub type SharedContention = Arc<Mutex<ContentionStats>>;
+
+pub fn create_contention() -> SharedContention {
+    Arc::new(Mutex::new(ContentionStats::default()))
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// SQLITE_BUSY (5) or SQLITE_LOCKED (6), including extended codes
+pub fn is_busy_error(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .and_then(|code| code.parse::<u32>().ok())
+        .is_some_and(|code| matches!(code & 0xFF, 5 | 6))
+}
+
+pub fn record_write(contention: &SharedContention, wait_started: Instant, began: Instant) {
+    if let Ok(mut stats) = contention.lock() {
+        stats
+            .write_waits
+            .record((began - wait_started).as_secs_f64() * 1000.0);
+        stats.writes.record(elapsed_ms(began));
+    }
+}
+
+pub fn record_error(contention: &SharedContention, error: &sqlx::Error) {
+    if is_busy_error(error) {
+        if let Ok(mut stats) = contention.lock() {
+            stats.busy_errors += 1;
+        }
+    }
+}
+
+/// Opens a read-only connection to the same database file as `pool`
+pub async fn open_read_connection(pool: &Pool<Sqlite>) -> Result<SqliteConnection, sqlx::Error> {
+    let options = pool.connect_options().as_ref().clone().read_only(true);
+    let mut conn = options.connect().await?;
+    // Salt-okunur açılış desteklenmese bile yazmalar reddedilsin
+    sqlx::query("PRAGMA query_only = ON")
+        .execute(&mut conn)
+        .await?;
+    Ok(conn)
+}
+
+/// One analytical query on its own read-only connection: `begin`, run the
+/// query on `conn()`, then `finish` with the result to record its timing
+pub struct ReadSession {
+    conn: SqliteConnection,
+    started: Instant,
+    contention: SharedContention,
+}
+
+impl ReadSession {
+    pub async fn begin(
+        pool: &Pool<Sqlite>,
+        contention: &SharedContention,
+    ) -> Result<Self, sqlx::Error> {
+        let started = Instant::now();
+        let conn = open_read_connection(pool)
+            .await
+            .inspect_err(|e| record_error(contention, e))?;
+        Ok(Self {
+            conn,
+            started,
+            contention: Arc::clone(contention),
+        })
+    }
+
+    pub fn conn(&mut self) -> &mut SqliteConnection {
+        &mut self.conn
+    }
+
+    /// Records timing (and busy errors) and closes the connection
+    pub async fn finish<T>(self, result: &Result<T, sqlx::Error>) {
+        match result {
+            Ok(_) => {
+                if let Ok(mut stats) = self.contention.lock() {
+                    stats.reads.record(elapsed_ms(self.started));
+                }
+            }
+            Err(e) => record_error(&self.contention, e),
+        }
+        let _ = self.conn.close().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[tokio::test]
+    async fn test_read_connection_rejects_writes() {
+        let dir = std::env::temp_dir().join(format!("driveanalizer-reader-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = db::open_pool(&dir.join("test.db")).await.unwrap();
+        let contention = create_contention();
+
+        let mut session = ReadSession::begin(&pool, &contention).await.unwrap();
+        let counts = db::get_range_aggregate(session.conn(), 0.0, 1.0).await;
+        assert_eq!(counts.as_ref().unwrap().sample_count, 0);
+        session.finish(&counts).await;
+
+        let mut conn = open_read_connection(&pool).await.unwrap();
+        assert!(sqlx::query("DELETE FROM disk_stats")
+            .execute(&mut conn)
+            .await
+            .is_err());
+
+        assert_eq!(contention.lock().unwrap().reads.count, 1);
+        pool.close().await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::monitor::MonitorStats;
+use serde::{Deserialize, Serialize};
+
+/// What the user says is wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    /// Totals or speeds don't match what other tools show
+    NumbersLookWrong,
+    /// The app itself uses too much CPU
+    HighCpu,
+    /// The database file keeps growing
+    DbTooBig,
+}
+
+/// Findings are returned most severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Critical,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Short id of the check that produced this finding
+    pub check: String,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+/// Everything the checks look at, gathered by the command up front so the
+/// rules themselves stay pure
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticInputs {
+    pub counter_backend: String,
+    pub counter_error: Option<String>,
+    pub elevated: bool,
+    pub database_ready: bool,
+    pub db_size_bytes: u64,
+    pub wal_size_bytes: u64,
+    pub table_rows: Vec<(String, i64)>,
+    pub oldest_sample_age_days: Option<f64>,
+    pub retention_days: u64,
+    pub loop_stats: MonitorStats,
+    pub seconds_since_last_tick: Option<f64>,
+    pub app_cpu_usage: f32,
+    pub cpu_count: usize,
+    pub live_streams: usize,
+}
+
+const MB: u64 = 1024 * 1024;
+
+fn finding(severity: Severity, check: &str, message: String, suggestion: Option<&str>) -> Finding {
+    Finding {
+        severity,
+        check: check.to_string(),
+        message,
+        suggestion: suggestion.map(str::to_string),
+    }
+}
+
+/// Loop health matters for every problem kind: a stalled or slow loop skews
+/// speeds, burns CPU and delays flushes.
+fn check_loop(inputs: &DiagnosticInputs, findings: &mut Vec<Finding>) {
+    match inputs.seconds_since_last_tick {
+        None => findings.push(finding(
+            Severity::Critical,
+            "monitor_loop",
+            "The monitoring loop has not completed a tick yet.".to_string(),
+            Some("Wait a few seconds after startup; if it persists, restart the app."),
+        )),
+        Some(age) if age > 10.0 => findings.push(finding(
+            Severity::Critical,
+            "monitor_loop",
+            format!(
+                "The monitoring loop last ticked {:.0}s ago and looks stalled.",
+                age
+            ),
+            Some("Restart the app and check the log for errors."),
+        )),
+        _ => {}
+    }
+
+    if inputs.loop_stats.avg_tick_ms > 500.0 {
+        findings.push(finding(
+            Severity::Warning,
+            "loop_timing",
+            format!(
+                "Each tick takes {:.0} ms on average (max {:.0} ms); speeds are averaged over longer than 1s.",
+                inputs.loop_stats.avg_tick_ms, inputs.loop_stats.max_tick_ms
+            ),
+            Some("Close other heavy monitoring tools or reduce the number of live views."),
+        ));
+    }
+}
+
+fn check_numbers(inputs: &DiagnosticInputs, findings: &mut Vec<Finding>) {
+    if let Some(error) = &inputs.counter_error {
+        findings.push(finding(
+            Severity::Critical,
+            "counter_backend",
+            format!("Disk performance counters failed: {}", error),
+            Some("Rebuild the counters with `lodctr /R` from an elevated prompt."),
+        ));
+    } else if inputs.counter_backend == "unavailable" {
+        findings.push(finding(
+            Severity::Warning,
+            "counter_backend",
+            "No disk performance counters on this platform; idle time, queue depth, IOPS and latency stay at defaults.".to_string(),
+            None,
+        ));
+    }
+
+    if !inputs.elevated {
+        findings.push(finding(
+            Severity::Warning,
+            "elevation",
+            "Not running as administrator; I/O of system and protected processes can't be read, so totals are lower than the disk counters.".to_string(),
+            Some("Run the app as administrator for complete per-process numbers."),
+        ));
+    }
+
+    if !inputs.database_ready {
+        findings.push(finding(
+            Severity::Critical,
+            "database",
+            "The database is not available; all-time totals and history can't be shown."
+                .to_string(),
+            Some("Check that the data directory is writable."),
+        ));
+    }
+}
+
+fn check_cpu(inputs: &DiagnosticInputs, findings: &mut Vec<Finding>) {
+    // sysinfo reports per-process CPU relative to one core
+    let per_machine = inputs.app_cpu_usage / inputs.cpu_count.max(1) as f32;
+    if per_machine > 5.0 {
+        findings.push(finding(
+            Severity::Critical,
+            "app_cpu",
+            format!("The app uses {:.1}% of total CPU.", per_machine),
+            Some("Hide views you don't need; live streams are only produced for open views."),
+        ));
+    } else if per_machine > 2.0 {
+        findings.push(finding(
+            Severity::Warning,
+            "app_cpu",
+            format!("The app uses {:.1}% of total CPU.", per_machine),
+            None,
+        ));
+    }
+
+    if inputs.live_streams > 1 {
+        findings.push(finding(
+            Severity::Info,
+            "live_streams",
+            format!("{} live metric streams are active.", inputs.live_streams),
+            Some("Unsubscribe streams for views that are not visible."),
+        ));
+    }
+}
+
+fn check_db_size(inputs: &DiagnosticInputs, findings: &mut Vec<Finding>) {
+    if inputs.db_size_bytes > 500 * MB {
+        findings.push(finding(
+            Severity::Critical,
+            "db_size",
+            format!("The database is {} MB.", inputs.db_size_bytes / MB),
+            Some("Run optimize_database, or lower the retention period."),
+        ));
+    } else if inputs.db_size_bytes > 100 * MB {
+        findings.push(finding(
+            Severity::Warning,
+            "db_size",
+            format!("The database is {} MB.", inputs.db_size_bytes / MB),
+            Some("Run optimize_database to drop old samples and VACUUM."),
+        ));
+    }
+
+    if inputs.wal_size_bytes > 50 * MB {
+        findings.push(finding(
+            Severity::Warning,
+            "wal_size",
+            format!(
+                "The write-ahead log is {} MB and hasn't been checkpointed.",
+                inputs.wal_size_bytes / MB
+            ),
+            Some("Run optimize_database; another process may be holding a read transaction open."),
+        ));
+    }
+
+    if let Some(age) = inputs.oldest_sample_age_days {
+        if age > inputs.retention_days as f64 + 1.0 {
+            findings.push(finding(
+                Severity::Warning,
+                "retention",
+                format!(
+                    "The oldest sample is {:.0} days old but retention is {} days; cleanup isn't running.",
+                    age, inputs.retention_days
+                ),
+                Some("Run optimize_database."),
+            ));
+        }
+    }
+
+    if let Some((table, rows)) = inputs.table_rows.iter().max_by_key(|(_, rows)| *rows) {
+        findings.push(finding(
+            Severity::Info,
+            "largest_table",
+            format!("{} is the largest table with {} rows.", table, rows),
+            None,
+        ));
+    }
+}
+
+/// Runs the checks relevant to `kind` and sorts findings by severity
+pub fn diagnose(kind: ProblemKind, inputs: &DiagnosticInputs) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    check_loop(inputs, &mut findings);
+
+    match kind {
+        ProblemKind::NumbersLookWrong => check_numbers(inputs, &mut findings),
+        ProblemKind::HighCpu => check_cpu(inputs, &mut findings),
+        ProblemKind::DbTooBig => check_db_size(inputs, &mut findings),
+    }
+
+    if findings.is_empty() {
+        findings.push(finding(
+            Severity::Info,
+            "no_issues",
+            "No problems detected by the automatic checks.".to_string(),
+            None,
+        ));
+    }
+
+    findings.sort_by_key(|f| f.severity);
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy() -> DiagnosticInputs {
+        DiagnosticInputs {
+            counter_backend: "pdh".to_string(),
+            elevated: true,
+            database_ready: true,
+            retention_days: 30,
+            loop_stats: MonitorStats {
+                tick_count: 10,
+                avg_tick_ms: 20.0,
+                ..Default::default()
+            },
+            seconds_since_last_tick: Some(1.0),
+            cpu_count: 8,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_healthy_system_reports_no_issues() {
+        let findings = diagnose(ProblemKind::NumbersLookWrong, &healthy());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, "no_issues");
+    }
+
+    #[test]
+    fn test_findings_sorted_by_severity() {
+        let inputs = DiagnosticInputs {
+            elevated: false,
+            counter_error: Some("PdhOpenQueryW failed".to_string()),
+            ..healthy()
+        };
+        let findings = diagnose(ProblemKind::NumbersLookWrong, &inputs);
+        assert_eq!(findings[0].check, "counter_backend");
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[1].check, "elevation");
+    }
+
+    #[test]
+    fn test_db_too_big_flags_stale_retention() {
+        let inputs = DiagnosticInputs {
+            db_size_bytes: 200 * MB,
+            oldest_sample_age_days: Some(90.0),
+            ..healthy()
+        };
+        let checks: Vec<String> = diagnose(ProblemKind::DbTooBig, &inputs)
+            .into_iter()
+            .map(|f| f.check)
+            .collect();
+        assert!(checks.contains(&"db_size".to_string()));
+        assert!(checks.contains(&"retention".to_string()));
+    }
+}
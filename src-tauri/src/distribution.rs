@@ -0,0 +1,137 @@
+//! Speed distribution of a time range: percentiles and a histogram per
+//! direction, so the frontend never needs the raw samples.
+
+use crate::models::DiskStat;
+use serde::Serialize;
+
+/// Upper bound of the first histogram bucket (slower samples count as idle)
+pub const IDLE_SPEED: u64 = 1024;
+
+/// Histogram bucket; buckets double in width from `IDLE_SPEED` upwards
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpeedBucket {
+    /// Bytes/s, inclusive
+    pub lower: u64,
+    /// Bytes/s, exclusive
+    pub upper: u64,
+    pub count: u64,
+}
+
+/// Distribution of one direction's speeds (bytes/s)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpeedDistribution {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    /// Up to the bucket holding `max`; empty without samples
+    pub histogram: Vec<SpeedBucket>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpeedDistributions {
+    pub sample_count: u64,
+    pub read: SpeedDistribution,
+    pub write: SpeedDistribution,
+}
+
+/// Nearest-rank percentile of sorted speeds
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn bucket_index(speed: u64) -> usize {
+    if speed < IDLE_SPEED {
+        0
+    } else {
+        (speed / IDLE_SPEED).ilog2() as usize + 1
+    }
+}
+
+fn bucket_bounds(index: usize) -> (u64, u64) {
+    if index == 0 {
+        (0, IDLE_SPEED)
+    } else {
+        let lower = IDLE_SPEED.saturating_mul(1 << (index - 1));
+        (lower, lower.saturating_mul(2))
+    }
+}
+
+fn distribution(mut speeds: Vec<u64>) -> SpeedDistribution {
+    speeds.sort_unstable();
+    let Some(&max) = speeds.last() else {
+        return SpeedDistribution::default();
+    };
+
+    let mut counts = vec![0u64; bucket_index(max) + 1];
+    for &speed in &speeds {
+        counts[bucket_index(speed)] += 1;
+    }
+
+    SpeedDistribution {
+        p50: percentile(&speeds, 50.0),
+        p90: percentile(&speeds, 90.0),
+        p99: percentile(&speeds, 99.0),
+        max,
+        histogram: counts
+            .into_iter()
+            .enumerate()
+            .map(|(index, count)| {
+                let (lower, upper) = bucket_bounds(index);
+                SpeedBucket {
+                    lower,
+                    upper,
+                    count,
+                }
+            })
+            .collect(),
+    }
+}
+
+pub fn speed_distributions(stats: &[DiskStat]) -> SpeedDistributions {
+    SpeedDistributions {
+        sample_count: stats.len() as u64,
+        read: distribution(stats.iter().map(|s| s.read_speed).collect()),
+        write: distribution(stats.iter().map(|s| s.write_speed).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_use_nearest_rank() {
+        let speeds: Vec<u64> = (1..=100).collect();
+        let result = distribution(speeds);
+        assert_eq!((result.p50, result.p90, result.p99), (50, 90, 99));
+        assert_eq!(result.max, 100);
+        // Everything below 1 KB/s is idle
+        assert_eq!(result.histogram.len(), 1);
+        assert_eq!(result.histogram[0].count, 100);
+    }
+
+    #[test]
+    fn test_histogram_buckets_double() {
+        let result = distribution(vec![0, 1024, 2047, 2048, 5000]);
+        let buckets: Vec<(u64, u64, u64)> = result
+            .histogram
+            .iter()
+            .map(|b| (b.lower, b.upper, b.count))
+            .collect();
+        assert_eq!(
+            buckets,
+            [
+                (0, 1024, 1),
+                (1024, 2048, 2),
+                (2048, 4096, 1),
+                (4096, 8192, 1)
+            ]
+        );
+        assert_eq!(distribution(Vec::new()).histogram, []);
+    }
+}
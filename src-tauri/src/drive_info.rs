@@ -0,0 +1,220 @@
+// Sürücü donanım bilgileri (model, seri no, bus tipi)
+// IOCTL_STORAGE_QUERY_PROPERTY birim handle'ı üzerinden diske iletilir
+
+use crate::models::DriveInfo;
+use crate::smart;
+use crate::volume_monitor;
+use sysinfo::{DiskKind, Disks};
+
+/// IOCTL_STORAGE_QUERY_PROPERTY ile okunan cihaz tanımlayıcısı
+#[derive(Debug, Clone, Default)]
+pub struct DeviceDescriptor {
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub bus_type: String,
+}
+
+/// STORAGE_BUS_TYPE değerini okunabilir isme çevir
+pub fn bus_type_name(bus_type: i32) -> &'static str {
+    match bus_type {
+        1 => "scsi",
+        2 => "atapi",
+        3 => "ata",
+        4 => "1394",
+        5 => "ssa",
+        6 => "fibre",
+        7 => "usb",
+        8 => "raid",
+        9 => "iscsi",
+        10 => "sas",
+        11 => "sata",
+        12 => "sd",
+        13 => "mmc",
+        14 => "virtual",
+        15 => "file_backed_virtual",
+        16 => "spaces",
+        17 => "nvme",
+        18 => "scm",
+        19 => "ufs",
+        _ => "unknown",
+    }
+}
+
+/// Descriptor buffer içindeki offset'ten NUL ile biten ASCII string oku
+/// (offset 0 = alan yok)
+pub fn read_descriptor_string(buffer: &[u8], offset: u32) -> Option<String> {
+    let start = offset as usize;
+    if start == 0 || start >= buffer.len() {
+        return None;
+    }
+    let end = buffer[start..]
+        .iter()
+        .position(|b| *b == 0)
+        .map(|p| start + p)
+        .unwrap_or(buffer.len());
+    let value = String::from_utf8_lossy(&buffer[start..end])
+        .trim()
+        .to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use super::{bus_type_name, read_descriptor_string, DeviceDescriptor};
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows::Win32::System::Ioctl::{
+        PropertyStandardQuery, StorageDeviceProperty, IOCTL_STORAGE_QUERY_PROPERTY,
+        STORAGE_DEVICE_DESCRIPTOR, STORAGE_PROPERTY_QUERY,
+    };
+    use windows::Win32::System::IO::DeviceIoControl;
+
+    /// Birimin (örn. "C:") bağlı olduğu diskin tanımlayıcısını sorgula.
+    /// Erişim hakkı 0 ile açıldığı için yönetici yetkisi gerekmez.
+    pub fn query_device(volume: &str) -> Result<DeviceDescriptor, String> {
+        let path: Vec<u16> = format!("\\\\.\\{}", volume)
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        unsafe {
+            let handle = CreateFileW(
+                PCWSTR::from_raw(path.as_ptr()),
+                0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                HANDLE::default(),
+            )
+            .map_err(|e| format!("CreateFileW ({}) failed: {}", volume, e))?;
+
+            let query = STORAGE_PROPERTY_QUERY {
+                PropertyId: StorageDeviceProperty,
+                QueryType: PropertyStandardQuery,
+                AdditionalParameters: [0],
+            };
+            let mut buffer = vec![0u8; 1024];
+            let mut returned: u32 = 0;
+            let result = DeviceIoControl(
+                handle,
+                IOCTL_STORAGE_QUERY_PROPERTY,
+                Some(&query as *const STORAGE_PROPERTY_QUERY as *const core::ffi::c_void),
+                std::mem::size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+                Some(buffer.as_mut_ptr() as *mut core::ffi::c_void),
+                buffer.len() as u32,
+                Some(&mut returned),
+                None,
+            );
+
+            // Temizlik
+            let _ = CloseHandle(handle);
+
+            result
+                .map_err(|e| format!("IOCTL_STORAGE_QUERY_PROPERTY ({}) failed: {}", volume, e))?;
+            if (returned as usize) < std::mem::size_of::<STORAGE_DEVICE_DESCRIPTOR>() {
+                return Err(format!("Short storage descriptor for {}", volume));
+            }
+            buffer.truncate(returned as usize);
+
+            let descriptor =
+                std::ptr::read_unaligned(buffer.as_ptr() as *const STORAGE_DEVICE_DESCRIPTOR);
+            let vendor = read_descriptor_string(&buffer, descriptor.VendorIdOffset);
+            let product = read_descriptor_string(&buffer, descriptor.ProductIdOffset);
+            let model = match (vendor, product) {
+                (Some(vendor), Some(product)) => Some(format!("{} {}", vendor, product)),
+                (vendor, product) => product.or(vendor),
+            };
+
+            Ok(DeviceDescriptor {
+                model,
+                serial: read_descriptor_string(&buffer, descriptor.SerialNumberOffset),
+                bus_type: bus_type_name(descriptor.BusType.0).to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::query_device;
+
+/// Windows dışı platformlar için fallback - donanım bilgisi yok
+#[cfg(not(windows))]
+pub fn query_device(_volume: &str) -> Result<DeviceDescriptor, String> {
+    Err("Device queries are only implemented on Windows".to_string())
+}
+
+/// Güvenli wrapper - hata durumunda bilinmeyen cihaz
+pub fn query_device_safe(volume: &str) -> DeviceDescriptor {
+    query_device(volume).unwrap_or_else(|_| DeviceDescriptor {
+        bus_type: "unknown".to_string(),
+        ..Default::default()
+    })
+}
+
+/// Tüm bağlı birimleri donanım detaylarıyla listele
+pub fn list_drives() -> Vec<DriveInfo> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut drives: Vec<DriveInfo> = Vec::new();
+
+    for disk in disks.list() {
+        let volume = volume_monitor::volume_name(&disk.mount_point().to_string_lossy());
+        if drives.iter().any(|d| d.volume == volume) {
+            continue;
+        }
+
+        let device = query_device_safe(&volume);
+        let nvme = (device.bus_type == "nvme")
+            .then(|| smart::read_nvme_health_safe(&volume))
+            .flatten();
+        drives.push(DriveInfo {
+            media_type: match disk.kind() {
+                DiskKind::SSD => "ssd",
+                DiskKind::HDD => "hdd",
+                DiskKind::Unknown(_) => "unknown",
+            }
+            .to_string(),
+            volume,
+            label: disk.name().to_string_lossy().to_string(),
+            model: device.model,
+            serial: device.serial,
+            bus_type: device.bus_type,
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            total_bytes: disk.total_space(),
+            free_bytes: disk.available_space(),
+            removable: disk.is_removable(),
+            temperature_c: nvme.and_then(|health| health.temperature_c),
+            percentage_used: nvme.map(|health| health.percentage_used),
+        });
+    }
+
+    drives.sort_by(|a, b| a.volume.cmp(&b.volume));
+    drives
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_descriptor_string() {
+        let mut buffer = vec![0u8; 8];
+        buffer.extend_from_slice(b"  Samsung SSD 980 \0");
+        assert_eq!(
+            read_descriptor_string(&buffer, 8).as_deref(),
+            Some("Samsung SSD 980")
+        );
+        assert_eq!(read_descriptor_string(&buffer, 0), None);
+        assert_eq!(read_descriptor_string(&buffer, 500), None);
+    }
+
+    #[test]
+    fn test_bus_type_name() {
+        assert_eq!(bus_type_name(17), "nvme");
+        assert_eq!(bus_type_name(7), "usb");
+        assert_eq!(bus_type_name(99), "unknown");
+    }
+}
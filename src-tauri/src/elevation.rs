@@ -0,0 +1,51 @@
+// Yönetici (elevated) olarak çalışıp çalışmadığımızı tespit etmek için
+
+#[cfg(windows)]
+mod windows_impl {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    /// Mevcut process token'ının elevated olup olmadığını sorgula
+    pub fn is_elevated() -> Result<bool, String> {
+        unsafe {
+            let mut token = HANDLE::default();
+            OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token)
+                .map_err(|e| format!("OpenProcessToken failed: {}", e))?;
+
+            let mut elevation = TOKEN_ELEVATION::default();
+            let mut returned: u32 = 0;
+            let result = GetTokenInformation(
+                token,
+                TokenElevation,
+                Some(&mut elevation as *mut TOKEN_ELEVATION as *mut core::ffi::c_void),
+                std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+                &mut returned,
+            );
+
+            // Temizlik
+            let _ = CloseHandle(token);
+
+            result.map_err(|e| format!("GetTokenInformation failed: {}", e))?;
+            Ok(elevation.TokenIsElevated != 0)
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::is_elevated;
+
+/// Windows dışı platformlar için fallback (root = elevated)
+#[cfg(not(windows))]
+pub fn is_elevated() -> Result<bool, String> {
+    Ok(std::env::var("USER")
+        .map(|user| user == "root")
+        .unwrap_or(false))
+}
+
+/// Güvenli wrapper - hata durumunda elevated değil kabul edilir
+pub fn is_elevated_safe() -> bool {
+    is_elevated().unwrap_or(false)
+}
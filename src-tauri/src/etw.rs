@@ -0,0 +1,302 @@
+// ETW trace oturumlarının yaşam döngüsü: deterministik adlar, çökme sonrası
+// kalan (orphan) oturumların temizlenmesi ve buffer/backlog sınırları.
+// ETW oturumları süreçten bağımsız yaşar; uygulama çökerse oturum açık kalır
+// ve aynı adla yeniden başlatmayı engeller.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Every session this app starts is named "<prefix> <purpose>"
+pub const SESSION_PREFIX: &str = "DriveAnalizer";
+
+/// Deterministic session name, so a session left behind by a crash is found
+/// (and stopped) by the next run
+pub fn session_name(purpose: &str) -> String {
+    format!("{} {}", SESSION_PREFIX, purpose)
+}
+
+pub fn is_own_session(name: &str) -> bool {
+    name.strip_prefix(SESSION_PREFIX)
+        .is_some_and(|rest| rest.starts_with(' '))
+}
+
+/// Kernel buffer sizes for a session and the cap on events waiting for the consumer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SessionLimits {
+    /// Size of each kernel buffer in KB
+    pub buffer_kb: u32,
+    pub min_buffers: u32,
+    pub max_buffers: u32,
+    /// Seconds between buffer flushes to the real-time consumer
+    pub flush_secs: u32,
+    /// Parsed events kept in memory before the oldest are dropped
+    pub max_backlog: usize,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            buffer_kb: 64,
+            min_buffers: 4,
+            max_buffers: 16,
+            flush_secs: 1,
+            max_backlog: 10_000,
+        }
+    }
+}
+
+/// Sessions in `active` that carry our prefix
+pub fn find_orphans(active: &[String]) -> Vec<String> {
+    active
+        .iter()
+        .filter(|name| is_own_session(name))
+        .cloned()
+        .collect()
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use super::SessionLimits;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{
+        ERROR_ALREADY_EXISTS, ERROR_SUCCESS, ERROR_WMI_INSTANCE_NOT_FOUND,
+    };
+    use windows::Win32::System::Diagnostics::Etw::*;
+
+    /// Oturum ve log dosyası adları için ayrılan WCHAR sayısı
+    const NAME_CHARS: usize = 1024;
+
+    /// EVENT_TRACE_PROPERTIES + ad alanı için hizalı buffer
+    fn properties_buffer() -> Vec<u64> {
+        let size = std::mem::size_of::<EVENT_TRACE_PROPERTIES>() + 2 * NAME_CHARS * 2;
+        let mut buffer = vec![0u64; size.div_ceil(8)];
+        let props = buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+        unsafe {
+            (*props).Wnode.BufferSize = size as u32;
+            (*props).LoggerNameOffset = std::mem::size_of::<EVENT_TRACE_PROPERTIES>() as u32;
+            (*props).LogFileNameOffset =
+                (std::mem::size_of::<EVENT_TRACE_PROPERTIES>() + NAME_CHARS * 2) as u32;
+        }
+        buffer
+    }
+
+    fn wide(name: &str) -> Vec<u16> {
+        name.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// Gerçek zamanlı bir oturum başlat; aynı adla kalmış oturum varsa önce durdur
+    pub fn start_session(name: &str, limits: &SessionLimits) -> Result<u64, String> {
+        let name_w = wide(name);
+        for attempt in 0..2 {
+            let mut buffer = properties_buffer();
+            let props = buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+            let mut handle = CONTROLTRACE_HANDLE::default();
+            let status = unsafe {
+                (*props).Wnode.Flags = WNODE_FLAG_TRACED_GUID;
+                // QueryPerformanceCounter zaman damgaları
+                (*props).Wnode.ClientContext = 1;
+                (*props).LogFileMode = EVENT_TRACE_REAL_TIME_MODE;
+                (*props).BufferSize = limits.buffer_kb;
+                (*props).MinimumBuffers = limits.min_buffers;
+                (*props).MaximumBuffers = limits.max_buffers;
+                (*props).FlushTimer = limits.flush_secs;
+                StartTraceW(&mut handle, PCWSTR::from_raw(name_w.as_ptr()), props)
+            };
+            match status {
+                ERROR_SUCCESS => return Ok(handle.Value),
+                ERROR_ALREADY_EXISTS if attempt == 0 => stop_session(name)?,
+                _ => return Err(format!("StartTraceW ({}) failed: {}", name, status.0)),
+            }
+        }
+        Err(format!(
+            "StartTraceW ({}) failed: session still exists",
+            name
+        ))
+    }
+
+    /// Oturumu adıyla durdur (zaten yoksa hata değil)
+    pub fn stop_session(name: &str) -> Result<(), String> {
+        let name_w = wide(name);
+        let mut buffer = properties_buffer();
+        let props = buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES;
+        let status = unsafe {
+            ControlTraceW(
+                CONTROLTRACE_HANDLE::default(),
+                PCWSTR::from_raw(name_w.as_ptr()),
+                props,
+                EVENT_TRACE_CONTROL_STOP,
+            )
+        };
+        match status {
+            ERROR_SUCCESS | ERROR_WMI_INSTANCE_NOT_FOUND => Ok(()),
+            _ => Err(format!("ControlTraceW ({}) failed: {}", name, status.0)),
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::{start_session, stop_session};
+
+/// Windows dışı platformlar için fallback - ETW yok
+#[cfg(not(windows))]
+pub fn start_session(_name: &str, _limits: &SessionLimits) -> Result<u64, String> {
+    Err("ETW is only available on Windows".to_string())
+}
+
+/// Windows dışı platformlar için fallback
+#[cfg(not(windows))]
+pub fn stop_session(_name: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/// Stops sessions left behind by a previous run. Skipped while another
+/// instance is running, since the sessions may be its live ones.
+///
+/// # Returns
+/// Names of the sessions that were stopped
+pub fn cleanup_orphans(other_instance_running: bool) -> Vec<String> {
+    if other_instance_running {
+        return Vec::new();
+    }
+    find_orphans(&crate::competitors::list_trace_sessions_safe())
+        .into_iter()
+        .filter(|name| match stop_session(name) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("[ETW] Failed to stop orphaned session: {}", e);
+                false
+            }
+        })
+        .collect()
+}
+
+/// Owns the sessions started by this process and stops them on drop
+#[derive(Debug, Default)]
+pub struct SessionManager {
+    limits: SessionLimits,
+    /// Session name -> trace handle
+    sessions: HashMap<String, u64>,
+}
+
+impl SessionManager {
+    pub fn new(limits: SessionLimits) -> Self {
+        Self {
+            limits,
+            sessions: HashMap::new(),
+        }
+    }
+
+    pub fn limits(&self) -> SessionLimits {
+        self.limits
+    }
+
+    /// Starts (or returns the already running) session for `purpose`
+    pub fn start(&mut self, purpose: &str) -> Result<String, String> {
+        let name = session_name(purpose);
+        if !self.sessions.contains_key(&name) {
+            let handle = start_session(&name, &self.limits)?;
+            println!("[ETW] Started session \"{}\"", name);
+            self.sessions.insert(name.clone(), handle);
+        }
+        Ok(name)
+    }
+
+    /// Trace handle of a running session (for enabling providers on it)
+    pub fn handle(&self, purpose: &str) -> Option<u64> {
+        self.sessions.get(&session_name(purpose)).copied()
+    }
+
+    pub fn stop(&mut self, purpose: &str) -> Result<(), String> {
+        let name = session_name(purpose);
+        if self.sessions.remove(&name).is_some() {
+            stop_session(&name)?;
+        }
+        Ok(())
+    }
+
+    pub fn stop_all(&mut self) {
+        for (name, _) in self.sessions.drain() {
+            if let Err(e) = stop_session(&name) {
+                eprintln!("[ETW] {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for SessionManager {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+/// Events waiting for the consumer, bounded so a stalled consumer can't grow memory
+#[derive(Debug)]
+pub struct TraceBacklog<T> {
+    events: VecDeque<T>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl<T> TraceBacklog<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    /// Queues an event, dropping the oldest one when full
+    pub fn push(&mut self, event: T) {
+        if self.events.len() >= self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events lost to the cap since the backlog was created
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphans_are_matched_by_prefix() {
+        assert_eq!(session_name("Disk IO"), "DriveAnalizer Disk IO");
+        let active = vec![
+            "NT Kernel Logger".to_string(),
+            session_name("Disk IO"),
+            "DriveAnalizerX".to_string(),
+        ];
+        assert_eq!(find_orphans(&active), vec![session_name("Disk IO")]);
+    }
+
+    #[test]
+    fn test_backlog_drops_oldest() {
+        let mut backlog = TraceBacklog::new(3);
+        for event in 0..5 {
+            backlog.push(event);
+        }
+        assert_eq!(backlog.len(), 3);
+        assert_eq!(backlog.dropped(), 2);
+        assert_eq!(backlog.drain().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert!(backlog.is_empty());
+    }
+}
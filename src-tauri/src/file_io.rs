@@ -0,0 +1,1049 @@
+// Dosya düzeyinde I/O: Microsoft-Windows-Kernel-File ETW sağlayıcısından gelen
+// Create/Read/Write/Close olaylarıyla okunan ve yazılan byte'ları dosya yoluna
+// (ve process'e) bağlar. sysinfo yalnızca process başına toplam verir.
+// İsteğe bağlıdır; ETW oturumu yönetici hakları (ya da Performance Log Users) ister.
+
+use crate::db;
+use crate::etw;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Settings key for file-level tracing (off by default)
+pub const FILE_TRACING_KEY: &str = "file_tracing_enabled";
+
+/// ETW session purpose (see `etw::session_name`)
+pub const TRACE_PURPOSE: &str = "File IO";
+
+/// Files (per process) tracked before new ones only count as untracked bytes
+pub const MAX_TRACKED_FILES: usize = 5_000;
+
+/// Files listed by `top_files` unless asked otherwise
+pub const TOP_FILES_LIMIT: usize = 50;
+
+/// Directory depth (below the drive) used when none is given
+pub const DEFAULT_DIRECTORY_DEPTH: usize = 3;
+
+/// Deepest directory level `directories` groups by
+pub const MAX_DIRECTORY_DEPTH: usize = 32;
+
+/// Files the memory manager pages to; their I/O is reported as paging
+pub const PAGING_FILES: &[&str] = &["pagefile.sys", "swapfile.sys"];
+
+/// Open file handles remembered for path lookups
+pub const MAX_OPEN_FILES: usize = 50_000;
+
+// Microsoft-Windows-Kernel-File event ids
+const EVENT_NAME_CREATE: u16 = 10;
+const EVENT_NAME_DELETE: u16 = 11;
+const EVENT_CREATE: u16 = 12;
+const EVENT_CLOSE: u16 = 14;
+const EVENT_READ: u16 = 15;
+const EVENT_WRITE: u16 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoDirection {
+    Read,
+    Write,
+}
+
+/// A parsed Kernel-File event
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileIoEvent {
+    /// A file object (Create) or file key (NameCreate) now refers to `path`
+    Opened {
+        handle: u64,
+        path: String,
+    },
+    Closed {
+        handle: u64,
+    },
+    Transfer {
+        pid: u32,
+        file_object: u64,
+        file_key: u64,
+        bytes: u64,
+        direction: IoDirection,
+    },
+}
+
+/// Bytes of one file by one process since tracing started
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileIoStat {
+    pub path: String,
+    pub pid: u32,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Entry of the top-files report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopFile {
+    pub path: String,
+    pub pid: u32,
+    pub process: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Bytes of the files under one directory since tracing started
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DirectoryStat {
+    pub directory: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    /// Distinct files (per process) counted
+    pub file_count: u64,
+}
+
+/// Paging-file I/O since tracing started: memory pressure rather than any
+/// process's own files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct PagingIo {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Whether `path` is a paging file (on any drive)
+pub fn is_paging_file(path: &str) -> bool {
+    let file_name = path.rsplit('\\').next().unwrap_or(path);
+    PAGING_FILES
+        .iter()
+        .any(|paging| file_name.eq_ignore_ascii_case(paging))
+}
+
+/// What kind of data a file extension holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Log,
+    Temp,
+    Database,
+    Media,
+    Other,
+}
+
+const LOG_EXTENSIONS: &[&str] = &["log", "etl", "evtx", "trace"];
+const TEMP_EXTENSIONS: &[&str] = &["tmp", "temp", "partial", "crdownload", "part", "bak"];
+const DATABASE_EXTENSIONS: &[&str] = &[
+    "db", "sqlite", "sqlite3", "db-wal", "db-shm", "ldb", "mdb", "edb", "jrs", "ndf", "mdf",
+];
+const MEDIA_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "webp", "heic", "raw", "mp3", "flac", "wav", "aac", "ogg", "mp4",
+    "mkv", "mov", "avi", "webm",
+];
+
+/// Lowercase extension of the file `path` names ("C:\a\b.LOG" -> "log");
+/// None for files without one
+pub fn extension_of(path: &str) -> Option<String> {
+    let file_name = path.rsplit('\\').next().unwrap_or(path);
+    let (stem, extension) = file_name.rsplit_once('.')?;
+    // ".gitconfig" is a dot file rather than an extension
+    (!stem.is_empty() && !extension.is_empty()).then(|| extension.to_ascii_lowercase())
+}
+
+/// The kind of data files with `extension` (lowercase) usually hold
+pub fn kind_of(extension: &str) -> FileKind {
+    let kinds = [
+        (LOG_EXTENSIONS, FileKind::Log),
+        (TEMP_EXTENSIONS, FileKind::Temp),
+        (DATABASE_EXTENSIONS, FileKind::Database),
+        (MEDIA_EXTENSIONS, FileKind::Media),
+    ];
+    kinds
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension))
+        .map_or(FileKind::Other, |&(_, kind)| kind)
+}
+
+/// Bytes of one process's files with one extension since tracing started
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtensionStat {
+    /// Lowercase, without the dot; empty for files without an extension
+    pub extension: String,
+    pub kind: FileKind,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub file_count: u64,
+}
+
+/// Directory of `path` cut to `depth` levels below its root
+/// ("C:\\Users\\me\\a.txt" at depth 1 -> "C:\\Users")
+pub fn directory_at(path: &str, depth: usize) -> String {
+    let mut components: Vec<&str> = path.split('\\').collect();
+    // The last component is the file itself
+    components.pop();
+    let root_len = if path.starts_with("\\\\") {
+        // \\server\share
+        4
+    } else {
+        1
+    };
+    let keep = (root_len + depth).min(components.len());
+    let directory = components[..keep].join("\\");
+    if keep == 1 && drive_of(&directory).is_some() {
+        format!("{}\\", directory)
+    } else {
+        directory
+    }
+}
+
+/// Bytes of one process on one drive since tracing started
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessDriveStat {
+    pub pid: u32,
+    pub name: String,
+    /// "C:", or "Other" for paths without a drive letter (network shares, devices)
+    pub drive: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Drive letter of a normalized path ("C:\x" -> "C:")
+pub fn drive_of(path: &str) -> Option<String> {
+    let mut chars = path.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => {
+            Some(format!("{}:", letter.to_ascii_uppercase()))
+        }
+        _ => None,
+    }
+}
+
+/// Name of a process for display; exited or unknown processes show their pid
+pub fn process_name(names: &HashMap<u32, String>, pid: u32) -> String {
+    names
+        .get(&pid)
+        .cloned()
+        .unwrap_or_else(|| format!("PID {}", pid))
+}
+
+/// Per-file byte counts built from the event stream
+#[derive(Debug, Default)]
+pub struct FileIoTracker {
+    /// File object / file key -> path
+    open_files: HashMap<u64, String>,
+    /// (pid, path) -> (read, write)
+    files: HashMap<(u32, String), (u64, u64)>,
+    /// Bytes of files opened before tracing started or beyond `MAX_TRACKED_FILES`
+    untracked: (u64, u64),
+    /// Bytes of the paging files, kept out of `files` (and so out of System's rows)
+    paging: (u64, u64),
+    /// The app's own process and files, not counted at all
+    ignored_pid: Option<u32>,
+    ignored_paths: Vec<String>,
+}
+
+impl FileIoTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, event: FileIoEvent) {
+        match event {
+            FileIoEvent::Opened { handle, path } => {
+                if self.open_files.len() < MAX_OPEN_FILES || self.open_files.contains_key(&handle) {
+                    self.open_files.insert(handle, path);
+                }
+            }
+            FileIoEvent::Closed { handle } => {
+                self.open_files.remove(&handle);
+            }
+            FileIoEvent::Transfer {
+                pid,
+                file_object,
+                file_key,
+                bytes,
+                direction,
+            } => {
+                let path = self
+                    .open_files
+                    .get(&file_object)
+                    .or_else(|| self.open_files.get(&file_key));
+                if self.ignored_pid == Some(pid)
+                    || path.is_some_and(|path| {
+                        self.ignored_paths
+                            .iter()
+                            .any(|ignored| path.eq_ignore_ascii_case(ignored))
+                    })
+                {
+                    return;
+                }
+                let counts = match path {
+                    Some(path) if is_paging_file(path) => &mut self.paging,
+                    Some(path) => {
+                        let key = (pid, path.clone());
+                        if self.files.len() < MAX_TRACKED_FILES || self.files.contains_key(&key) {
+                            self.files.entry(key).or_insert((0, 0))
+                        } else {
+                            &mut self.untracked
+                        }
+                    }
+                    None => &mut self.untracked,
+                };
+                match direction {
+                    IoDirection::Read => counts.0 = counts.0.saturating_add(bytes),
+                    IoDirection::Write => counts.1 = counts.1.saturating_add(bytes),
+                }
+            }
+        }
+    }
+
+    /// Every tracked file, busiest first
+    pub fn files(&self) -> Vec<FileIoStat> {
+        let mut stats: Vec<FileIoStat> = self
+            .files
+            .iter()
+            .map(|((pid, path), (read_bytes, write_bytes))| FileIoStat {
+                path: path.clone(),
+                pid: *pid,
+                read_bytes: *read_bytes,
+                write_bytes: *write_bytes,
+            })
+            .collect();
+        stats.sort_by_key(|s| std::cmp::Reverse(s.read_bytes + s.write_bytes));
+        stats
+    }
+
+    /// The `limit` busiest files (per process) with process names resolved
+    pub fn top_files(&self, limit: usize, names: &HashMap<u32, String>) -> Vec<TopFile> {
+        self.files()
+            .into_iter()
+            .take(limit)
+            .map(|stat| TopFile {
+                process: process_name(names, stat.pid),
+                total_bytes: stat.read_bytes + stat.write_bytes,
+                path: stat.path,
+                pid: stat.pid,
+                read_bytes: stat.read_bytes,
+                write_bytes: stat.write_bytes,
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Bytes per directory `depth` levels below the drive, busiest first
+    pub fn directories(&self, depth: usize) -> Vec<DirectoryStat> {
+        let depth = depth.min(MAX_DIRECTORY_DEPTH);
+        let mut grouped: HashMap<String, DirectoryStat> = HashMap::new();
+        for ((_, path), (read, write)) in &self.files {
+            let directory = directory_at(path, depth);
+            let entry = grouped
+                .entry(directory.clone())
+                .or_insert_with(|| DirectoryStat {
+                    directory,
+                    read_bytes: 0,
+                    write_bytes: 0,
+                    file_count: 0,
+                });
+            entry.read_bytes = entry.read_bytes.saturating_add(*read);
+            entry.write_bytes = entry.write_bytes.saturating_add(*write);
+            entry.file_count += 1;
+        }
+        let mut stats: Vec<DirectoryStat> = grouped.into_values().collect();
+        stats.sort_by_key(|s| std::cmp::Reverse(s.read_bytes + s.write_bytes));
+        stats
+    }
+
+    /// Bytes of the files of `pids` per extension, most written first
+    pub fn extensions(&self, pids: &[u32]) -> Vec<ExtensionStat> {
+        let mut grouped: HashMap<String, ExtensionStat> = HashMap::new();
+        for ((pid, path), (read, write)) in &self.files {
+            if !pids.contains(pid) {
+                continue;
+            }
+            let extension = extension_of(path).unwrap_or_default();
+            let entry = grouped
+                .entry(extension.clone())
+                .or_insert_with(|| ExtensionStat {
+                    kind: kind_of(&extension),
+                    extension,
+                    read_bytes: 0,
+                    write_bytes: 0,
+                    file_count: 0,
+                });
+            entry.read_bytes = entry.read_bytes.saturating_add(*read);
+            entry.write_bytes = entry.write_bytes.saturating_add(*write);
+            entry.file_count += 1;
+        }
+        let mut stats: Vec<ExtensionStat> = grouped.into_values().collect();
+        stats.sort_by_key(|s| std::cmp::Reverse((s.write_bytes, s.read_bytes)));
+        stats
+    }
+
+    /// Bytes per process and drive, busiest first (`names` maps pid -> process name)
+    pub fn process_drives(&self, names: &HashMap<u32, String>) -> Vec<ProcessDriveStat> {
+        let mut grouped: HashMap<(u32, String), (u64, u64)> = HashMap::new();
+        for ((pid, path), (read, write)) in &self.files {
+            let drive = drive_of(path).unwrap_or_else(|| "Other".to_string());
+            let entry = grouped.entry((*pid, drive)).or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(*read);
+            entry.1 = entry.1.saturating_add(*write);
+        }
+        let mut stats: Vec<ProcessDriveStat> = grouped
+            .into_iter()
+            .map(
+                |((pid, drive), (read_bytes, write_bytes))| ProcessDriveStat {
+                    pid,
+                    name: process_name(names, pid),
+                    drive,
+                    read_bytes,
+                    write_bytes,
+                },
+            )
+            .collect();
+        stats.sort_by_key(|s| std::cmp::Reverse(s.read_bytes + s.write_bytes));
+        stats
+    }
+
+    /// Stops counting transfers of `pid` and of `paths` (None and an empty
+    /// list count everything again)
+    pub fn ignore(&mut self, pid: Option<u32>, paths: Vec<String>) {
+        self.ignored_pid = pid;
+        self.ignored_paths = paths;
+    }
+
+    /// (read, write) bytes that could not be tied to a path
+    pub fn untracked(&self) -> (u64, u64) {
+        self.untracked
+    }
+
+    pub fn paging(&self) -> PagingIo {
+        PagingIo {
+            read_bytes: self.paging.0,
+            write_bytes: self.paging.1,
+        }
+    }
+
+    /// Drops the byte counts (open file names are kept for later transfers)
+    pub fn reset(&mut self) {
+        self.files.clear();
+        self.untracked = (0, 0);
+        self.paging = (0, 0);
+    }
+}
+
+pub type SharedFileIo = Arc<Mutex<FileIoTracker>>;
+
+pub fn create_file_io() -> SharedFileIo {
+    Arc::new(Mutex::new(FileIoTracker::new()))
+}
+
+pub async fn is_enabled(pool: &Pool<Sqlite>) -> Result<bool, sqlx::Error> {
+    Ok(db::get_setting(pool, FILE_TRACING_KEY)
+        .await?
+        .is_some_and(|value| value == "true"))
+}
+
+pub async fn set_enabled(pool: &Pool<Sqlite>, enabled: bool) -> Result<(), sqlx::Error> {
+    db::set_setting(
+        pool,
+        FILE_TRACING_KEY,
+        if enabled { "true" } else { "false" },
+    )
+    .await
+}
+
+/// Olay verisinde sırayla alan okuyan küçük yardımcı
+struct Fields<'a> {
+    data: &'a [u8],
+    pointer_size: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn pointer(&mut self) -> Option<u64> {
+        if self.pointer_size == 4 {
+            self.u32().map(u64::from)
+        } else {
+            self.u64()
+        }
+    }
+
+    /// Null-terminated UTF-16 string (the rest of the data if unterminated)
+    fn wstring(&mut self) -> String {
+        let units: Vec<u16> = self
+            .data
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|unit| *unit != 0)
+            .collect();
+        self.data = &[];
+        String::from_utf16_lossy(&units)
+    }
+}
+
+/// Parses a Kernel-File event payload (manifest version 1 layouts).
+/// `pointer_size` is 4 for events from 32-bit headers, 8 otherwise.
+pub fn parse_event(id: u16, pointer_size: usize, pid: u32, data: &[u8]) -> Option<FileIoEvent> {
+    let mut fields = Fields { data, pointer_size };
+    match id {
+        EVENT_CREATE => {
+            // Irp, FileObject, IssuingThreadId, CreateOptions, CreateAttributes, ShareAccess, FileName
+            fields.pointer()?;
+            let handle = fields.pointer()?;
+            fields.take(16)?;
+            Some(FileIoEvent::Opened {
+                handle,
+                path: fields.wstring(),
+            })
+        }
+        EVENT_NAME_CREATE => {
+            // FileKey, FileName
+            let handle = fields.pointer()?;
+            Some(FileIoEvent::Opened {
+                handle,
+                path: fields.wstring(),
+            })
+        }
+        EVENT_CLOSE => {
+            // Irp, FileObject, FileKey, IssuingThreadId
+            fields.pointer()?;
+            Some(FileIoEvent::Closed {
+                handle: fields.pointer()?,
+            })
+        }
+        EVENT_NAME_DELETE => Some(FileIoEvent::Closed {
+            handle: fields.pointer()?,
+        }),
+        EVENT_READ | EVENT_WRITE => {
+            // ByteOffset, Irp, FileObject, FileKey, IssuingThreadId, IOSize, IOFlags, ExtraFlags
+            fields.u64()?;
+            fields.pointer()?;
+            let file_object = fields.pointer()?;
+            let file_key = fields.pointer()?;
+            fields.u32()?;
+            let bytes = u64::from(fields.u32()?);
+            Some(FileIoEvent::Transfer {
+                pid,
+                file_object,
+                file_key,
+                bytes,
+                direction: if id == EVENT_READ {
+                    IoDirection::Read
+                } else {
+                    IoDirection::Write
+                },
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites an NT device path ("\Device\HarddiskVolume3\x") to its drive
+/// letter form ("C:\x") using (device, drive) pairs
+pub fn normalize_path(path: &str, devices: &[(String, String)]) -> String {
+    for (device, drive) in devices {
+        if let Some(rest) = path.strip_prefix(device.as_str()) {
+            if rest.is_empty() || rest.starts_with('\\') {
+                return format!("{}{}", drive, rest);
+            }
+        }
+    }
+    path.to_string()
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use super::{normalize_path, parse_event, FileIoEvent, SharedFileIo};
+    use windows::core::{GUID, PCWSTR, PWSTR};
+    use windows::Win32::Storage::FileSystem::QueryDosDeviceW;
+    use windows::Win32::System::Diagnostics::Etw::*;
+
+    /// Microsoft-Windows-Kernel-File
+    const KERNEL_FILE_PROVIDER: GUID = GUID::from_u128(0xedd08927_9cc4_4e65_b970_c2560fb5c289);
+
+    /// FILENAME | FILEIO | CREATE | READ | WRITE
+    const KERNEL_FILE_KEYWORDS: u64 = 0x10 | 0x20 | 0x80 | 0x100 | 0x200;
+
+    /// TRACE_LEVEL_INFORMATION
+    const LEVEL_INFORMATION: u8 = 4;
+
+    /// EVENT_HEADER_FLAG_32_BIT_HEADER
+    const HEADER_FLAG_32_BIT: u16 = 0x20;
+
+    /// Geri çağrıya Context üzerinden verilen durum
+    struct Consumer {
+        tracker: SharedFileIo,
+        devices: Vec<(String, String)>,
+    }
+
+    /// "\Device\HarddiskVolumeN" -> "C:" eşlemesi
+    fn device_map() -> Vec<(String, String)> {
+        let mut devices = Vec::new();
+        for letter in b'A'..=b'Z' {
+            let drive = format!("{}:", letter as char);
+            let drive_w: Vec<u16> = drive.encode_utf16().chain(std::iter::once(0)).collect();
+            let mut target = [0u16; 512];
+            let len =
+                unsafe { QueryDosDeviceW(PCWSTR::from_raw(drive_w.as_ptr()), Some(&mut target)) };
+            if len == 0 {
+                continue;
+            }
+            let end = target
+                .iter()
+                .position(|unit| *unit == 0)
+                .unwrap_or(target.len());
+            devices.push((String::from_utf16_lossy(&target[..end]), drive));
+        }
+        devices
+    }
+
+    unsafe extern "system" fn on_event(record: *mut EVENT_RECORD) {
+        let record = &*record;
+        if record.EventHeader.ProviderId != KERNEL_FILE_PROVIDER || record.UserContext.is_null() {
+            return;
+        }
+        let consumer = &*(record.UserContext as *const Consumer);
+        let pointer_size = if record.EventHeader.Flags & HEADER_FLAG_32_BIT != 0 {
+            4
+        } else {
+            8
+        };
+        let data = std::slice::from_raw_parts(
+            record.UserData as *const u8,
+            record.UserDataLength as usize,
+        );
+        let Some(event) = parse_event(
+            record.EventHeader.EventDescriptor.Id,
+            pointer_size,
+            record.EventHeader.ProcessId,
+            data,
+        ) else {
+            return;
+        };
+        let event = match event {
+            FileIoEvent::Opened { handle, path } => FileIoEvent::Opened {
+                handle,
+                path: normalize_path(&path, &consumer.devices),
+            },
+            other => other,
+        };
+        if let Ok(mut tracker) = consumer.tracker.lock() {
+            tracker.apply(event);
+        }
+    }
+
+    /// Sağlayıcıyı oturumda etkinleştir ve tüketici thread'ini başlat.
+    /// ProcessTrace, oturum durdurulana kadar bloklar.
+    pub fn start_consumer(
+        session_name: &str,
+        session_handle: u64,
+        tracker: SharedFileIo,
+    ) -> Result<std::thread::JoinHandle<()>, String> {
+        let status = unsafe {
+            EnableTraceEx2(
+                CONTROLTRACE_HANDLE {
+                    Value: session_handle,
+                },
+                &KERNEL_FILE_PROVIDER,
+                EVENT_CONTROL_CODE_ENABLE_PROVIDER,
+                LEVEL_INFORMATION,
+                KERNEL_FILE_KEYWORDS,
+                0,
+                0,
+                None,
+            )
+        };
+        if status.is_err() {
+            return Err(format!("EnableTraceEx2 (Kernel-File) failed: {}", status.0));
+        }
+
+        let context = Box::into_raw(Box::new(Consumer {
+            tracker,
+            devices: device_map(),
+        }));
+        let mut name_w: Vec<u16> = session_name
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let mut logfile = EVENT_TRACE_LOGFILEW::default();
+        logfile.LoggerName = PWSTR(name_w.as_mut_ptr());
+        logfile.Anonymous1.ProcessTraceMode =
+            PROCESS_TRACE_MODE_REAL_TIME | PROCESS_TRACE_MODE_EVENT_RECORD;
+        logfile.Anonymous2.EventRecordCallback = Some(on_event);
+        logfile.Context = context as *mut std::ffi::c_void;
+
+        let handle = unsafe { OpenTraceW(&mut logfile) };
+        if handle.Value == u64::MAX {
+            drop(unsafe { Box::from_raw(context) });
+            return Err("OpenTraceW (Kernel-File) failed".to_string());
+        }
+
+        let context_addr = context as usize;
+        std::thread::Builder::new()
+            .name("file-io-trace".to_string())
+            .spawn(move || {
+                let status = unsafe { ProcessTrace(&[handle], None, None) };
+                if status.is_err() {
+                    eprintln!("[FileIO] ProcessTrace ended: {}", status.0);
+                }
+                unsafe {
+                    let _ = CloseTrace(handle);
+                    drop(Box::from_raw(context_addr as *mut Consumer));
+                }
+            })
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Running file-level trace; stopping (or dropping) it ends the ETW session
+pub struct FileTracer {
+    sessions: etw::SessionManager,
+    consumer: Option<std::thread::JoinHandle<()>>,
+}
+
+impl FileTracer {
+    #[cfg(windows)]
+    pub fn start(tracker: SharedFileIo) -> Result<Self, String> {
+        let mut sessions = etw::SessionManager::new(etw::SessionLimits::default());
+        let name = sessions.start(TRACE_PURPOSE)?;
+        let handle = sessions
+            .handle(TRACE_PURPOSE)
+            .ok_or_else(|| format!("ETW session \"{}\" not running", name))?;
+        let consumer = windows_impl::start_consumer(&name, handle, tracker)?;
+        println!("[FileIO] File-level tracing started.");
+        Ok(Self {
+            sessions,
+            consumer: Some(consumer),
+        })
+    }
+
+    /// Windows dışı platformlar için fallback - ETW yok
+    #[cfg(not(windows))]
+    pub fn start(_tracker: SharedFileIo) -> Result<Self, String> {
+        Err("ETW is only available on Windows".to_string())
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.sessions.stop_all();
+        if let Some(consumer) = self.consumer.take() {
+            let _ = consumer.join();
+            println!("[FileIO] File-level tracing stopped.");
+        }
+    }
+}
+
+impl Drop for FileTracer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide(text: &str) -> Vec<u8> {
+        text.encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect()
+    }
+
+    fn create_payload(file_object: u64, path: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(1u64.to_le_bytes()); // Irp
+        data.extend(file_object.to_le_bytes());
+        data.extend([0u8; 16]);
+        data.extend(wide(path));
+        data
+    }
+
+    fn transfer_payload(file_object: u64, file_key: u64, size: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(0u64.to_le_bytes()); // ByteOffset
+        data.extend(1u64.to_le_bytes()); // Irp
+        data.extend(file_object.to_le_bytes());
+        data.extend(file_key.to_le_bytes());
+        data.extend(7u32.to_le_bytes()); // IssuingThreadId
+        data.extend(size.to_le_bytes());
+        data.extend([0u8; 8]);
+        data
+    }
+
+    #[test]
+    fn test_events_are_parsed_and_attributed() {
+        let mut tracker = FileIoTracker::new();
+        let opened =
+            parse_event(EVENT_CREATE, 8, 10, &create_payload(0xA0, "C:\\log.txt")).unwrap();
+        assert_eq!(
+            opened,
+            FileIoEvent::Opened {
+                handle: 0xA0,
+                path: "C:\\log.txt".to_string()
+            }
+        );
+        tracker.apply(opened);
+
+        for (id, size) in [(EVENT_WRITE, 4096), (EVENT_WRITE, 100), (EVENT_READ, 10)] {
+            tracker.apply(parse_event(id, 8, 10, &transfer_payload(0xA0, 0xB0, size)).unwrap());
+        }
+        // Unknown file object: counted, but without a path
+        tracker.apply(parse_event(EVENT_WRITE, 8, 10, &transfer_payload(0xC0, 0xD0, 50)).unwrap());
+
+        assert_eq!(
+            tracker.files(),
+            vec![FileIoStat {
+                path: "C:\\log.txt".to_string(),
+                pid: 10,
+                read_bytes: 10,
+                write_bytes: 4196,
+            }]
+        );
+        assert_eq!(tracker.untracked(), (0, 50));
+
+        // After Close, later transfers can't be tied to the path
+        let mut close = 1u64.to_le_bytes().to_vec();
+        close.extend(0xA0u64.to_le_bytes());
+        tracker.apply(parse_event(EVENT_CLOSE, 8, 10, &close).unwrap());
+        tracker.apply(parse_event(EVENT_WRITE, 8, 10, &transfer_payload(0xA0, 0xB0, 1)).unwrap());
+        assert_eq!(tracker.untracked(), (0, 51));
+        assert!(parse_event(EVENT_READ, 8, 10, &[0u8; 4]).is_none());
+    }
+
+    #[test]
+    fn test_device_paths_use_drive_letters() {
+        let devices = vec![("\\Device\\HarddiskVolume3".to_string(), "C:".to_string())];
+        assert_eq!(
+            normalize_path("\\Device\\HarddiskVolume3\\Windows\\a.dll", &devices),
+            "C:\\Windows\\a.dll"
+        );
+        // HarddiskVolume30 is another volume
+        assert_eq!(
+            normalize_path("\\Device\\HarddiskVolume30\\x", &devices),
+            "\\Device\\HarddiskVolume30\\x"
+        );
+    }
+
+    #[test]
+    fn test_process_bytes_are_split_by_drive() {
+        let mut tracker = FileIoTracker::new();
+        for (handle, path) in [
+            (1, "C:\\a"),
+            (2, "c:\\b"),
+            (3, "D:\\c"),
+            (4, "\\\\server\\share\\d"),
+        ] {
+            tracker.apply(FileIoEvent::Opened {
+                handle,
+                path: path.to_string(),
+            });
+        }
+        for (pid, handle, bytes) in [(10, 1, 100), (10, 2, 50), (10, 3, 500), (20, 4, 1)] {
+            tracker.apply(FileIoEvent::Transfer {
+                pid,
+                file_object: handle,
+                file_key: 0,
+                bytes,
+                direction: IoDirection::Write,
+            });
+        }
+
+        let names = HashMap::from([(10, "app.exe".to_string())]);
+        let rows: Vec<(String, String, u64)> = tracker
+            .process_drives(&names)
+            .into_iter()
+            .map(|s| (s.name, s.drive, s.write_bytes))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("app.exe".to_string(), "D:".to_string(), 500),
+                ("app.exe".to_string(), "C:".to_string(), 150),
+                ("PID 20".to_string(), "Other".to_string(), 1),
+            ]
+        );
+
+        let top = tracker.top_files(2, &names);
+        assert_eq!(top.len(), 2);
+        assert_eq!(
+            (
+                top[0].path.as_str(),
+                top[0].process.as_str(),
+                top[0].total_bytes
+            ),
+            ("D:\\c", "app.exe", 500)
+        );
+    }
+
+    #[test]
+    fn test_ignored_process_and_files_are_not_counted() {
+        let mut tracker = FileIoTracker::new();
+        for (handle, path) in [(1, "C:\\Data\\app.db"), (2, "C:\\Data\\other.txt")] {
+            tracker.apply(FileIoEvent::Opened {
+                handle,
+                path: path.to_string(),
+            });
+        }
+        tracker.ignore(Some(7), vec!["c:\\data\\APP.DB".to_string()]);
+        // Own pid (even on an unknown file), then the database written by the system
+        for (pid, handle) in [(7, 2), (7, 99), (4, 1), (4, 2)] {
+            tracker.apply(FileIoEvent::Transfer {
+                pid,
+                file_object: handle,
+                file_key: 0,
+                bytes: 10,
+                direction: IoDirection::Write,
+            });
+        }
+
+        let files = tracker.files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            (files[0].pid, files[0].path.as_str()),
+            (4, "C:\\Data\\other.txt")
+        );
+        assert_eq!(tracker.untracked(), (0, 0));
+    }
+    #[test]
+    fn test_writes_are_grouped_by_directory() {
+        let temp = "C:\\Users\\me\\AppData\\Local\\Temp";
+        assert_eq!(directory_at(&format!("{}\\a.tmp", temp), 5), temp);
+        assert_eq!(directory_at(&format!("{}\\a.tmp", temp), 1), "C:\\Users");
+        assert_eq!(directory_at("C:\\pagefile.sys", 3), "C:\\");
+        assert_eq!(
+            directory_at("\\\\nas\\backup\\x\\y.bin", 0),
+            "\\\\nas\\backup"
+        );
+
+        let mut tracker = FileIoTracker::new();
+        let files = [
+            "C:\\Windows\\log.txt".to_string(),
+            format!("{}\\a.tmp", temp),
+            format!("{}\\sub\\b.tmp", temp),
+        ];
+        for (handle, path) in files.into_iter().enumerate() {
+            tracker.apply(FileIoEvent::Opened {
+                handle: handle as u64,
+                path,
+            });
+            tracker.apply(FileIoEvent::Transfer {
+                pid: 1,
+                file_object: handle as u64,
+                file_key: 0,
+                bytes: 100 * (handle as u64 + 1),
+                direction: IoDirection::Write,
+            });
+        }
+
+        let directories = tracker.directories(5);
+        assert_eq!(directories[0].directory, temp);
+        assert_eq!(
+            (directories[0].write_bytes, directories[0].file_count),
+            (500, 2)
+        );
+        assert_eq!(directories[1].directory, "C:\\Windows");
+    }
+
+    #[test]
+    fn test_paging_file_io_is_counted_apart() {
+        assert!(is_paging_file("D:\\PAGEFILE.SYS"));
+        assert!(!is_paging_file("C:\\hiberfil.sys"));
+
+        let mut tracker = FileIoTracker::new();
+        for (handle, path) in [
+            (1, "C:\\pagefile.sys"),
+            (2, "C:\\swapfile.sys"),
+            (3, "C:\\a.txt"),
+        ] {
+            tracker.apply(FileIoEvent::Opened {
+                handle,
+                path: path.to_string(),
+            });
+        }
+        for (handle, direction) in [
+            (1, IoDirection::Write),
+            (2, IoDirection::Read),
+            (3, IoDirection::Write),
+        ] {
+            tracker.apply(FileIoEvent::Transfer {
+                pid: 4,
+                file_object: handle,
+                file_key: 0,
+                bytes: 4096,
+                direction,
+            });
+        }
+
+        assert_eq!(
+            tracker.paging(),
+            PagingIo {
+                read_bytes: 4096,
+                write_bytes: 4096
+            }
+        );
+        assert_eq!(tracker.files().len(), 1);
+        tracker.reset();
+        assert_eq!(tracker.paging(), PagingIo::default());
+    }
+
+    #[test]
+    fn test_process_writes_are_grouped_by_extension() {
+        assert_eq!(extension_of("C:\\Logs\\App.LOG").as_deref(), Some("log"));
+        assert_eq!(extension_of("C:\\a.b\\.gitconfig"), None);
+        assert_eq!(kind_of("db-wal"), FileKind::Database);
+
+        let mut tracker = FileIoTracker::new();
+        let files = [
+            (1, "C:\\App\\state.db", 100),
+            (1, "C:\\App\\state.db-wal", 400),
+            (2, "C:\\App\\run.log", 300),
+            (1, "C:\\App\\README", 5),
+            (9, "C:\\Other\\x.tmp", 1000),
+        ];
+        for (handle, (pid, path, bytes)) in files.into_iter().enumerate() {
+            tracker.apply(FileIoEvent::Opened {
+                handle: handle as u64,
+                path: path.to_string(),
+            });
+            tracker.apply(FileIoEvent::Transfer {
+                pid,
+                file_object: handle as u64,
+                file_key: 0,
+                bytes,
+                direction: IoDirection::Write,
+            });
+        }
+
+        let rows: Vec<(String, FileKind, u64)> = tracker
+            .extensions(&[1, 2])
+            .into_iter()
+            .map(|s| (s.extension, s.kind, s.write_bytes))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("db-wal".to_string(), FileKind::Database, 400),
+                ("log".to_string(), FileKind::Log, 300),
+                ("db".to_string(), FileKind::Database, 100),
+                (String::new(), FileKind::Other, 5),
+            ]
+        );
+    }
+}
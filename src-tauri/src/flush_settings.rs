@@ -0,0 +1,136 @@
+//! When queued samples reach the database: every `flush_secs`, or earlier
+//! once `buffer_records` disk samples are waiting. Longer periods and bigger
+//! buffers mean fewer, larger transactions (less write amplification on SSDs)
+//! but more data lost if the app is killed without a clean shutdown.
+
+use crate::db;
+use crate::sampling;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const FLUSH_PERIOD_KEY: &str = "flush_period_secs";
+pub const FLUSH_BUFFER_KEY: &str = "flush_buffer_records";
+
+pub const DEFAULT_FLUSH_SECS: u64 = 10;
+pub const MIN_FLUSH_SECS: u64 = 1;
+pub const MAX_FLUSH_SECS: u64 = 600;
+
+/// Largest buffer accepted
+pub const MAX_BUFFER_RECORDS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlushSettings {
+    /// Seconds between database writes (on AC power, outside low-wear mode)
+    pub flush_secs: u64,
+    /// Disk samples waiting before an early flush; 0 = a whole flush period
+    pub buffer_records: usize,
+}
+
+impl Default for FlushSettings {
+    fn default() -> Self {
+        Self {
+            flush_secs: DEFAULT_FLUSH_SECS,
+            buffer_records: 0,
+        }
+    }
+}
+
+impl FlushSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_FLUSH_SECS..=MAX_FLUSH_SECS).contains(&self.flush_secs) {
+            return Err(format!(
+                "Flush period must be between {} and {} seconds",
+                MIN_FLUSH_SECS, MAX_FLUSH_SECS
+            ));
+        }
+        if self.buffer_records > MAX_BUFFER_RECORDS {
+            return Err(format!(
+                "At most {} records can be buffered",
+                MAX_BUFFER_RECORDS
+            ));
+        }
+        Ok(())
+    }
+
+    /// Disk samples allowed to wait in the coalescer at `interval_ms`;
+    /// `flush_secs` is the period in effect (battery mode can stretch it)
+    pub fn buffer_limit(&self, interval_ms: u64, flush_secs: u64) -> usize {
+        if self.buffer_records == 0 {
+            sampling::ticks_per(Duration::from_secs(flush_secs), interval_ms) as usize + 1
+        } else {
+            self.buffer_records
+        }
+    }
+}
+
+pub type SharedFlushSettings = Arc<Mutex<FlushSettings>>;
+
+pub fn create_flush_settings() -> SharedFlushSettings {
+    Arc::new(Mutex::new(FlushSettings::default()))
+}
+
+/// Reads the persisted settings (defaults when never set, clamped when invalid)
+pub async fn load_flush_settings(pool: &Pool<Sqlite>) -> Result<FlushSettings, sqlx::Error> {
+    let defaults = FlushSettings::default();
+    let flush_secs = db::get_setting(pool, FLUSH_PERIOD_KEY)
+        .await?
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|secs| secs.clamp(MIN_FLUSH_SECS, MAX_FLUSH_SECS))
+        .unwrap_or(defaults.flush_secs);
+    let buffer_records = db::get_setting(pool, FLUSH_BUFFER_KEY)
+        .await?
+        .and_then(|value| value.parse::<usize>().ok())
+        .map(|records| records.min(MAX_BUFFER_RECORDS))
+        .unwrap_or(defaults.buffer_records);
+    Ok(FlushSettings {
+        flush_secs,
+        buffer_records,
+    })
+}
+
+pub async fn save_flush_settings(
+    pool: &Pool<Sqlite>,
+    settings: &FlushSettings,
+) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, FLUSH_PERIOD_KEY, &settings.flush_secs.to_string()).await?;
+    db::set_setting(pool, FLUSH_BUFFER_KEY, &settings.buffer_records.to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_defaults_to_the_flush_period() {
+        let settings = FlushSettings::default();
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.buffer_limit(250, settings.flush_secs), 41);
+
+        // A long period isn't cut short by the default buffer
+        let slow = FlushSettings {
+            flush_secs: 300,
+            buffer_records: 0,
+        };
+        assert_eq!(slow.buffer_limit(1000, slow.flush_secs), 301);
+
+        let custom = FlushSettings {
+            flush_secs: 120,
+            buffer_records: 500,
+        };
+        assert_eq!(custom.buffer_limit(250, custom.flush_secs), 500);
+        assert!(FlushSettings {
+            flush_secs: 0,
+            ..custom
+        }
+        .validate()
+        .is_err());
+        assert!(FlushSettings {
+            buffer_records: MAX_BUFFER_RECORDS + 1,
+            ..custom
+        }
+        .validate()
+        .is_err());
+    }
+}
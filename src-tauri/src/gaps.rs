@@ -0,0 +1,111 @@
+//! Periods without samples, so charts can draw "no data" regions instead of
+//! interpolating across time the app was not recording.
+
+use crate::models::{DiskStat, TimelineEvent};
+use crate::monitor::{GAP_EVENT_KIND, PAUSE_EVENT_KIND};
+use crate::sampling;
+use serde::Serialize;
+
+/// Shortest hole between samples reported as a gap (three of the longest
+/// sampling interval, so a slow interval or a late tick is not a gap)
+pub const MIN_GAP_SECS: f64 = 3.0 * sampling::MAX_INTERVAL_MS as f64 / 1000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapReason {
+    /// Monitoring was paused by the user
+    Paused,
+    /// The machine slept or hibernated while the app was running
+    Sleep,
+    /// The app was not running (or the machine was off)
+    NotRunning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataGap {
+    pub start: f64,
+    pub end: f64,
+    pub reason: GapReason,
+}
+
+fn overlaps(event: &TimelineEvent, start: f64, end: f64) -> bool {
+    event.start_time < end && event.end_time.is_none_or(|event_end| event_end > start)
+}
+
+fn reason(events: &[TimelineEvent], start: f64, end: f64) -> GapReason {
+    let covered_by = |kind: &str| {
+        events
+            .iter()
+            .any(|event| event.kind == kind && overlaps(event, start, end))
+    };
+    if covered_by(PAUSE_EVENT_KIND) {
+        GapReason::Paused
+    } else if covered_by(GAP_EVENT_KIND) {
+        GapReason::Sleep
+    } else {
+        GapReason::NotRunning
+    }
+}
+
+/// Gaps in `stats` (ordered by time) from `start` on, including one before
+/// the first sample. The end of the range is left open: the newest samples
+/// may still be buffered, and no samples at all gives no gaps.
+pub fn find_gaps(stats: &[DiskStat], events: &[TimelineEvent], start: f64) -> Vec<DataGap> {
+    let Some(first) = stats.first() else {
+        return Vec::new();
+    };
+
+    std::iter::once((start, first.timestamp))
+        .chain(stats.windows(2).map(|w| (w[0].timestamp, w[1].timestamp)))
+        .filter(|(from, to)| to - from >= MIN_GAP_SECS)
+        .map(|(from, to)| DataGap {
+            start: from,
+            end: to,
+            reason: reason(events, from, to),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: &str, start_time: f64, end_time: f64) -> TimelineEvent {
+        TimelineEvent {
+            id: 0,
+            kind: kind.to_string(),
+            start_time,
+            end_time: Some(end_time),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_gaps_are_found_and_classified() {
+        let stats: Vec<DiskStat> = [100.0, 101.0, 110.0, 500.0, 501.0, 900.0, 5000.0]
+            .into_iter()
+            .map(DiskStat::idle)
+            .collect();
+        let events = vec![
+            event(PAUSE_EVENT_KIND, 111.0, 499.0),
+            event(GAP_EVENT_KIND, 502.0, 899.0),
+        ];
+
+        let gaps = find_gaps(&stats, &events, 0.0);
+        let found: Vec<(f64, f64, GapReason)> =
+            gaps.iter().map(|g| (g.start, g.end, g.reason)).collect();
+        assert_eq!(
+            found,
+            [
+                (0.0, 100.0, GapReason::NotRunning),
+                (110.0, 500.0, GapReason::Paused),
+                (501.0, 900.0, GapReason::Sleep),
+                (900.0, 5000.0, GapReason::NotRunning),
+            ]
+        );
+
+        // A range starting at the first sample has no leading gap
+        assert_eq!(find_gaps(&stats, &events, 100.0).len(), 3);
+        assert!(find_gaps(&[], &events, 0.0).is_empty());
+    }
+}
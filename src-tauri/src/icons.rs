@@ -0,0 +1,222 @@
+//! Executable icons for the top-processes list: extracted with the shell API,
+//! encoded as base64 PNG and cached per path (failures too, so a missing exe
+//! is not retried on every render).
+
+use base64::Engine;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Cached paths before the cache is cleared
+pub const MAX_CACHED_ICONS: usize = 256;
+
+/// 32-bit pixels, top row first
+pub struct IconPixels {
+    pub width: u32,
+    pub height: u32,
+    /// BGRA, as GDI returns them
+    pub bgra: Vec<u8>,
+}
+
+/// Lowercased exe path -> base64 PNG (None when no icon could be read)
+pub type IconCache = Arc<Mutex<HashMap<String, Option<String>>>>;
+
+pub fn create_icon_cache() -> IconCache {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use super::IconPixels;
+    use std::ffi::c_void;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::Storage::FileSystem::FILE_FLAGS_AND_ATTRIBUTES;
+    use windows::Win32::UI::Shell::{SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_LARGEICON};
+    use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, ICONINFO};
+
+    /// Exe dosyasının büyük simgesini 32 bit BGRA piksellere çevir
+    pub fn extract_icon(exe_path: &str) -> Result<IconPixels, String> {
+        let wide: Vec<u16> = exe_path.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            let mut info = SHFILEINFOW::default();
+            let found = SHGetFileInfoW(
+                PCWSTR(wide.as_ptr()),
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                Some(&mut info as *mut SHFILEINFOW),
+                std::mem::size_of::<SHFILEINFOW>() as u32,
+                SHGFI_ICON | SHGFI_LARGEICON,
+            );
+            if found == 0 || info.hIcon.is_invalid() {
+                return Err(format!("No icon for {}", exe_path));
+            }
+
+            let mut icon_info = ICONINFO::default();
+            let result = GetIconInfo(info.hIcon, &mut icon_info)
+                .map_err(|e| format!("GetIconInfo failed: {}", e))
+                .and_then(|_| read_bitmap(&icon_info));
+
+            // Simge ve bitmap'ler bizim sorumluluğumuzda
+            if !icon_info.hbmColor.is_invalid() {
+                let _ = DeleteObject(icon_info.hbmColor);
+            }
+            if !icon_info.hbmMask.is_invalid() {
+                let _ = DeleteObject(icon_info.hbmMask);
+            }
+            let _ = DestroyIcon(info.hIcon);
+            result
+        }
+    }
+
+    unsafe fn read_bitmap(icon_info: &ICONINFO) -> Result<IconPixels, String> {
+        if icon_info.hbmColor.is_invalid() {
+            return Err("Monochrome icons are not supported".to_string());
+        }
+
+        let mut bitmap = BITMAP::default();
+        if GetObjectW(
+            icon_info.hbmColor,
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut bitmap as *mut BITMAP as *mut c_void),
+        ) == 0
+        {
+            return Err("GetObjectW failed".to_string());
+        }
+        let (width, height) = (bitmap.bmWidth, bitmap.bmHeight);
+
+        let mut header = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negatif yükseklik: satırlar yukarıdan aşağıya
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bgra = vec![0u8; (width * height * 4) as usize];
+
+        let dc = GetDC(HWND::default());
+        let lines = GetDIBits(
+            dc,
+            icon_info.hbmColor,
+            0,
+            height as u32,
+            Some(bgra.as_mut_ptr() as *mut c_void),
+            &mut header,
+            DIB_RGB_COLORS,
+        );
+        ReleaseDC(HWND::default(), dc);
+        if lines == 0 {
+            return Err("GetDIBits failed".to_string());
+        }
+
+        Ok(IconPixels {
+            width: width as u32,
+            height: height as u32,
+            bgra,
+        })
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::extract_icon;
+
+/// Windows dışı platformlar için fallback
+#[cfg(not(windows))]
+pub fn extract_icon(_exe_path: &str) -> Result<IconPixels, String> {
+    Err("Icon extraction is only available on Windows".to_string())
+}
+
+/// PNG bytes of `pixels`. Icons without any alpha (pre-XP style) are made opaque.
+pub fn encode_png(pixels: &IconPixels) -> Result<Vec<u8>, String> {
+    let opaque = pixels.bgra.chunks_exact(4).all(|px| px[3] == 0);
+    let rgba: Vec<u8> = pixels
+        .bgra
+        .chunks_exact(4)
+        .flat_map(|px| [px[2], px[1], px[0], if opaque { 255 } else { px[3] }])
+        .collect();
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, pixels.width, pixels.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&rgba).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Base64 PNG of the icon of `exe_path`, from the cache or via `extract`
+pub fn icon(
+    cache: &IconCache,
+    exe_path: &str,
+    extract: impl FnOnce(&str) -> Result<IconPixels, String>,
+) -> Result<String, String> {
+    let key = exe_path.to_lowercase();
+    let cached = cache
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .get(&key)
+        .cloned();
+    let encoded = match cached {
+        Some(encoded) => encoded,
+        None => {
+            let encoded = extract(exe_path)
+                .and_then(|pixels| encode_png(&pixels))
+                .map(|png| base64::engine::general_purpose::STANDARD.encode(png));
+            if let Err(e) = &encoded {
+                eprintln!("[Icons] {}: {}", exe_path, e);
+            }
+            let encoded = encoded.ok();
+            let mut cache = cache.lock().map_err(|e| format!("Lock error: {}", e))?;
+            if cache.len() >= MAX_CACHED_ICONS {
+                cache.clear();
+            }
+            cache.insert(key, encoded.clone());
+            encoded
+        }
+    };
+    encoded.ok_or_else(|| format!("No icon for {}", exe_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(alpha: u8) -> IconPixels {
+        IconPixels {
+            width: 2,
+            height: 1,
+            bgra: vec![10, 20, 30, alpha, 40, 50, 60, alpha],
+        }
+    }
+
+    #[test]
+    fn test_png_is_decodable_rgba() {
+        for (alpha, expected) in [(128, 128), (0, 255)] {
+            let png = encode_png(&pixels(alpha)).unwrap();
+            let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+            let mut rgba = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut rgba).unwrap();
+            assert_eq!(rgba, [30, 20, 10, expected, 60, 50, 40, expected]);
+        }
+    }
+
+    #[test]
+    fn test_icons_and_failures_are_cached() {
+        let cache = create_icon_cache();
+        let first = icon(&cache, "C:\\App\\a.exe", |_| Ok(pixels(255))).unwrap();
+        let second = icon(&cache, "c:\\app\\A.EXE", |_| panic!("not cached")).unwrap();
+        assert_eq!(first, second);
+
+        assert!(icon(&cache, "C:\\gone.exe", |_| Err("missing".to_string())).is_err());
+        assert!(icon(&cache, "C:\\gone.exe", |_| panic!("not cached")).is_err());
+    }
+}
@@ -0,0 +1,399 @@
+//! Shareable incident bundles: everything recorded in a time range as one
+//! JSON file ("here's the weird thing my disk did at 3 AM").
+
+use crate::burst::BURST_EVENT_KIND;
+use crate::models::{DiskStat, PhysicalDiskStat, ProcessSample, TimelineEvent};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Sub-directory of the data directory that holds exported bundles
+pub const EXPORTS_DIR: &str = "exports";
+
+/// Bumped when the bundle layout changes
+pub const BUNDLE_FORMAT_VERSION: u32 = 3;
+
+/// A burst (or other episode) with its details decoded
+#[derive(Debug, Clone, Serialize)]
+pub struct Episode {
+    pub kind: String,
+    pub start_time: f64,
+    pub end_time: Option<f64>,
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentBundle {
+    pub format_version: u32,
+    pub app_version: String,
+    /// User-defined label (or generated pseudonym) of the exporting machine
+    pub machine_label: String,
+    pub created_at: f64,
+    pub start: f64,
+    pub end: f64,
+    pub anonymized: bool,
+    /// Values bucketed and paths/usernames stripped (see `sanitize`)
+    pub sanitized: bool,
+    pub disk_stats: Vec<DiskStat>,
+    pub disks: Vec<PhysicalDiskStat>,
+    pub process_samples: Vec<ProcessSample>,
+    /// Timeline events other than episodes (maintenance runs, ...)
+    pub events: Vec<TimelineEvent>,
+    /// I/O bursts in the range
+    pub episodes: Vec<Episode>,
+}
+
+/// Recorded data of the range, before anonymization
+pub struct IncidentData {
+    pub disk_stats: Vec<DiskStat>,
+    pub disks: Vec<PhysicalDiskStat>,
+    pub process_samples: Vec<ProcessSample>,
+    pub events: Vec<TimelineEvent>,
+}
+
+/// Stable "process-N" aliases, numbered by first appearance
+#[derive(Debug, Default)]
+struct Aliases(HashMap<String, String>);
+
+impl Aliases {
+    fn alias(&mut self, name: &str) -> String {
+        let next = self.0.len() + 1;
+        self.0
+            .entry(name.to_string())
+            .or_insert_with(|| format!("process-{}", next))
+            .clone()
+    }
+
+    /// Replaces every "name" string inside a burst's top_processes
+    fn anonymize_details(&mut self, details: &mut serde_json::Value) {
+        if let Some(processes) = details
+            .get_mut("top_processes")
+            .and_then(|v| v.as_array_mut())
+        {
+            for process in processes {
+                if let Some(name) = process.get("name").and_then(|n| n.as_str()) {
+                    let alias = self.alias(name);
+                    process["name"] = serde_json::Value::String(alias);
+                }
+            }
+        }
+    }
+}
+
+pub fn build_bundle(
+    data: IncidentData,
+    start: f64,
+    end: f64,
+    anonymize: bool,
+    app_version: String,
+    machine_label: String,
+    created_at: f64,
+) -> IncidentBundle {
+    let mut aliases = Aliases::default();
+
+    let mut process_samples = data.process_samples;
+    if anonymize {
+        for sample in &mut process_samples {
+            sample.name = aliases.alias(&sample.name);
+        }
+    }
+
+    let (bursts, events): (Vec<TimelineEvent>, Vec<TimelineEvent>) = data
+        .events
+        .into_iter()
+        .partition(|event| event.kind == BURST_EVENT_KIND);
+
+    let episodes = bursts
+        .into_iter()
+        .map(|event| {
+            let mut details = event
+                .details
+                .as_deref()
+                .and_then(|d| serde_json::from_str(d).ok())
+                .unwrap_or(serde_json::Value::Null);
+            if anonymize {
+                aliases.anonymize_details(&mut details);
+            }
+            Episode {
+                kind: event.kind,
+                start_time: event.start_time,
+                end_time: event.end_time,
+                details,
+            }
+        })
+        .collect();
+
+    IncidentBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version,
+        machine_label,
+        created_at,
+        start,
+        end,
+        anonymized: anonymize,
+        sanitized: false,
+        disk_stats: data.disk_stats,
+        disks: data.disks,
+        process_samples,
+        events,
+        episodes,
+    }
+}
+
+/// Placeholder that replaces user names found in paths
+const USER_PLACEHOLDER: &str = "<user>";
+
+/// Rounds to two significant digits (1234567 -> 1200000) so exact byte
+/// counts cannot be used to fingerprint a machine or a file
+pub fn bucket(value: u64) -> u64 {
+    let mut scale = 1;
+    while value / scale >= 100 {
+        scale *= 10;
+    }
+    (value + scale / 2) / scale * scale
+}
+
+fn round_tenths(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Last path component: `C:\Users\bob\tool.exe` -> `tool.exe`
+pub fn strip_path(name: &str) -> String {
+    name.rsplit(['\\', '/']).next().unwrap_or(name).to_string()
+}
+
+/// Replaces the segment after `Users` / `home` in any path inside `text`
+pub fn strip_usernames(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(['\\', '/']) {
+        let (head, tail) = rest.split_at(pos + 1);
+        out.push_str(head);
+        rest = tail;
+
+        let segment_end = rest.find(['\\', '/']).unwrap_or(rest.len());
+        let segment = &rest[..segment_end];
+        if segment.eq_ignore_ascii_case("users") || segment == "home" {
+            out.push_str(segment);
+            rest = &rest[segment_end..];
+            if let Some(separator) = rest.chars().next() {
+                out.push(separator);
+                rest = &rest[separator.len_utf8()..];
+                let user_end = rest
+                    .find(|c: char| c == '\\' || c == '/' || c == '"' || c.is_whitespace())
+                    .unwrap_or(rest.len());
+                if user_end > 0 {
+                    out.push_str(USER_PLACEHOLDER);
+                    rest = &rest[user_end..];
+                }
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Buckets integers and scrubs strings in decoded episode details
+fn sanitize_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Number(n) => {
+            if let Some(v) = n.as_u64() {
+                *n = bucket(v).into();
+            }
+        }
+        serde_json::Value::String(text) => *text = strip_usernames(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(sanitize_value),
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                match item {
+                    serde_json::Value::String(name) if key == "name" => *name = strip_path(name),
+                    // Timestamps only lose their sub-second precision
+                    serde_json::Value::Number(n) if key.ends_with("_time") => {
+                        if let Some(t) = n.as_f64() {
+                            *item = serde_json::json!(t.round());
+                        }
+                    }
+                    _ => sanitize_value(item),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Sanitization pass for bundles meant to be shared publicly
+///
+/// Byte counts and speeds are rounded to two significant digits, other
+/// measurements to one decimal, timestamps to whole seconds; process names
+/// lose their directories and user names are removed from any path.
+pub fn sanitize(bundle: &mut IncidentBundle) {
+    for stat in &mut bundle.disk_stats {
+        stat.timestamp = stat.timestamp.round();
+        stat.read_bytes = bucket(stat.read_bytes);
+        stat.write_bytes = bucket(stat.write_bytes);
+        stat.read_speed = bucket(stat.read_speed);
+        stat.write_speed = bucket(stat.write_speed);
+        stat.idle_time = round_tenths(stat.idle_time);
+        stat.queue_depth = round_tenths(stat.queue_depth);
+        stat.read_iops = round_tenths(stat.read_iops);
+        stat.write_iops = round_tenths(stat.write_iops);
+        stat.read_latency_ms = round_tenths(stat.read_latency_ms);
+        stat.write_latency_ms = round_tenths(stat.write_latency_ms);
+    }
+    for disk in &mut bundle.disks {
+        disk.timestamp = disk.timestamp.round();
+        disk.read_speed = bucket(disk.read_speed);
+        disk.write_speed = bucket(disk.write_speed);
+        disk.idle_time = round_tenths(disk.idle_time);
+        disk.queue_depth = round_tenths(disk.queue_depth);
+    }
+    for sample in &mut bundle.process_samples {
+        sample.timestamp = sample.timestamp.round();
+        sample.name = strip_path(&sample.name);
+        sample.read_bytes = bucket(sample.read_bytes);
+        sample.write_bytes = bucket(sample.write_bytes);
+    }
+    for event in &mut bundle.events {
+        event.details = event.details.as_deref().map(strip_usernames);
+    }
+    for episode in &mut bundle.episodes {
+        sanitize_value(&mut episode.details);
+    }
+    bundle.sanitized = true;
+}
+
+/// Writes the bundle to `<data_dir>/exports/incident-<start>-<end>.json`
+pub fn write_bundle(
+    bundle: &IncidentBundle,
+    data_dir: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    let dir = data_dir.join(EXPORTS_DIR);
+    std::fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "incident-{}-{}.json",
+        bundle.start as u64, bundle.end as u64
+    ));
+    std::fs::write(&path, serde_json::to_vec_pretty(bundle)?)?;
+    println!("[Incident] Exported {}", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> IncidentData {
+        IncidentData {
+            disk_stats: Vec::new(),
+            disks: Vec::new(),
+            process_samples: vec![ProcessSample {
+                timestamp: 10.0,
+                name: "secret-project.exe".to_string(),
+                read_bytes: 1,
+                write_bytes: 2,
+            }],
+            events: vec![
+                TimelineEvent {
+                    id: 1,
+                    kind: BURST_EVENT_KIND.to_string(),
+                    start_time: 5.0,
+                    end_time: Some(9.0),
+                    details: Some(
+                        r#"{"direction":"write","top_processes":[{"name":"secret-project.exe","bytes":5},{"name":"other.exe","bytes":1}]}"#
+                            .to_string(),
+                    ),
+                },
+                TimelineEvent {
+                    id: 2,
+                    kind: "defrag".to_string(),
+                    start_time: 1.0,
+                    end_time: None,
+                    details: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_bundle_splits_episodes_from_events() {
+        let bundle = build_bundle(
+            data(),
+            0.0,
+            20.0,
+            false,
+            "1.0.0".to_string(),
+            "amber-otter-0001".to_string(),
+            30.0,
+        );
+        assert_eq!(bundle.events.len(), 1);
+        assert_eq!(bundle.episodes.len(), 1);
+        assert_eq!(bundle.episodes[0].details["direction"], "write");
+        assert_eq!(bundle.process_samples[0].name, "secret-project.exe");
+    }
+
+    #[test]
+    fn test_anonymization_uses_consistent_aliases() {
+        let bundle = build_bundle(
+            data(),
+            0.0,
+            20.0,
+            true,
+            "1.0.0".to_string(),
+            "amber-otter-0001".to_string(),
+            30.0,
+        );
+        assert_eq!(bundle.process_samples[0].name, "process-1");
+        let top = &bundle.episodes[0].details["top_processes"];
+        assert_eq!(top[0]["name"], "process-1");
+        assert_eq!(top[1]["name"], "process-2");
+        assert!(!serde_json::to_string(&bundle)
+            .unwrap()
+            .contains("secret-project"));
+    }
+
+    #[test]
+    fn test_bucket_keeps_two_significant_digits() {
+        assert_eq!(bucket(0), 0);
+        assert_eq!(bucket(87), 87);
+        assert_eq!(bucket(1234), 1200);
+        assert_eq!(bucket(1_250_000), 1_300_000);
+    }
+
+    #[test]
+    fn test_paths_and_usernames_are_stripped() {
+        assert_eq!(strip_path(r"C:\Users\alice\bin\tool.exe"), "tool.exe");
+        assert_eq!(strip_path("tool.exe"), "tool.exe");
+        assert_eq!(
+            strip_usernames(r"backup of C:\Users\alice\Documents failed"),
+            r"backup of C:\Users\<user>\Documents failed"
+        );
+        assert_eq!(strip_usernames("/home/bob/.cache"), "/home/<user>/.cache");
+    }
+
+    #[test]
+    fn test_sanitize_rounds_episode_details() {
+        let mut data = data();
+        data.process_samples[0].name = r"C:\Users\alice\secret-project.exe".to_string();
+        data.process_samples[0].write_bytes = 123_456;
+        let mut bundle = build_bundle(
+            data,
+            0.0,
+            20.0,
+            false,
+            "1.0.0".to_string(),
+            "amber-otter-0001".to_string(),
+            30.0,
+        );
+        bundle.episodes[0].details["peak_speed"] = serde_json::json!(98_765_432u64);
+        sanitize(&mut bundle);
+
+        assert!(bundle.sanitized);
+        // The label is already a pseudonym and survives sanitization
+        assert_eq!(bundle.machine_label, "amber-otter-0001");
+        assert_eq!(bundle.process_samples[0].name, "secret-project.exe");
+        assert_eq!(bundle.process_samples[0].write_bytes, 120_000);
+        assert_eq!(bundle.episodes[0].details["peak_speed"], 99_000_000);
+        assert!(!serde_json::to_string(&bundle).unwrap().contains("alice"));
+    }
+}
@@ -0,0 +1,90 @@
+// Process I/O önceliği (Windows IO_PRIORITY_HINT): düşük öncelikli arka plan
+// I/O'su diski meşgul gösterse de ön plandaki işleri yavaşlatmaz
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoPriority {
+    /// Very low or low: yields to everything else (indexers, Defender, backups)
+    Background,
+    Normal,
+    /// High or critical (the memory manager, audio)
+    High,
+}
+
+/// Maps an IO_PRIORITY_HINT value (0 = very low ... 4 = critical)
+pub fn from_hint(hint: u32) -> Option<IoPriority> {
+    match hint {
+        0 | 1 => Some(IoPriority::Background),
+        2 => Some(IoPriority::Normal),
+        3 | 4 => Some(IoPriority::High),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use super::IoPriority;
+    use windows::Wdk::System::Threading::{NtQueryInformationProcess, PROCESSINFOCLASS};
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    /// ProcessIoPriority bilgi sınıfı
+    const PROCESS_IO_PRIORITY: PROCESSINFOCLASS = PROCESSINFOCLASS(33);
+
+    /// Process'in I/O önceliğini oku (erişim yoksa None)
+    pub fn query(pid: u32) -> Option<IoPriority> {
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut hint: u32 = 0;
+            let mut returned: u32 = 0;
+            let status = NtQueryInformationProcess(
+                handle,
+                PROCESS_IO_PRIORITY,
+                &mut hint as *mut u32 as *mut core::ffi::c_void,
+                std::mem::size_of::<u32>() as u32,
+                &mut returned,
+            );
+
+            // Temizlik
+            let _ = CloseHandle(handle);
+
+            if status.is_ok() {
+                super::from_hint(hint)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::query;
+
+/// Windows dışı platformlar için fallback
+#[cfg(not(windows))]
+pub fn query(_pid: u32) -> Option<IoPriority> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints_map_to_three_levels() {
+        let levels: Vec<Option<IoPriority>> = (0..6).map(from_hint).collect();
+        assert_eq!(
+            levels,
+            [
+                Some(IoPriority::Background),
+                Some(IoPriority::Background),
+                Some(IoPriority::Normal),
+                Some(IoPriority::High),
+                Some(IoPriority::High),
+                None,
+            ]
+        );
+    }
+}
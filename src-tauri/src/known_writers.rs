@@ -0,0 +1,121 @@
+//! Well-known background writers (Defender scans, the search indexer,
+//! telemetry, browser caches, ...). Top-process rows that match one carry its
+//! id, and the table explains what the process does and how to tame it.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KnownWriter {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// Lowercase executable names
+    pub processes: &'static [&'static str],
+    /// Services recognized inside a "svchost (...)" row
+    pub services: &'static [&'static str],
+    pub explanation: &'static str,
+    pub mitigation: &'static str,
+}
+
+pub const KNOWN_WRITERS: &[KnownWriter] = &[
+    KnownWriter {
+        id: "defender",
+        title: "Microsoft Defender",
+        processes: &["msmpeng.exe", "mpdefendercoreservice.exe", "nissrv.exe", "mpcmdrun.exe"],
+        services: &["WinDefend"],
+        explanation: "Defender reads files to scan them and writes its signature updates and scan cache; full scans read the whole drive.",
+        mitigation: "Schedule full scans for idle hours and add exclusions for build output, VM images and game libraries you trust.",
+    },
+    KnownWriter {
+        id: "search_indexer",
+        title: "Windows Search indexer",
+        processes: &["searchindexer.exe", "searchprotocolhost.exe", "searchfilterhost.exe"],
+        services: &["WSearch"],
+        explanation: "The indexer reads new and changed files and rewrites its index database, heavily so after large copies or on first run.",
+        mitigation: "Remove large or fast-changing folders from Indexing Options, or switch the index to Classic mode.",
+    },
+    KnownWriter {
+        id: "telemetry",
+        title: "Windows telemetry",
+        processes: &["compattelrunner.exe", "devicecensus.exe"],
+        services: &["DiagTrack", "dmwappushservice"],
+        explanation: "Compatibility and diagnostics collectors scan installed programs and queue their reports on disk.",
+        mitigation: "Lower optional diagnostic data in Settings > Privacy > Diagnostics & feedback.",
+    },
+    KnownWriter {
+        id: "windows_update",
+        title: "Windows Update",
+        processes: &["tiworker.exe", "trustedinstaller.exe", "mousocoreworker.exe", "usocoreworker.exe"],
+        services: &["wuauserv", "UsoSvc", "TrustedInstaller"],
+        explanation: "Updates are downloaded, unpacked and installed into the component store, which also gets cleaned up afterwards.",
+        mitigation: "Set active hours so installs run while the machine is idle; the I/O stops once pending updates are done.",
+    },
+    KnownWriter {
+        id: "sysmain",
+        title: "SysMain (Superfetch)",
+        processes: &[],
+        services: &["SysMain"],
+        explanation: "SysMain preloads frequently used programs into memory and keeps its usage database up to date.",
+        mitigation: "Usually harmless on SSDs; the SysMain service can be disabled on machines where its reads get in the way.",
+    },
+    KnownWriter {
+        id: "browser_cache",
+        title: "Browser cache",
+        processes: &["chrome.exe", "msedge.exe", "firefox.exe", "brave.exe", "opera.exe", "vivaldi.exe"],
+        services: &[],
+        explanation: "Browsers write their disk cache, session state and profile databases continuously while open.",
+        mitigation: "Limit the cache size, close unused tabs, or keep the profile on a drive where the writes don't matter.",
+    },
+    KnownWriter {
+        id: "cloud_sync",
+        title: "Cloud sync",
+        processes: &["onedrive.exe", "dropbox.exe", "googledrivefs.exe"],
+        services: &[],
+        explanation: "Sync clients hash and copy changed files and keep a local database of the synced tree.",
+        mitigation: "Pause syncing during large copies, or exclude folders with build output and caches.",
+    },
+];
+
+/// Services of a "svchost (a, b)" row (see `services::svchost_name`)
+fn svchost_services(name: &str) -> impl Iterator<Item = &str> {
+    name.strip_prefix("svchost (")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .into_iter()
+        .flat_map(|list| list.split(", "))
+        .map(|service| service.split(" +").next().unwrap_or(service))
+}
+
+/// The known writer a process row belongs to; `name` may also be an exe path
+/// (keying by path, see `process_names`)
+pub fn identify(name: &str) -> Option<&'static KnownWriter> {
+    let file_name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+    KNOWN_WRITERS.iter().find(|writer| {
+        writer
+            .processes
+            .iter()
+            .any(|process| file_name.eq_ignore_ascii_case(process))
+            || svchost_services(file_name).any(|service| {
+                writer
+                    .services
+                    .iter()
+                    .any(|known| service.eq_ignore_ascii_case(known))
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_processes_and_hosted_services_are_identified() {
+        let id = |name: &str| identify(name).map(|writer| writer.id);
+        assert_eq!(id("MsMpEng.exe"), Some("defender"));
+        assert_eq!(
+            id("C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe"),
+            Some("browser_cache")
+        );
+        assert_eq!(id("svchost (BITS, wuauserv)"), Some("windows_update"));
+        assert_eq!(id("svchost (a, b, c +2)"), None);
+        assert_eq!(id("code.exe"), None);
+    }
+}
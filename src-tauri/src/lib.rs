@@ -1,6 +1,5 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
-use sqlx::{Pool, Sqlite};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
@@ -8,108 +7,2211 @@ use std::sync::{
 use tauri::{Manager, Emitter};
 use tokio::sync::Notify;
 
+pub mod activity;
+pub mod backup;
+pub mod burst;
+pub mod capabilities;
+pub mod categories;
+pub mod channels;
+pub mod clock;
+pub mod competitors;
+pub mod composition;
+pub mod core;
+pub mod coverage;
+pub mod data_dir;
 mod db;
-mod models;
 pub mod db_cleanup;
-pub mod scheduled_tasks;
+pub mod db_reader;
+pub mod diagnostics;
+pub mod distribution;
+pub mod drive_info;
+pub mod elevation;
+pub mod etw;
+pub mod file_io;
+pub mod flush_settings;
+pub mod gaps;
+pub mod icons;
+pub mod incident;
+pub mod io_priority;
+pub mod known_writers;
+pub mod low_wear;
+pub mod machine_label;
+pub mod maintenance;
+mod models;
+pub mod moment;
 pub mod monitor;
+pub mod monitor_loop;
+pub mod payload;
+pub mod peaks;
 pub mod perf_counters;
+pub mod power;
+pub mod process_comparison;
+pub mod process_control;
+pub mod process_filter;
+pub mod process_groups;
 pub mod process_monitor;
+pub mod process_names;
+pub mod process_sampler;
+pub mod sampling;
+pub mod scheduled_tasks;
+pub mod search;
+pub mod services;
+pub mod session;
+pub mod settings_transfer;
+pub mod smart;
+pub mod smoothing;
+pub mod soak;
+pub mod ssd_wear;
+pub mod storage;
+#[cfg(feature = "parquet-storage")]
+pub mod storage_parquet;
+pub mod subscriptions;
+pub mod telemetry;
+pub mod text_summary;
+pub mod top_processes;
+pub mod volume_monitor;
+pub mod write_coalescer;
+
+use models::AllTimeTotals;
+use models::AppMetrics;
+use models::AppStatus;
+use models::DiskStat;
+use models::DriveInfo;
+use models::FreeSpaceSample;
+use models::Moment;
+use models::PhysicalDiskStat;
+use models::ProcessHistoryChunk;
+use models::ProcessIOStat;
+use models::ResetDatabaseResponse;
+use models::SmoothedSpeeds;
+use models::SpeedPeak;
+use models::SsdWear;
+use models::TemperatureSample;
+use models::TimelineEvent;
+use models::VersionInfo;
+use models::VolumeStat;
+use process_monitor::ProcessAccumulators;
+use search::{SearchResult, SearchScope};
+use std::env;
+use std::fs;
+use subscriptions::{MetricStream, MetricSubscriptions};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use volume_monitor::VolumeStats;
+
+/// How long closing the main window waits for the monitor's final flush
+const SHUTDOWN_ACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Database pool state wrapper
+pub struct DbPool(pub db::SharedPool);
+
+// Process accumulators state wrapper
+pub struct ProcessAccumulatorsState(pub ProcessAccumulators);
+
+// Reset signal wrapper
+pub struct ResetSignal(pub Arc<AtomicBool>);
+
+// Monitoring paused by the user
+pub struct PausedState(pub Arc<AtomicBool>);
+
+// Main window minimized or hidden (live payloads suspended)
+pub struct WindowHiddenState(pub Arc<AtomicBool>);
+
+// The monitor's process sampler (None until the loop starts)
+pub struct ProcessSamplerState(pub process_sampler::SharedSampler);
+
+// File-level I/O counts and the ETW trace feeding them (None while off)
+pub struct FileIoState(pub file_io::SharedFileIo);
+pub struct FileTracerState(pub Mutex<Option<file_io::FileTracer>>);
+
+// Extracted executable icons (base64 PNG by path)
+pub struct IconCacheState(pub icons::IconCache);
+
+// Processes left out of the per-process stats
+pub struct ProcessFilterState(pub process_filter::SharedProcessFilter);
+
+// Number of processes in the streamed top-processes list
+pub struct TopLimitState(pub top_processes::SharedTopLimit);
+
+// Read/write totals of the running session
+pub struct SessionTotalsState(pub session::SharedSessionTotals);
+
+// Shutdown signal wrapper for graceful exit
+pub struct ShutdownSignal(pub Arc<AtomicBool>);
+pub struct ShutdownNotify(pub Arc<Notify>);
+
+// Live event subscriptions declared by the frontend
+pub struct MetricSubscriptionsState(pub MetricSubscriptions);
+
+// Per-volume live stats state wrapper
+pub struct VolumeStatsState(pub VolumeStats);
+
+// Time series storage backend
+pub struct StorageState(pub storage::SharedStorage);
+
+// Read-only connection and writer contention metrics
+pub struct DbContentionState(pub db_reader::SharedContention);
+
+// Monitor loop health counters
+pub struct MonitorStatsState(pub monitor::SharedMonitorStats);
+
+// Live payload encoding and the latest binary frames
+pub struct LiveFramesState(pub payload::SharedLiveFrames);
+
+// Binary stream channels opened by the frontend
+pub struct ChannelsState(pub channels::SharedChannels);
+
+// Reduced sampling settings used on battery
+pub struct BatteryThrottleState(pub power::SharedBatteryThrottle);
+
+// Maximum drive activity LED update rate
+pub struct LedRateState(pub activity::SharedLedRate);
+
+// Low-wear (RAM journal) database write settings
+pub struct LowWearState(pub low_wear::SharedLowWear);
+
+// Database flush period and buffer size
+pub struct FlushSettingsState(pub flush_settings::SharedFlushSettings);
+
+// Monitor sampling interval (milliseconds)
+pub struct SampleIntervalState(pub sampling::SharedInterval);
+
+// Opt-in usage counters
+pub struct TelemetryState(pub telemetry::Telemetry);
+
+// System state wrapper for metrics
+pub struct SystemState(pub Mutex<System>);
+
+// Confirmation tokens of pending process kills
+pub struct KillTokensState(pub process_control::KillTokens);
+
+// Rows queued for the next database flush
+pub struct WriteCoalescerState(pub write_coalescer::WriteCoalescer);
+
+// Startup capability probe results
+pub struct CapabilitiesState(pub capabilities::SharedCapabilities);
+
+// Single-instance lock file (taken during setup, released on close)
+pub struct InstanceLockState(pub Mutex<Option<competitors::InstanceLock>>);
+
+#[tauri::command]
+fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
+#[tauri::command]
+async fn get_alltime_totals(db_pool: tauri::State<'_, DbPool>) -> Result<AllTimeTotals, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::alltime_totals(&pool).await.map_err(|e| e.to_string())
+}
+
+// save_session_to_alltime command removed as it was causing double counting.
+// Monitor handles real-time updates to process_history.
+
+#[tauri::command]
+async fn get_process_history(
+    db_pool: tauri::State<'_, DbPool>,
+) -> Result<std::collections::HashMap<String, (u64, u64)>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::process_history(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Pages through process_history for virtual scrolling (limit is clamped to 1..=1000)
+#[tauri::command]
+async fn get_process_history_chunk(
+    db_pool: tauri::State<'_, DbPool>,
+    cursor: Option<String>,
+    limit: u32,
+) -> Result<ProcessHistoryChunk, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::process_history_chunk(&pool, cursor.as_deref(), limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_process_history_totals(
+    db_pool: tauri::State<'_, DbPool>,
+) -> Result<AllTimeTotals, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::process_history_totals(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_timeline_events(
+    db_pool: tauri::State<'_, DbPool>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<TimelineEvent>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::timeline_events(&pool, start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recorded disk stats (speeds, IOPS, latency, idle, queue) within a time range
+#[tauri::command]
+async fn get_disk_stats_history(
+    storage_state: tauri::State<'_, StorageState>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<DiskStat>, String> {
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    core::disk_stats_history(store.as_ref(), start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 5s/30s moving averages of the recorded speeds within a time range
+#[tauri::command]
+async fn get_smoothed_history(
+    storage_state: tauri::State<'_, StorageState>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<SmoothedSpeeds>, String> {
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    core::smoothed_history(store.as_ref(), start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Free space per volume over time (volume = None returns every volume)
+#[tauri::command]
+async fn get_free_space_history(
+    db_pool: tauri::State<'_, DbPool>,
+    contention: tauri::State<'_, DbContentionState>,
+    start: f64,
+    end: f64,
+    volume: Option<String>,
+) -> Result<Vec<FreeSpaceSample>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::free_space_history(&pool, &contention.0, start, end, volume.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Drive temperatures over time (disk = None returns every disk)
+#[tauri::command]
+async fn get_temperature_history(
+    db_pool: tauri::State<'_, DbPool>,
+    contention: tauri::State<'_, DbContentionState>,
+    start: f64,
+    end: f64,
+    disk: Option<String>,
+) -> Result<Vec<TemperatureSample>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::temperature_history(&pool, &contention.0, start, end, disk.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// All-time read and write peaks with when they happened and the top process
+#[tauri::command]
+async fn get_peaks(db_pool: tauri::State<'_, DbPool>) -> Result<Vec<SpeedPeak>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::all_time_peaks(&pool).await.map_err(|e| e.to_string())
+}
+
+/// Read-only connection timings and writer wait/busy counts
+#[tauri::command]
+fn get_db_contention(
+    contention: tauri::State<'_, DbContentionState>,
+) -> Result<db_reader::ContentionStats, String> {
+    contention
+        .0
+        .lock()
+        .map(|stats| stats.clone())
+        .map_err(|e| format!("Lock error: {}", e))
+}
+
+/// Mounted drives with model, serial, bus type, SSD/HDD, filesystem and capacity
+#[tauri::command]
+async fn list_drives() -> Result<Vec<DriveInfo>, String> {
+    tokio::task::spawn_blocking(drive_info::list_drives)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Natural-language summary of recent activity for screen readers and the tray tooltip
+#[tauri::command]
+async fn get_text_summary(
+    storage_state: tauri::State<'_, StorageState>,
+    accumulators: tauri::State<'_, ProcessAccumulatorsState>,
+    range: text_summary::SummaryRange,
+) -> Result<text_summary::TextSummary, String> {
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    let end = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    core::text_summary(store.as_ref(), &accumulators.0, range, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read/write composition of a range: direction, transfer size and per-day split
+#[tauri::command]
+async fn get_io_composition(
+    storage_state: tauri::State<'_, StorageState>,
+    start: f64,
+    end: f64,
+    utc_offset_minutes: Option<i32>,
+) -> Result<composition::IoComposition, String> {
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    let offset_secs = i64::from(utc_offset_minutes.unwrap_or(0)) * 60;
+    core::io_composition(store.as_ref(), start, end, offset_secs)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Speed percentiles (p50/p90/p99) and histograms of a range
+#[tauri::command]
+async fn get_speed_distribution(
+    storage_state: tauri::State<'_, StorageState>,
+    start: f64,
+    end: f64,
+) -> Result<distribution::SpeedDistributions, String> {
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    core::speed_distribution(store.as_ref(), start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Periods of a range without samples (app closed, paused or asleep)
+#[tauri::command]
+async fn get_data_gaps(
+    db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<gaps::DataGap>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    core::data_gaps(&pool, store.as_ref(), start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// User-assigned process categories and the category names to offer
+#[tauri::command]
+async fn get_process_categories(
+    db_pool: tauri::State<'_, DbPool>,
+) -> Result<categories::ProcessCategories, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::process_categories(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Tags a process name with a category (None removes the tag)
+#[tauri::command]
+async fn set_process_category(
+    db_pool: tauri::State<'_, DbPool>,
+    name: String,
+    category: Option<String>,
+) -> Result<(), String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::set_process_category(&pool, &name, category.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read/write totals per process category and local day of a range
+#[tauri::command]
+async fn get_category_totals(
+    db_pool: tauri::State<'_, DbPool>,
+    contention: tauri::State<'_, DbContentionState>,
+    start: f64,
+    end: f64,
+    utc_offset_minutes: Option<i32>,
+) -> Result<Vec<categories::CategoryDay>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let offset_secs = i64::from(utc_offset_minutes.unwrap_or(0)) * 60;
+    core::category_totals(&pool, &contention.0, start, end, offset_secs)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Seconds per day the monitor was collecting, with a coverage percentage
+#[tauri::command]
+async fn get_monitoring_coverage(
+    db_pool: tauri::State<'_, DbPool>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<coverage::DayCoverage>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    core::monitoring_coverage(&pool, start, end, now)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_physical_disk_stats(
+    storage_state: tauri::State<'_, StorageState>,
+    start: f64,
+    end: f64,
+) -> Result<Vec<PhysicalDiskStat>, String> {
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+    core::physical_disk_stats(store.as_ref(), start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// What was happening at an instant: nearest sample, per-disk counters,
+/// active processes and timeline events
+#[tauri::command]
+async fn get_moment(
+    db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    contention: tauri::State<'_, DbContentionState>,
+    writes: tauri::State<'_, WriteCoalescerState>,
+    sampler: tauri::State<'_, ProcessSamplerState>,
+    timestamp: f64,
+) -> Result<Moment, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
+
+    // Rows of the current flush period aren't in the database yet
+    let (start, end) = moment::window(timestamp);
+    let mut pending = write_coalescer::pending_between(&writes.0, start, end);
+    if let Some(sampler) = process_sampler::current(&sampler.0) {
+        pending.merge_process_deltas(sampler.unsaved_deltas().await);
+    }
+
+    core::moment_at(&pool, store.as_ref(), &contention.0, timestamp, pending)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Declares which live streams the frontend currently displays.
+/// The monitor skips computing/emitting the others until they are requested again.
+#[tauri::command]
+fn set_metric_subscriptions(
+    subscriptions: tauri::State<'_, MetricSubscriptionsState>,
+    streams: Vec<MetricStream>,
+) -> Result<(), String> {
+    let mut guard = subscriptions
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    guard.set_shared(streams);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_metric_subscriptions(
+    subscriptions: tauri::State<'_, MetricSubscriptionsState>,
+) -> Result<Vec<MetricStream>, String> {
+    let guard = subscriptions
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    Ok(guard.streams())
+}
+
+/// Adds streams the calling window needs (e.g. when the dashboard opens);
+/// returns every stream currently wanted by any window
+#[tauri::command]
+fn subscribe_metrics(
+    subscriptions: tauri::State<'_, MetricSubscriptionsState>,
+    window: tauri::Window,
+    streams: Vec<MetricStream>,
+) -> Result<Vec<MetricStream>, String> {
+    let mut guard = subscriptions
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    guard.subscribe(window.label(), streams);
+    Ok(guard.streams())
+}
+
+/// Removes streams the calling window no longer displays
+#[tauri::command]
+fn unsubscribe_metrics(
+    subscriptions: tauri::State<'_, MetricSubscriptionsState>,
+    window: tauri::Window,
+    streams: Vec<MetricStream>,
+) -> Result<Vec<MetricStream>, String> {
+    let mut guard = subscriptions
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    guard.unsubscribe(window.label(), &streams);
+    Ok(guard.streams())
+}
+
+/// Current speeds and session totals for each mounted volume (C:, D:, ...)
+#[tauri::command]
+fn get_volume_stats(volume_stats: tauri::State<'_, VolumeStatsState>) -> Vec<VolumeStat> {
+    volume_monitor::snapshot(&volume_stats.0)
+}
+
+/// Ranked fuzzy search over process names, exe paths and traced file paths.
+/// File paths are only available when file-level tracing has produced them.
+#[tauri::command]
+async fn fuzzy_search(
+    db_pool: tauri::State<'_, DbPool>,
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    system_state: tauri::State<'_, SystemState>,
+    file_io_state: tauri::State<'_, FileIoState>,
+    scope: SearchScope,
+    query: String,
+) -> Result<Vec<SearchResult>, String> {
+    telemetry::record_feature(&telemetry_state.0, "fuzzy_search");
+    let mut candidates: Vec<(SearchScope, String)> = Vec::new();
+
+    // Live processes
+    if scope.includes(SearchScope::Processes) || scope.includes(SearchScope::ExePaths) {
+        let mut sys = system_state.0.lock().map_err(|e| e.to_string())?;
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, process_monitor::name_refresh());
+        for process in sys.processes().values() {
+            if scope.includes(SearchScope::Processes) {
+                candidates.push((
+                    SearchScope::Processes,
+                    process.name().to_string_lossy().to_string(),
+                ));
+            }
+            if scope.includes(SearchScope::ExePaths) {
+                if let Some(exe) = process.exe() {
+                    candidates.push((SearchScope::ExePaths, exe.to_string_lossy().to_string()));
+                }
+            }
+        }
+    }
+
+    // All-time process names
+    if scope.includes(SearchScope::Processes) {
+        if let Some(pool) = db::current_pool(&db_pool.0) {
+            let history = db::get_process_history(&pool)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?;
+            candidates.extend(
+                history
+                    .into_keys()
+                    .map(|name| (SearchScope::Processes, name)),
+            );
+        }
+    }
+
+    // Files seen by the file-level tracer this session
+    if scope.includes(SearchScope::Files) {
+        let tracker = file_io_state
+            .0
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        candidates.extend(
+            tracker
+                .files()
+                .into_iter()
+                .map(|stat| (SearchScope::Files, stat.path)),
+        );
+    }
+
+    Ok(search::fuzzy_search(candidates, &query, 50))
+}
+
+/// Moves the database, backups and logs to `new_dir` (copy, verify checksums,
+/// switch, delete old). The monitor keeps buffering in memory meanwhile.
+#[tauri::command]
+async fn migrate_app_data(
+    db_pool: tauri::State<'_, DbPool>,
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    app_handle: tauri::AppHandle,
+    new_dir: String,
+) -> Result<data_dir::MigrationReport, String> {
+    telemetry::record_feature(&telemetry_state.0, "migrate_app_data");
+    let report =
+        data_dir::migrate_app_data(&app_handle, &db_pool.0, std::path::Path::new(&new_dir))
+            .await
+            .map_err(|e| format!("Migration failed: {}", e))?;
+
+    let _ = app_handle.emit("data-dir-changed", &report);
+
+    Ok(report)
+}
+
+/// Creates a checksummed backup and rotates old ones (defaults: 7 daily, 4 weekly)
+#[tauri::command]
+async fn create_backup(
+    db_pool: tauri::State<'_, DbPool>,
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    app_handle: tauri::AppHandle,
+    keep_daily: Option<u32>,
+    keep_weekly: Option<u32>,
+) -> Result<backup::BackupInfo, String> {
+    telemetry::record_feature(&telemetry_state.0, "create_backup");
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+
+    let defaults = backup::BackupRotation::default();
+    let rotation = backup::BackupRotation {
+        keep_daily: keep_daily.unwrap_or(defaults.keep_daily),
+        keep_weekly: keep_weekly.unwrap_or(defaults.keep_weekly),
+    };
+    let data_dir = data_dir::resolve_data_dir(&app_handle).map_err(|e| e.to_string())?;
+
+    backup::create_backup(&pool, &data_dir, rotation)
+        .await
+        .map_err(|e| format!("Backup failed: {}", e))
+}
+
+/// Writes a shareable JSON bundle of everything recorded in a time range and
+/// returns its path (process names optionally replaced by aliases, values
+/// optionally bucketed and paths/usernames stripped)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn export_incident(
+    db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    contention: tauri::State<'_, DbContentionState>,
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    app_handle: tauri::AppHandle,
+    start: f64,
+    end: f64,
+    anonymize: Option<bool>,
+    sanitize: Option<bool>,
+) -> Result<String, String> {
+    telemetry::record_feature(&telemetry_state.0, "export_incident");
+    if end <= start {
+        return Err("End must be after start".to_string());
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let store = storage::current(&storage_state.0).ok_or("Database not initialized")?;
+
+    let disk_stats = store
+        .disk_stats_history(start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+    let disks = store
+        .physical_disk_stats(start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut read = db_reader::ReadSession::begin(&pool, &contention.0)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let process_samples = db::get_process_samples(read.conn(), start, end).await;
+    read.finish(&process_samples).await;
+    let process_samples = process_samples.map_err(|e| format!("Database error: {}", e))?;
+
+    let events = db::get_timeline_events(&pool, start, end)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let machine_label = machine_label::load_or_create_label(&pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    let mut bundle = incident::build_bundle(
+        incident::IncidentData {
+            disk_stats,
+            disks,
+            process_samples,
+            events,
+        },
+        start,
+        end,
+        anonymize.unwrap_or(false),
+        app_handle.package_info().version.to_string(),
+        machine_label,
+        created_at,
+    );
+    if sanitize.unwrap_or(false) {
+        incident::sanitize(&mut bundle);
+    }
+
+    let data_dir = data_dir::resolve_data_dir(&app_handle).map_err(|e| e.to_string())?;
+    let path = tokio::task::spawn_blocking(move || {
+        incident::write_bundle(&bundle, &data_dir).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn list_backups(app_handle: tauri::AppHandle) -> Result<Vec<backup::BackupInfo>, String> {
+    let data_dir = data_dir::resolve_data_dir(&app_handle).map_err(|e| e.to_string())?;
+    backup::list_backups(&data_dir).map_err(|e| e.to_string())
+}
+
+/// Checks a backup's SHA-256 against its manifest and runs an integrity check
+#[tauri::command]
+async fn verify_backup(
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    path: String,
+) -> Result<backup::BackupVerification, String> {
+    telemetry::record_feature(&telemetry_state.0, "verify_backup");
+    backup::verify_backup(std::path::Path::new(&path))
+        .await
+        .map_err(|e| format!("Verification failed: {}", e))
+}
+
+/// Version and environment details for the About panel and bug reports
+#[tauri::command]
+async fn get_version_info(
+    db_pool: tauri::State<'_, DbPool>,
+    app_handle: tauri::AppHandle,
+) -> Result<VersionInfo, String> {
+    // Still useful before the database is ready; fall back to unknowns
+    let (schema_version, sqlite_version) = match db::current_pool(&db_pool.0) {
+        Some(pool) => db::get_schema_info(&pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?,
+        None => (0, "unknown".to_string()),
+    };
+
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
+    Ok(VersionInfo {
+        app_version: app_handle.package_info().version.to_string(),
+        git_hash: non_empty(env!("DRIVEANALIZER_GIT_HASH")),
+        schema_version,
+        sqlx_version: non_empty(env!("DRIVEANALIZER_SQLX_VERSION")),
+        sqlite_version,
+        os_version: System::long_os_version().unwrap_or_else(|| "unknown".to_string()),
+        kernel_version: System::kernel_version().unwrap_or_else(|| "unknown".to_string()),
+        elevated: elevation::is_elevated_safe(),
+        portable: data_dir::is_portable(&app_handle),
+    })
+}
+
+/// Environment status, including other monitoring tools and instances that
+/// may conflict with our trace sessions or counters
+#[tauri::command]
+async fn get_status(
+    system_state: tauri::State<'_, SystemState>,
+    paused: tauri::State<'_, PausedState>,
+    instance_lock: tauri::State<'_, InstanceLockState>,
+) -> Result<AppStatus, String> {
+    let own_name = own_executable_name();
+    let processes: Vec<(u32, String)> = {
+        let mut sys = system_state.0.lock().map_err(|e| e.to_string())?;
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, process_monitor::name_refresh());
+        sys.processes()
+            .iter()
+            .map(|(pid, process)| (pid.as_u32(), process.name().to_string_lossy().to_string()))
+            .collect()
+    };
+    let mut competing_monitors = competitors::match_processes(
+        processes.iter().map(|(pid, name)| (*pid, name.as_str())),
+        std::process::id(),
+        &own_name,
+    );
+
+    // An instance started under another executable name is only known from the lock file
+    let lock_pid = instance_lock
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|lock| lock.other_pid);
+    if let Some(pid) = lock_pid {
+        if !competing_monitors.iter().any(|m| m.pid == Some(pid)) {
+            competing_monitors.push(competitors::other_instance(pid));
+        }
+    }
+
+    let sessions = tokio::task::spawn_blocking(competitors::list_trace_sessions_safe)
+        .await
+        .map_err(|e| e.to_string())?;
+    competing_monitors.extend(competitors::match_sessions(&sessions));
+
+    Ok(AppStatus {
+        elevated: elevation::is_elevated_safe(),
+        counter_backend: perf_counters::counter_backend().to_string(),
+        monitoring_paused: paused.0.load(Ordering::Relaxed),
+        competing_monitors,
+    })
+}
+
+fn own_executable_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Shows exactly what a telemetry report would contain, whether enabled or not
+#[tauri::command]
+fn preview_telemetry_payload(
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    app_handle: tauri::AppHandle,
+) -> telemetry::TelemetryPayload {
+    telemetry::build_payload(&app_handle, &telemetry_state.0)
+}
+
+#[tauri::command]
+async fn get_telemetry_status(
+    db_pool: tauri::State<'_, DbPool>,
+) -> Result<telemetry::TelemetryStatus, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let enabled = telemetry::is_enabled(&pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(telemetry::TelemetryStatus {
+        enabled,
+        endpoint_configured: telemetry::TELEMETRY_ENDPOINT.is_some(),
+    })
+}
+
+/// Stops collecting until resume_monitoring (e.g. during a benchmark that
+/// shouldn't be counted); the paused period is kept as a timeline event
+#[tauri::command]
+fn pause_monitoring(paused: tauri::State<'_, PausedState>) {
+    paused.0.store(true, Ordering::Relaxed);
+}
+
+/// Resumes collecting; I/O done while paused is not attributed
+#[tauri::command]
+fn resume_monitoring(paused: tauri::State<'_, PausedState>) {
+    paused.0.store(false, Ordering::Relaxed);
+}
+
+#[tauri::command]
+fn is_monitoring_paused(paused: tauri::State<'_, PausedState>) -> bool {
+    paused.0.load(Ordering::Relaxed)
+}
+
+/// Latest MessagePack frame of a live stream (when the binary encoding is selected)
+#[tauri::command]
+fn get_live_frame(
+    live_frames: tauri::State<'_, LiveFramesState>,
+    stream: MetricStream,
+) -> Result<tauri::ipc::Response, String> {
+    payload::latest_frame(&live_frames.0, stream)
+        .map(tauri::ipc::Response::new)
+        .ok_or_else(|| "No frame available".to_string())
+}
+
+#[tauri::command]
+fn get_payload_encoding(
+    live_frames: tauri::State<'_, LiveFramesState>,
+) -> payload::PayloadEncoding {
+    payload::encoding(&live_frames.0)
+}
+
+/// Switches live payloads between JSON events and MessagePack frames
+#[tauri::command]
+async fn set_payload_encoding(
+    db_pool: tauri::State<'_, DbPool>,
+    live_frames: tauri::State<'_, LiveFramesState>,
+    encoding: payload::PayloadEncoding,
+) -> Result<(), String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    payload::save_encoding(&pool, encoding)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    payload::set_encoding(&live_frames.0, encoding);
+    Ok(())
+}
+
+/// Streams disk-metrics as MessagePack frames to this channel until
+/// unsubscribe_metric_channel is called or the window goes away
+#[tauri::command]
+fn subscribe_disk_metrics(
+    live_channels: tauri::State<'_, ChannelsState>,
+    channel: tauri::ipc::Channel,
+) -> u32 {
+    channels::subscribe(&live_channels.0, MetricStream::DiskMetrics, channel)
+}
+
+/// Streams `stream` as MessagePack frames to this channel; each window
+/// subscribes on its own and only pays for the streams it displays
+#[tauri::command]
+fn subscribe_metric_channel(
+    live_channels: tauri::State<'_, ChannelsState>,
+    stream: MetricStream,
+    channel: tauri::ipc::Channel,
+) -> Result<u32, String> {
+    if !channels::is_channel_stream(stream) {
+        return Err(format!(
+            "{} is not available as a channel",
+            stream.event_name()
+        ));
+    }
+    Ok(channels::subscribe(&live_channels.0, stream, channel))
+}
+
+#[tauri::command]
+fn unsubscribe_metric_channel(live_channels: tauri::State<'_, ChannelsState>, id: u32) -> bool {
+    channels::unsubscribe(&live_channels.0, id)
+}
+
+/// Whether disk-metrics and top-processes are also emitted as window events (for older frontends)
+#[tauri::command]
+fn get_legacy_disk_metrics_event(live_channels: tauri::State<'_, ChannelsState>) -> bool {
+    channels::legacy_disk_metrics_event(&live_channels.0)
+}
+
+#[tauri::command]
+async fn set_legacy_disk_metrics_event(
+    db_pool: tauri::State<'_, DbPool>,
+    live_channels: tauri::State<'_, ChannelsState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    channels::save_legacy_disk_metrics_event(&pool, enabled)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    channels::set_legacy_disk_metrics_event(&live_channels.0, enabled);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_battery_throttle(
+    throttle: tauri::State<'_, BatteryThrottleState>,
+) -> Result<power::BatteryThrottle, String> {
+    throttle
+        .0
+        .lock()
+        .map(|t| *t)
+        .map_err(|e| format!("Lock error: {}", e))
+}
+
+/// Configures how sampling and flushing slow down on battery
+#[tauri::command]
+async fn set_battery_throttle(
+    db_pool: tauri::State<'_, DbPool>,
+    throttle: tauri::State<'_, BatteryThrottleState>,
+    settings: power::BatteryThrottle,
+) -> Result<(), String> {
+    if !(sampling::MIN_INTERVAL_MS..=sampling::MAX_INTERVAL_MS).contains(&settings.interval_ms) {
+        return Err(format!(
+            "Interval must be between {} and {} ms",
+            sampling::MIN_INTERVAL_MS,
+            sampling::MAX_INTERVAL_MS
+        ));
+    }
+    if settings.flush_secs == 0 {
+        return Err("Flush period must be at least one second".to_string());
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    power::save_battery_throttle(&pool, &settings)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut guard = throttle
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    *guard = settings;
+    Ok(())
+}
+
+/// Label exported data is tagged with instead of the hostname
+#[tauri::command]
+async fn get_machine_label(db_pool: tauri::State<'_, DbPool>) -> Result<String, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    machine_label::load_or_create_label(&pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Sets the machine label; `None` replaces it with a new pseudonym
+#[tauri::command]
+async fn set_machine_label(
+    db_pool: tauri::State<'_, DbPool>,
+    label: Option<String>,
+) -> Result<String, String> {
+    let label = match label {
+        Some(label) => machine_label::validate_label(&label)?,
+        None => machine_label::generate_pseudonym(),
+    };
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    machine_label::save_label(&pool, &label)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(label)
+}
+
+#[tauri::command]
+fn get_low_wear_mode(
+    low_wear_mode: tauri::State<'_, LowWearState>,
+) -> Result<low_wear::LowWearMode, String> {
+    low_wear_mode
+        .0
+        .lock()
+        .map(|m| *m)
+        .map_err(|e| format!("Lock error: {}", e))
+}
+
+/// Enables low-wear writes: the database is written every `flush_minutes`
+/// in one transaction, and up to that much data is lost on a crash
+#[tauri::command]
+async fn set_low_wear_mode(
+    db_pool: tauri::State<'_, DbPool>,
+    low_wear_mode: tauri::State<'_, LowWearState>,
+    settings: low_wear::LowWearMode,
+) -> Result<(), String> {
+    if !(low_wear::MIN_FLUSH_MINUTES..=low_wear::MAX_FLUSH_MINUTES)
+        .contains(&settings.flush_minutes)
+    {
+        return Err(format!(
+            "Flush period must be between {} and {} minutes",
+            low_wear::MIN_FLUSH_MINUTES,
+            low_wear::MAX_FLUSH_MINUTES
+        ));
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    low_wear::save_low_wear(&pool, &settings)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut guard = low_wear_mode
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    *guard = settings;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_flush_settings(
+    flush_settings: tauri::State<'_, FlushSettingsState>,
+) -> Result<flush_settings::FlushSettings, String> {
+    flush_settings
+        .0
+        .lock()
+        .map(|s| *s)
+        .map_err(|e| format!("Lock error: {}", e))
+}
+
+/// Changes how often queued samples are written and how many may wait before
+/// an early write; applies from the next tick
+#[tauri::command]
+async fn set_flush_settings(
+    db_pool: tauri::State<'_, DbPool>,
+    flush_settings: tauri::State<'_, FlushSettingsState>,
+    settings: flush_settings::FlushSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    flush_settings::save_flush_settings(&pool, &settings)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut guard = flush_settings
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    *guard = settings;
+    Ok(())
+}
+
+/// Processes excluded from the per-process stats
+#[tauri::command]
+fn get_process_filter(
+    process_filter: tauri::State<'_, ProcessFilterState>,
+) -> Result<process_filter::ProcessFilter, String> {
+    process_filter
+        .0
+        .lock()
+        .map(|filter| filter.clone())
+        .map_err(|e| format!("Lock error: {}", e))
+}
+
+/// Applies the stored settings to the running app: at startup, and again
+/// after an import replaced them
+async fn load_stored_settings(app_handle: &tauri::AppHandle, pool: &sqlx::Pool<sqlx::Sqlite>) {
+    match sampling::load_interval_ms(pool).await {
+        Ok(interval_ms) => app_handle
+            .state::<SampleIntervalState>()
+            .0
+            .store(interval_ms, Ordering::Relaxed),
+        Err(e) => eprintln!("[Monitor] Failed to load sampling interval: {}", e),
+    }
+    match payload::load_encoding(pool).await {
+        Ok(encoding) => payload::set_encoding(&app_handle.state::<LiveFramesState>().0, encoding),
+        Err(e) => eprintln!("[Monitor] Failed to load payload encoding: {}", e),
+    }
+    match power::load_battery_throttle(pool).await {
+        Ok(settings) => {
+            if let Ok(mut guard) = app_handle.state::<BatteryThrottleState>().0.lock() {
+                *guard = settings;
+            }
+        }
+        Err(e) => eprintln!("[Monitor] Failed to load battery settings: {}", e),
+    }
+    match channels::load_legacy_disk_metrics_event(pool).await {
+        Ok(enabled) => {
+            channels::set_legacy_disk_metrics_event(&app_handle.state::<ChannelsState>().0, enabled)
+        }
+        Err(e) => eprintln!("[Monitor] Failed to load disk-metrics event setting: {}", e),
+    }
+    match activity::load_led_hz(pool).await {
+        Ok(hz) => activity::store_led_hz(&app_handle.state::<LedRateState>().0, hz),
+        Err(e) => eprintln!("[Monitor] Failed to load activity LED rate: {}", e),
+    }
+    match top_processes::load_top_limit(pool).await {
+        Ok(limit) => app_handle
+            .state::<TopLimitState>()
+            .0
+            .store(limit, Ordering::Relaxed),
+        Err(e) => eprintln!("[Monitor] Failed to load the top-processes limit: {}", e),
+    }
+    match low_wear::load_low_wear(pool).await {
+        Ok(settings) => {
+            if let Ok(mut guard) = app_handle.state::<LowWearState>().0.lock() {
+                *guard = settings;
+            }
+        }
+        Err(e) => eprintln!("[Monitor] Failed to load low-wear settings: {}", e),
+    }
+    match flush_settings::load_flush_settings(pool).await {
+        Ok(settings) => {
+            if let Ok(mut guard) = app_handle.state::<FlushSettingsState>().0.lock() {
+                *guard = settings;
+            }
+        }
+        Err(e) => eprintln!("[Monitor] Failed to load flush settings: {}", e),
+    }
+    match process_filter::load_process_filter(pool).await {
+        Ok(filter) => {
+            apply_self_exclusion(
+                app_handle,
+                &app_handle.state::<FileIoState>().0,
+                filter.exclude_self,
+            );
+            // Rows still pending under the old names when the setting changed
+            match core::normalize_recorded_names(pool, &filter.names).await {
+                Ok(0) => {}
+                Ok(merged) => println!(
+                    "[Monitor] Merged {} process names into their normalized form",
+                    merged
+                ),
+                Err(e) => eprintln!(
+                    "[Monitor] Failed to normalize recorded process names: {}",
+                    e
+                ),
+            }
+            if let Ok(mut guard) = app_handle.state::<ProcessFilterState>().0.lock() {
+                *guard = filter;
+            }
+        }
+        Err(e) => eprintln!("[Monitor] Failed to load the process exclusion list: {}", e),
+    }
+}
+
+/// Writes the portable settings and process categories to one JSON file and
+/// returns its path, for moving the configuration to another machine
+#[tauri::command]
+async fn export_settings(
+    db_pool: tauri::State<'_, DbPool>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let file = settings_transfer::export_settings(&pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let data_dir = data_dir::resolve_data_dir(&app_handle).map_err(|e| e.to_string())?;
+    let path = tokio::task::spawn_blocking(move || {
+        settings_transfer::write_settings_file(&file, &data_dir, timestamp)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Replaces the settings with those of an exported file and applies them
+/// (file-level tracing is only started, never stopped, until the next launch)
+#[tauri::command]
+async fn import_settings(
+    db_pool: tauri::State<'_, DbPool>,
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<settings_transfer::ImportSummary, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let file = tokio::task::spawn_blocking(move || {
+        settings_transfer::read_settings_file(std::path::Path::new(&path))
+            .map_err(|e| format!("Could not read settings: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let summary = settings_transfer::import_settings(&pool, &file).await?;
+
+    load_stored_settings(&app_handle, &pool).await;
+    // Tracing follows the imported flag whichever way it went
+    let applied = if file_io::is_enabled(&pool).await.unwrap_or(false) {
+        start_file_tracing(&app_handle)
+    } else {
+        stop_file_tracing(&app_handle)
+    };
+    if let Err(e) = applied {
+        eprintln!("[FileIO] Could not apply file-level tracing: {}", e);
+    }
+    println!(
+        "[Settings] Imported {} settings and {} process categories",
+        summary.settings, summary.categories
+    );
+    Ok(summary)
+}
+
+/// Leaves the app's own PID and database files out of file-level tracing
+/// (or counts them again)
+fn apply_self_exclusion(
+    app_handle: &tauri::AppHandle,
+    tracker: &file_io::SharedFileIo,
+    exclude_self: bool,
+) {
+    let (pid, paths) = if exclude_self {
+        let paths = data_dir::db_path(app_handle)
+            .map(|db| {
+                [
+                    db.clone(),
+                    db.with_extension("db-wal"),
+                    db.with_extension("db-shm"),
+                ]
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+            })
+            .unwrap_or_default();
+        (Some(std::process::id()), paths)
+    } else {
+        (None, Vec::new())
+    };
+    if let Ok(mut tracker) = tracker.lock() {
+        tracker.ignore(pid, paths);
+    }
+}
+
+/// Replaces the exclusion list, allowlist and pinned processes; takes effect
+/// from the next tick
+#[tauri::command]
+async fn set_process_filter(
+    app_handle: tauri::AppHandle,
+    db_pool: tauri::State<'_, DbPool>,
+    process_filter: tauri::State<'_, ProcessFilterState>,
+    file_io_state: tauri::State<'_, FileIoState>,
+    filter: process_filter::ProcessFilter,
+) -> Result<(), String> {
+    // Name normalization has its own command, which also merges the history
+    let (names, stored_command_lines) = {
+        let guard = process_filter
+            .0
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        (guard.names.clone(), guard.store_command_lines)
+    };
+    let filter = process_filter::ProcessFilter {
+        excluded: process_filter::normalize(&filter.excluded)?,
+        allowlist_only: filter.allowlist_only,
+        allowed: process_filter::normalize(&filter.allowed)?,
+        exclude_self: filter.exclude_self,
+        names,
+        pinned: process_filter::normalize(&filter.pinned)?,
+        store_command_lines: filter.store_command_lines,
+    };
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    process_filter::save_process_filter(&pool, &filter)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    // Opting out also drops the command lines kept so far
+    if stored_command_lines && !filter.store_command_lines {
+        db::clear_process_command_lines(&pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+    apply_self_exclusion(&app_handle, &file_io_state.0, filter.exclude_self);
+    let mut guard = process_filter
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    *guard = filter;
+    Ok(())
+}
+
+/// Changes how process names are keyed ("Code.exe" vs "code.exe", version
+/// suffixes, exe paths) and merges the recorded history under the new keys;
+/// returns how many recorded names were merged away
+#[tauri::command]
+async fn set_name_normalization(
+    db_pool: tauri::State<'_, DbPool>,
+    process_filter: tauri::State<'_, ProcessFilterState>,
+    names: process_names::NameNormalization,
+) -> Result<usize, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let filter = {
+        let mut guard = process_filter
+            .0
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        guard.names = names.clone();
+        guard.clone()
+    };
+    process_filter::save_process_filter(&pool, &filter)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    core::normalize_recorded_names(&pool, &names)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Maximum number of drive-activity updates per second
+#[tauri::command]
+fn get_activity_led_hz(led_rate: tauri::State<'_, LedRateState>) -> f64 {
+    activity::interval_ms_to_hz(led_rate.0.load(Ordering::Relaxed))
+}
+
+/// Changes the drive-activity update rate (0.1 - 20 Hz); it never exceeds the sampling rate
+#[tauri::command]
+async fn set_activity_led_hz(
+    db_pool: tauri::State<'_, DbPool>,
+    led_rate: tauri::State<'_, LedRateState>,
+    hz: f64,
+) -> Result<(), String> {
+    if !(activity::MIN_LED_HZ..=activity::MAX_LED_HZ).contains(&hz) {
+        return Err(format!(
+            "Rate must be between {} and {} Hz",
+            activity::MIN_LED_HZ,
+            activity::MAX_LED_HZ
+        ));
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    activity::save_led_hz(&pool, hz)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    activity::store_led_hz(&led_rate.0, hz);
+    Ok(())
+}
+
+/// Current monitor sampling interval in milliseconds
+#[tauri::command]
+fn get_sample_interval(interval: tauri::State<'_, SampleIntervalState>) -> u64 {
+    sampling::current_ms(&interval.0)
+}
+
+/// Changes the monitor sampling interval (250 ms - 10 s); applies from the next tick
+#[tauri::command]
+async fn set_sample_interval(
+    db_pool: tauri::State<'_, DbPool>,
+    interval: tauri::State<'_, SampleIntervalState>,
+    interval_ms: u64,
+) -> Result<(), String> {
+    if !(sampling::MIN_INTERVAL_MS..=sampling::MAX_INTERVAL_MS).contains(&interval_ms) {
+        return Err(format!(
+            "Interval must be between {} and {} ms",
+            sampling::MIN_INTERVAL_MS,
+            sampling::MAX_INTERVAL_MS
+        ));
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    sampling::save_interval_ms(&pool, interval_ms)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    interval.0.store(interval_ms, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Opt in to / out of anonymous telemetry (off until the user enables it)
+#[tauri::command]
+async fn set_telemetry_enabled(
+    db_pool: tauri::State<'_, DbPool>,
+    enabled: bool,
+) -> Result<(), String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    telemetry::set_enabled(&pool, enabled)
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
 
-use models::AllTimeTotals;
-use models::AppMetrics;
-use models::ResetDatabaseResponse;
-use process_monitor::ProcessAccumulators;
-use std::env;
-use std::fs;
-use sysinfo::{Pid, ProcessesToUpdate, System};
+/// What this machine and account allow (PDH, ETW, SMART, elevation), with a
+/// per-feature matrix the UI uses to grey out unavailable features
+#[tauri::command]
+async fn get_capabilities(
+    db_pool: tauri::State<'_, DbPool>,
+    capabilities_state: tauri::State<'_, CapabilitiesState>,
+) -> Result<capabilities::Capabilities, String> {
+    if let Some(cached) = capabilities_state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+    {
+        return Ok(cached);
+    }
 
-// Database pool state wrapper
-pub struct DbPool(pub Arc<Mutex<Option<Pool<Sqlite>>>>);
+    // Called before the startup probe finished; until the database is open
+    // file-level tracing may still be starting its ETW session
+    if db::current_pool(&db_pool.0).is_none() {
+        return Err("Capabilities are still being probed".to_string());
+    }
+    let probed = tokio::task::spawn_blocking(capabilities::probe)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut guard = capabilities_state.0.lock().map_err(|e| e.to_string())?;
+    *guard = Some(probed.clone());
+    Ok(probed)
+}
 
-// Process accumulators state wrapper
-pub struct ProcessAccumulatorsState(pub ProcessAccumulators);
+/// Current top-process list, for views that don't follow the live stream;
+/// `limit` overrides the configured length (0 lists every process)
+#[tauri::command]
+async fn get_top_processes(
+    sampler: tauri::State<'_, ProcessSamplerState>,
+    limit: Option<usize>,
+) -> Result<Vec<ProcessIOStat>, String> {
+    let limit = limit.map(top_processes::validate_limit).transpose()?;
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    Ok(sampler.top_processes(limit).await)
+}
 
-// Reset signal wrapper
-pub struct ResetSignal(pub Arc<AtomicBool>);
+/// Number of processes in the top-processes list (0 = every process)
+#[tauri::command]
+fn get_top_processes_limit(top_limit: tauri::State<'_, TopLimitState>) -> usize {
+    top_processes::current_limit(&top_limit.0)
+}
 
-// Shutdown signal wrapper for graceful exit
-pub struct ShutdownSignal(pub Arc<AtomicBool>);
-pub struct ShutdownNotify(pub Arc<Notify>);
+/// Changes the length of the top-processes list; applies from the next tick
+#[tauri::command]
+async fn set_top_processes_limit(
+    db_pool: tauri::State<'_, DbPool>,
+    top_limit: tauri::State<'_, TopLimitState>,
+    limit: usize,
+) -> Result<(), String> {
+    let limit = top_processes::validate_limit(limit)?;
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    top_processes::save_top_limit(&pool, limit)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    top_limit.0.store(limit, Ordering::Relaxed);
+    Ok(())
+}
 
-// System state wrapper for metrics
-pub struct SystemState(pub Mutex<System>);
+/// Days of recorded data kept before the cleanup deletes it
+#[tauri::command]
+async fn get_retention_days(db_pool: tauri::State<'_, DbPool>) -> Result<u64, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    Ok(db_cleanup::load_retention_policy(&pool).await.keep_days)
+}
 
+/// Changes the retention period; the next prune and cleanup apply it
 #[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
+async fn set_retention_days(db_pool: tauri::State<'_, DbPool>, days: u64) -> Result<(), String> {
+    let days = db_cleanup::validate_retention_days(days)?;
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    db_cleanup::save_retention_days(&pool, days)
+        .await
+        .map_err(|e| format!("Database error: {}", e))
 }
 
+/// Shows the executable of a monitored process selected in Explorer/Finder
 #[tauri::command]
-async fn get_alltime_totals(db_pool: tauri::State<'_, DbPool>) -> Result<AllTimeTotals, String> {
-    let pool_opt = {
-        let guard = db_pool.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-        guard.clone()
-    };
+async fn open_process_location(
+    app_handle: tauri::AppHandle,
+    sampler: tauri::State<'_, ProcessSamplerState>,
+    name: String,
+) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
 
-    if let Some(pool) = pool_opt {
-        match db::get_alltime_totals(&pool).await {
-            Ok((read_bytes, write_bytes)) => Ok(AllTimeTotals {
-                read_bytes,
-                write_bytes,
-            }),
-            Err(e) => Err(format!("Database error: {}", e)),
-        }
-    } else {
-        Err("Database not initialized".to_string())
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    let exe_path = sampler.exe_path(name.clone()).await.ok_or_else(|| {
+        format!(
+            "{} is not running and no executable path was recorded for it",
+            name
+        )
+    })?;
+    if !std::path::Path::new(&exe_path).exists() {
+        return Err(format!("{} no longer exists", exe_path));
     }
+    app_handle
+        .opener()
+        .reveal_item_in_dir(&exe_path)
+        .map_err(|e| format!("Failed to open {}: {}", exe_path, e))
 }
 
-// save_session_to_alltime command removed as it was causing double counting.
-// Monitor handles real-time updates to process_history.
+/// Live processes grouped by application (parent-PID trees), each with its raw per-process rows
+#[tauri::command]
+async fn get_application_groups(
+    sampler: tauri::State<'_, ProcessSamplerState>,
+) -> Result<Vec<process_groups::ApplicationGroup>, String> {
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    Ok(sampler.application_groups().await)
+}
 
+/// Live parent/child process tree with per-node and rolled-up I/O, for the tree view
 #[tauri::command]
-async fn get_process_history(
+async fn get_process_tree(
+    sampler: tauri::State<'_, ProcessSamplerState>,
+) -> Result<Vec<process_groups::ProcessTreeNode>, String> {
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    Ok(sampler.process_tree().await)
+}
+
+/// Per-tick bytes of one process name over the last minute, for row sparklines
+#[tauri::command]
+async fn get_process_sparkline(
+    sampler: tauri::State<'_, ProcessSamplerState>,
+    name: String,
+) -> Result<Vec<process_monitor::SparklinePoint>, String> {
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    Ok(sampler.sparkline(name).await)
+}
+
+/// The individual PIDs behind a name in the top-processes list, with their
+/// start times and counters (which of the node.exe instances is writing)
+#[tauri::command]
+async fn get_process_instances(
+    name: String,
+    sampler: tauri::State<'_, ProcessSamplerState>,
+) -> Result<Vec<models::ProcessInstance>, String> {
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    Ok(sampler.instances(name).await)
+}
+
+/// The built-in table of well-known background writers, with what they do
+/// and how to reduce their I/O (rows carry the matching `known_writer` id)
+#[tauri::command]
+fn get_known_writers() -> &'static [known_writers::KnownWriter] {
+    known_writers::KNOWN_WRITERS
+}
+
+/// How much of this session's I/O was Windows itself versus the user's apps
+#[tauri::command]
+async fn get_system_user_split(
+    sampler: tauri::State<'_, ProcessSamplerState>,
+) -> Result<process_monitor::SystemUserSplit, String> {
+    let sampler =
+        process_sampler::current(&sampler.0).ok_or_else(|| "Monitor not running".to_string())?;
+    Ok(sampler.system_user_split().await)
+}
+
+/// Bytes of one process name per hour or local day of a range (e.g. a month of steam.exe)
+#[tauri::command]
+async fn get_process_series(
     db_pool: tauri::State<'_, DbPool>,
-) -> Result<std::collections::HashMap<String, (u64, u64)>, String> {
-    let pool_opt = {
-        let guard = db_pool.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-        guard.clone()
-    };
+    contention: tauri::State<'_, DbContentionState>,
+    name: String,
+    start: f64,
+    end: f64,
+    bucket: models::SeriesBucket,
+    utc_offset_minutes: Option<i32>,
+) -> Result<Vec<models::ProcessSeriesPoint>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let offset_secs = i64::from(utc_offset_minutes.unwrap_or(0)) * 60;
+    core::process_series(&pool, &contention.0, &name, start, end, bucket, offset_secs)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    if let Some(pool) = pool_opt {
-        db::get_process_history(&pool)
+/// A process's session bytes next to its all-time totals and its average day
+#[tauri::command]
+async fn get_process_comparison(
+    db_pool: tauri::State<'_, DbPool>,
+    contention: tauri::State<'_, DbContentionState>,
+    sampler: tauri::State<'_, ProcessSamplerState>,
+    name: String,
+    utc_offset_minutes: Option<i32>,
+) -> Result<process_comparison::ProcessComparison, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let offset_secs = i64::from(utc_offset_minutes.unwrap_or(0)) * 60;
+    let session = match process_sampler::current(&sampler.0) {
+        Some(sampler) => sampler
+            .top_processes(Some(0))
             .await
-            .map_err(|e| format!("Database error: {}", e))
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(&name))
+            .map_or((0, 0), |p| (p.read_bytes, p.write_bytes)),
+        None => (0, 0),
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    core::process_comparison(&pool, &contention.0, &name, session, now, offset_secs)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Everything known about one process name: all-time totals, per-day history,
+/// first/last seen and, while it runs, its instances and current rates
+#[tauri::command]
+async fn get_process_details(
+    db_pool: tauri::State<'_, DbPool>,
+    contention: tauri::State<'_, DbContentionState>,
+    sampler: tauri::State<'_, ProcessSamplerState>,
+    file_io_state: tauri::State<'_, FileIoState>,
+    name: String,
+    utc_offset_minutes: Option<i32>,
+) -> Result<models::ProcessDetails, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let offset_secs = i64::from(utc_offset_minutes.unwrap_or(0)) * 60;
+    let mut details = core::process_details(&pool, &contention.0, &name, offset_secs)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(sampler) = process_sampler::current(&sampler.0) {
+        details.instances = sampler.instances(name).await;
+    }
+    for instance in &details.instances {
+        details.read_speed = details.read_speed.saturating_add(instance.stat.read_speed);
+        details.write_speed = details
+            .write_speed
+            .saturating_add(instance.stat.write_speed);
+    }
+    details.exe_path = details
+        .instances
+        .iter()
+        .find_map(|p| p.stat.exe_path.clone());
+    if details.command_line.is_none() {
+        details.command_line = details
+            .instances
+            .iter()
+            .find_map(|p| p.stat.command_line.clone());
+    }
+    // Traced files are kept per pid, so only running instances can be matched
+    let pids: Vec<u32> = details.instances.iter().map(|p| p.stat.pid).collect();
+    if !pids.is_empty() {
+        let tracker = file_io_state
+            .0
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        details.extensions = tracker.extensions(&pids);
+    }
+    Ok(details)
+}
+
+/// Whether file-level tracing is enabled and its ETW session is running
+#[derive(serde::Serialize)]
+struct FileTracingStatus {
+    enabled: bool,
+    running: bool,
+}
+
+/// Starts the file-level ETW trace unless it is already running
+fn start_file_tracing(app: &tauri::AppHandle) -> Result<(), String> {
+    let file_tracer = app.state::<FileTracerState>();
+    let mut tracer = file_tracer
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    if tracer.is_none() {
+        let tracker = Arc::clone(&app.state::<FileIoState>().0);
+        *tracer = Some(file_io::FileTracer::start(tracker)?);
+    }
+    Ok(())
+}
+
+fn stop_file_tracing(app: &tauri::AppHandle) -> Result<(), String> {
+    let tracer = app
+        .state::<FileTracerState>()
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .take();
+    if let Some(tracer) = tracer {
+        tracer.stop();
+    }
+    Ok(())
+}
+
+/// Probes what is available (slow calls, off the async runtime). The ETW
+/// probe starts a session of its own, so this runs once file-level tracing
+/// has started rather than competing with it for a session slot.
+fn spawn_capability_probe(cache: capabilities::SharedCapabilities) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let probed = capabilities::probe();
+        let unavailable: Vec<&str> = probed
+            .features
+            .iter()
+            .filter(|f| !f.available)
+            .map(|f| f.feature.as_str())
+            .collect();
+        if !unavailable.is_empty() {
+            println!("[App] Unavailable features: {}", unavailable.join(", "));
+        }
+        if let Ok(mut guard) = cache.lock() {
+            *guard = Some(probed);
+        }
+    });
+}
+
+#[tauri::command]
+async fn get_file_tracing(
+    db_pool: tauri::State<'_, DbPool>,
+    file_tracer: tauri::State<'_, FileTracerState>,
+) -> Result<FileTracingStatus, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let enabled = file_io::is_enabled(&pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let running = file_tracer
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .is_some();
+    Ok(FileTracingStatus { enabled, running })
+}
+
+/// Turns file-level I/O attribution on or off (needs ETW rights to start)
+#[tauri::command]
+async fn set_file_tracing(
+    db_pool: tauri::State<'_, DbPool>,
+    app_handle: tauri::AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    if enabled {
+        start_file_tracing(&app_handle)?;
     } else {
-        Err("Database not initialized".to_string())
+        stop_file_tracing(&app_handle)?;
     }
+    file_io::set_enabled(&pool, enabled)
+        .await
+        .map_err(|e| format!("Database error: {}", e))
 }
 
+/// pid -> name of the running processes
+fn running_process_names(
+    system_state: &SystemState,
+) -> Result<std::collections::HashMap<u32, String>, String> {
+    let mut sys = system_state.0.lock().map_err(|e| e.to_string())?;
+    sys.refresh_processes_specifics(ProcessesToUpdate::All, process_monitor::name_refresh());
+    Ok(sys
+        .processes()
+        .iter()
+        .map(|(pid, process)| (pid.as_u32(), process.name().to_string_lossy().to_string()))
+        .collect())
+}
+
+/// Files with the most bytes read/written this session (from file-level tracing)
 #[tauri::command]
-async fn get_process_history_totals(
+fn get_top_files(
+    file_io_state: tauri::State<'_, FileIoState>,
+    system_state: tauri::State<'_, SystemState>,
+    limit: Option<usize>,
+) -> Result<Vec<file_io::TopFile>, String> {
+    let names = running_process_names(&system_state)?;
+    let tracker = file_io_state
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    Ok(tracker.top_files(limit.unwrap_or(file_io::TOP_FILES_LIMIT), &names))
+}
+
+/// File-level bytes grouped by directory, `depth` levels below the drive
+#[tauri::command]
+fn get_directory_io(
+    file_io_state: tauri::State<'_, FileIoState>,
+    depth: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<file_io::DirectoryStat>, String> {
+    let tracker = file_io_state
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    let mut directories = tracker.directories(depth.unwrap_or(file_io::DEFAULT_DIRECTORY_DEPTH));
+    directories.truncate(limit.unwrap_or(file_io::TOP_FILES_LIMIT));
+    Ok(directories)
+}
+
+/// Paging-file bytes this session (from file-level tracing), not counted as
+/// any process's files; `None` while tracing is off, when nothing is measured
+#[tauri::command]
+fn get_paging_io(
+    file_io_state: tauri::State<'_, FileIoState>,
+    file_tracer: tauri::State<'_, FileTracerState>,
+) -> Result<Option<file_io::PagingIo>, String> {
+    let running = file_tracer
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .is_some();
+    if !running {
+        return Ok(None);
+    }
+    let tracker = file_io_state
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    Ok(Some(tracker.paging()))
+}
+
+/// Icon of an executable as base64 PNG (cached per path)
+#[tauri::command]
+async fn get_process_icon(
+    icon_cache: tauri::State<'_, IconCacheState>,
+    exe_path: String,
+) -> Result<String, String> {
+    let cache = Arc::clone(&icon_cache.0);
+    tokio::task::spawn_blocking(move || icons::icon(&cache, &exe_path, icons::extract_icon))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Processes a kill of `target` (a PID or a process name) would end, with the
+/// token `kill_process` needs to go ahead
+#[tauri::command]
+fn prepare_kill_process(
+    system_state: tauri::State<'_, SystemState>,
+    kill_tokens: tauri::State<'_, KillTokensState>,
+    target: process_control::KillTarget,
+) -> Result<process_control::KillPlan, String> {
+    let processes = {
+        let mut sys = system_state.0.lock().map_err(|e| e.to_string())?;
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, process_monitor::name_refresh());
+        let running: Vec<(u32, String, u64)> = sys
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let name = process.name().to_string_lossy().to_string();
+                (pid.as_u32(), name, process.start_time())
+            })
+            .collect();
+        process_control::resolve(
+            &target,
+            running
+                .iter()
+                .map(|(pid, name, start_time)| (*pid, name.as_str(), *start_time)),
+            std::process::id(),
+        )
+    };
+    if processes.is_empty() {
+        return Err("No matching running process".to_string());
+    }
+    kill_tokens
+        .0
+        .issue(target, processes, std::time::Instant::now())
+}
+
+/// Ends the processes planned by `prepare_kill_process`, one result per PID
+#[tauri::command]
+fn kill_process(
+    system_state: tauri::State<'_, SystemState>,
+    kill_tokens: tauri::State<'_, KillTokensState>,
+    target: process_control::KillTarget,
+    confirmation_token: String,
+) -> Result<Vec<process_control::KillResult>, String> {
+    let planned = kill_tokens
+        .0
+        .redeem(&confirmation_token, &target, std::time::Instant::now())?;
+    let pids: Vec<Pid> = planned.iter().map(|p| Pid::from_u32(p.pid)).collect();
+    let mut sys = system_state.0.lock().map_err(|e| e.to_string())?;
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        process_monitor::name_refresh(),
+    );
+
+    Ok(planned
+        .into_iter()
+        .map(|candidate| {
+            // A reused PID must not take down an unrelated process, even
+            // one running the same executable: (pid, start time) must match
+            let error = match sys.process(Pid::from_u32(candidate.pid)) {
+                Some(process)
+                    if candidate.is_same_process(
+                        &process.name().to_string_lossy(),
+                        process.start_time(),
+                    ) =>
+                {
+                    if process.kill() {
+                        None
+                    } else {
+                        Some("Access denied or the process refused to exit".to_string())
+                    }
+                }
+                _ => Some("Process is no longer running".to_string()),
+            };
+            if let Some(e) = &error {
+                eprintln!(
+                    "[Processes] Failed to kill {} ({}): {}",
+                    candidate.name, candidate.pid, e
+                );
+            } else {
+                println!("[Processes] Killed {} ({})", candidate.name, candidate.pid);
+            }
+            process_control::KillResult {
+                pid: candidate.pid,
+                name: candidate.name,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect())
+}
+
+/// Per-process bytes broken down by drive (from file-level tracing)
+#[tauri::command]
+fn get_process_drive_io(
+    file_io_state: tauri::State<'_, FileIoState>,
+    system_state: tauri::State<'_, SystemState>,
+) -> Result<Vec<file_io::ProcessDriveStat>, String> {
+    let names = running_process_names(&system_state)?;
+    let tracker = file_io_state
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    Ok(tracker.process_drives(&names))
+}
+
+/// Bytes read and written since the app started (or since the last reset)
+#[tauri::command]
+fn get_current_session_totals(
+    session_totals: tauri::State<'_, SessionTotalsState>,
+) -> Result<session::SessionTotals, String> {
+    Ok(session::current(&session_totals.0))
+}
+
+/// Summary of the previous session (recorded at shutdown), if any
+#[tauri::command]
+async fn get_last_session_summary(
     db_pool: tauri::State<'_, DbPool>,
-) -> Result<AllTimeTotals, String> {
-    let pool_opt = {
-        let guard = db_pool.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-        guard.clone()
+) -> Result<Option<session::SessionSummary>, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    core::last_session_summary(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Whether collection is actually working: loop ticks, flushes, buffered
+/// rows and per-process counter availability
+#[tauri::command]
+fn get_monitor_status(
+    monitor_stats: tauri::State<'_, MonitorStatsState>,
+    paused: tauri::State<'_, PausedState>,
+    window_hidden: tauri::State<'_, WindowHiddenState>,
+) -> Result<monitor::MonitorStatus, String> {
+    let stats = monitor_stats.0.lock().map_err(|e| e.to_string())?.clone();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+
+    Ok(monitor::MonitorStatus {
+        seconds_since_last_tick: (stats.tick_count > 0)
+            .then(|| (now - stats.last_tick_at).max(0.0)),
+        stats,
+        paused: paused.0.load(Ordering::Relaxed),
+        live_updates_suspended: window_hidden.0.load(Ordering::Relaxed),
+    })
+}
+
+/// Runs the checks relevant to a reported problem and returns prioritized findings
+#[tauri::command]
+async fn diagnose_problem(
+    db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    system_state: tauri::State<'_, SystemState>,
+    monitor_stats: tauri::State<'_, MonitorStatsState>,
+    metric_subscriptions: tauri::State<'_, MetricSubscriptionsState>,
+    app_handle: tauri::AppHandle,
+    kind: diagnostics::ProblemKind,
+) -> Result<Vec<diagnostics::Finding>, String> {
+    let pool = db::current_pool(&db_pool.0);
+    let loop_stats = monitor_stats.0.lock().map_err(|e| e.to_string())?.clone();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+
+    let (app_cpu_usage, cpu_count) = {
+        let mut sys = system_state.0.lock().map_err(|e| e.to_string())?;
+        let pid = Pid::from_u32(std::process::id());
+        sys.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+        (
+            sys.process(pid).map(|p| p.cpu_usage()).unwrap_or(0.0),
+            sys.cpus().len(),
+        )
     };
 
-    if let Some(pool) = pool_opt {
-        let history = db::get_process_history(&pool)
+    let counter_error = tokio::task::spawn_blocking(perf_counters::get_disk_perf_metrics)
+        .await
+        .map_err(|e| e.to_string())?
+        .err();
+
+    let db_path = data_dir::db_path(&app_handle).map_err(|e| e.to_string())?;
+    let file_size = |path: std::path::PathBuf| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+
+    let retention_days = match &pool {
+        Some(pool) => db_cleanup::load_retention_policy(pool).await.keep_days,
+        None => db_cleanup::RetentionPolicy::default().keep_days,
+    };
+    let mut inputs = diagnostics::DiagnosticInputs {
+        counter_backend: perf_counters::counter_backend().to_string(),
+        counter_error,
+        elevated: elevation::is_elevated_safe(),
+        database_ready: pool.is_some(),
+        db_size_bytes: file_size(db_path.clone()),
+        wal_size_bytes: file_size(db_path.with_extension("db-wal")),
+        retention_days,
+        seconds_since_last_tick: (loop_stats.tick_count > 0)
+            .then(|| (now - loop_stats.last_tick_at).max(0.0)),
+        loop_stats,
+        app_cpu_usage,
+        cpu_count,
+        live_streams: metric_subscriptions
+            .0
+            .lock()
+            .map(|s| s.streams().len())
+            .unwrap_or(0),
+        ..Default::default()
+    };
+
+    if let Some(pool) = &pool {
+        inputs.table_rows = db::get_table_row_counts(pool)
             .await
             .map_err(|e| format!("Database error: {}", e))?;
+    }
+    if let Some(store) = storage::current(&storage_state.0) {
+        inputs.oldest_sample_age_days = store
+            .oldest_disk_stat()
+            .await
+            .map_err(|e| e.to_string())?
+            .map(|oldest| (now - oldest) / 86400.0);
+    }
 
-        let total_read: u64 = history.values().map(|(r, _)| r).sum();
-        let total_write: u64 = history.values().map(|(_, w)| w).sum();
+    Ok(diagnostics::diagnose(kind, &inputs))
+}
+
+/// Active time series backend and the one configured for the next start
+#[tauri::command]
+async fn get_storage_backend(
+    db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+) -> Result<serde_json::Value, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+
+    let active = storage_state
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .as_ref()
+        .map(|store| store.backend());
+
+    Ok(serde_json::json!({
+        "active": active,
+        "configured": storage::configured_backend(&pool).await,
+        "parquet_available": storage::StorageBackend::Parquet.is_compiled_in(),
+    }))
+}
+
+/// Selects the time series backend; takes effect after a restart
+#[tauri::command]
+async fn set_storage_backend(
+    db_pool: tauri::State<'_, DbPool>,
+    backend: storage::StorageBackend,
+) -> Result<(), String> {
+    if !backend.is_compiled_in() {
+        return Err(format!(
+            "Backend '{}' is not available in this build",
+            backend.as_str()
+        ));
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    db::set_setting(&pool, storage::STORAGE_BACKEND_KEY, backend.as_str())
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Estimated SSD endurance use for a volume; non-NVMe drives need a rated TBW set first.
+/// Drives without a write counter fall back to the system-wide totals (`system_wide`)
+#[tauri::command]
+async fn get_ssd_wear(
+    db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    volume: String,
+) -> Result<SsdWear, String> {
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+
+    let rated_tbw: Option<f64> = db::get_setting(&pool, &ssd_wear::rated_tbw_key(&volume))
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .and_then(|value| value.parse().ok());
+
+    let (_, app_written_bytes) = db::get_alltime_totals(&pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    // Write rate over the last week drives the end-of-life projection
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    let avg_write_bytes_per_sec = match storage::current(&storage_state.0) {
+        Some(store) => store
+            .range_aggregate(now - 7.0 * 86_400.0, now)
+            .await
+            .map(|aggregate| aggregate.avg_write_speed)
+            .unwrap_or(0.0),
+        None => 0.0,
+    };
 
-        Ok(AllTimeTotals {
-            read_bytes: total_read,
-            write_bytes: total_write,
+    let smart_volume = volume.clone();
+    let (nvme, attributes) =
+        tokio::task::spawn_blocking(move || match smart::read_nvme_health_safe(&smart_volume) {
+            Some(health) => (Some(health), Vec::new()),
+            None => (None, smart::read_attributes_safe(&smart_volume)),
         })
-    } else {
-        Err("Database not initialized".to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // NVMe drives report their own wear; others need a rated TBW from the user
+    if rated_tbw.is_none() && nvme.is_none() {
+        return Err("Rated TBW not set for this volume".to_string());
     }
+
+    Ok(ssd_wear::estimate(&ssd_wear::WearInputs {
+        volume,
+        rated_tbw,
+        nvme,
+        host_writes_bytes: smart::host_writes_bytes(&attributes),
+        app_written_bytes,
+        avg_write_bytes_per_sec,
+        now,
+    }))
+}
+
+/// Stores the manufacturer's rated TBW (terabytes written) for a volume
+#[tauri::command]
+async fn set_ssd_rated_tbw(
+    db_pool: tauri::State<'_, DbPool>,
+    volume: String,
+    rated_tbw: f64,
+) -> Result<(), String> {
+    if !(rated_tbw.is_finite() && rated_tbw > 0.0) {
+        return Err("Rated TBW must be a positive number".to_string());
+    }
+
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    db::set_setting(
+        &pool,
+        &ssd_wear::rated_tbw_key(&volume),
+        &rated_tbw.to_string(),
+    )
+    .await
+    .map_err(|e| format!("Database error: {}", e))
 }
 
 #[tauri::command]
@@ -158,22 +2260,24 @@ fn get_app_metrics(
 #[tauri::command]
 async fn reset_database(
     db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    telemetry_state: tauri::State<'_, TelemetryState>,
     reset_signal: tauri::State<'_, ResetSignal>,
     app_handle: tauri::AppHandle,
 ) -> Result<ResetDatabaseResponse, String> {
+    telemetry::record_feature(&telemetry_state.0, "reset_database");
     // Reset database with size info
-    let pool_opt = {
-        let guard = db_pool.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-        guard.clone()
-    };
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let (db_size_before, db_size_after) = db::reset_database_with_size(&pool, &app_handle)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
 
-    let (db_size_before, db_size_after) = if let Some(pool) = pool_opt {
-        db::reset_database_with_size(&pool, &app_handle)
-            .await
-            .map_err(|e| format!("Database error: {}", e))?
-    } else {
-        return Err("Database not initialized".to_string());
-    };
+    // File backends keep the time series outside the database
+    if let Some(store) = storage::current(&storage_state.0) {
+        if store.backend() != storage::StorageBackend::Sqlite {
+            store.clear().await.map_err(|e| e.to_string())?;
+        }
+    }
 
     // Signal monitors to reset their baselines (includes process accumulators)
     reset_signal.0.store(true, Ordering::Relaxed);
@@ -190,61 +2294,47 @@ async fn reset_database(
 #[tauri::command]
 async fn optimize_database(
     db_pool: tauri::State<'_, DbPool>,
+    storage_state: tauri::State<'_, StorageState>,
+    telemetry_state: tauri::State<'_, TelemetryState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
-    let pool_opt = {
-        let guard = db_pool.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-        guard.clone()
-    };
+    telemetry::record_feature(&telemetry_state.0, "optimize_database");
+    let pool = core::pool(&db_pool.0).map_err(|e| e.to_string())?;
+    let policy = db_cleanup::load_retention_policy(&pool).await;
+    let store = core::store(&storage_state.0).map_err(|e| e.to_string())?;
 
-    if let Some(pool) = pool_opt {
-        // Run cleanup with default retention policy (30 days)
-        let policy = db_cleanup::RetentionPolicy::default();
-        
-        let cleaned_records = db_cleanup::cleanup_old_data(&pool, &policy)
-            .await
-            .map_err(|e| format!("Cleanup error: {}", e))?;
-
-        // Get database size before VACUUM
-        let db_size_before = match std::fs::metadata(
-            std::path::PathBuf::from(&std::env::var("APPDATA").unwrap_or_default())
-                .join("driveanalizer")
-                .join("drive_analytics.db")
-        ) {
-            Ok(metadata) => metadata.len(),
-            Err(_) => 0,
-        };
-
-        // Run VACUUM to reclaim space
-        db_cleanup::vacuum_database(&pool)
-            .await
-            .map_err(|e| format!("VACUUM error: {}", e))?;
+    let cleaned_records = db_cleanup::cleanup_old_data(&pool, store.as_ref(), &policy)
+        .await
+        .map_err(|e| format!("Cleanup error: {}", e))?;
 
-        // Run ANALYZE for query optimization
-        db_cleanup::analyze_database(&pool)
-            .await
-            .map_err(|e| format!("ANALYZE error: {}", e))?;
-
-        // Get database size after VACUUM
-        let db_size_after = match std::fs::metadata(
-            std::path::PathBuf::from(&std::env::var("APPDATA").unwrap_or_default())
-                .join("driveanalizer")
-                .join("drive_analytics.db")
-        ) {
-            Ok(metadata) => metadata.len(),
-            Err(_) => 0,
-        };
-
-        let freed_bytes = db_size_before.saturating_sub(db_size_after);
-
-        Ok(serde_json::json!({
-            "cleaned_records": cleaned_records,
-            "freed_bytes": freed_bytes,
-            "db_size_before": db_size_before,
-            "db_size_after": db_size_after,
-        }))
-    } else {
-        Err("Database not initialized".to_string())
-    }
+    // Get database size before VACUUM
+    let db_size_before = db::get_database_size(&app_handle)
+        .map(|(size, _)| size)
+        .unwrap_or(0);
+
+    // Run VACUUM to reclaim space
+    db_cleanup::vacuum_database(&pool)
+        .await
+        .map_err(|e| format!("VACUUM error: {}", e))?;
+
+    // Run ANALYZE for query optimization
+    db_cleanup::analyze_database(&pool)
+        .await
+        .map_err(|e| format!("ANALYZE error: {}", e))?;
+
+    // Get database size after VACUUM
+    let db_size_after = db::get_database_size(&app_handle)
+        .map(|(size, _)| size)
+        .unwrap_or(0);
+
+    let freed_bytes = db_size_before.saturating_sub(db_size_after);
+
+    Ok(serde_json::json!({
+        "cleaned_records": cleaned_records,
+        "freed_bytes": freed_bytes,
+        "db_size_before": db_size_before,
+        "db_size_after": db_size_after,
+    }))
 }
 pub fn run() {
     // Create shared pool state
@@ -255,11 +2345,54 @@ pub fn run() {
     let process_accumulators = process_monitor::create_accumulators();
     let process_accumulators_state = ProcessAccumulatorsState(Arc::clone(&process_accumulators));
 
+    // Create shared metric subscriptions (everything subscribed by default)
+    let metric_subscriptions = subscriptions::create_subscriptions();
+    let metric_subscriptions_state = MetricSubscriptionsState(Arc::clone(&metric_subscriptions));
+
+    // Create shared per-volume stats
+    let volume_stats = volume_monitor::create_volume_stats();
+    let volume_stats_state = VolumeStatsState(Arc::clone(&volume_stats));
+
     // Create shared reset signal
     let reset_signal = Arc::new(AtomicBool::new(false));
     let reset_signal_state = ResetSignal(Arc::clone(&reset_signal));
     let reset_signal_monitor = Arc::clone(&reset_signal);
 
+    // Create shared pause flag
+    let paused = Arc::new(AtomicBool::new(false));
+    let paused_state = PausedState(Arc::clone(&paused));
+    let paused_monitor = Arc::clone(&paused);
+
+    // Create shared window visibility flag
+    let window_hidden = Arc::new(AtomicBool::new(false));
+    let window_hidden_state = WindowHiddenState(Arc::clone(&window_hidden));
+    let window_hidden_monitor = Arc::clone(&window_hidden);
+
+    // Create the process sampler slot (filled when the monitor loop starts)
+    let shared_sampler = process_sampler::create_shared_sampler();
+    let shared_sampler_state = ProcessSamplerState(Arc::clone(&shared_sampler));
+    let shared_sampler_monitor = Arc::clone(&shared_sampler);
+
+    // Create shared file-level I/O counts (tracing starts once the setting is loaded)
+    let file_io_tracker = file_io::create_file_io();
+    let file_io_state = FileIoState(Arc::clone(&file_io_tracker));
+    let file_io_monitor = Arc::clone(&file_io_tracker);
+
+    // Create the process exclusion list (loaded once the database is open)
+    let process_filter = process_filter::create_process_filter();
+    let process_filter_state = ProcessFilterState(Arc::clone(&process_filter));
+    let process_filter_monitor = Arc::clone(&process_filter);
+
+    // Create the top-processes limit (loaded once the database is open)
+    let top_limit = top_processes::create_top_limit();
+    let top_limit_state = TopLimitState(Arc::clone(&top_limit));
+    let top_limit_monitor = Arc::clone(&top_limit);
+
+    // Create shared session totals
+    let session_totals = session::create_session_totals();
+    let session_totals_state = SessionTotalsState(Arc::clone(&session_totals));
+    let session_totals_monitor = Arc::clone(&session_totals);
+
     // Create shared shutdown signal
     let shutdown_signal = Arc::new(AtomicBool::new(false));
     let shutdown_signal_state = ShutdownSignal(Arc::clone(&shutdown_signal));
@@ -270,36 +2403,201 @@ pub fn run() {
     let shutdown_notify_state = ShutdownNotify(Arc::clone(&shutdown_notify));
     let shutdown_notify_monitor = Arc::clone(&shutdown_notify);
 
+    // Create the shutdown acknowledgement (monitor -> close handler)
+    let shutdown_done = Arc::new(Notify::new());
+    let shutdown_done_monitor = Arc::clone(&shutdown_done);
+
+    // Create the storage slot (filled once the database is open)
+    let shared_storage = storage::create_shared_storage();
+    let storage_state = StorageState(Arc::clone(&shared_storage));
+
+    // Create the central write coalescer (flushed by the monitor loop)
+    let write_coalescer = write_coalescer::create_write_coalescer();
+
+    // Create reader/writer contention metrics
+    let db_contention = db_reader::create_contention();
+    let db_contention_state = DbContentionState(Arc::clone(&db_contention));
+
+    // Create shared monitor loop stats
+    let monitor_stats = monitor::create_monitor_stats();
+    let monitor_stats_state = MonitorStatsState(Arc::clone(&monitor_stats));
+
+    // Create the monitor sampling interval (loaded from settings once the database is open)
+    let sample_interval = sampling::create_interval();
+    let sample_interval_state = SampleIntervalState(Arc::clone(&sample_interval));
+
+    // Create the live payload encoding (loaded from settings once the database is open)
+    let live_frames = payload::create_live_frames();
+    let live_frames_state = LiveFramesState(Arc::clone(&live_frames));
+
+    // Create the battery throttle settings (loaded once the database is open)
+    let battery_throttle = power::create_battery_throttle();
+    let battery_throttle_state = BatteryThrottleState(Arc::clone(&battery_throttle));
+
+    // Create the drive activity LED rate (loaded from settings once the database is open)
+    let led_rate = activity::create_led_rate();
+    let led_rate_state = LedRateState(Arc::clone(&led_rate));
+
+    // Create the low-wear write settings (loaded once the database is open)
+    let low_wear_mode = low_wear::create_low_wear();
+    let low_wear_state = LowWearState(Arc::clone(&low_wear_mode));
+
+    // Create the flush settings (loaded once the database is open)
+    let flush_settings = flush_settings::create_flush_settings();
+    let flush_settings_state = FlushSettingsState(Arc::clone(&flush_settings));
+
+    // Create the capability probe results (filled in during setup)
+    let capabilities_cache = capabilities::create_capabilities();
+    let capabilities_state = CapabilitiesState(Arc::clone(&capabilities_cache));
+    let write_coalescer_state = WriteCoalescerState(Arc::clone(&write_coalescer));
+
+    // Create the binary stream channels (opened by the frontend)
+    let live_channels = channels::create_channels();
+    let live_channels_state = ChannelsState(Arc::clone(&live_channels));
+
+    // Create opt-in telemetry counters
+    let telemetry_counters = telemetry::create_telemetry();
+    let telemetry_state = TelemetryState(Arc::clone(&telemetry_counters));
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(db_pool)
         .manage(process_accumulators_state)
         .manage(reset_signal_state)
+        .manage(paused_state)
+        .manage(window_hidden_state)
+        .manage(session_totals_state)
+        .manage(file_io_state)
+        .manage(process_filter_state)
+        .manage(top_limit_state)
+        .manage(FileTracerState(Mutex::new(None)))
+        .manage(IconCacheState(icons::create_icon_cache()))
+        .manage(shared_sampler_state)
         .manage(shutdown_signal_state)
         .manage(shutdown_notify_state)
+        .manage(metric_subscriptions_state)
+        .manage(volume_stats_state)
+        .manage(telemetry_state)
+        .manage(monitor_stats_state)
+        .manage(storage_state)
+        .manage(db_contention_state)
+        .manage(sample_interval_state)
+        .manage(live_frames_state)
+        .manage(battery_throttle_state)
+        .manage(live_channels_state)
+        .manage(led_rate_state)
+        .manage(low_wear_state)
+        .manage(flush_settings_state)
+        .manage(capabilities_state)
+        .manage(write_coalescer_state)
         .manage(SystemState(Mutex::new(System::new_all())))
+        .manage(KillTokensState(process_control::KillTokens::default()))
+        .manage(InstanceLockState(Mutex::new(None)))
+        // Streams a closed window subscribed to are no longer wanted
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Ok(mut subscriptions) = window.state::<MetricSubscriptionsState>().0.lock() {
+                    subscriptions.remove_window(window.label());
+                }
+            }
+        })
         .setup(move |app| {
             let app_handle = app.handle().clone();
             let pool_for_setup = Arc::clone(&db_pool_clone);
             let accumulators_for_monitor = Arc::clone(&process_accumulators);
+            let subscriptions_for_monitor = Arc::clone(&metric_subscriptions);
+            let volume_stats_for_monitor = Arc::clone(&volume_stats);
+            let telemetry_for_scheduler = Arc::clone(&telemetry_counters);
+            let monitor_stats_for_monitor = Arc::clone(&monitor_stats);
+            let storage_for_setup = Arc::clone(&shared_storage);
+            let writes_for_monitor = Arc::clone(&write_coalescer);
+            let writes_for_free_space = Arc::clone(&write_coalescer);
+            let contention_for_setup = Arc::clone(&db_contention);
+            let interval_for_monitor = Arc::clone(&sample_interval);
+            let live_frames_for_monitor = Arc::clone(&live_frames);
+            let throttle_for_monitor = Arc::clone(&battery_throttle);
+            let channels_for_monitor = Arc::clone(&live_channels);
+            let led_rate_for_monitor = Arc::clone(&led_rate);
+            let low_wear_for_monitor = Arc::clone(&low_wear_mode);
+            let flush_settings_for_monitor = Arc::clone(&flush_settings);
+
+            // Count crashes for telemetry (only reported if the user opts in)
+            telemetry::install_panic_hook(app_handle.clone());
+
+            // Record this instance in the lock file (or note the one already running)
+            if let Ok(dir) = app.path().app_data_dir() {
+                let own_name = own_executable_name();
+                let lock = competitors::InstanceLock::acquire(&dir, |pid| {
+                    let mut sys = System::new();
+                    let pid = Pid::from_u32(pid);
+                    sys.refresh_processes_specifics(
+                        ProcessesToUpdate::Some(&[pid]),
+                        process_monitor::name_refresh(),
+                    );
+                    sys.process(pid)
+                        .is_some_and(|process| process.name().to_string_lossy().eq_ignore_ascii_case(&own_name))
+                });
+                if let Some(pid) = lock.other_pid {
+                    println!("[App] Another instance is already running (PID {}).", pid);
+                }
+                // Trace sessions outlive a crashed process and would block ours from starting
+                for name in etw::cleanup_orphans(lock.other_pid.is_some()) {
+                    println!("[ETW] Stopped orphaned session \"{}\"", name);
+                }
+                if let Ok(mut guard) = app.state::<InstanceLockState>().0.lock() {
+                    *guard = Some(lock);
+                }
+            }
+
+            // Probed once the database decided whether file-level tracing runs
+            let capabilities_for_probe = Arc::clone(&capabilities_cache);
 
             // Setup window close event to trigger graceful shutdown
             let main_window = app.get_webview_window("main");
             if let Some(window) = main_window {
                 let shutdown_clone = Arc::clone(&shutdown_signal_monitor);
                 let shutdown_notify_monitor = Arc::clone(&shutdown_notify_monitor);
+                let shutdown_done_close = Arc::clone(&shutdown_done);
+                let lock_handle = app.handle().clone();
+                let visibility_window = window.clone();
+                let close_window = window.clone();
 
                 window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { .. } = event {
+                    // Minimize, restore and hide/show all end in a resize or focus change
+                    if let tauri::WindowEvent::Resized(_) | tauri::WindowEvent::Focused(_) = event {
+                        let hidden = visibility_window.is_minimized().unwrap_or(false)
+                            || !visibility_window.is_visible().unwrap_or(true);
+                        lock_handle.state::<WindowHiddenState>().0.store(hidden, Ordering::Relaxed);
+                    }
+                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                        // The window is destroyed once the monitor confirms its final flush
+                        api.prevent_close();
+                        if shutdown_clone.swap(true, Ordering::Relaxed) {
+                            return;
+                        }
                         println!("[App] Close requested, triggering shutdown signal.");
-                        shutdown_clone.store(true, Ordering::Relaxed);
                         shutdown_notify_monitor.notify_waiters();
 
-                        // Give monitor time to flush buffer
-                        std::thread::sleep(std::time::Duration::from_millis(1000));
-
-                        // Final sleep to ensure everything is written
-                        std::thread::sleep(std::time::Duration::from_millis(200));
+                        let done = Arc::clone(&shutdown_done_close);
+                        let handle = lock_handle.clone();
+                        let window = close_window.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if tokio::time::timeout(SHUTDOWN_ACK_TIMEOUT, done.notified()).await.is_err() {
+                                eprintln!(
+                                    "[App] Monitor did not confirm the final flush within {:?}, closing anyway.",
+                                    SHUTDOWN_ACK_TIMEOUT
+                                );
+                            }
+                            if let Some(tracer) = handle.state::<FileTracerState>().0.lock().ok().and_then(|mut guard| guard.take()) {
+                                tracer.stop();
+                            }
+                            if let Ok(mut guard) = handle.state::<InstanceLockState>().0.lock() {
+                                if let Some(lock) = guard.as_mut() {
+                                    lock.release();
+                                }
+                            }
+                            let _ = window.destroy();
+                        });
                     }
                 });
             }
@@ -309,18 +2607,45 @@ pub fn run() {
                 match db::init_db(&app_handle).await {
                     Ok(pool) => {
                         // Store pool in state
+                        let backend = storage::configured_backend(&pool).await;
+                        load_stored_settings(&app_handle, &pool).await;
+                        match file_io::is_enabled(&pool).await {
+                            Ok(true) => {
+                                if let Err(e) = start_file_tracing(&app_handle) {
+                                    eprintln!("[FileIO] Could not start file-level tracing: {}", e);
+                                }
+                            }
+                            Ok(false) => {}
+                            Err(e) => eprintln!("[FileIO] Failed to load file tracing setting: {}", e),
+                        }
+                        spawn_capability_probe(capabilities_for_probe);
                         if let Ok(mut pool_guard) = pool_for_setup.lock() {
-                            *pool_guard = Some(pool.clone());
+                            *pool_guard = Some(pool);
+                        }
+
+                        // Open the time series backend
+                        let app_for_storage = app_handle.clone();
+                        let store = storage::open_storage(
+                            backend,
+                            Arc::clone(&pool_for_setup),
+                            Arc::clone(&contention_for_setup),
+                            move || data_dir::resolve_data_dir(&app_for_storage).ok(),
+                        );
+                        println!("[Storage] Using {} backend", backend.as_str());
+                        if let Ok(mut storage_guard) = storage_for_setup.lock() {
+                            *storage_guard = Some(store);
                         }
                         
                         // Start scheduled tasks
-                        let pool_for_cleanup = Arc::new(pool.clone());
-                        let pool_for_analyze = Arc::new(pool.clone());
-                        let pool_for_checkpoint = Arc::new(pool.clone());
+                        let pool_for_cleanup = Arc::clone(&pool_for_setup);
+                        let storage_for_cleanup = Arc::clone(&storage_for_setup);
+                        let pool_for_analyze = Arc::clone(&pool_for_setup);
+                        let pool_for_checkpoint = Arc::clone(&pool_for_setup);
+                        let pool_for_free_space = Arc::clone(&pool_for_setup);
                         
                         // Spawn cleanup scheduler (24 hours)
                         tauri::async_runtime::spawn(
-                            scheduled_tasks::start_cleanup_scheduler(pool_for_cleanup)
+                            scheduled_tasks::start_cleanup_scheduler(pool_for_cleanup, storage_for_cleanup)
                         );
                         
                         // Spawn analyze scheduler (7 days)
@@ -332,20 +2657,56 @@ pub fn run() {
                         tauri::async_runtime::spawn(
                             scheduled_tasks::start_wal_checkpoint_scheduler(pool_for_checkpoint)
                         );
+
+                        // Spawn free space sampler (5 minutes)
+                        tauri::async_runtime::spawn(
+                            scheduled_tasks::start_free_space_scheduler(pool_for_free_space, writes_for_free_space)
+                        );
                         
+                        // Spawn telemetry reporter (24 hours, opt-in)
+                        tauri::async_runtime::spawn(telemetry::start_telemetry_scheduler(
+                            app_handle.clone(),
+                            Arc::clone(&pool_for_setup),
+                            telemetry_for_scheduler,
+                        ));
+
                         println!("[Schedulers] All database maintenance schedulers started");
                         
                         monitor::init_monitoring(
-                            pool,
+                            Arc::clone(&pool_for_setup),
                             app_handle,
-                            reset_signal_monitor,
-                            shutdown_signal_monitor,
-                            shutdown_notify_monitor,
-                            accumulators_for_monitor,
+                            monitor::MonitorHandles {
+                                reset_signal: reset_signal_monitor,
+                                shutdown_signal: shutdown_signal_monitor,
+                                shutdown_notify: shutdown_notify_monitor,
+                                shutdown_done: shutdown_done_monitor,
+                                paused: paused_monitor,
+                                accumulators: accumulators_for_monitor,
+                                subscriptions: subscriptions_for_monitor,
+                                volume_stats: volume_stats_for_monitor,
+                                stats: monitor_stats_for_monitor,
+                                storage: storage_for_setup,
+                                writes: writes_for_monitor,
+                                contention: contention_for_setup,
+                                interval: interval_for_monitor,
+                                live_frames: live_frames_for_monitor,
+                                battery_throttle: throttle_for_monitor,
+                                channels: channels_for_monitor,
+                                led_rate: led_rate_for_monitor,
+                                low_wear: low_wear_for_monitor,
+                                flush_settings: flush_settings_for_monitor,
+                                window_hidden: window_hidden_monitor,
+                                session_totals: session_totals_monitor,
+                                sampler: shared_sampler_monitor,
+                                file_io: file_io_monitor,
+                                process_filter: process_filter_monitor,
+                                top_limit: top_limit_monitor,
+                            },
                         );
                     }
                     Err(e) => {
                         eprintln!("Failed to initialize database: {}", e);
+                        spawn_capability_probe(capabilities_for_probe);
                     }
                 }
             });
@@ -360,7 +2721,103 @@ pub fn run() {
             reset_database,
             optimize_database,
             get_process_history,
-            get_process_history_totals
+            get_process_history_totals,
+            get_process_history_chunk,
+            get_timeline_events,
+            get_physical_disk_stats,
+            get_free_space_history,
+            get_disk_stats_history,
+            get_smoothed_history,
+            set_metric_subscriptions,
+            get_metric_subscriptions,
+            subscribe_metrics,
+            unsubscribe_metrics,
+            get_volume_stats,
+            fuzzy_search,
+            migrate_app_data,
+            create_backup,
+            list_backups,
+            verify_backup,
+            get_version_info,
+            get_status,
+            get_monitor_status,
+            get_last_session_summary,
+            get_current_session_totals,
+            get_top_processes,
+            get_application_groups,
+            get_process_tree,
+            export_settings,
+            import_settings,
+            open_process_location,
+            get_process_sparkline,
+            get_process_details,
+            get_process_series,
+            get_process_comparison,
+            get_system_user_split,
+            get_process_instances,
+            get_known_writers,
+            get_top_processes_limit,
+            set_top_processes_limit,
+            get_retention_days,
+            set_retention_days,
+            set_name_normalization,
+            get_file_tracing,
+            set_file_tracing,
+            get_process_drive_io,
+            get_top_files,
+            get_directory_io,
+            get_paging_io,
+            get_process_icon,
+            prepare_kill_process,
+            kill_process,
+            get_capabilities,
+            preview_telemetry_payload,
+            get_telemetry_status,
+            set_telemetry_enabled,
+            diagnose_problem,
+            list_drives,
+            get_text_summary,
+            get_io_composition,
+            get_speed_distribution,
+            get_data_gaps,
+            get_monitoring_coverage,
+            get_process_categories,
+            set_process_category,
+            get_category_totals,
+            get_storage_backend,
+            set_storage_backend,
+            get_ssd_wear,
+            set_ssd_rated_tbw,
+            get_temperature_history,
+            get_db_contention,
+            get_moment,
+            export_incident,
+            get_peaks,
+            get_sample_interval,
+            set_sample_interval,
+            pause_monitoring,
+            resume_monitoring,
+            is_monitoring_paused,
+            get_live_frame,
+            get_payload_encoding,
+            set_payload_encoding,
+            get_battery_throttle,
+            set_battery_throttle,
+            get_activity_led_hz,
+            set_activity_led_hz,
+            get_low_wear_mode,
+            set_low_wear_mode,
+            get_flush_settings,
+            set_flush_settings,
+            get_process_filter,
+            set_process_filter,
+            get_machine_label,
+            set_machine_label,
+            subscribe_disk_metrics,
+            subscribe_metric_channel,
+            unsubscribe_metric_channel,
+            get_legacy_disk_metrics_event,
+            set_legacy_disk_metrics_event
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,114 @@
+//! Low-wear write mode for SD cards and cheap eMMC storage: samples stay in
+//! the in-memory write coalescer (the RAM journal) and reach the database
+//! only every few minutes, as one large transaction. Up to one flush period
+//! of data is lost if the app is killed without a clean shutdown.
+
+use crate::db;
+use crate::sampling;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const LOW_WEAR_ENABLED_KEY: &str = "low_wear_enabled";
+pub const LOW_WEAR_FLUSH_MINUTES_KEY: &str = "low_wear_flush_minutes";
+
+pub const MIN_FLUSH_MINUTES: u64 = 1;
+pub const MAX_FLUSH_MINUTES: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LowWearMode {
+    pub enabled: bool,
+    /// Minutes between database writes while enabled (the data-loss window)
+    pub flush_minutes: u64,
+}
+
+impl Default for LowWearMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            flush_minutes: 15,
+        }
+    }
+}
+
+impl LowWearMode {
+    /// Flush period replacing the normal one while enabled
+    pub fn flush_secs(&self, normal_flush_secs: u64) -> u64 {
+        if self.enabled {
+            normal_flush_secs.max(
+                self.flush_minutes
+                    .clamp(MIN_FLUSH_MINUTES, MAX_FLUSH_MINUTES)
+                    * 60,
+            )
+        } else {
+            normal_flush_secs
+        }
+    }
+
+    /// Disk stats the coalescer may hold before flushing early; in low-wear
+    /// mode the whole flush period is kept in memory, otherwise `normal_limit`
+    pub fn pending_limit(&self, interval_ms: u64, flush_secs: u64, normal_limit: usize) -> usize {
+        if self.enabled {
+            sampling::ticks_per(Duration::from_secs(flush_secs), interval_ms) as usize + 1
+        } else {
+            normal_limit
+        }
+    }
+}
+
+pub type SharedLowWear = Arc<Mutex<LowWearMode>>;
+
+pub fn create_low_wear() -> SharedLowWear {
+    Arc::new(Mutex::new(LowWearMode::default()))
+}
+
+pub async fn load_low_wear(pool: &Pool<Sqlite>) -> Result<LowWearMode, sqlx::Error> {
+    let defaults = LowWearMode::default();
+    let enabled = db::get_setting(pool, LOW_WEAR_ENABLED_KEY)
+        .await?
+        .map(|value| value == "true")
+        .unwrap_or(defaults.enabled);
+    let flush_minutes = db::get_setting(pool, LOW_WEAR_FLUSH_MINUTES_KEY)
+        .await?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(defaults.flush_minutes);
+    Ok(LowWearMode {
+        enabled,
+        flush_minutes,
+    })
+}
+
+pub async fn save_low_wear(pool: &Pool<Sqlite>, mode: &LowWearMode) -> Result<(), sqlx::Error> {
+    db::set_setting(
+        pool,
+        LOW_WEAR_ENABLED_KEY,
+        if mode.enabled { "true" } else { "false" },
+    )
+    .await?;
+    db::set_setting(
+        pool,
+        LOW_WEAR_FLUSH_MINUTES_KEY,
+        &mode.flush_minutes.to_string(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_wear_holds_a_whole_period() {
+        let mode = LowWearMode {
+            enabled: true,
+            flush_minutes: 10,
+        };
+        assert_eq!(mode.flush_secs(10), 600);
+        assert_eq!(mode.pending_limit(1000, 600, 60), 601);
+
+        let off = LowWearMode::default();
+        assert_eq!(off.flush_secs(10), 10);
+        assert_eq!(off.pending_limit(1000, 10, 60), 60);
+    }
+}
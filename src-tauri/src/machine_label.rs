@@ -0,0 +1,91 @@
+//! Machine label attached to exported data so bundles from several machines
+//! can be told apart without revealing the hostname.
+
+use crate::db;
+use sqlx::{Pool, Sqlite};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+pub const MACHINE_LABEL_KEY: &str = "machine_label";
+
+/// Longest label accepted from the user
+pub const MAX_LABEL_LEN: usize = 64;
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "brisk", "calm", "dusty", "eager", "frosty", "gentle", "hollow", "ivory", "jolly",
+    "keen", "lucky", "misty", "noble", "olive", "quiet", "rapid", "silent", "tidy", "vivid",
+];
+
+const NOUNS: &[&str] = &[
+    "badger", "comet", "falcon", "harbor", "heron", "lantern", "maple", "meadow", "otter",
+    "pebble", "quartz", "raven", "river", "spruce", "summit", "thistle", "tundra", "walrus",
+    "willow", "zephyr",
+];
+
+/// "adjective-noun-xxxx" built from the given random bits
+pub fn pseudonym_from(bits: u64) -> String {
+    let adjective = ADJECTIVES[(bits % ADJECTIVES.len() as u64) as usize];
+    let noun = NOUNS[((bits >> 16) % NOUNS.len() as u64) as usize];
+    format!("{}-{}-{:04x}", adjective, noun, (bits >> 32) & 0xffff)
+}
+
+/// Fresh pseudonym; never derived from the hostname or any hardware id
+pub fn generate_pseudonym() -> String {
+    pseudonym_from(RandomState::new().build_hasher().finish())
+}
+
+/// Trims the label and rejects empty, overlong or control-character labels
+pub fn validate_label(label: &str) -> Result<String, String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("Machine label must not be empty".to_string());
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(format!(
+            "Machine label must be at most {} characters",
+            MAX_LABEL_LEN
+        ));
+    }
+    if label.chars().any(char::is_control) {
+        return Err("Machine label must not contain control characters".to_string());
+    }
+    Ok(label.to_string())
+}
+
+/// Stored label, generating and persisting a pseudonym on first use
+pub async fn load_or_create_label(pool: &Pool<Sqlite>) -> Result<String, sqlx::Error> {
+    if let Some(label) = db::get_setting(pool, MACHINE_LABEL_KEY).await? {
+        if let Ok(label) = validate_label(&label) {
+            return Ok(label);
+        }
+    }
+    let label = generate_pseudonym();
+    save_label(pool, &label).await?;
+    Ok(label)
+}
+
+pub async fn save_label(pool: &Pool<Sqlite>, label: &str) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, MACHINE_LABEL_KEY, label).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudonym_shape_and_validation() {
+        let label = pseudonym_from(0x1234_5678_9abc_def0);
+        let parts: Vec<&str> = label.split('-').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(ADJECTIVES.contains(&parts[0]));
+        assert!(NOUNS.contains(&parts[1]));
+        assert_eq!(parts[2].len(), 4);
+        assert_eq!(validate_label(&label), Ok(label.clone()));
+        assert!(validate_label(&generate_pseudonym()).is_ok());
+
+        assert_eq!(validate_label("  lab-pc  "), Ok("lab-pc".to_string()));
+        assert!(validate_label("   ").is_err());
+        assert!(validate_label("a\nb").is_err());
+        assert!(validate_label(&"x".repeat(MAX_LABEL_LEN + 1)).is_err());
+    }
+}
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--soak") {
+        std::process::exit(driveanalizer_lib::soak::run_from_args(&args));
+    }
     driveanalizer_lib::run()
 }
@@ -0,0 +1,85 @@
+use crate::services;
+
+/// Timeline event kind used for Windows drive optimization runs
+pub const DEFRAG_EVENT_KIND: &str = "defrag";
+
+/// Executables started by the scheduled "Optimize Drives" task and Optimize-Volume
+pub const DEFRAG_PROCESS_NAMES: &[&str] = &["defrag.exe", "dfrgui.exe"];
+
+/// Service that hosts the optimization engine inside svchost.exe
+pub const DEFRAG_SERVICE_NAME: &str = "defragsvc";
+
+/// State change reported by the detector
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaintenanceTransition {
+    Started { start_time: f64 },
+    Ended { start_time: f64, end_time: f64 },
+}
+
+/// Tracks whether Windows' drive optimization is currently running
+///
+/// Defragmentation and TRIM passes generate large I/O bursts that are not
+/// attributable to any user application, so the monitor tags those periods
+/// in the timeline.
+#[derive(Debug, Default)]
+pub struct MaintenanceDetector {
+    active_since: Option<f64>,
+}
+
+impl MaintenanceDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active_since.is_some()
+    }
+
+    /// Feeds the current observation and returns a transition if the state changed
+    pub fn observe(&mut self, running: bool, now: f64) -> Option<MaintenanceTransition> {
+        match (self.active_since, running) {
+            (None, true) => {
+                self.active_since = Some(now);
+                Some(MaintenanceTransition::Started { start_time: now })
+            }
+            (Some(start_time), false) => {
+                self.active_since = None;
+                Some(MaintenanceTransition::Ended {
+                    start_time,
+                    end_time: now,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Checks whether the defrag service is running (Windows only, SCM query)
+pub fn is_defrag_service_running() -> bool {
+    services::is_service_running_safe(DEFRAG_SERVICE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detector_start_and_end() {
+        let mut detector = MaintenanceDetector::new();
+        assert_eq!(detector.observe(false, 1.0), None);
+        assert_eq!(
+            detector.observe(true, 2.0),
+            Some(MaintenanceTransition::Started { start_time: 2.0 })
+        );
+        assert!(detector.is_active());
+        assert_eq!(detector.observe(true, 3.0), None);
+        assert_eq!(
+            detector.observe(false, 4.0),
+            Some(MaintenanceTransition::Ended {
+                start_time: 2.0,
+                end_time: 4.0
+            })
+        );
+        assert!(!detector.is_active());
+    }
+}
@@ -1,4 +1,6 @@
-use serde::Serialize;
+use crate::file_io::ExtensionStat;
+use crate::io_priority::IoPriority;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DiskStat {
@@ -9,17 +11,191 @@ pub struct DiskStat {
     pub write_speed: u64,
     pub idle_time: f64,
     pub queue_depth: f64,
+    /// Read operations per second
+    pub read_iops: f64,
+    /// Write operations per second
+    pub write_iops: f64,
+    /// Average time per read operation in milliseconds
+    pub read_latency_ms: f64,
+    /// Average time per write operation in milliseconds
+    pub write_latency_ms: f64,
+    /// Monotonic seconds since the monitoring session started (unaffected by
+    /// system clock changes; 0 for samples recorded before it was stored)
+    pub monotonic_offset: f64,
 }
 
-/// Per-process disk I/O statistics
+#[cfg(test)]
+impl DiskStat {
+    /// An idle sample at `timestamp`; tests override the fields they care about
+    pub fn idle(timestamp: f64) -> Self {
+        Self {
+            timestamp,
+            read_bytes: 0,
+            write_bytes: 0,
+            read_speed: 0,
+            write_speed: 0,
+            idle_time: 100.0,
+            queue_depth: 0.0,
+            read_iops: 0.0,
+            write_iops: 0.0,
+            read_latency_ms: 0.0,
+            write_latency_ms: 0.0,
+            monotonic_offset: timestamp,
+        }
+    }
+}
+
+/// Per physical disk sample (one row per disk per sample)
+#[derive(Debug, Clone, Serialize)]
+pub struct PhysicalDiskStat {
+    pub timestamp: f64,
+    pub disk: String,
+    pub read_speed: u64,
+    pub write_speed: u64,
+    pub idle_time: f64,
+    pub queue_depth: f64,
+}
+
+/// Current speeds and session totals for a mounted volume (drive letter)
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeStat {
+    pub volume: String,
+    pub read_speed: u64,
+    pub write_speed: u64,
+    pub session_read_bytes: u64,
+    pub session_write_bytes: u64,
+    /// PhysicalDisk instance the volume lives on (None when not mapped)
+    pub disk: Option<String>,
+    /// Idle time (%) of that physical disk
+    pub disk_idle_time: Option<f64>,
+    /// Average queue length of that physical disk
+    pub disk_queue_depth: Option<f64>,
+}
+
+/// A mounted volume, as reported in drive-added / drive-removed events
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MountedVolume {
+    pub volume: String,
+    pub label: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub removable: bool,
+}
+
+/// One drive temperature reading (SMART attribute 194 or the NVMe health log)
+#[derive(Debug, Clone, Serialize)]
+pub struct TemperatureSample {
+    pub timestamp: f64,
+    /// Volume used to reach the drive (one sample per physical disk)
+    pub disk: String,
+    pub temperature_c: f64,
+}
+
+/// Hardware and filesystem details of a volume for the drive picker
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveInfo {
+    pub volume: String,
+    pub label: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    /// "nvme", "sata", "usb", ... ("unknown" when the query isn't available)
+    pub bus_type: String,
+    /// "ssd", "hdd" or "unknown"
+    pub media_type: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub removable: bool,
+    /// From the NVMe health log (None for other buses or when unreadable)
+    pub temperature_c: Option<f64>,
+    pub percentage_used: Option<u8>,
+}
+
+/// Estimated SSD endurance use against the drive's rated TBW
 #[derive(Debug, Clone, Serialize)]
+pub struct SsdWear {
+    pub volume: String,
+    /// User supplied rating; None when only the NVMe health log is available
+    pub rated_tbw: Option<f64>,
+    /// SMART "Total LBAs Written" in bytes, when readable
+    pub host_writes_bytes: Option<u64>,
+    /// NVMe "Percentage Used" as reported by the drive
+    pub drive_percent_used: Option<u8>,
+    pub temperature_c: Option<f64>,
+    /// All-time writes the app recorded, summed over every drive
+    pub app_written_bytes: u64,
+    pub percent_used: f64,
+    /// Unix seconds at the current write rate; None when it can't be projected.
+    /// The rate is the app's system-wide average, not this drive's alone
+    pub projected_end_of_life: Option<f64>,
+    /// "nvme", "smart" or "app", whichever write total the estimate used
+    pub source: String,
+    /// `percent_used` comes from the system-wide app total because the drive
+    /// reported no write counter, so it overstates this volume's wear
+    pub system_wide: bool,
+}
+
+/// Averages and peaks of disk_stats over a time range
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RangeAggregate {
+    pub sample_count: u64,
+    pub avg_read_speed: f64,
+    pub avg_write_speed: f64,
+    pub max_read_speed: u64,
+    pub max_write_speed: u64,
+    pub avg_idle_time: f64,
+}
+
+/// Free space of one volume at a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct FreeSpaceSample {
+    pub timestamp: f64,
+    pub volume: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+}
+
+/// Per-process disk I/O statistics
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct ProcessIOStat {
     pub pid: u32,
     pub name: String,
+    /// Label for the busiest instance when it differs from the name (a
+    /// svchost's hosted services)
+    pub display_name: Option<String>,
     pub exe_path: Option<String>,
+    /// Of the running instance with the most I/O (tells node.exe instances apart)
+    pub command_line: Option<String>,
+    /// Account of that same instance
+    pub user: Option<String>,
+    /// Whether that instance is part of Windows (session 0 or a service account)
+    pub system: bool,
     pub read_bytes: u64,
     pub write_bytes: u64,
     pub total_bytes: u64,
+    /// Bytes/s over the last few ticks (who is busy right now)
+    pub read_speed: u64,
+    pub write_speed: u64,
+    /// Pinned by the user: listed even when outside the top or idle
+    pub pinned: bool,
+    /// Id of the well-known background writer this is (see `known_writers`)
+    pub known_writer: Option<&'static str>,
+    /// I/O priority of the busiest instance (background I/O yields to the rest)
+    pub io_priority: Option<IoPriority>,
+}
+
+/// One running process behind a name in the top-processes list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessInstance {
+    /// Session counters and speeds of this PID alone
+    #[serde(flatten)]
+    pub stat: ProcessIOStat,
+    pub parent_pid: Option<u32>,
+    /// Unix time the process started (0 when unknown)
+    pub start_time: u64,
+    /// Bytes since the process started, including before the monitor did
+    pub lifetime_read_bytes: u64,
+    pub lifetime_write_bytes: u64,
 }
 
 /// All-time totals from database
@@ -29,6 +205,80 @@ pub struct AllTimeTotals {
     pub write_bytes: u64,
 }
 
+/// A single all-time row from process_history
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessHistoryEntry {
+    pub name: String,
+    /// Last recorded command line of the instance with the most I/O
+    pub command_line: Option<String>,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub total_bytes: u64,
+    /// First and last flush with I/O (None for rows recorded before this was tracked)
+    pub first_seen: Option<f64>,
+    pub last_seen: Option<f64>,
+}
+
+/// Bucket size of a per-process time series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesBucket {
+    Hour,
+    Day,
+}
+
+impl SeriesBucket {
+    pub fn secs(self) -> i64 {
+        match self {
+            SeriesBucket::Hour => 3600,
+            SeriesBucket::Day => 86400,
+        }
+    }
+}
+
+/// Bytes of one process name in one hour or (local) day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessSeriesPoint {
+    /// Unix time the bucket starts (the local midnight for days)
+    pub start: f64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Everything recorded about one process name
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessDetails {
+    pub name: String,
+    /// Of a running instance (not recorded for exited processes)
+    pub exe_path: Option<String>,
+    pub command_line: Option<String>,
+    /// All-time totals from process_history
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    /// First and last flush with I/O
+    pub first_seen: Option<f64>,
+    pub last_seen: Option<f64>,
+    /// Oldest day first
+    pub days: Vec<ProcessSeriesPoint>,
+    /// Running instances with their session counters, lowest PID first
+    pub instances: Vec<ProcessInstance>,
+    /// The running instances' traced file bytes per extension (empty without
+    /// file-level tracing)
+    pub extensions: Vec<ExtensionStat>,
+    pub read_speed: u64,
+    pub write_speed: u64,
+}
+
+/// One page of process_history for virtual scrolling
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessHistoryChunk {
+    pub rows: Vec<ProcessHistoryEntry>,
+    /// Cursor for the next page; None when the end was reached
+    pub next_cursor: Option<String>,
+    /// Total number of rows in process_history (for sizing the scroll area)
+    pub total_count: u64,
+}
+
 /// Reset database response with database size info
 #[derive(Debug, Clone, Serialize)]
 pub struct ResetDatabaseResponse {
@@ -43,3 +293,111 @@ pub struct AppMetrics {
     pub ram_usage: u64,
     pub cpu_usage: f32,
 }
+
+/// Build and environment details for support / the About panel
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub app_version: String,
+    pub git_hash: Option<String>,
+    pub schema_version: i64,
+    pub sqlx_version: Option<String>,
+    pub sqlite_version: String,
+    pub os_version: String,
+    pub kernel_version: String,
+    pub elevated: bool,
+    pub portable: bool,
+}
+
+/// Another monitoring tool or instance that may conflict with our trace
+/// sessions or counters
+#[derive(Debug, Clone, Serialize)]
+pub struct CompetingMonitor {
+    /// "process", "trace_session" or "instance"
+    pub kind: String,
+    pub name: String,
+    pub pid: Option<u32>,
+    pub guidance: String,
+}
+
+/// Runtime environment summary returned by `get_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct AppStatus {
+    pub elevated: bool,
+    pub counter_backend: String,
+    pub monitoring_paused: bool,
+    pub competing_monitors: Vec<CompetingMonitor>,
+}
+
+/// Bytes a process read/wrote during one flush interval ending at `timestamp`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSample {
+    pub timestamp: f64,
+    pub name: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Everything recorded around one instant, for scrub-to-inspect on the chart
+#[derive(Debug, Clone, Serialize)]
+pub struct Moment {
+    pub timestamp: f64,
+    /// Nearest per-second sample (speeds plus IOPS, latency, idle and queue counters)
+    pub disk_stat: Option<DiskStat>,
+    /// Nearest per-disk breakdown, one entry per physical disk
+    pub disks: Vec<PhysicalDiskStat>,
+    /// Processes active in the flush interval covering the instant, busiest first
+    pub top_processes: Vec<ProcessSample>,
+    /// Timeline events spanning the instant
+    pub events: Vec<TimelineEvent>,
+}
+
+/// Exponential moving averages of the speeds (bytes/sec)
+#[derive(Debug, Clone, Serialize)]
+pub struct SmoothedSpeeds {
+    pub timestamp: f64,
+    pub read_speed_5s: u64,
+    pub write_speed_5s: u64,
+    pub read_speed_30s: u64,
+    pub write_speed_30s: u64,
+}
+
+/// Highest speed ever observed in one direction
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeedPeak {
+    /// "read" or "write"
+    pub direction: String,
+    pub speed: u64,
+    pub timestamp: f64,
+    /// Process with the most bytes in that direction during the peak tick
+    pub process: Option<String>,
+}
+
+/// Bytes one process contributed to a burst
+#[derive(Debug, Clone, Serialize)]
+pub struct BurstProcess {
+    pub name: String,
+    pub bytes: u64,
+}
+
+/// A sustained read or write burst (stored as an io_burst timeline event)
+#[derive(Debug, Clone, Serialize)]
+pub struct IoBurst {
+    /// "read" or "write"
+    pub direction: String,
+    pub start_time: f64,
+    pub end_time: f64,
+    /// Highest per-second speed during the burst
+    pub peak_speed: u64,
+    pub total_bytes: u64,
+    pub top_processes: Vec<BurstProcess>,
+}
+
+/// A tagged period on the timeline (e.g. a Windows drive optimization run)
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEvent {
+    pub id: i64,
+    pub kind: String,
+    pub start_time: f64,
+    pub end_time: Option<f64>,
+    pub details: Option<String>,
+}
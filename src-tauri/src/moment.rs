@@ -0,0 +1,123 @@
+use crate::models::{DiskStat, PhysicalDiskStat, ProcessSample};
+use std::collections::HashMap;
+
+/// Seconds searched on each side of the requested instant
+pub const MOMENT_WINDOW_SECS: f64 = 30.0;
+
+/// How many processes a moment lists
+const TOP_PROCESS_COUNT: usize = 10;
+
+/// Range of disk samples searched for `timestamp`
+pub fn window(timestamp: f64) -> (f64, f64) {
+    (
+        timestamp - MOMENT_WINDOW_SECS,
+        timestamp + MOMENT_WINDOW_SECS,
+    )
+}
+
+/// Per-second sample closest to `timestamp`
+pub fn nearest_disk_stat(stats: Vec<DiskStat>, timestamp: f64) -> Option<DiskStat> {
+    stats.into_iter().min_by(|a, b| {
+        (a.timestamp - timestamp)
+            .abs()
+            .total_cmp(&(b.timestamp - timestamp).abs())
+    })
+}
+
+/// All per-disk rows of the sampling tick closest to `timestamp`
+pub fn nearest_disks(stats: Vec<PhysicalDiskStat>, timestamp: f64) -> Vec<PhysicalDiskStat> {
+    let Some(closest) = stats
+        .iter()
+        .map(|s| s.timestamp)
+        .min_by(|a, b| (a - timestamp).abs().total_cmp(&(b - timestamp).abs()))
+    else {
+        return Vec::new();
+    };
+    stats
+        .into_iter()
+        .filter(|s| s.timestamp == closest)
+        .collect()
+}
+
+/// Processes of the flush interval covering `timestamp`
+///
+/// A sample is written at the end of its interval, so the covering interval is
+/// the first sample at or after the instant. Busiest processes come first.
+pub fn covering_processes(samples: Vec<ProcessSample>, timestamp: f64) -> Vec<ProcessSample> {
+    let Some(interval_end) = samples
+        .iter()
+        .map(|s| s.timestamp)
+        .filter(|t| *t >= timestamp)
+        .min_by(|a, b| a.total_cmp(b))
+    else {
+        return Vec::new();
+    };
+
+    let mut processes: Vec<ProcessSample> = samples
+        .into_iter()
+        .filter(|s| s.timestamp == interval_end)
+        .collect();
+    processes.sort_by_key(|s| std::cmp::Reverse(s.read_bytes.saturating_add(s.write_bytes)));
+    processes.truncate(TOP_PROCESS_COUNT);
+    processes
+}
+
+/// Processes of the flush interval still in progress, as of `now`
+pub fn unflushed_processes(deltas: HashMap<String, (u64, u64)>, now: f64) -> Vec<ProcessSample> {
+    let samples = deltas
+        .into_iter()
+        .map(|(name, (read_bytes, write_bytes))| ProcessSample {
+            timestamp: now,
+            name,
+            read_bytes,
+            write_bytes,
+        })
+        .collect();
+    covering_processes(samples, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: f64, name: &str, write_bytes: u64) -> ProcessSample {
+        ProcessSample {
+            timestamp,
+            name: name.to_string(),
+            read_bytes: 0,
+            write_bytes,
+        }
+    }
+
+    #[test]
+    fn test_covering_processes_uses_interval_ending_after_instant() {
+        let samples = vec![
+            sample(10.0, "a.exe", 5),
+            sample(20.0, "a.exe", 1),
+            sample(20.0, "b.exe", 9),
+            sample(30.0, "c.exe", 100),
+        ];
+        let processes = covering_processes(samples, 15.0);
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[0].name, "b.exe");
+        assert!(covering_processes(vec![sample(10.0, "a.exe", 1)], 15.0).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_disks_groups_by_tick() {
+        let disk = |timestamp: f64, disk: &str| PhysicalDiskStat {
+            timestamp,
+            disk: disk.to_string(),
+            read_speed: 0,
+            write_speed: 0,
+            idle_time: 100.0,
+            queue_depth: 0.0,
+        };
+        let disks = nearest_disks(
+            vec![disk(10.0, "0 C:"), disk(10.0, "1 D:"), disk(15.0, "0 C:")],
+            11.0,
+        );
+        assert_eq!(disks.len(), 2);
+        assert!(nearest_disks(Vec::new(), 11.0).is_empty());
+    }
+}
@@ -1,143 +1,223 @@
+//! Wires the monitor loop to the app: live payloads go out as window events,
+//! channel frames and MessagePack frames, app events as window events
+
+use crate::activity::SharedLedRate;
+use crate::channels::{self, SharedChannels};
 use crate::db;
-use crate::models::DiskStat;
-use crate::perf_counters;
+use crate::db_reader::SharedContention;
+use crate::file_io::SharedFileIo;
+use crate::flush_settings::SharedFlushSettings;
+use crate::low_wear::SharedLowWear;
+use crate::monitor_loop::{LivePayload, LoopHandles, MonitorEmitter, MonitorEvent, MonitorLoop};
+use crate::payload::{self, PayloadEncoder, PayloadEncoding, SharedLiveFrames};
+use crate::power::SharedBatteryThrottle;
+use crate::process_filter::SharedProcessFilter;
 use crate::process_monitor::{ProcessAccumulators, ProcessMonitor};
-use sqlx::{Pool, Sqlite};
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
+use crate::process_sampler::SharedSampler;
+use crate::sampling::SharedInterval;
+use crate::session::SharedSessionTotals;
+use crate::storage::SharedStorage;
+use crate::subscriptions::{self, MetricStream, MetricSubscriptions};
+use crate::top_processes::SharedTopLimit;
+use crate::volume_monitor::VolumeStats;
+use crate::write_coalescer::WriteCoalescer;
+use serde::Serialize;
+use std::sync::{atomic::AtomicBool, Arc};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Notify;
-use tokio::time::{sleep, Duration};
-
-pub fn init_monitoring(
-    pool: Pool<Sqlite>,
-    app: AppHandle,
-    reset_signal: Arc<AtomicBool>,
-    shutdown_signal: Arc<AtomicBool>,
-    shutdown_notify: Arc<Notify>,
-    accumulators: ProcessAccumulators,
-) {
-    tauri::async_runtime::spawn(async move {
-        let mut buffer: Vec<DiskStat> = Vec::new();
-        let mut process_monitor = ProcessMonitor::new(accumulators);
-        
-        let mut session_read_bytes: u64 = 0;
-        let mut session_write_bytes: u64 = 0;
-
-        let mut tick_count: u64 = 0;
-        let mut last_flush = std::time::Instant::now();
-        let mut cached_perf_metrics: (f64, f64) = (100.0, 0.0);
-
-        loop {
-            // Shutdown check
-            if shutdown_signal.load(Ordering::Relaxed) {
-                println!("[Monitor] Shutdown signal received. Flushing remaining buffer.");
-                if !buffer.is_empty() {
-                    if let Err(e) = db::insert_stats_batch(&pool, &buffer).await {
-                        eprintln!("[Monitor] Final DB Flush Error: {}", e);
-                    } else {
-                        println!("[Monitor] Successfully flushed {} records.", buffer.len());
-                    }
-                }
-                break;
-            }
 
-            // Reset check
-            if reset_signal.load(Ordering::Relaxed) {
-                println!("[Monitor] Reset signal received. Resetting baselines.");
-                session_read_bytes = 0;
-                session_write_bytes = 0;
-                buffer.clear();
-                last_flush = std::time::Instant::now();
-                process_monitor.reset();
-                reset_signal.store(false, Ordering::Relaxed);
-            }
-
-            // 1. Disk performance metrics (every 5 ticks)
-            if tick_count % 5 == 0 {
-                if let Ok(metrics) =
-                    tokio::task::spawn_blocking(perf_counters::get_disk_perf_metrics_safe).await
-                {
-                    cached_perf_metrics = metrics;
-                }
-            }
-            let (idle, queue) = cached_perf_metrics;
+pub use crate::monitor_loop::{
+    create_monitor_stats, MonitorStats, SharedMonitorStats, GAP_EVENT_KIND, PAUSE_EVENT_KIND,
+};
 
-            // 2. Update processes and get deltas
-            let (tick_read_delta, tick_write_delta) = process_monitor.update();
+/// `get_monitor_status` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorStatus {
+    #[serde(flatten)]
+    pub stats: MonitorStats,
+    pub paused: bool,
+    /// Live payloads skipped while the main window is hidden
+    pub live_updates_suspended: bool,
+    /// None until the first tick completed
+    pub seconds_since_last_tick: Option<f64>,
+}
 
-            // Update session totals
-            session_read_bytes = session_read_bytes.saturating_add(tick_read_delta);
-            session_write_bytes = session_write_bytes.saturating_add(tick_write_delta);
+/// Shared state the monitor loop reads signals from and publishes live data to
+pub struct MonitorHandles {
+    pub reset_signal: Arc<AtomicBool>,
+    pub shutdown_signal: Arc<AtomicBool>,
+    pub shutdown_notify: Arc<Notify>,
+    /// Notified once the final flush after a shutdown signal is done
+    pub shutdown_done: Arc<Notify>,
+    /// Set by pause_monitoring; nothing is sampled or recorded while true
+    pub paused: Arc<AtomicBool>,
+    pub accumulators: ProcessAccumulators,
+    pub subscriptions: MetricSubscriptions,
+    pub volume_stats: VolumeStats,
+    pub stats: SharedMonitorStats,
+    pub storage: SharedStorage,
+    pub writes: WriteCoalescer,
+    pub contention: SharedContention,
+    pub interval: SharedInterval,
+    pub live_frames: SharedLiveFrames,
+    pub battery_throttle: SharedBatteryThrottle,
+    pub channels: SharedChannels,
+    pub led_rate: SharedLedRate,
+    pub low_wear: SharedLowWear,
+    /// Flush period and buffer size (on AC power, outside low-wear mode)
+    pub flush_settings: SharedFlushSettings,
+    /// Main window minimized or hidden: live payloads are skipped, recording goes on
+    pub window_hidden: Arc<AtomicBool>,
+    pub session_totals: SharedSessionTotals,
+    /// Filled with the sampler once it runs, for on-demand queries
+    pub sampler: SharedSampler,
+    /// Per-file counts from file-level tracing (empty while it is off)
+    pub file_io: SharedFileIo,
+    /// Processes left out of the per-process accounting
+    pub process_filter: SharedProcessFilter,
+    /// Length of the streamed top-processes list
+    pub top_limit: SharedTopLimit,
+}
 
-            let stat = DiskStat {
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_secs_f64())
-                    .unwrap_or(0.0),
-                read_bytes: session_read_bytes,
-                write_bytes: session_write_bytes,
-                read_speed: tick_read_delta,
-                write_speed: tick_write_delta,
-                idle_time: idle,
-                queue_depth: queue,
-            };
+pub fn init_monitoring(shared_pool: db::SharedPool, app: AppHandle, handles: MonitorHandles) {
+    let MonitorHandles {
+        reset_signal,
+        shutdown_signal,
+        shutdown_notify,
+        shutdown_done,
+        paused,
+        accumulators,
+        subscriptions,
+        volume_stats,
+        stats,
+        storage,
+        writes,
+        contention,
+        interval,
+        live_frames,
+        battery_throttle,
+        channels,
+        led_rate,
+        low_wear,
+        flush_settings,
+        window_hidden,
+        session_totals,
+        sampler,
+        file_io,
+        process_filter,
+        top_limit,
+    } = handles;
 
-            // Emit Dashboard Metrics
-            if let Err(e) = app.emit("disk-metrics", &stat) {
-                eprintln!("[Monitor] Failed to emit event: {}", e);
-            }
+    let loop_handles = LoopHandles {
+        shared_pool,
+        reset_signal,
+        paused,
+        volume_stats,
+        stats,
+        storage,
+        writes,
+        contention,
+        interval,
+        battery_throttle,
+        led_rate,
+        low_wear,
+        flush_settings,
+        window_hidden,
+        session_totals,
+        sampler,
+        file_io,
+    };
+    let emitter = AppEmitter {
+        app,
+        live_frames,
+        channels,
+        subscriptions,
+        encoder: PayloadEncoder::new(),
+    };
+    let monitor = ProcessMonitor::new(accumulators)
+        .with_filter(process_filter)
+        .with_top_limit(top_limit);
 
-            // Emit Top Processes (Every tick)
-            tick_count += 1;
-            // if tick_count % 2 == 0 {
-            let process_stats = process_monitor.get_top_processes();
-            if let Err(e) = app.emit("top-processes", &process_stats) {
-                eprintln!("[Monitor] Failed to emit top-processes: {}", e);
+    tauri::async_runtime::spawn(async move {
+        match MonitorLoop::new(loop_handles, monitor, emitter) {
+            Ok(monitor_loop) => monitor_loop.run(&shutdown_signal, &shutdown_notify).await,
+            Err(e) => {
+                eprintln!(
+                    "[Monitor] Failed to start the process sampler thread: {}",
+                    e
+                );
+                return;
             }
-            // }
+        }
 
-            // Unified Flush - Every 10 seconds
-            buffer.push(stat.clone());
-            if buffer.len() >= 60 || last_flush.elapsed() >= std::time::Duration::from_secs(10) {
-                // 1. Flush Disk Stats
-                if !buffer.is_empty() {
-                    if let Err(e) = db::insert_stats_batch(&pool, &buffer).await {
-                        eprintln!("[Monitor] DB Error: {}", e);
-                    }
-                    buffer.clear();
-                }
+        // Lets the close handler destroy the window (a stored permit if it isn't waiting yet)
+        println!("[Monitor] Shutdown complete.");
+        shutdown_done.notify_one();
+    });
+}
 
-                // 2. Flush Process History Deltas
-                let deltas = process_monitor.get_deltas_for_db();
-                if !deltas.is_empty() {
-                    let pool_clone = pool.clone();
-                    tauri::async_runtime::spawn(async move {
-                        if let Err(e) = db::update_process_history(&pool_clone, deltas).await {
-                            eprintln!("[Monitor] Failed to auto-save process history: {}", e);
-                        }
-                    });
-                }
+/// Delivers the loop's output to the windows
+struct AppEmitter {
+    app: AppHandle,
+    live_frames: SharedLiveFrames,
+    channels: SharedChannels,
+    subscriptions: MetricSubscriptions,
+    encoder: PayloadEncoder,
+}
 
-                // Periodic cleanup - every hour
-                if tick_count % 3600 == 0 && tick_count > 0 {
-                    let pool_cleanup = pool.clone();
-                    tauri::async_runtime::spawn(async move {
-                        let _ = db::cleanup_old_data(&pool_cleanup, 7).await;
-                    });
-                }
+impl AppEmitter {
+    /// Channel streams go out as window events only for older frontends
+    /// (JSON) or to refresh the latest binary frame (MessagePack); diff
+    /// frames only make sense as events, not as the latest binary frame
+    fn window_event(&self, stream: MetricStream) -> bool {
+        let enabled = match payload::encoding(&self.live_frames) {
+            PayloadEncoding::Json => channels::legacy_disk_metrics_event(&self.channels),
+            PayloadEncoding::MessagePack => stream != MetricStream::TopProcessesDiff,
+        };
+        enabled && subscriptions::is_subscribed(&self.subscriptions, stream)
+    }
+}
 
-                last_flush = std::time::Instant::now();
-            }
+impl MonitorEmitter for AppEmitter {
+    fn wants(&self, stream: MetricStream) -> bool {
+        if channels::is_channel_stream(stream) {
+            channels::has_subscribers(&self.channels, stream) || self.window_event(stream)
+        } else {
+            subscriptions::is_subscribed(&self.subscriptions, stream)
+        }
+    }
 
-            tokio::select! {
-                _ = sleep(Duration::from_secs(1)) => {}
-                _ = shutdown_notify.notified() => {
-                    println!("[Monitor] Notification received. Waking up.");
-                }
-            }
+    fn publish(&mut self, live: LivePayload<'_>) -> Result<(), String> {
+        let stream = live.stream();
+        if !channels::is_channel_stream(stream) {
+            return payload::publish(
+                &self.app,
+                &self.live_frames,
+                &mut self.encoder,
+                stream,
+                &live,
+            );
         }
-    });
+        // Binary frames to channel subscribers, the window event only when
+        // the compatibility setting asks for it
+        let sent = channels::send(&self.channels, &mut self.encoder, stream, &live);
+        if self.window_event(stream) {
+            payload::publish(
+                &self.app,
+                &self.live_frames,
+                &mut self.encoder,
+                stream,
+                &live,
+            )?;
+        }
+        sent.map(|_| ())
+    }
+
+    fn generation(&self) -> u64 {
+        channels::generation(&self.channels)
+    }
+
+    fn emit(&mut self, event: MonitorEvent<'_>) {
+        let _ = self.app.emit(event.name(), &event);
+    }
 }
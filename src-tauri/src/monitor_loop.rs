@@ -0,0 +1,1167 @@
+//! The monitor tick, independent of Tauri
+//!
+//! `MonitorLoop` samples processes and disk counters, records everything
+//! through the write coalescer and hands live payloads and app events to a
+//! `MonitorEmitter`. The app delivers them as window events and channel
+//! frames (`monitor::init_monitoring`); the soak runner and the integration
+//! tests drive the same loop without a window.
+
+use crate::activity::{self, Activity, ActivityLed, SharedLedRate};
+use crate::burst::{self, BurstConfig, BurstDetector, BurstDirection};
+use crate::clock::SessionClock;
+use crate::core::CoreError;
+use crate::db;
+use crate::db_cleanup;
+use crate::db_reader::SharedContention;
+use crate::file_io::{self, SharedFileIo, TopFile};
+use crate::flush_settings::{self, SharedFlushSettings};
+use crate::low_wear::{self, SharedLowWear};
+use crate::maintenance::{self, MaintenanceDetector, MaintenanceTransition};
+use crate::models::{
+    DiskStat, IoBurst, MountedVolume, PhysicalDiskStat, ProcessIOStat, SmoothedSpeeds,
+    TemperatureSample, TimelineEvent,
+};
+use crate::peaks::PeakTracker;
+use crate::perf_counters::{DiskPerfMetrics, PerfCounterSession, PhysicalDiskMetrics};
+use crate::power::{self, PowerProfile, SharedBatteryThrottle};
+use crate::process_monitor::{ProcessMapSizes, ProcessMonitor};
+use crate::process_sampler::{ProcessSampler, ProcessTick, SharedSampler, TickRequest};
+use crate::sampling::{self, SharedInterval};
+use crate::session::{self, SessionSummary, SessionTotals, SharedSessionTotals};
+use crate::smart;
+use crate::smoothing::SpeedSmoother;
+use crate::storage::{self, SharedStorage};
+use crate::subscriptions::MetricStream;
+use crate::top_processes::{TopProcessesDiff, TopProcessesFrame};
+use crate::volume_monitor::{self, VolumeStats};
+use crate::write_coalescer::{self, WriteCoalescer};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::time::Instant;
+use tokio::sync::Notify;
+use tokio::time::{sleep, Duration};
+
+/// Loop health counters, read by diagnostics
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MonitorStats {
+    pub tick_count: u64,
+    /// Work time of the last tick (excludes the sleep between ticks)
+    pub last_tick_ms: f64,
+    /// Exponential moving average of the tick work time
+    pub avg_tick_ms: f64,
+    pub max_tick_ms: f64,
+    /// Unix time the last tick finished
+    pub last_tick_at: f64,
+    /// Sizes of the process accounting maps (bounded by LRU eviction)
+    pub process_maps: ProcessMapSizes,
+    /// Unix time of the last successful database flush
+    pub last_flush_at: Option<f64>,
+    /// Error of the most recent flush, cleared by the next successful one
+    pub last_flush_error: Option<String>,
+    /// Rows waiting in the write coalescer
+    pub buffered_records: usize,
+    /// False until any process reports non-zero I/O counters (they can be
+    /// unavailable without elevation)
+    pub process_counters_available: bool,
+}
+
+pub type SharedMonitorStats = Arc<Mutex<MonitorStats>>;
+
+pub fn create_monitor_stats() -> SharedMonitorStats {
+    Arc::new(Mutex::new(MonitorStats::default()))
+}
+
+impl MonitorStats {
+    fn record_tick(&mut self, tick_ms: f64, now: f64) {
+        self.avg_tick_ms = if self.tick_count == 0 {
+            tick_ms
+        } else {
+            self.avg_tick_ms * 0.9 + tick_ms * 0.1
+        };
+        self.tick_count += 1;
+        self.last_tick_ms = tick_ms;
+        self.max_tick_ms = self.max_tick_ms.max(tick_ms);
+        self.last_tick_at = now;
+    }
+
+    fn record_flush(&mut self, result: &Result<usize, String>, now: f64) {
+        match result {
+            Ok(_) => {
+                self.last_flush_at = Some(now);
+                self.last_flush_error = None;
+            }
+            Err(e) => self.last_flush_error = Some(e.clone()),
+        }
+    }
+}
+
+/// How often disk performance counters and defrag activity are sampled
+const PERF_SAMPLE_PERIOD: Duration = Duration::from_secs(5);
+
+/// How often drive temperatures are sampled
+const TEMPERATURE_SAMPLE_PERIOD: Duration = Duration::from_secs(60);
+
+/// How often the power source is checked
+const POWER_CHECK_PERIOD: Duration = Duration::from_secs(30);
+
+/// How often old time series data is pruned
+const PRUNE_PERIOD: Duration = Duration::from_secs(3600);
+
+/// Timeline event kind covering a period the user paused monitoring
+pub const PAUSE_EVENT_KIND: &str = "monitoring_paused";
+
+/// Timeline event kind covering time the loop did not run (sleep/hibernate)
+pub const GAP_EVENT_KIND: &str = "monitoring_gap";
+
+/// disk-metrics payload: the sample plus the latest drive temperatures
+#[derive(Serialize)]
+pub struct DiskMetricsEvent<'a> {
+    #[serde(flatten)]
+    pub stat: &'a DiskStat,
+    pub temperatures: &'a [TemperatureSample],
+}
+
+/// One payload of a live stream; serializes as the payload itself
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LivePayload<'a> {
+    DiskMetrics(DiskMetricsEvent<'a>),
+    DiskBreakdown(&'a [PhysicalDiskStat]),
+    SmoothedSpeeds(&'a SmoothedSpeeds),
+    DriveActivity(Activity),
+    TopProcesses(&'a [ProcessIOStat]),
+    TopProcessesDiff(&'a TopProcessesFrame),
+    TopFiles(&'a [TopFile]),
+}
+
+impl LivePayload<'_> {
+    pub fn stream(&self) -> MetricStream {
+        match self {
+            LivePayload::DiskMetrics(_) => MetricStream::DiskMetrics,
+            LivePayload::DiskBreakdown(_) => MetricStream::DiskBreakdown,
+            LivePayload::SmoothedSpeeds(_) => MetricStream::SmoothedSpeeds,
+            LivePayload::DriveActivity(_) => MetricStream::DriveActivity,
+            LivePayload::TopProcesses(_) => MetricStream::TopProcesses,
+            LivePayload::TopProcessesDiff(_) => MetricStream::TopProcessesDiff,
+            LivePayload::TopFiles(_) => MetricStream::TopFiles,
+        }
+    }
+}
+
+/// One-off notification from the loop; serializes as the payload itself
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum MonitorEvent<'a> {
+    PowerProfile(&'a PowerProfile),
+    SessionSummary(&'a SessionSummary),
+    DriveAdded(&'a MountedVolume),
+    DriveRemoved(&'a MountedVolume),
+    MaintenanceActivity(&'a TimelineEvent),
+    IoBurst(&'a TimelineEvent),
+}
+
+impl MonitorEvent<'_> {
+    /// Window event name the frontend listens to
+    pub fn name(&self) -> &'static str {
+        match self {
+            MonitorEvent::PowerProfile(_) => "power-profile",
+            MonitorEvent::SessionSummary(_) => "session-summary",
+            MonitorEvent::DriveAdded(_) => "drive-added",
+            MonitorEvent::DriveRemoved(_) => "drive-removed",
+            MonitorEvent::MaintenanceActivity(_) => "maintenance-activity",
+            MonitorEvent::IoBurst(_) => "io-burst",
+        }
+    }
+}
+
+/// Where the loop's live payloads and events go
+pub trait MonitorEmitter: Send {
+    /// True while `stream` has a listener; payloads nobody reads are not built
+    fn wants(&self, stream: MetricStream) -> bool;
+
+    fn publish(&mut self, payload: LivePayload<'_>) -> Result<(), String>;
+
+    /// Changes whenever a new listener needs a full top-processes-diff frame
+    fn generation(&self) -> u64;
+
+    fn emit(&mut self, event: MonitorEvent<'_>);
+}
+
+/// Emitter for running without a front end: nothing listens
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoEmitter;
+
+impl MonitorEmitter for NoEmitter {
+    fn wants(&self, _stream: MetricStream) -> bool {
+        false
+    }
+
+    fn publish(&mut self, _payload: LivePayload<'_>) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    fn emit(&mut self, _event: MonitorEvent<'_>) {}
+}
+
+/// Shared state the loop reads settings and signals from and records into
+pub struct LoopHandles {
+    /// Empty while the data directory is migrated; records stay buffered meanwhile
+    pub shared_pool: db::SharedPool,
+    pub reset_signal: Arc<AtomicBool>,
+    /// Set by pause_monitoring; nothing is sampled or recorded while true
+    pub paused: Arc<AtomicBool>,
+    pub volume_stats: VolumeStats,
+    pub stats: SharedMonitorStats,
+    pub storage: SharedStorage,
+    pub writes: WriteCoalescer,
+    pub contention: SharedContention,
+    pub interval: SharedInterval,
+    pub battery_throttle: SharedBatteryThrottle,
+    pub led_rate: SharedLedRate,
+    pub low_wear: SharedLowWear,
+    /// Flush period and buffer size (on AC power, outside low-wear mode)
+    pub flush_settings: SharedFlushSettings,
+    /// Main window minimized or hidden: live payloads are skipped, recording goes on
+    pub window_hidden: Arc<AtomicBool>,
+    pub session_totals: SharedSessionTotals,
+    /// Filled with the sampler once the loop is created, for on-demand queries
+    pub sampler: SharedSampler,
+    /// Per-file counts from file-level tracing (empty while it is off)
+    pub file_io: SharedFileIo,
+}
+
+impl LoopHandles {
+    /// Handles over `shared_pool` and `storage` with default settings
+    pub fn new(
+        shared_pool: db::SharedPool,
+        storage: SharedStorage,
+        contention: SharedContention,
+    ) -> Self {
+        Self {
+            shared_pool,
+            reset_signal: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            volume_stats: volume_monitor::create_volume_stats(),
+            stats: create_monitor_stats(),
+            storage,
+            writes: write_coalescer::create_write_coalescer(),
+            contention,
+            interval: sampling::create_interval(),
+            battery_throttle: power::create_battery_throttle(),
+            led_rate: activity::create_led_rate(),
+            low_wear: low_wear::create_low_wear(),
+            flush_settings: flush_settings::create_flush_settings(),
+            window_hidden: Arc::new(AtomicBool::new(false)),
+            session_totals: session::create_session_totals(),
+            sampler: crate::process_sampler::create_shared_sampler(),
+            file_io: file_io::create_file_io(),
+        }
+    }
+}
+
+/// The monitoring loop: one `tick` per sampling interval
+pub struct MonitorLoop<E> {
+    handles: LoopHandles,
+    emitter: E,
+    sampler: ProcessSampler,
+    session_started: f64,
+    session_read_bytes: u64,
+    session_write_bytes: u64,
+    tick_count: u64,
+    last_flush: Instant,
+    // Wall time rather than a tick multiple: flush ticks needn't land on
+    // one, and the tick count restarts after a sleep gap
+    last_prune: Instant,
+    cached_perf_metrics: DiskPerfMetrics,
+    // Opened on the first tick and kept for the lifetime of the loop
+    perf_session: Option<PerfCounterSession>,
+    perf_retry_at: Option<Instant>,
+    latest_disks: Vec<PhysicalDiskMetrics>,
+    maintenance_detector: MaintenanceDetector,
+    maintenance_event_id: Option<i64>,
+    last_volume_sample: Option<Instant>,
+    drive_watcher: volume_monitor::DriveWatcher,
+    latest_temperatures: Vec<TemperatureSample>,
+    interval_ms: u64,
+    read_bursts: BurstDetector,
+    write_bursts: BurstDetector,
+    peak_tracker: PeakTracker,
+    smoother: SpeedSmoother,
+    activity_led: ActivityLed,
+    top_processes_diff: TopProcessesDiff,
+    emitter_generation: u64,
+    // Open pause timeline event (None while running)
+    pause_event_id: Option<i64>,
+    was_paused: bool,
+    was_hidden: bool,
+    on_battery: bool,
+    last_power_check: Option<Instant>,
+    power_profile: Option<PowerProfile>,
+    // Start of the previous sampled tick, on both clocks: the monotonic
+    // clock may stop while the system is suspended, the wall clock doesn't
+    last_sampled: Option<(Instant, f64)>,
+    session_clock: SessionClock,
+}
+
+impl<E: MonitorEmitter> MonitorLoop<E> {
+    /// Starts the sampler thread for `monitor` (process scanning never
+    /// blocks the runtime) and publishes it in `handles.sampler`
+    pub fn new(handles: LoopHandles, monitor: ProcessMonitor, emitter: E) -> std::io::Result<Self> {
+        let sampler = ProcessSampler::spawn(monitor)?;
+        if let Ok(mut slot) = handles.sampler.lock() {
+            *slot = Some(sampler.clone());
+        }
+        let interval_ms = sampling::current_ms(&handles.interval);
+        Ok(Self {
+            handles,
+            emitter,
+            sampler,
+            session_started: unix_now(),
+            session_read_bytes: 0,
+            session_write_bytes: 0,
+            tick_count: 0,
+            last_flush: Instant::now(),
+            last_prune: Instant::now(),
+            cached_perf_metrics: DiskPerfMetrics::default(),
+            perf_session: None,
+            perf_retry_at: None,
+            latest_disks: Vec::new(),
+            maintenance_detector: MaintenanceDetector::new(),
+            maintenance_event_id: None,
+            last_volume_sample: None,
+            drive_watcher: volume_monitor::DriveWatcher::new(),
+            latest_temperatures: Vec::new(),
+            interval_ms,
+            read_bursts: BurstDetector::new(
+                BurstDirection::Read,
+                BurstConfig::for_interval(interval_ms),
+            ),
+            write_bursts: BurstDetector::new(
+                BurstDirection::Write,
+                BurstConfig::for_interval(interval_ms),
+            ),
+            peak_tracker: PeakTracker::new(),
+            smoother: SpeedSmoother::new(),
+            activity_led: ActivityLed::new(),
+            top_processes_diff: TopProcessesDiff::new(),
+            emitter_generation: 0,
+            pause_event_id: None,
+            was_paused: false,
+            was_hidden: false,
+            on_battery: false,
+            last_power_check: None,
+            power_profile: None,
+            last_sampled: None,
+            session_clock: SessionClock::new(),
+        })
+    }
+
+    pub fn sampler(&self) -> &ProcessSampler {
+        &self.sampler
+    }
+
+    pub fn emitter(&self) -> &E {
+        &self.emitter
+    }
+
+    /// Sampling interval in effect (the battery profile can stretch it)
+    pub fn interval_ms(&self) -> u64 {
+        self.interval_ms
+    }
+
+    /// Ticks until `shutdown_signal` is set, then flushes and records the session
+    pub async fn run(mut self, shutdown_signal: &AtomicBool, shutdown_notify: &Notify) {
+        loop {
+            if shutdown_signal.load(Ordering::Relaxed) {
+                println!("[Monitor] Shutdown signal received. Flushing remaining buffer.");
+                self.shutdown().await;
+                return;
+            }
+            self.tick().await;
+            wait_for_next_tick(self.interval_ms, shutdown_notify).await;
+        }
+    }
+
+    /// Drops everything counted so far: session totals, buffered records,
+    /// burst/peak/smoothing state and the process baselines
+    pub fn reset(&mut self) {
+        self.session_started = unix_now();
+        self.session_read_bytes = 0;
+        self.session_write_bytes = 0;
+        session::publish(
+            &self.handles.session_totals,
+            SessionTotals {
+                start_time: self.session_started,
+                ..Default::default()
+            },
+        );
+        write_coalescer::clear(&self.handles.writes);
+        self.read_bursts.reset();
+        self.write_bursts.reset();
+        self.peak_tracker.reset();
+        self.smoother.reset();
+        self.top_processes_diff.reset();
+        if let Ok(mut tracker) = self.handles.file_io.lock() {
+            tracker.reset();
+        }
+        self.last_flush = Instant::now();
+        self.sampler.reset();
+        self.session_clock.restart_interval();
+        if let Ok(mut volumes) = self.handles.volume_stats.lock() {
+            volumes.clear();
+        }
+        self.last_volume_sample = None;
+    }
+
+    /// Writes everything queued in one transaction (the periodic flush).
+    /// Fails without touching the buffer while the pool is out.
+    pub async fn flush(&mut self) -> Result<usize, String> {
+        let Some(pool) = db::current_pool(&self.handles.shared_pool) else {
+            return Err(CoreError::NotInitialized.to_string());
+        };
+        let store = storage::current(&self.handles.storage);
+        let writes = &self.handles.writes;
+
+        write_coalescer::queue_process_deltas(writes, self.sampler.take_deltas().await);
+        let result =
+            write_coalescer::flush(writes, &pool, store.as_ref(), &self.handles.contention).await;
+        if let Err(e) = &result {
+            eprintln!("[Monitor] DB Error: {}", e);
+        }
+        if let Ok(mut stats) = self.handles.stats.lock() {
+            stats.record_flush(&result, unix_now());
+        }
+
+        // Periodic cleanup - every hour
+        if let Some(store) = store {
+            if self.last_prune.elapsed() >= PRUNE_PERIOD {
+                self.last_prune = Instant::now();
+                let pool_cleanup = pool.clone();
+                tokio::spawn(async move {
+                    let _ = db_cleanup::prune_time_series(&pool_cleanup, store.as_ref()).await;
+                });
+            }
+            self.last_flush = Instant::now();
+        }
+        result
+    }
+
+    /// Final flush: process deltas, a burst still in progress, open timeline
+    /// events and the summary of this session
+    pub async fn shutdown(&mut self) {
+        // Summary of this session for the "last session" view of the next launch
+        let summary = session::summarize(
+            self.session_started,
+            unix_now(),
+            self.session_read_bytes,
+            self.session_write_bytes,
+            &self.sampler.top_processes(None).await,
+        );
+        self.emitter.emit(MonitorEvent::SessionSummary(&summary));
+
+        let Some(pool) = db::current_pool(&self.handles.shared_pool) else {
+            eprintln!("[Monitor] Database unavailable at shutdown, buffered records not saved.");
+            return;
+        };
+        let store = storage::current(&self.handles.storage);
+
+        // Persist process deltas accumulated since the last periodic flush
+        let writes = &self.handles.writes;
+        write_coalescer::queue_process_deltas(writes, self.sampler.take_deltas().await);
+        match write_coalescer::flush(writes, &pool, store.as_ref(), &self.handles.contention).await
+        {
+            Ok(count) => println!("[Monitor] Successfully flushed {} records.", count),
+            Err(e) => eprintln!("[Monitor] Final DB Flush Error: {}", e),
+        }
+        if let Some(store) = &store {
+            if let Err(e) = store.flush().await {
+                eprintln!("[Monitor] Final storage flush error: {}", e);
+            }
+        }
+
+        // Record a burst still in progress
+        let now = unix_now();
+        for burst in [self.read_bursts.finish(now), self.write_bursts.finish(now)]
+            .into_iter()
+            .flatten()
+        {
+            record_burst(&mut self.emitter, &pool, burst).await;
+        }
+
+        // Close any open maintenance period so it doesn't stay unbounded
+        if let Some(id) = self.maintenance_event_id.take() {
+            let _ = db::close_timeline_event(&pool, id, unix_now()).await;
+        }
+        if let Some(id) = self.pause_event_id.take() {
+            let _ = db::close_timeline_event(&pool, id, unix_now()).await;
+        }
+
+        let details = serde_json::to_string(&summary).ok();
+        if let Err(e) = db::insert_completed_timeline_event(
+            &pool,
+            session::SESSION_EVENT_KIND,
+            summary.start_time,
+            summary.end_time,
+            details.as_deref(),
+        )
+        .await
+        {
+            eprintln!("[Monitor] Failed to record session summary: {}", e);
+        }
+    }
+
+    /// One sampling pass: counters, processes, events, live payloads, and
+    /// the periodic flush when it is due
+    pub async fn tick(&mut self) {
+        let tick_started = Instant::now();
+        let handles = &self.handles;
+
+        // Power source: on battery the interval and flush period are stretched
+        if self
+            .last_power_check
+            .is_none_or(|checked| checked.elapsed() >= POWER_CHECK_PERIOD)
+        {
+            self.on_battery = tokio::task::spawn_blocking(power::is_on_battery_safe)
+                .await
+                .unwrap_or(false);
+            self.last_power_check = Some(Instant::now());
+        }
+        let throttle = handles
+            .battery_throttle
+            .lock()
+            .map(|t| *t)
+            .unwrap_or_default();
+        // Low-wear mode keeps samples in memory for minutes between writes
+        let wear = handles.low_wear.lock().map(|m| *m).unwrap_or_default();
+        let flush = handles
+            .flush_settings
+            .lock()
+            .map(|f| *f)
+            .unwrap_or_default();
+        let profile = power::effective_profile(
+            sampling::current_ms(&handles.interval),
+            wear.flush_secs(flush.flush_secs),
+            self.on_battery,
+            &throttle,
+        );
+        if self.power_profile != Some(profile) {
+            if self.power_profile.map_or(profile.reduced, |previous| {
+                previous.reduced != profile.reduced
+            }) {
+                println!(
+                    "[Monitor] Power profile: battery={}, interval {} ms, flush every {} s",
+                    profile.on_battery, profile.interval_ms, profile.flush_secs
+                );
+            }
+            self.emitter.emit(MonitorEvent::PowerProfile(&profile));
+            self.power_profile = Some(profile);
+        }
+
+        // Interval changed (settings or power source): burst durations are counted in ticks
+        let configured_ms = profile.interval_ms;
+        if configured_ms != self.interval_ms {
+            println!(
+                "[Monitor] Sampling interval changed to {} ms",
+                configured_ms
+            );
+            self.interval_ms = configured_ms;
+            self.read_bursts = BurstDetector::new(
+                BurstDirection::Read,
+                BurstConfig::for_interval(configured_ms),
+            );
+            self.write_bursts = BurstDetector::new(
+                BurstDirection::Write,
+                BurstConfig::for_interval(configured_ms),
+            );
+        }
+        let interval_ms = self.interval_ms;
+
+        // The pool is taken out of the shared slot while the data directory is
+        // being migrated; until it is back, records stay buffered in memory.
+        let pool = db::current_pool(&handles.shared_pool);
+
+        // Reset check
+        if handles.reset_signal.load(Ordering::Relaxed) {
+            println!("[Monitor] Reset signal received. Resetting baselines.");
+            self.reset();
+            self.handles.reset_signal.store(false, Ordering::Relaxed);
+        }
+        let handles = &self.handles;
+
+        // Paused: no sampling, but reset and shutdown are still handled
+        if handles.paused.load(Ordering::Relaxed) {
+            if !self.was_paused {
+                println!("[Monitor] Monitoring paused.");
+                self.was_paused = true;
+                let now = unix_now();
+                if let Some(pool) = &pool {
+                    for burst in [self.read_bursts.finish(now), self.write_bursts.finish(now)]
+                        .into_iter()
+                        .flatten()
+                    {
+                        record_burst(&mut self.emitter, pool, burst).await;
+                    }
+                    match db::insert_timeline_event(pool, PAUSE_EVENT_KIND, now, None).await {
+                        Ok(id) => self.pause_event_id = Some(id),
+                        Err(e) => eprintln!("[Monitor] Failed to record pause: {}", e),
+                    }
+                }
+            }
+            // Keep the loop visibly alive for diagnostics
+            if let Ok(mut stats) = self.handles.stats.lock() {
+                stats.last_tick_at = unix_now();
+            }
+            return;
+        }
+        if self.was_paused {
+            println!("[Monitor] Monitoring resumed. Resetting baselines.");
+            self.was_paused = false;
+            // I/O done during the pause is not counted
+            self.sampler.rebaseline();
+            self.session_clock.restart_interval();
+            self.last_volume_sample = None;
+            self.last_sampled = None;
+            self.perf_session = None;
+            self.perf_retry_at = None;
+            self.smoother.reset();
+            if let (Some(id), Some(pool)) = (self.pause_event_id.take(), &pool) {
+                if let Err(e) = db::close_timeline_event(pool, id, unix_now()).await {
+                    eprintln!("[Monitor] Failed to close pause event: {}", e);
+                }
+            }
+        }
+
+        // Sleep/resume: cumulative counters jumped during the gap, so take new
+        // baselines instead of booking everything as this tick's I/O
+        let tick_wall = unix_now();
+        if let Some((last_instant, last_wall)) = self.last_sampled {
+            let elapsed = last_instant
+                .elapsed()
+                .max(Duration::from_secs_f64((tick_wall - last_wall).max(0.0)));
+            if sampling::is_time_gap(elapsed, interval_ms) {
+                println!(
+                    "[Monitor] {:.0} s gap since the last tick (sleep/resume). Resetting baselines.",
+                    elapsed.as_secs_f64()
+                );
+                self.sampler.rebaseline();
+                self.session_clock.restart_interval();
+                self.last_volume_sample = None;
+                self.cached_perf_metrics = DiskPerfMetrics::default();
+                self.perf_session = None;
+                self.perf_retry_at = None;
+                self.smoother.reset();
+                if let Some(pool) = &pool {
+                    for burst in [
+                        self.read_bursts.finish(last_wall),
+                        self.write_bursts.finish(last_wall),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    {
+                        record_burst(&mut self.emitter, pool, burst).await;
+                    }
+                    match db::insert_timeline_event(pool, GAP_EVENT_KIND, last_wall, None).await {
+                        Ok(id) => {
+                            if let Err(e) = db::close_timeline_event(pool, id, tick_wall).await {
+                                eprintln!("[Monitor] Failed to close gap event: {}", e);
+                            }
+                        }
+                        Err(e) => eprintln!("[Monitor] Failed to record gap: {}", e),
+                    }
+                }
+                // Refresh the drive list and per-disk breakdown on this tick
+                self.tick_count = 0;
+            }
+        }
+        self.last_sampled = Some((Instant::now(), tick_wall));
+
+        // Nobody looks at the charts while the window is hidden; the drive
+        // LED keeps going for the tray
+        let live = !self.handles.window_hidden.load(Ordering::Relaxed);
+        if live == self.was_hidden {
+            if live {
+                println!("[Monitor] Window visible. Resuming live updates.");
+                // Listeners missed the diffs sent meanwhile
+                self.top_processes_diff.reset();
+            } else {
+                println!("[Monitor] Window hidden. Suspending live updates.");
+            }
+            self.was_hidden = !live;
+        }
+
+        // 1. Disk performance counters (persistent query, collected every tick)
+        let session = self.perf_session.take();
+        let retry_open = self
+            .perf_retry_at
+            .is_none_or(|at| at.elapsed() >= PERF_SAMPLE_PERIOD);
+        if let Ok((session, sample)) = tokio::task::spawn_blocking(move || match session {
+            Some(mut session) => {
+                let sample = session.collect();
+                (Some(session), Some(sample))
+            }
+            // A new query only takes its baseline; rates follow from the next tick
+            None if retry_open => (PerfCounterSession::open_safe(), None),
+            None => (None, None),
+        })
+        .await
+        {
+            if session.is_none() && retry_open {
+                self.perf_retry_at = Some(Instant::now());
+            }
+            self.perf_session = session;
+            match sample {
+                Some(Ok(sample)) => {
+                    self.cached_perf_metrics = sample.totals;
+
+                    // Per volume speeds and session totals, plus the load of the disk each volume is on
+                    let elapsed_secs = self
+                        .last_volume_sample
+                        .map(|t| t.elapsed().as_secs_f64())
+                        .unwrap_or(0.0);
+                    self.last_volume_sample = Some(Instant::now());
+                    if let Ok(mut guard) = self.handles.volume_stats.lock() {
+                        volume_monitor::apply_sample(&mut guard, &sample.volumes, elapsed_secs);
+                        volume_monitor::apply_disk_load(&mut guard, &sample.disks);
+                    }
+                    self.latest_disks = sample.disks;
+                }
+                Some(Err(e)) => {
+                    eprintln!(
+                        "[PerfCounters] Collection failed: {}. Reopening counters.",
+                        e
+                    );
+                    self.perf_session = None;
+                    self.cached_perf_metrics = DiskPerfMetrics::default();
+                }
+                None => {}
+            }
+        }
+
+        // Drive hotplug and the per-disk breakdown (every 5 seconds)
+        let writes = &self.handles.writes;
+        if self
+            .tick_count
+            .is_multiple_of(sampling::ticks_per(PERF_SAMPLE_PERIOD, interval_ms))
+        {
+            if let Ok(mounted) =
+                tokio::task::spawn_blocking(volume_monitor::list_mounted_volumes).await
+            {
+                let changes = self.drive_watcher.update(mounted);
+                volume_monitor::apply_drive_changes(&self.handles.volume_stats, &changes);
+                for volume in &changes.added {
+                    println!("[Monitor] Drive added: {}", volume.volume);
+                    self.emitter.emit(MonitorEvent::DriveAdded(volume));
+                }
+                for volume in &changes.removed {
+                    println!("[Monitor] Drive removed: {}", volume.volume);
+                    self.emitter.emit(MonitorEvent::DriveRemoved(volume));
+                }
+            }
+
+            // Per physical disk breakdown
+            if !self.latest_disks.is_empty() {
+                let timestamp = unix_now();
+                let breakdown: Vec<PhysicalDiskStat> = self
+                    .latest_disks
+                    .iter()
+                    .map(|d| PhysicalDiskStat {
+                        timestamp,
+                        disk: d.instance.clone(),
+                        read_speed: d.read_bytes_per_sec.max(0.0) as u64,
+                        write_speed: d.write_bytes_per_sec.max(0.0) as u64,
+                        idle_time: d.idle_time,
+                        queue_depth: d.queue_depth,
+                    })
+                    .collect();
+
+                if live && self.emitter.wants(MetricStream::DiskBreakdown) {
+                    if let Err(e) = self.emitter.publish(LivePayload::DiskBreakdown(&breakdown)) {
+                        eprintln!("[Monitor] Failed to emit disk-breakdown: {}", e);
+                    }
+                }
+                write_coalescer::queue_physical_disk_stats(writes, breakdown);
+            }
+        }
+
+        // Drive temperatures (SMART/NVMe) for correlating throttling with heavy I/O
+        if self
+            .tick_count
+            .is_multiple_of(sampling::ticks_per(TEMPERATURE_SAMPLE_PERIOD, interval_ms))
+        {
+            let timestamp = unix_now();
+            if let Ok(samples) =
+                tokio::task::spawn_blocking(move || smart::sample_temperatures(timestamp)).await
+            {
+                write_coalescer::queue_temperatures(writes, samples.clone());
+                self.latest_temperatures = samples;
+            }
+        }
+
+        // 2. Update processes and get deltas (scanned on the sampler thread)
+        let maintenance_tick = self
+            .tick_count
+            .is_multiple_of(sampling::ticks_per(PERF_SAMPLE_PERIOD, interval_ms));
+        let request = TickRequest {
+            top_processes: live
+                && [MetricStream::TopProcesses, MetricStream::TopProcessesDiff]
+                    .into_iter()
+                    .any(|stream| self.emitter.wants(stream)),
+            running: maintenance_tick.then_some(maintenance::DEFRAG_PROCESS_NAMES),
+        };
+        let mut processes = match self.sampler.sample(request).await {
+            Some(processes) => processes,
+            None => {
+                eprintln!("[Monitor] Process sampler thread stopped.");
+                ProcessTick::default()
+            }
+        };
+        let (tick_read_delta, tick_write_delta) = (processes.read_delta, processes.write_delta);
+
+        // Queue exited processes with the next flush so their attribution survives a restart
+        write_coalescer::queue_process_deltas(writes, std::mem::take(&mut processes.exited));
+        write_coalescer::queue_command_lines(writes, std::mem::take(&mut processes.command_lines));
+
+        // Update session totals
+        self.session_read_bytes = self.session_read_bytes.saturating_add(tick_read_delta);
+        self.session_write_bytes = self.session_write_bytes.saturating_add(tick_write_delta);
+        session::publish(
+            &self.handles.session_totals,
+            SessionTotals {
+                start_time: self.session_started,
+                read_bytes: self.session_read_bytes,
+                write_bytes: self.session_write_bytes,
+            },
+        );
+
+        // Speeds use the monotonic time the deltas actually cover, so neither
+        // a late tick nor a system clock change skews them
+        let reading = self.session_clock.read();
+        if let Some(step) = reading.clock_step {
+            println!("[Monitor] System clock changed by {:+.1} s", step);
+        }
+        let now = reading.timestamp;
+        let elapsed = reading
+            .elapsed
+            .unwrap_or(Duration::from_millis(interval_ms));
+        // Coverage counts the time this tick accounts for (at most one long interval)
+        let covered = elapsed
+            .as_secs_f64()
+            .min(sampling::MAX_INTERVAL_MS as f64 / 1000.0);
+        write_coalescer::queue_coverage(writes, now - covered, now);
+        let read_speed = sampling::per_second_over(tick_read_delta, elapsed);
+        let write_speed = sampling::per_second_over(tick_write_delta, elapsed);
+
+        // All-time peaks (stored peaks are loaded once the database is up)
+        if !self.peak_tracker.is_loaded() {
+            if let Some(pool) = &pool {
+                match db::get_speed_peaks(pool).await {
+                    Ok(peaks) => self.peak_tracker.load(peaks),
+                    Err(e) => eprintln!("[Monitor] Failed to load peaks: {}", e),
+                }
+            }
+        }
+        let tick_processes = &processes.processes;
+        let new_peaks = self
+            .peak_tracker
+            .observe(read_speed, write_speed, now, tick_processes);
+        if !new_peaks.is_empty() {
+            write_coalescer::queue_peaks(&self.handles.writes, new_peaks);
+        }
+
+        // Sustained I/O bursts, with the processes behind them
+        let finished_bursts = [
+            self.read_bursts
+                .observe(read_speed, tick_read_delta, now, tick_processes),
+            self.write_bursts
+                .observe(write_speed, tick_write_delta, now, tick_processes),
+        ];
+        if let Some(pool) = &pool {
+            for burst in finished_bursts.into_iter().flatten() {
+                record_burst(&mut self.emitter, pool, burst).await;
+            }
+        }
+
+        // 3. Drive optimization detection (same cadence as perf counters)
+        if maintenance_tick {
+            let running = processes.running.unwrap_or(false)
+                || tokio::task::spawn_blocking(maintenance::is_defrag_service_running)
+                    .await
+                    .unwrap_or(false);
+
+            match (self.maintenance_detector.observe(running, now), &pool) {
+                (Some(MaintenanceTransition::Started { start_time }), Some(pool)) => {
+                    println!("[Monitor] Drive optimization started.");
+                    match db::insert_timeline_event(
+                        pool,
+                        maintenance::DEFRAG_EVENT_KIND,
+                        start_time,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok(id) => {
+                            self.maintenance_event_id = Some(id);
+                            let event = TimelineEvent {
+                                id,
+                                kind: maintenance::DEFRAG_EVENT_KIND.to_string(),
+                                start_time,
+                                end_time: None,
+                                details: None,
+                            };
+                            self.emitter.emit(MonitorEvent::MaintenanceActivity(&event));
+                        }
+                        Err(e) => {
+                            eprintln!("[Monitor] Failed to record maintenance event: {}", e)
+                        }
+                    }
+                }
+                (
+                    Some(MaintenanceTransition::Ended {
+                        start_time,
+                        end_time,
+                    }),
+                    Some(pool),
+                ) => {
+                    println!("[Monitor] Drive optimization finished.");
+                    if let Some(id) = self.maintenance_event_id.take() {
+                        if let Err(e) = db::close_timeline_event(pool, id, end_time).await {
+                            eprintln!("[Monitor] Failed to close maintenance event: {}", e);
+                        }
+                        let event = TimelineEvent {
+                            id,
+                            kind: maintenance::DEFRAG_EVENT_KIND.to_string(),
+                            start_time,
+                            end_time: Some(end_time),
+                            details: None,
+                        };
+                        self.emitter.emit(MonitorEvent::MaintenanceActivity(&event));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let perf = &self.cached_perf_metrics;
+        let stat = DiskStat {
+            timestamp: now,
+            read_bytes: self.session_read_bytes,
+            write_bytes: self.session_write_bytes,
+            read_speed,
+            write_speed,
+            idle_time: perf.idle_time,
+            queue_depth: perf.queue_depth,
+            read_iops: perf.read_iops,
+            write_iops: perf.write_iops,
+            read_latency_ms: perf.read_latency * 1000.0,
+            write_latency_ms: perf.write_latency * 1000.0,
+            monotonic_offset: reading.offset,
+        };
+
+        // Dashboard metrics with the latest drive temperatures
+        if live && self.emitter.wants(MetricStream::DiskMetrics) {
+            let event = DiskMetricsEvent {
+                stat: &stat,
+                temperatures: &self.latest_temperatures,
+            };
+            if let Err(e) = self.emitter.publish(LivePayload::DiskMetrics(event)) {
+                eprintln!("[Monitor] Failed to emit disk-metrics: {}", e);
+            }
+        }
+
+        // Smoothed speeds (computed every tick so the averages stay continuous)
+        let smoothed = self.smoother.observe(read_speed, write_speed, now);
+        if live && self.emitter.wants(MetricStream::SmoothedSpeeds) {
+            if let Err(e) = self.emitter.publish(LivePayload::SmoothedSpeeds(&smoothed)) {
+                eprintln!("[Monitor] Failed to emit smoothed-speeds: {}", e);
+            }
+        }
+
+        // Drive LED (only state changes, rate limited for the widget/tray)
+        if self.emitter.wants(MetricStream::DriveActivity) {
+            let state = activity::classify(tick_read_delta, tick_write_delta);
+            if let Some(state) = self.activity_led.observe(
+                state,
+                Instant::now(),
+                activity::min_interval(&self.handles.led_rate),
+            ) {
+                if let Err(e) = self.emitter.publish(LivePayload::DriveActivity(state)) {
+                    eprintln!("[Monitor] Failed to emit drive-activity: {}", e);
+                }
+            }
+        }
+
+        // Emit Top Processes (Every tick, only while a view displays them).
+        // `top-processes` carries the whole list; `top-processes-diff`
+        // listeners get changed entries only
+        self.tick_count += 1;
+        if let Some(process_stats) = &processes.top_processes {
+            if let Err(e) = self
+                .emitter
+                .publish(LivePayload::TopProcesses(process_stats))
+            {
+                eprintln!("[Monitor] Failed to emit top-processes: {}", e);
+            }
+
+            if self.emitter.wants(MetricStream::TopProcessesDiff) {
+                let generation = self.emitter.generation();
+                let frame = self
+                    .top_processes_diff
+                    .observe(process_stats, generation != self.emitter_generation);
+                self.emitter_generation = generation;
+                if let Some(frame) = &frame {
+                    if let Err(e) = self.emitter.publish(LivePayload::TopProcessesDiff(frame)) {
+                        eprintln!("[Monitor] Failed to emit top-processes-diff: {}", e);
+                    }
+                }
+            } else {
+                // The next listener starts from a full snapshot
+                self.top_processes_diff.reset();
+            }
+        }
+
+        // Emit Top Files (only while file tracing has data and a view displays them)
+        if live && self.emitter.wants(MetricStream::TopFiles) {
+            let file_io = &self.handles.file_io;
+            let has_files = file_io
+                .lock()
+                .map(|tracker| !tracker.is_empty())
+                .unwrap_or(false);
+            if has_files {
+                let names = self.sampler.process_names().await;
+                let top_files = file_io
+                    .lock()
+                    .map(|tracker| tracker.top_files(file_io::TOP_FILES_LIMIT, &names))
+                    .unwrap_or_default();
+                if let Err(e) = self.emitter.publish(LivePayload::TopFiles(&top_files)) {
+                    eprintln!("[Monitor] Failed to emit top-files: {}", e);
+                }
+            }
+        }
+
+        // Unified Flush - Every flush period (10 s by default, longer on battery), one transaction for everything queued
+        write_coalescer::queue_disk_stat(&self.handles.writes, stat);
+        let pending_limit = wear.pending_limit(
+            interval_ms,
+            profile.flush_secs,
+            flush.buffer_limit(interval_ms, profile.flush_secs),
+        );
+        if write_coalescer::pending_disk_stats(&self.handles.writes) >= pending_limit
+            || self.last_flush.elapsed() >= Duration::from_secs(profile.flush_secs)
+        {
+            // Without a pool the records stay buffered until it is back
+            let _ = self.flush().await;
+        }
+
+        if let Ok(mut stats) = self.handles.stats.lock() {
+            stats.record_tick(tick_started.elapsed().as_secs_f64() * 1000.0, unix_now());
+            stats.process_maps = processes.map_sizes;
+            stats.buffered_records = write_coalescer::pending_rows(&self.handles.writes);
+            stats.process_counters_available = processes.counters_available;
+        }
+    }
+}
+
+/// Sleeps one interval, or less when shutdown is requested
+async fn wait_for_next_tick(interval_ms: u64, shutdown_notify: &Notify) {
+    tokio::select! {
+        _ = sleep(Duration::from_millis(interval_ms)) => {}
+        _ = shutdown_notify.notified() => {
+            println!("[Monitor] Notification received. Waking up.");
+        }
+    }
+}
+
+/// Stores a finished burst as a timeline event and notifies the UI
+async fn record_burst(emitter: &mut impl MonitorEmitter, pool: &Pool<Sqlite>, burst: IoBurst) {
+    println!(
+        "[Monitor] {} burst: peak {} B/s over {:.0}s",
+        burst.direction,
+        burst.peak_speed,
+        burst.end_time - burst.start_time
+    );
+    let details = serde_json::to_string(&burst).ok();
+    match db::insert_completed_timeline_event(
+        pool,
+        burst::BURST_EVENT_KIND,
+        burst.start_time,
+        burst.end_time,
+        details.as_deref(),
+    )
+    .await
+    {
+        Ok(id) => {
+            let event = TimelineEvent {
+                id,
+                kind: burst::BURST_EVENT_KIND.to_string(),
+                start_time: burst.start_time,
+                end_time: Some(burst.end_time),
+                details,
+            };
+            emitter.emit(MonitorEvent::IoBurst(&event));
+        }
+        Err(e) => eprintln!("[Monitor] Failed to record burst: {}", e),
+    }
+}
+
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_tick_tracks_average_and_max() {
+        let mut stats = MonitorStats::default();
+        stats.record_tick(10.0, 1.0);
+        assert_eq!(stats.avg_tick_ms, 10.0);
+
+        stats.record_tick(110.0, 2.0);
+        assert_eq!(stats.tick_count, 2);
+        assert_eq!(stats.max_tick_ms, 110.0);
+        assert!((stats.avg_tick_ms - 20.0).abs() < 1e-9);
+        assert_eq!(stats.last_tick_at, 2.0);
+    }
+
+    #[test]
+    fn test_record_flush_keeps_last_success() {
+        let mut stats = MonitorStats::default();
+        stats.record_flush(&Ok(3), 5.0);
+        stats.record_flush(&Err("disk full".to_string()), 6.0);
+        assert_eq!(stats.last_flush_at, Some(5.0));
+        assert_eq!(stats.last_flush_error.as_deref(), Some("disk full"));
+
+        stats.record_flush(&Ok(0), 7.0);
+        assert_eq!(stats.last_flush_at, Some(7.0));
+        assert!(stats.last_flush_error.is_none());
+    }
+
+    #[test]
+    fn test_live_payloads_encode_as_their_content() {
+        let processes = vec![ProcessIOStat {
+            name: "copy.exe".to_string(),
+            write_bytes: 300,
+            ..Default::default()
+        }];
+        let mut encoder = crate::payload::PayloadEncoder::new();
+        let payload = LivePayload::TopProcesses(&processes);
+        assert_eq!(payload.stream(), MetricStream::TopProcesses);
+        let frame = encoder.msgpack(&payload).unwrap().to_vec();
+        assert_eq!(encoder.msgpack(&processes).unwrap(), frame.as_slice());
+
+        let activity = LivePayload::DriveActivity(Activity::Write);
+        assert_eq!(serde_json::to_string(&activity).unwrap(), "\"write\"");
+    }
+}
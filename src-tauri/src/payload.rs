@@ -0,0 +1,181 @@
+//! Encoding of the high-frequency live payloads (disk-metrics, top-processes, ...)
+//!
+//! Payloads are serialized once into buffers that are reused every tick.
+//! JSON goes out through `emit_str`, so Tauri doesn't serialize it again.
+//! With MessagePack the latest frame of each stream is kept instead. The
+//! frontend pulls it as raw bytes with `get_live_frame` and decodes it with
+//! `src/utils/msgpack.ts`.
+
+use crate::db;
+use crate::subscriptions::MetricStream;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// app_settings key holding the live payload encoding
+pub const PAYLOAD_ENCODING_KEY: &str = "payload_encoding";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadEncoding {
+    /// Window events with JSON payloads (what older frontends listen to)
+    #[default]
+    Json,
+    /// Binary frames fetched with `get_live_frame`
+    MessagePack,
+}
+
+impl PayloadEncoding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PayloadEncoding::Json => "json",
+            PayloadEncoding::MessagePack => "messagepack",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "json" => Some(PayloadEncoding::Json),
+            "messagepack" => Some(PayloadEncoding::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+/// Selected encoding plus the latest MessagePack frame per stream
+#[derive(Debug, Default)]
+pub struct LiveFrames {
+    pub encoding: PayloadEncoding,
+    frames: HashMap<MetricStream, Vec<u8>>,
+}
+
+pub type SharedLiveFrames = Arc<Mutex<LiveFrames>>;
+
+pub fn create_live_frames() -> SharedLiveFrames {
+    Arc::new(Mutex::new(LiveFrames::default()))
+}
+
+pub fn encoding(frames: &SharedLiveFrames) -> PayloadEncoding {
+    frames.lock().map(|f| f.encoding).unwrap_or_default()
+}
+
+pub fn set_encoding(frames: &SharedLiveFrames, encoding: PayloadEncoding) {
+    if let Ok(mut frames) = frames.lock() {
+        frames.encoding = encoding;
+        frames.frames.clear();
+    }
+}
+
+/// Copy of the latest frame of a stream (None before the first tick)
+pub fn latest_frame(frames: &SharedLiveFrames, stream: MetricStream) -> Option<Vec<u8>> {
+    frames.lock().ok()?.frames.get(&stream).cloned()
+}
+
+/// Serialization buffers reused across ticks
+#[derive(Debug, Default)]
+pub struct PayloadEncoder {
+    json: Vec<u8>,
+    msgpack: Vec<u8>,
+}
+
+impl PayloadEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// JSON text of `value`; the returned String is allocated at its exact size
+    pub fn json<T: Serialize>(&mut self, value: &T) -> Result<String, String> {
+        self.json.clear();
+        serde_json::to_writer(&mut self.json, value).map_err(|e| e.to_string())?;
+        std::str::from_utf8(&self.json)
+            .map(str::to_owned)
+            .map_err(|e| e.to_string())
+    }
+
+    /// MessagePack encoding of `value` with structs as maps (field names kept)
+    pub fn msgpack<T: Serialize>(&mut self, value: &T) -> Result<&[u8], String> {
+        self.msgpack.clear();
+        value
+            .serialize(&mut rmp_serde::Serializer::new(&mut self.msgpack).with_struct_map())
+            .map_err(|e| e.to_string())?;
+        Ok(&self.msgpack)
+    }
+}
+
+/// Sends one payload of `stream` in the selected encoding
+pub fn publish<T: Serialize>(
+    app: &AppHandle,
+    frames: &SharedLiveFrames,
+    encoder: &mut PayloadEncoder,
+    stream: MetricStream,
+    payload: &T,
+) -> Result<(), String> {
+    match encoding(frames) {
+        PayloadEncoding::Json => {
+            let json = encoder.json(payload)?;
+            app.emit_str(stream.event_name(), json)
+                .map_err(|e| e.to_string())
+        }
+        PayloadEncoding::MessagePack => {
+            let bytes = encoder.msgpack(payload)?;
+            let mut frames = frames.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let frame = frames.frames.entry(stream).or_default();
+            frame.clear();
+            frame.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+}
+
+pub async fn load_encoding(pool: &Pool<Sqlite>) -> Result<PayloadEncoding, sqlx::Error> {
+    Ok(db::get_setting(pool, PAYLOAD_ENCODING_KEY)
+        .await?
+        .and_then(|value| PayloadEncoding::parse(&value))
+        .unwrap_or_default())
+}
+
+pub async fn save_encoding(
+    pool: &Pool<Sqlite>,
+    encoding: PayloadEncoding,
+) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, PAYLOAD_ENCODING_KEY, encoding.as_str()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProcessIOStat;
+
+    fn stats() -> Vec<ProcessIOStat> {
+        vec![ProcessIOStat {
+            name: "copy.exe".to_string(),
+            read_bytes: 1,
+            write_bytes: 300,
+            total_bytes: 301,
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_json_matches_serde_json() {
+        let mut encoder = PayloadEncoder::new();
+        let first = encoder.json(&stats()).unwrap();
+        assert_eq!(first, serde_json::to_string(&stats()).unwrap());
+        // The buffer is reused, not appended to
+        assert_eq!(encoder.json(&stats()).unwrap(), first);
+    }
+
+    #[test]
+    fn test_msgpack_keeps_field_names() {
+        let mut encoder = PayloadEncoder::new();
+        let bytes = encoder.msgpack(&stats()).unwrap().to_vec();
+        // fixarray(1), then a fixmap with one entry per field starting with "pid"
+        let fields = serde_json::to_value(&stats()[0]).unwrap();
+        let fields = fields.as_object().unwrap().len() as u8;
+        assert_eq!(&bytes[..3], &[0x91, 0x80 | fields, 0xa3]);
+        assert_eq!(&bytes[3..6], b"pid");
+        assert_eq!(encoder.msgpack(&stats()).unwrap(), bytes.as_slice());
+    }
+}
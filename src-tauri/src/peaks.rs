@@ -0,0 +1,108 @@
+use crate::models::SpeedPeak;
+use crate::process_monitor::TickDeltas;
+
+/// Keeps the all-time read/write peaks in memory so only new records are written
+///
+/// The stored peaks are loaded once the database is available; until then
+/// nothing is reported (the upsert would keep the higher value anyway).
+#[derive(Debug, Default)]
+pub struct PeakTracker {
+    read: Option<SpeedPeak>,
+    write: Option<SpeedPeak>,
+    loaded: bool,
+}
+
+impl PeakTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn load(&mut self, peaks: Vec<SpeedPeak>) {
+        for peak in peaks {
+            match peak.direction.as_str() {
+                "read" => self.read = Some(peak),
+                "write" => self.write = Some(peak),
+                _ => {}
+            }
+        }
+        self.loaded = true;
+    }
+
+    /// Forgets everything (database reset); peaks are reloaded on the next tick
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Feeds one tick; returns the peaks it beat
+    pub fn observe(
+        &mut self,
+        read_speed: u64,
+        write_speed: u64,
+        now: f64,
+        process_deltas: &TickDeltas,
+    ) -> Vec<SpeedPeak> {
+        if !self.loaded {
+            return Vec::new();
+        }
+
+        let mut records = Vec::new();
+        for (direction, speed, current) in [
+            ("read", read_speed, &mut self.read),
+            ("write", write_speed, &mut self.write),
+        ] {
+            if speed == 0 || current.as_ref().is_some_and(|peak| peak.speed >= speed) {
+                continue;
+            }
+            let process = process_deltas
+                .iter()
+                .map(|(name, (read, write))| {
+                    (name, if direction == "read" { *read } else { *write })
+                })
+                .filter(|(_, bytes)| *bytes > 0)
+                .max_by_key(|(_, bytes)| *bytes)
+                .map(|(name, _)| name.to_string());
+
+            let peak = SpeedPeak {
+                direction: direction.to_string(),
+                speed,
+                timestamp: now,
+                process,
+            };
+            *current = Some(peak.clone());
+            records.push(peak);
+        }
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_new_records_are_reported() {
+        let mut tracker = PeakTracker::new();
+        let deltas =
+            TickDeltas::from([("copy.exe".into(), (10, 900)), ("av.exe".into(), (80, 100))]);
+        assert!(tracker.observe(100, 100, 1.0, &deltas).is_empty());
+
+        tracker.load(vec![SpeedPeak {
+            direction: "write".to_string(),
+            speed: 500,
+            timestamp: 0.0,
+            process: None,
+        }]);
+        let records = tracker.observe(90, 400, 2.0, &deltas);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].direction, "read");
+        assert_eq!(records[0].process.as_deref(), Some("av.exe"));
+
+        let records = tracker.observe(50, 1000, 3.0, &deltas);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].process.as_deref(), Some("copy.exe"));
+    }
+}
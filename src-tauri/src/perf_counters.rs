@@ -2,118 +2,395 @@
 // PowerShell subprocess overhead'ini ortadan kaldırır
 // Fallback: PowerShell veya varsayılan değerler
 
+/// Tüm diskler (_Total) için performans metrikleri
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiskPerfMetrics {
+    pub idle_time: f64,
+    pub queue_depth: f64,
+    /// Saniyedeki okuma işlemi sayısı (IOPS)
+    pub read_iops: f64,
+    /// Saniyedeki yazma işlemi sayısı (IOPS)
+    pub write_iops: f64,
+    /// Ortalama okuma gecikmesi (saniye)
+    pub read_latency: f64,
+    /// Ortalama yazma gecikmesi (saniye)
+    pub write_latency: f64,
+}
+
+impl Default for DiskPerfMetrics {
+    /// Varsayılan: %100 idle, 0 queue, 0 IOPS, 0 gecikme
+    fn default() -> Self {
+        Self {
+            idle_time: 100.0,
+            queue_depth: 0.0,
+            read_iops: 0.0,
+            write_iops: 0.0,
+            read_latency: 0.0,
+            write_latency: 0.0,
+        }
+    }
+}
+
+/// Tek bir fiziksel diskin performans metrikleri
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicalDiskMetrics {
+    /// PDH instance adı (örn. "0 C: D:")
+    pub instance: String,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub idle_time: f64,
+    pub queue_depth: f64,
+}
+
+impl PhysicalDiskMetrics {
+    /// Instance adındaki sürücü harfleri ("0 C: D:" -> ["C:", "D:"])
+    pub fn volumes(&self) -> impl Iterator<Item = &str> {
+        self.instance
+            .split_whitespace()
+            .skip(1)
+            .filter(|part| part.ends_with(':'))
+    }
+}
+
+/// Tek bir mantıksal birimin (sürücü harfi) performans metrikleri
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicalDiskMetrics {
+    /// PDH instance adı (örn. "C:" veya "HarddiskVolume1")
+    pub volume: String,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+}
+
+/// Tek bir toplama sonucu: toplam, disk bazlı ve birim bazlı değerler
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerfSample {
+    pub totals: DiskPerfMetrics,
+    pub disks: Vec<PhysicalDiskMetrics>,
+    pub volumes: Vec<LogicalDiskMetrics>,
+}
+
 #[cfg(windows)]
 mod windows_impl {
+    use super::{DiskPerfMetrics, LogicalDiskMetrics, PerfSample, PhysicalDiskMetrics};
+    use std::collections::BTreeMap;
     use windows::core::PCWSTR;
     use windows::Win32::System::Performance::*;
 
-    /// Disk performans metriklerini Windows PDH API ile al
-    pub fn get_disk_perf_metrics() -> Result<(f64, f64), String> {
-        unsafe {
-            // Query handle oluştur
-            let mut query_handle: isize = 0;
-            let status = PdhOpenQueryW(PCWSTR::null(), 0, &mut query_handle);
-            if status != 0 {
-                return Err(format!("PdhOpenQueryW failed: {}", status));
+    const TOTAL_PATHS: [&str; 6] = [
+        "\\PhysicalDisk(_Total)\\% Idle Time",
+        "\\PhysicalDisk(_Total)\\Avg. Disk Queue Length",
+        "\\PhysicalDisk(_Total)\\Disk Reads/sec",
+        "\\PhysicalDisk(_Total)\\Disk Writes/sec",
+        "\\PhysicalDisk(_Total)\\Avg. Disk sec/Read",
+        "\\PhysicalDisk(_Total)\\Avg. Disk sec/Write",
+    ];
+
+    const PHYSICAL_PATHS: [&str; 4] = [
+        "\\PhysicalDisk(*)\\Disk Read Bytes/sec",
+        "\\PhysicalDisk(*)\\Disk Write Bytes/sec",
+        "\\PhysicalDisk(*)\\% Idle Time",
+        "\\PhysicalDisk(*)\\Avg. Disk Queue Length",
+    ];
+
+    const LOGICAL_PATHS: [&str; 2] = [
+        "\\LogicalDisk(*)\\Disk Read Bytes/sec",
+        "\\LogicalDisk(*)\\Disk Write Bytes/sec",
+    ];
+
+    /// Açık kalan PDH query'si. Counter'lar bir kez eklenir; her `collect`
+    /// bir önceki toplamadan bu yana geçen süreyi ölçer, bu yüzden bekleme gerekmez.
+    /// PDH handle'ları thread'e bağlı değildir (spawn_blocking ile taşınabilir).
+    #[derive(Debug)]
+    pub struct PerfCounterSession {
+        query: isize,
+        totals: Vec<isize>,
+        physical: Vec<isize>,
+        logical: Vec<isize>,
+    }
+
+    impl PerfCounterSession {
+        /// Query'yi aç, counter'ları ekle ve ilk (baseline) örneği al.
+        /// PdhAddEnglishCounterW kullanarak her dilde çalışmasını sağla
+        pub fn open() -> Result<Self, String> {
+            unsafe {
+                let mut query: isize = 0;
+                let status = PdhOpenQueryW(PCWSTR::null(), 0, &mut query);
+                if status != 0 {
+                    return Err(format!("PdhOpenQueryW failed: {}", status));
+                }
+
+                // Drop hata durumunda query'yi kapatır
+                let mut session = Self {
+                    query,
+                    totals: Vec::new(),
+                    physical: Vec::new(),
+                    logical: Vec::new(),
+                };
+                session.totals = session.add_counters(&TOTAL_PATHS)?;
+                session.physical = session.add_counters(&PHYSICAL_PATHS)?;
+                session.logical = session.add_counters(&LOGICAL_PATHS)?;
+
+                // İlk sorgu (baseline) - rate counter'ları bir sonraki toplamadan itibaren geçerli
+                let status = PdhCollectQueryData(session.query);
+                if status != 0 {
+                    return Err(format!("PdhCollectQueryData (baseline) failed: {}", status));
+                }
+                Ok(session)
             }
+        }
 
-            // Counter path'leri
-            let idle_path: Vec<u16> = "\\PhysicalDisk(_Total)\\% Idle Time\0"
-                .encode_utf16()
-                .collect();
-            let queue_path: Vec<u16> = "\\PhysicalDisk(_Total)\\Avg. Disk Queue Length\0"
-                .encode_utf16()
-                .collect();
-
-            // Counter'ları ekle (PdhAddEnglishCounterW kullanarak her dilde çalışmasını sağla)
-            let mut idle_counter: isize = 0;
-            let mut queue_counter: isize = 0;
-
-            let status = PdhAddEnglishCounterW(
-                query_handle,
-                PCWSTR::from_raw(idle_path.as_ptr()),
-                0,
-                &mut idle_counter,
-            );
-            if status != 0 {
-                PdhCloseQuery(query_handle);
-                return Err(format!("PdhAddEnglishCounterW (idle) failed: {}", status));
+        unsafe fn add_counters(&self, paths: &[&str]) -> Result<Vec<isize>, String> {
+            let mut counters: Vec<isize> = Vec::with_capacity(paths.len());
+            for path in paths {
+                let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+                let mut counter: isize = 0;
+                let status = PdhAddEnglishCounterW(
+                    self.query,
+                    PCWSTR::from_raw(wide.as_ptr()),
+                    0,
+                    &mut counter,
+                );
+                if status != 0 {
+                    return Err(format!(
+                        "PdhAddEnglishCounterW ({}) failed: {}",
+                        path, status
+                    ));
+                }
+                counters.push(counter);
             }
+            Ok(counters)
+        }
+
+        /// Tek bir PdhCollectQueryData ile tüm değerleri oku
+        pub fn collect(&mut self) -> Result<PerfSample, String> {
+            unsafe {
+                let status = PdhCollectQueryData(self.query);
+                if status != 0 {
+                    return Err(format!("PdhCollectQueryData failed: {}", status));
+                }
+
+                let defaults = DiskPerfMetrics::default();
+                let value = |index: usize| read_counter_value(self.totals[index]);
+                let totals = DiskPerfMetrics {
+                    idle_time: value(0).unwrap_or(defaults.idle_time),
+                    queue_depth: value(1).unwrap_or(defaults.queue_depth),
+                    read_iops: value(2).unwrap_or(defaults.read_iops),
+                    write_iops: value(3).unwrap_or(defaults.write_iops),
+                    read_latency: value(4).unwrap_or(defaults.read_latency),
+                    write_latency: value(5).unwrap_or(defaults.write_latency),
+                };
+
+                let disks = read_instances(&self.physical)
+                    .into_iter()
+                    .map(|(instance, values)| PhysicalDiskMetrics {
+                        instance,
+                        read_bytes_per_sec: values[0].unwrap_or(0.0),
+                        write_bytes_per_sec: values[1].unwrap_or(0.0),
+                        idle_time: values[2].unwrap_or(100.0),
+                        queue_depth: values[3].unwrap_or(0.0),
+                    })
+                    .collect();
+
+                let volumes = read_instances(&self.logical)
+                    .into_iter()
+                    .map(|(volume, values)| LogicalDiskMetrics {
+                        volume,
+                        read_bytes_per_sec: values[0].unwrap_or(0.0),
+                        write_bytes_per_sec: values[1].unwrap_or(0.0),
+                    })
+                    .collect();
 
-            let status = PdhAddEnglishCounterW(
-                query_handle,
-                PCWSTR::from_raw(queue_path.as_ptr()),
-                0,
-                &mut queue_counter,
-            );
-            if status != 0 {
-                PdhCloseQuery(query_handle);
-                return Err(format!("PdhAddEnglishCounterW (queue) failed: {}", status));
+                Ok(PerfSample {
+                    totals,
+                    disks,
+                    volumes,
+                })
             }
+        }
+    }
 
-            // İlk sorgu (baseline için)
-            let status = PdhCollectQueryData(query_handle);
-            if status != 0 {
-                PdhCloseQuery(query_handle);
-                return Err(format!("PdhCollectQueryData (1) failed: {}", status));
+    impl Drop for PerfCounterSession {
+        fn drop(&mut self) {
+            // Temizlik
+            unsafe {
+                PdhCloseQuery(self.query);
             }
+        }
+    }
 
-            // Kısa bekleme (100ms) - performans counter'ları için gerekli
-            std::thread::sleep(std::time::Duration::from_millis(100));
+    /// Tek instance'lı counter değerini oku
+    unsafe fn read_counter_value(counter: isize) -> Option<f64> {
+        let mut value = PDH_FMT_COUNTERVALUE::default();
+        let status = PdhGetFormattedCounterValue(counter, PDH_FMT_DOUBLE, None, &mut value);
+        if status == 0 {
+            Some(value.Anonymous.doubleValue)
+        } else {
+            None
+        }
+    }
 
-            // İkinci sorgu (gerçek değerler)
-            let status = PdhCollectQueryData(query_handle);
-            if status != 0 {
-                PdhCloseQuery(query_handle);
-                return Err(format!("PdhCollectQueryData (2) failed: {}", status));
-            }
+    /// Wildcard counter'ın tüm instance değerlerini oku
+    unsafe fn read_counter_array(counter: isize) -> Vec<(String, f64)> {
+        let mut buffer_size: u32 = 0;
+        let mut item_count: u32 = 0;
 
-            // Değerleri al
-            let mut idle_value = PDH_FMT_COUNTERVALUE::default();
-            let mut queue_value = PDH_FMT_COUNTERVALUE::default();
-
-            let status =
-                PdhGetFormattedCounterValue(idle_counter, PDH_FMT_DOUBLE, None, &mut idle_value);
-            let idle_time = if status == 0 {
-                idle_value.Anonymous.doubleValue
-            } else {
-                0.0
-            };
-
-            let status =
-                PdhGetFormattedCounterValue(queue_counter, PDH_FMT_DOUBLE, None, &mut queue_value);
-            let queue_depth = if status == 0 {
-                queue_value.Anonymous.doubleValue
-            } else {
-                0.0
-            };
+        // İlk çağrı gerekli buffer boyutunu döndürür
+        let status = PdhGetFormattedCounterArrayW(
+            counter,
+            PDH_FMT_DOUBLE,
+            &mut buffer_size,
+            &mut item_count,
+            None,
+        );
+        if status != PDH_MORE_DATA || buffer_size == 0 {
+            return Vec::new();
+        }
 
-            // Temizlik
-            PdhCloseQuery(query_handle);
+        // Instance isimleri aynı buffer'ın sonunda tutulur, bu yüzden byte boyutuna göre ayır
+        let item_size = std::mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>();
+        let mut buffer = vec![
+            PDH_FMT_COUNTERVALUE_ITEM_W::default();
+            (buffer_size as usize).div_ceil(item_size)
+        ];
 
-            Ok((idle_time, queue_depth))
+        let status = PdhGetFormattedCounterArrayW(
+            counter,
+            PDH_FMT_DOUBLE,
+            &mut buffer_size,
+            &mut item_count,
+            Some(buffer.as_mut_ptr()),
+        );
+        if status != 0 {
+            return Vec::new();
+        }
+
+        buffer
+            .iter()
+            .take(item_count as usize)
+            .filter(|item| item.FmtValue.CStatus == 0)
+            .filter_map(|item| {
+                let name = item.szName.to_string().ok()?;
+                Some((name, item.FmtValue.Anonymous.doubleValue))
+            })
+            .collect()
+    }
+
+    /// Wildcard counter'ları instance adına göre birleştir.
+    /// Dönüş: instance adı -> her counter için değer (`_Total` hariç)
+    unsafe fn read_instances(counters: &[isize]) -> BTreeMap<String, Vec<Option<f64>>> {
+        // BTreeMap: sıra sabit kalsın
+        let mut instances: BTreeMap<String, Vec<Option<f64>>> = BTreeMap::new();
+        for (index, counter) in counters.iter().enumerate() {
+            for (instance, value) in read_counter_array(*counter) {
+                if instance == "_Total" {
+                    continue;
+                }
+                let values = instances
+                    .entry(instance)
+                    .or_insert_with(|| vec![None; counters.len()]);
+                values[index] = Some(value);
+            }
         }
+        instances
     }
 }
 
 #[cfg(windows)]
-pub use windows_impl::get_disk_perf_metrics;
+pub use windows_impl::PerfCounterSession;
 
-/// Windows dışı platformlar için fallback
+/// Windows dışı platformlar için fallback - her toplama varsayılan değerler döndürür
 #[cfg(not(windows))]
-pub fn get_disk_perf_metrics() -> Result<(f64, f64), String> {
-    // Linux/macOS için henüz implemente edilmedi
-    // Varsayılan değerler döndür
-    Ok((100.0, 0.0))
+#[derive(Debug)]
+pub struct PerfCounterSession;
+
+#[cfg(not(windows))]
+impl PerfCounterSession {
+    pub fn open() -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    pub fn collect(&mut self) -> Result<PerfSample, String> {
+        // Linux/macOS için henüz implemente edilmedi
+        Ok(PerfSample::default())
+    }
+}
+
+impl PerfCounterSession {
+    /// Güvenli wrapper - açılamazsa None
+    pub fn open_safe() -> Option<Self> {
+        match Self::open() {
+            Ok(session) => Some(session),
+            Err(e) => {
+                eprintln!("[PerfCounters] Failed to open counter session: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Kullanılan sayaç altyapısı (diagnostics / capabilities için)
+pub fn counter_backend() -> &'static str {
+    if cfg!(windows) {
+        "pdh"
+    } else {
+        "unavailable"
+    }
+}
+
+/// Tek seferlik örnek (monitor dışındaki çağıranlar için): geçici bir
+/// oturum aç, rate counter'ları için kısa bekle ve topla
+fn sample_once() -> Result<PerfSample, String> {
+    let mut session = PerfCounterSession::open()?;
+    if cfg!(windows) {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    session.collect()
+}
+
+/// Disk performans metriklerini tek seferlik örnekle
+pub fn get_disk_perf_metrics() -> Result<DiskPerfMetrics, String> {
+    sample_once().map(|sample| sample.totals)
+}
+
+/// Her fiziksel disk için okuma/yazma hızı, idle ve kuyruk derinliğini al
+pub fn get_physical_disk_metrics() -> Result<Vec<PhysicalDiskMetrics>, String> {
+    sample_once().map(|sample| sample.disks)
+}
+
+/// Güvenli wrapper - hata durumunda boş liste
+pub fn get_physical_disk_metrics_safe() -> Vec<PhysicalDiskMetrics> {
+    match get_physical_disk_metrics() {
+        Ok(disks) => disks,
+        Err(e) => {
+            eprintln!("[PerfCounters] Per-disk error: {}. Skipping breakdown.", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Her mantıksal birim (C:, D: ...) için okuma/yazma hızını al
+pub fn get_logical_disk_metrics() -> Result<Vec<LogicalDiskMetrics>, String> {
+    sample_once().map(|sample| sample.volumes)
+}
+
+/// Güvenli wrapper - hata durumunda boş liste
+pub fn get_logical_disk_metrics_safe() -> Vec<LogicalDiskMetrics> {
+    match get_logical_disk_metrics() {
+        Ok(volumes) => volumes,
+        Err(e) => {
+            eprintln!("[PerfCounters] Per-volume error: {}. Skipping volumes.", e);
+            Vec::new()
+        }
+    }
 }
 
 /// Güvenli wrapper - hata durumunda varsayılan değerler
-pub fn get_disk_perf_metrics_safe() -> (f64, f64) {
+pub fn get_disk_perf_metrics_safe() -> DiskPerfMetrics {
     match get_disk_perf_metrics() {
         Ok(metrics) => metrics,
         Err(e) => {
             eprintln!("[PerfCounters] Error: {}. Using defaults.", e);
-            (100.0, 0.0) // Varsayılan: %100 idle, 0 queue
+            DiskPerfMetrics::default()
         }
     }
 }
@@ -124,8 +401,43 @@ mod tests {
 
     #[test]
     fn test_get_metrics_safe() {
-        let (idle, queue) = get_disk_perf_metrics_safe();
-        assert!(idle >= 0.0 && idle <= 100.0);
-        assert!(queue >= 0.0);
+        let metrics = get_disk_perf_metrics_safe();
+        assert!((0.0..=100.0).contains(&metrics.idle_time));
+        assert!(metrics.queue_depth >= 0.0);
+        assert!(metrics.read_iops >= 0.0);
+        assert!(metrics.write_iops >= 0.0);
+        assert!(metrics.read_latency >= 0.0);
+        assert!(metrics.write_latency >= 0.0);
+    }
+
+    #[test]
+    fn test_session_collects_repeatedly() {
+        let mut session = PerfCounterSession::open().unwrap();
+        for _ in 0..3 {
+            let sample = session.collect().unwrap();
+            assert!((0.0..=100.0).contains(&sample.totals.idle_time));
+        }
+    }
+
+    #[test]
+    fn test_instance_volumes() {
+        let disk = PhysicalDiskMetrics {
+            instance: "0 C: D:".to_string(),
+            read_bytes_per_sec: 0.0,
+            write_bytes_per_sec: 0.0,
+            idle_time: 100.0,
+            queue_depth: 0.0,
+        };
+        assert_eq!(disk.volumes().collect::<Vec<_>>(), vec!["C:", "D:"]);
+    }
+
+    #[test]
+    fn test_get_physical_disk_metrics_safe() {
+        for disk in get_physical_disk_metrics_safe() {
+            assert!(!disk.instance.is_empty());
+            assert_ne!(disk.instance, "_Total");
+            assert!(disk.read_bytes_per_sec >= 0.0);
+            assert!(disk.write_bytes_per_sec >= 0.0);
+        }
     }
 }
@@ -0,0 +1,197 @@
+// Pil ile çalışırken örnekleme ve flush sıklığını azaltmak için güç kaynağı tespiti
+
+use crate::db;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::sync::{Arc, Mutex};
+
+pub const BATTERY_THROTTLE_ENABLED_KEY: &str = "battery_throttle_enabled";
+pub const BATTERY_INTERVAL_MS_KEY: &str = "battery_interval_ms";
+pub const BATTERY_FLUSH_SECS_KEY: &str = "battery_flush_secs";
+
+#[cfg(windows)]
+mod windows_impl {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    /// AC hattı durumunu sorgula (None = bilinmiyor)
+    pub fn is_on_battery() -> Result<Option<bool>, String> {
+        unsafe {
+            let mut status = SYSTEM_POWER_STATUS::default();
+            GetSystemPowerStatus(&mut status)
+                .map_err(|e| format!("GetSystemPowerStatus failed: {}", e))?;
+            // 0 = pil, 1 = şebeke, 255 = bilinmiyor
+            Ok(match status.ACLineStatus {
+                0 => Some(true),
+                1 => Some(false),
+                _ => None,
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::is_on_battery;
+
+/// Windows dışı platformlar için fallback (Linux power_supply sınıfı)
+#[cfg(not(windows))]
+pub fn is_on_battery() -> Result<Option<bool>, String> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return Ok(None);
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim() == "Mains" {
+            let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+            return Ok(Some(online.trim() == "0"));
+        }
+    }
+    Ok(None)
+}
+
+/// Güvenli wrapper - bilinmiyorsa şebekede kabul edilir
+pub fn is_on_battery_safe() -> bool {
+    is_on_battery().ok().flatten().unwrap_or(false)
+}
+
+/// Sampling and flush settings used while the machine runs on battery
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, serde::Deserialize)]
+pub struct BatteryThrottle {
+    pub enabled: bool,
+    /// Minimum monitor interval on battery
+    pub interval_ms: u64,
+    /// Seconds between database flushes on battery
+    pub flush_secs: u64,
+}
+
+impl Default for BatteryThrottle {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: 5000,
+            flush_secs: 60,
+        }
+    }
+}
+
+pub type SharedBatteryThrottle = Arc<Mutex<BatteryThrottle>>;
+
+pub fn create_battery_throttle() -> SharedBatteryThrottle {
+    Arc::new(Mutex::new(BatteryThrottle::default()))
+}
+
+/// `power-profile` payload: what the monitor is currently running at
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PowerProfile {
+    pub on_battery: bool,
+    /// True while sampling runs at reduced resolution
+    pub reduced: bool,
+    pub interval_ms: u64,
+    pub flush_secs: u64,
+}
+
+/// Effective interval and flush period for the current power source
+pub fn effective_profile(
+    configured_ms: u64,
+    normal_flush_secs: u64,
+    on_battery: bool,
+    throttle: &BatteryThrottle,
+) -> PowerProfile {
+    if on_battery && throttle.enabled {
+        let interval_ms = configured_ms.max(throttle.interval_ms);
+        let flush_secs = normal_flush_secs.max(throttle.flush_secs);
+        PowerProfile {
+            on_battery,
+            reduced: interval_ms != configured_ms || flush_secs != normal_flush_secs,
+            interval_ms,
+            flush_secs,
+        }
+    } else {
+        PowerProfile {
+            on_battery,
+            reduced: false,
+            interval_ms: configured_ms,
+            flush_secs: normal_flush_secs,
+        }
+    }
+}
+
+pub async fn load_battery_throttle(pool: &Pool<Sqlite>) -> Result<BatteryThrottle, sqlx::Error> {
+    let defaults = BatteryThrottle::default();
+    let enabled = db::get_setting(pool, BATTERY_THROTTLE_ENABLED_KEY)
+        .await?
+        .map(|value| value == "true")
+        .unwrap_or(defaults.enabled);
+    let interval_ms = db::get_setting(pool, BATTERY_INTERVAL_MS_KEY)
+        .await?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(defaults.interval_ms);
+    let flush_secs = db::get_setting(pool, BATTERY_FLUSH_SECS_KEY)
+        .await?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(defaults.flush_secs);
+    Ok(BatteryThrottle {
+        enabled,
+        interval_ms,
+        flush_secs,
+    })
+}
+
+pub async fn save_battery_throttle(
+    pool: &Pool<Sqlite>,
+    throttle: &BatteryThrottle,
+) -> Result<(), sqlx::Error> {
+    db::set_setting(
+        pool,
+        BATTERY_THROTTLE_ENABLED_KEY,
+        if throttle.enabled { "true" } else { "false" },
+    )
+    .await?;
+    db::set_setting(
+        pool,
+        BATTERY_INTERVAL_MS_KEY,
+        &throttle.interval_ms.to_string(),
+    )
+    .await?;
+    db::set_setting(
+        pool,
+        BATTERY_FLUSH_SECS_KEY,
+        &throttle.flush_secs.to_string(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_only_slows_down() {
+        let throttle = BatteryThrottle::default();
+        let profile = effective_profile(1000, 10, true, &throttle);
+        assert!(profile.reduced);
+        assert_eq!(profile.interval_ms, 5000);
+        assert_eq!(profile.flush_secs, 60);
+
+        // A slower configured interval is kept as is
+        assert_eq!(
+            effective_profile(10_000, 10, true, &throttle).interval_ms,
+            10_000
+        );
+
+        let ac = effective_profile(1000, 10, false, &throttle);
+        assert!(!ac.reduced);
+        assert_eq!(ac.interval_ms, 1000);
+
+        let disabled = BatteryThrottle {
+            enabled: false,
+            ..throttle
+        };
+        assert!(!effective_profile(1000, 10, true, &disabled).reduced);
+    }
+
+    #[test]
+    fn test_power_source_query_does_not_panic() {
+        let _ = is_on_battery_safe();
+    }
+}
@@ -0,0 +1,114 @@
+//! "Is today unusual for this process?": a process's current-session and
+//! today's bytes next to its all-time totals and its average day.
+
+use crate::models::ProcessSeriesPoint;
+use serde::Serialize;
+
+const SECS_PER_DAY: f64 = 86400.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessComparison {
+    pub name: String,
+    /// Since the monitor started (including bytes not yet written to the database)
+    pub session_read_bytes: u64,
+    pub session_write_bytes: u64,
+    pub all_time_read_bytes: u64,
+    pub all_time_write_bytes: u64,
+    /// Recorded bytes of the current local day
+    pub today_read_bytes: u64,
+    pub today_write_bytes: u64,
+    /// Calendar days before today the average is taken over, from the first
+    /// recorded day on (idle days count as zero)
+    pub previous_days: u64,
+    pub avg_daily_read_bytes: f64,
+    pub avg_daily_write_bytes: f64,
+    /// Today's total over the average day's total (None without previous days
+    /// or when the average is zero)
+    pub today_vs_average: Option<f64>,
+}
+
+/// Session and all-time totals as (read, write); `days` is the process's
+/// per-local-day series and `today_start` the current local midnight
+pub fn compare(
+    name: &str,
+    session: (u64, u64),
+    all_time: (u64, u64),
+    days: &[ProcessSeriesPoint],
+    today_start: f64,
+) -> ProcessComparison {
+    let (mut today_read, mut today_write) = (0u64, 0u64);
+    let (mut past_read, mut past_write) = (0u64, 0u64);
+    let mut first_day: Option<f64> = None;
+    for day in days {
+        if day.start >= today_start {
+            today_read = today_read.saturating_add(day.read_bytes);
+            today_write = today_write.saturating_add(day.write_bytes);
+        } else {
+            past_read = past_read.saturating_add(day.read_bytes);
+            past_write = past_write.saturating_add(day.write_bytes);
+            first_day = Some(first_day.map_or(day.start, |first| first.min(day.start)));
+        }
+    }
+
+    let previous_days = first_day
+        .map(|first| ((today_start - first) / SECS_PER_DAY).round().max(1.0) as u64)
+        .unwrap_or(0);
+    let average = |bytes: u64| {
+        if previous_days == 0 {
+            0.0
+        } else {
+            bytes as f64 / previous_days as f64
+        }
+    };
+    let (avg_read, avg_write) = (average(past_read), average(past_write));
+    let avg_total = avg_read + avg_write;
+
+    ProcessComparison {
+        name: name.to_string(),
+        session_read_bytes: session.0,
+        session_write_bytes: session.1,
+        all_time_read_bytes: all_time.0,
+        all_time_write_bytes: all_time.1,
+        today_read_bytes: today_read,
+        today_write_bytes: today_write,
+        previous_days,
+        avg_daily_read_bytes: avg_read,
+        avg_daily_write_bytes: avg_write,
+        today_vs_average: (avg_total > 0.0)
+            .then(|| today_read.saturating_add(today_write) as f64 / avg_total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(index: i64, write_bytes: u64) -> ProcessSeriesPoint {
+        ProcessSeriesPoint {
+            start: index as f64 * SECS_PER_DAY,
+            read_bytes: 0,
+            write_bytes,
+        }
+    }
+
+    #[test]
+    fn test_today_against_the_average_day() {
+        let today = 100.0 * SECS_PER_DAY;
+        // Four days back, with two idle days in between
+        let days = [day(96, 100), day(99, 300), day(100, 800)];
+        let comparison = compare("steam.exe", (0, 50), (0, 1200), &days, today);
+
+        assert_eq!(comparison.today_write_bytes, 800);
+        assert_eq!(comparison.previous_days, 4);
+        assert_eq!(comparison.avg_daily_write_bytes, 100.0);
+        assert_eq!(comparison.today_vs_average, Some(8.0));
+    }
+
+    #[test]
+    fn test_new_process_has_no_average() {
+        let today = 100.0 * SECS_PER_DAY;
+        let comparison = compare("new.exe", (0, 10), (0, 10), &[day(100, 10)], today);
+        assert_eq!(comparison.previous_days, 0);
+        assert_eq!(comparison.today_vs_average, None);
+    }
+}
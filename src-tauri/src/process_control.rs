@@ -0,0 +1,214 @@
+//! Stopping a runaway writer from the top-processes table. Killing takes two
+//! steps: the target is first resolved to PIDs and a short-lived, single-use
+//! token is handed out, and only a kill presenting that token for the same
+//! target goes ahead, so a stray click (or a stale table row) cannot end a
+//! process.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a confirmation token stays valid
+pub const KILL_TOKEN_TTL: Duration = Duration::from_secs(30);
+
+/// A single PID or every process with a name (case-insensitive)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KillTarget {
+    Pid(u32),
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KillCandidate {
+    pub pid: u32,
+    pub name: String,
+    /// Seconds since the epoch; with `pid` it identifies the process, so a
+    /// reused PID (even of the same executable) is not ended
+    pub start_time: u64,
+}
+
+impl KillCandidate {
+    /// Whether the process now running under this PID is still the planned one
+    pub fn is_same_process(&self, name: &str, start_time: u64) -> bool {
+        self.start_time == start_time && self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+/// What a kill of the target would end, and the token confirming it
+#[derive(Debug, Clone, Serialize)]
+pub struct KillPlan {
+    pub token: String,
+    pub processes: Vec<KillCandidate>,
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KillResult {
+    pub pid: u32,
+    pub name: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Running processes (pid, name, start time) matching `target`, lowest PID
+/// first. The app itself is never a candidate.
+pub fn resolve<'a>(
+    target: &KillTarget,
+    running: impl IntoIterator<Item = (u32, &'a str, u64)>,
+    own_pid: u32,
+) -> Vec<KillCandidate> {
+    let mut candidates: Vec<KillCandidate> = running
+        .into_iter()
+        .filter(|&(pid, name, _)| {
+            pid != own_pid
+                && match target {
+                    KillTarget::Pid(target) => pid == *target,
+                    KillTarget::Name(target) => name.eq_ignore_ascii_case(target.trim()),
+                }
+        })
+        .map(|(pid, name, start_time)| KillCandidate {
+            pid,
+            name: name.to_string(),
+            start_time,
+        })
+        .collect();
+    candidates.sort_by_key(|c| c.pid);
+    candidates
+}
+
+struct PendingKill {
+    target: KillTarget,
+    processes: Vec<KillCandidate>,
+    issued_at: Instant,
+}
+
+/// Outstanding confirmation tokens
+#[derive(Default)]
+pub struct KillTokens(Mutex<HashMap<String, PendingKill>>);
+
+fn new_token() -> String {
+    // RandomState is seeded from the OS per instance
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0),
+    );
+    format!("{:016x}", hasher.finish())
+}
+
+impl KillTokens {
+    pub fn issue(
+        &self,
+        target: KillTarget,
+        processes: Vec<KillCandidate>,
+        now: Instant,
+    ) -> Result<KillPlan, String> {
+        let mut pending = self.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        pending.retain(|_, kill| now.saturating_duration_since(kill.issued_at) <= KILL_TOKEN_TTL);
+        let token = new_token();
+        pending.insert(
+            token.clone(),
+            PendingKill {
+                target,
+                processes: processes.clone(),
+                issued_at: now,
+            },
+        );
+        Ok(KillPlan {
+            token,
+            processes,
+            expires_in_secs: KILL_TOKEN_TTL.as_secs(),
+        })
+    }
+
+    /// The planned processes, if `token` was issued for `target` and has not
+    /// expired. A token is consumed by its first use, valid or not.
+    pub fn redeem(
+        &self,
+        token: &str,
+        target: &KillTarget,
+        now: Instant,
+    ) -> Result<Vec<KillCandidate>, String> {
+        let mut pending = self.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let kill = pending
+            .remove(token)
+            .ok_or_else(|| "Unknown or already used confirmation token".to_string())?;
+        if now.saturating_duration_since(kill.issued_at) > KILL_TOKEN_TTL {
+            return Err("Confirmation token has expired".to_string());
+        }
+        if kill.target != *target {
+            return Err("Confirmation token was issued for another process".to_string());
+        }
+        Ok(kill.processes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targets_resolve_without_the_app_itself() {
+        let running = [
+            (30, "node.exe", 100),
+            (10, "Node.exe", 100),
+            (20, "app.exe", 100),
+            (40, "node.exe", 100),
+        ];
+        let pids = |target: KillTarget| -> Vec<u32> {
+            resolve(&target, running, 40)
+                .into_iter()
+                .map(|c| c.pid)
+                .collect()
+        };
+        assert_eq!(pids(KillTarget::Name("NODE.EXE".to_string())), [10, 30]);
+        assert_eq!(pids(KillTarget::Pid(20)), [20]);
+        assert!(pids(KillTarget::Pid(40)).is_empty());
+    }
+
+    #[test]
+    fn test_reused_pids_are_not_the_planned_process() {
+        let candidate = resolve(&KillTarget::Pid(30), [(30, "node.exe", 100)], 1).remove(0);
+        assert!(candidate.is_same_process("Node.exe", 100));
+        // Another node.exe started under the same PID
+        assert!(!candidate.is_same_process("node.exe", 160));
+        assert!(!candidate.is_same_process("app.exe", 100));
+    }
+
+    #[test]
+    fn test_tokens_are_single_use_and_expire() {
+        let tokens = KillTokens::default();
+        let target = KillTarget::Pid(7);
+        let processes = vec![KillCandidate {
+            pid: 7,
+            name: "a.exe".to_string(),
+            start_time: 100,
+        }];
+        let now = Instant::now();
+
+        let plan = tokens
+            .issue(target.clone(), processes.clone(), now)
+            .unwrap();
+        assert!(tokens
+            .redeem(&plan.token, &KillTarget::Pid(8), now)
+            .is_err());
+        // The mismatch used it up
+        assert!(tokens.redeem(&plan.token, &target, now).is_err());
+
+        let plan = tokens
+            .issue(target.clone(), processes.clone(), now)
+            .unwrap();
+        assert_eq!(tokens.redeem(&plan.token, &target, now).unwrap(), processes);
+        assert!(tokens.redeem(&plan.token, &target, now).is_err());
+
+        let plan = tokens.issue(target.clone(), processes, now).unwrap();
+        let later = now + KILL_TOKEN_TTL + Duration::from_secs(1);
+        assert!(tokens.redeem(&plan.token, &target, later).is_err());
+    }
+}
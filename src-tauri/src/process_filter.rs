@@ -0,0 +1,199 @@
+//! Processes the user doesn't want in their stats (backup agents, indexers):
+//! excluded processes are neither accumulated nor written to the database.
+//! In allowlist-only mode just the listed processes are tracked by name and
+//! everything else is counted as "Others", which keeps the history small on
+//! servers running thousands of processes. The app's own I/O (mostly its
+//! SQLite writes) can be left out as well. The filter also carries how the
+//! tracked processes' names are normalized (see `process_names`) and the
+//! pinned processes, listed in the top processes whatever their rank.
+
+use crate::db;
+use crate::process_names::NameNormalization;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::{Arc, Mutex};
+
+pub const EXCLUDED_PROCESSES_KEY: &str = "excluded_processes";
+pub const ALLOWED_PROCESSES_KEY: &str = "allowed_processes";
+pub const ALLOWLIST_ONLY_KEY: &str = "allowlist_only";
+pub const EXCLUDE_SELF_KEY: &str = "exclude_self";
+pub const NAME_NORMALIZATION_KEY: &str = "name_normalization";
+pub const PINNED_PROCESSES_KEY: &str = "pinned_processes";
+pub const STORE_COMMAND_LINES_KEY: &str = "store_command_lines";
+
+/// Longest list accepted
+pub const MAX_FILTER_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessFilter {
+    /// Process names (`backup.exe`), exe paths or folders (`C:\Program Files\Backup`)
+    pub excluded: Vec<String>,
+    /// Track only `allowed` by name and lump everything else into "Others"
+    #[serde(default)]
+    pub allowlist_only: bool,
+    /// Same patterns as `excluded`
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    /// Leave out this app's process (and its database files in file-level mode)
+    #[serde(default)]
+    pub exclude_self: bool,
+    /// How the names of tracked processes are keyed
+    #[serde(default)]
+    pub names: NameNormalization,
+    /// Process names always in the top-processes list and session summaries
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// Keep whole command lines in the history; arguments can hold file
+    /// names, URLs or tokens, so only the exe path is stored unless opted in
+    #[serde(default)]
+    pub store_command_lines: bool,
+}
+
+fn is_path(pattern: &str) -> bool {
+    pattern.contains(['\\', '/'])
+}
+
+/// Case-insensitive match of a name, or of an exe path against a file or folder
+fn matches(pattern: &str, name: &str, exe_path: Option<&str>) -> bool {
+    if !is_path(pattern) {
+        return pattern.eq_ignore_ascii_case(name);
+    }
+    let Some(exe_path) = exe_path else {
+        return false;
+    };
+    let pattern = pattern.trim_end_matches(['\\', '/']).to_lowercase();
+    let exe_path = exe_path.to_lowercase();
+    exe_path == pattern
+        || exe_path
+            .strip_prefix(&pattern)
+            .is_some_and(|rest| rest.starts_with(['\\', '/']))
+}
+
+impl ProcessFilter {
+    pub fn is_excluded(&self, name: &str, exe_path: Option<&str>) -> bool {
+        self.excluded
+            .iter()
+            .any(|pattern| matches(pattern, name, exe_path))
+    }
+
+    /// Whether the process is tracked under its own name rather than as "Others"
+    pub fn is_tracked(&self, name: &str, exe_path: Option<&str>) -> bool {
+        !self.allowlist_only
+            || self
+                .allowed
+                .iter()
+                .any(|pattern| matches(pattern, name, exe_path))
+    }
+}
+
+/// Trimmed entries without blanks and case-insensitive duplicates
+pub fn normalize(entries: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for entry in entries {
+        let entry = entry.trim();
+        if !entry.is_empty() && !normalized.iter().any(|e| e.eq_ignore_ascii_case(entry)) {
+            normalized.push(entry.to_string());
+        }
+    }
+    if normalized.len() > MAX_FILTER_ENTRIES {
+        return Err(format!(
+            "At most {} processes can be listed",
+            MAX_FILTER_ENTRIES
+        ));
+    }
+    Ok(normalized)
+}
+
+pub type SharedProcessFilter = Arc<Mutex<ProcessFilter>>;
+
+pub fn create_process_filter() -> SharedProcessFilter {
+    Arc::new(Mutex::new(ProcessFilter::default()))
+}
+
+pub async fn load_process_filter(pool: &Pool<Sqlite>) -> Result<ProcessFilter, sqlx::Error> {
+    let list = |value: Option<String>| -> Vec<String> {
+        value
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or_default()
+    };
+    Ok(ProcessFilter {
+        excluded: list(db::get_setting(pool, EXCLUDED_PROCESSES_KEY).await?),
+        allowlist_only: db::get_setting(pool, ALLOWLIST_ONLY_KEY)
+            .await?
+            .is_some_and(|value| value == "true"),
+        allowed: list(db::get_setting(pool, ALLOWED_PROCESSES_KEY).await?),
+        exclude_self: db::get_setting(pool, EXCLUDE_SELF_KEY)
+            .await?
+            .is_some_and(|value| value == "true"),
+        names: db::get_setting(pool, NAME_NORMALIZATION_KEY)
+            .await?
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or_default(),
+        pinned: list(db::get_setting(pool, PINNED_PROCESSES_KEY).await?),
+        store_command_lines: db::get_setting(pool, STORE_COMMAND_LINES_KEY)
+            .await?
+            .is_some_and(|value| value == "true"),
+    })
+}
+
+pub async fn save_process_filter(
+    pool: &Pool<Sqlite>,
+    filter: &ProcessFilter,
+) -> Result<(), sqlx::Error> {
+    let list =
+        |entries: &[String]| serde_json::to_string(entries).unwrap_or_else(|_| "[]".to_string());
+    db::set_setting(pool, EXCLUDED_PROCESSES_KEY, &list(&filter.excluded)).await?;
+    db::set_setting(pool, ALLOWED_PROCESSES_KEY, &list(&filter.allowed)).await?;
+    db::set_setting(pool, PINNED_PROCESSES_KEY, &list(&filter.pinned)).await?;
+    let flag = |enabled: bool| if enabled { "true" } else { "false" };
+    db::set_setting(pool, ALLOWLIST_ONLY_KEY, flag(filter.allowlist_only)).await?;
+    db::set_setting(pool, EXCLUDE_SELF_KEY, flag(filter.exclude_self)).await?;
+    db::set_setting(
+        pool,
+        STORE_COMMAND_LINES_KEY,
+        flag(filter.store_command_lines),
+    )
+    .await?;
+    let names = serde_json::to_string(&filter.names).unwrap_or_else(|_| "{}".to_string());
+    db::set_setting(pool, NAME_NORMALIZATION_KEY, &names).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_paths_and_folders_match() {
+        let filter = ProcessFilter {
+            excluded: vec![
+                "Backup.exe".to_string(),
+                "C:\\Program Files\\Indexer\\".to_string(),
+            ],
+            ..ProcessFilter::default()
+        };
+        assert!(filter.is_excluded("backup.exe", None));
+        assert!(filter.is_excluded("idx.exe", Some("c:\\program files\\indexer\\bin\\idx.exe")));
+        assert!(!filter.is_excluded("idx.exe", Some("C:\\Program Files\\Indexer2\\idx.exe")));
+        assert!(!filter.is_excluded("explorer.exe", None));
+    }
+
+    #[test]
+    fn test_entries_are_normalized() {
+        let entries = [" a.exe ", "", "A.EXE", "b.exe"].map(String::from);
+        assert_eq!(normalize(&entries).unwrap(), ["a.exe", "b.exe"]);
+        let too_many: Vec<String> = (0..=MAX_FILTER_ENTRIES).map(|i| i.to_string()).collect();
+        assert!(normalize(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_allowlist_only_tracks_listed_processes() {
+        let mut filter = ProcessFilter {
+            allowed: vec!["sqlservr.exe".to_string()],
+            ..ProcessFilter::default()
+        };
+        assert!(filter.is_tracked("svchost.exe", None));
+        filter.allowlist_only = true;
+        assert!(filter.is_tracked("SQLSERVR.EXE", None));
+        assert!(!filter.is_tracked("svchost.exe", None));
+    }
+}
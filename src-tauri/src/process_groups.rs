@@ -0,0 +1,372 @@
+//! Application trees: browsers and Electron apps run as dozens of helper
+//! processes, so live per-process I/O is also summed per application by
+//! following parent PIDs up to the process that started the app. The full
+//! parent/child hierarchy is also available as a tree for the tree view.
+
+use crate::known_writers;
+use crate::models::ProcessIOStat;
+use crate::process_monitor::{ProcessIOAccumulator, ProcessSnapshot};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Longest parent chain followed (guards against cycles from reused PIDs)
+pub const MAX_TREE_DEPTH: usize = 64;
+
+/// Live I/O of one application tree
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplicationGroup {
+    /// Topmost process of the tree
+    pub root_pid: u32,
+    pub name: String,
+    pub exe_path: Option<String>,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub total_bytes: u64,
+    pub read_speed: u64,
+    pub write_speed: u64,
+    /// The raw per-process rows of the tree, busiest first
+    pub processes: Vec<ProcessIOStat>,
+}
+
+/// One live process in the parent/child tree
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessTreeNode {
+    pub pid: u32,
+    pub name: String,
+    pub exe_path: Option<String>,
+    /// This process's own session bytes and speeds
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub read_speed: u64,
+    pub write_speed: u64,
+    /// Including every descendant
+    pub total_read_bytes: u64,
+    pub total_write_bytes: u64,
+    pub total_read_speed: u64,
+    pub total_write_speed: u64,
+    /// Busiest (rolled-up bytes) first
+    pub children: Vec<ProcessTreeNode>,
+}
+
+fn exe_dir(process: &ProcessSnapshot) -> Option<&str> {
+    let path = process.exe_path.as_deref()?;
+    path.rfind(['\\', '/']).map(|end| &path[..end])
+}
+
+/// The Windows folders hold unrelated system processes, not one application
+fn is_system_dir(dir: &str) -> bool {
+    let dir = dir.to_ascii_lowercase();
+    dir.ends_with("\\system32") || dir.ends_with("\\syswow64")
+}
+
+/// Whether `child` is part of the application `parent` belongs to: the same
+/// executable name, or an executable from the same (non-system) folder
+fn same_application(child: &ProcessSnapshot, parent: &ProcessSnapshot) -> bool {
+    if child.name.eq_ignore_ascii_case(&parent.name) {
+        return true;
+    }
+    match (exe_dir(child), exe_dir(parent)) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b) && !is_system_dir(a),
+        _ => false,
+    }
+}
+
+/// PID of the topmost ancestor of `pid` that belongs to the same application
+pub fn application_root(processes: &HashMap<u32, ProcessSnapshot>, pid: u32) -> u32 {
+    let mut current = pid;
+    for _ in 0..MAX_TREE_DEPTH {
+        let Some(process) = processes.get(&current) else {
+            break;
+        };
+        let Some(parent_pid) = process.parent_pid.filter(|&parent| parent != current) else {
+            break;
+        };
+        match processes.get(&parent_pid) {
+            Some(parent) if same_application(process, parent) => current = parent_pid,
+            _ => break,
+        }
+    }
+    current
+}
+
+/// Live processes with I/O this session grouped into application trees,
+/// busiest first. Exited processes have no parent left to follow and are
+/// only in the per-name list.
+pub fn group_by_application(
+    processes: &HashMap<u32, ProcessSnapshot>,
+    accumulators: &HashMap<u32, ProcessIOAccumulator>,
+    speeds: &HashMap<u32, (u64, u64)>,
+) -> Vec<ApplicationGroup> {
+    let mut groups: HashMap<u32, ApplicationGroup> = HashMap::new();
+
+    for (&pid, process) in processes {
+        let Some(acc) = accumulators.get(&pid) else {
+            continue;
+        };
+        if acc.read_bytes == 0 && acc.write_bytes == 0 {
+            continue;
+        }
+
+        let root_pid = application_root(processes, pid);
+        let group = groups.entry(root_pid).or_insert_with(|| {
+            let root = processes.get(&root_pid).unwrap_or(process);
+            ApplicationGroup {
+                root_pid,
+                name: root.name.to_string(),
+                exe_path: root.exe_path.as_deref().map(String::from),
+                read_bytes: 0,
+                write_bytes: 0,
+                total_bytes: 0,
+                read_speed: 0,
+                write_speed: 0,
+                processes: Vec::new(),
+            }
+        });
+        group.read_bytes = group.read_bytes.saturating_add(acc.read_bytes);
+        group.write_bytes = group.write_bytes.saturating_add(acc.write_bytes);
+        group.total_bytes = group.read_bytes.saturating_add(group.write_bytes);
+        let (read_speed, write_speed) = speeds.get(&pid).copied().unwrap_or((0, 0));
+        group.read_speed = group.read_speed.saturating_add(read_speed);
+        group.write_speed = group.write_speed.saturating_add(write_speed);
+        group.processes.push(ProcessIOStat {
+            pid,
+            name: process.name.to_string(),
+            display_name: process.display_name.as_deref().map(String::from),
+            exe_path: process.exe_path.as_deref().map(String::from),
+            command_line: process.command_line.as_deref().map(String::from),
+            user: process.user.as_deref().map(String::from),
+            system: process.system,
+            read_bytes: acc.read_bytes,
+            write_bytes: acc.write_bytes,
+            total_bytes: acc.read_bytes.saturating_add(acc.write_bytes),
+            read_speed,
+            write_speed,
+            pinned: false,
+            known_writer: known_writers::identify(&process.name).map(|writer| writer.id),
+            io_priority: process.io_priority,
+        });
+    }
+
+    let mut groups: Vec<ApplicationGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        group
+            .processes
+            .sort_by_key(|p| std::cmp::Reverse(p.total_bytes));
+    }
+    groups.sort_by_key(|g| std::cmp::Reverse(g.total_bytes));
+    groups
+}
+
+/// The live processes as a parent/child forest with rolled-up I/O, busiest
+/// tree first. Processes whose parent exited are roots; so is one process of
+/// each parent cycle (reused PIDs).
+pub fn process_tree(
+    processes: &HashMap<u32, ProcessSnapshot>,
+    accumulators: &HashMap<u32, ProcessIOAccumulator>,
+    speeds: &HashMap<u32, (u64, u64)>,
+) -> Vec<ProcessTreeNode> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut roots: Vec<u32> = Vec::new();
+    for (&pid, process) in processes {
+        match process.parent_pid {
+            Some(parent) if parent != pid && processes.contains_key(&parent) => {
+                children.entry(parent).or_default().push(pid)
+            }
+            _ => roots.push(pid),
+        }
+    }
+    // Lowest PIDs first, so a cycle is entered at the same process every time
+    roots.sort_unstable();
+    let mut rest: Vec<u32> = processes.keys().copied().collect();
+    rest.sort_unstable();
+
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut tree: Vec<ProcessTreeNode> = Vec::new();
+    for pid in roots.into_iter().chain(rest) {
+        if !visited.contains(&pid) {
+            tree.push(tree_node(
+                pid,
+                processes,
+                accumulators,
+                speeds,
+                &children,
+                &mut visited,
+            ));
+        }
+    }
+    tree.sort_by_key(|node| {
+        std::cmp::Reverse(node.total_read_bytes.saturating_add(node.total_write_bytes))
+    });
+    tree
+}
+
+fn tree_node(
+    pid: u32,
+    processes: &HashMap<u32, ProcessSnapshot>,
+    accumulators: &HashMap<u32, ProcessIOAccumulator>,
+    speeds: &HashMap<u32, (u64, u64)>,
+    children: &HashMap<u32, Vec<u32>>,
+    visited: &mut HashSet<u32>,
+) -> ProcessTreeNode {
+    visited.insert(pid);
+    let process = &processes[&pid];
+    let (read_bytes, write_bytes) = accumulators
+        .get(&pid)
+        .map_or((0, 0), |acc| (acc.read_bytes, acc.write_bytes));
+    let (read_speed, write_speed) = speeds.get(&pid).copied().unwrap_or((0, 0));
+    let mut node = ProcessTreeNode {
+        pid,
+        name: process.name.to_string(),
+        exe_path: process.exe_path.as_deref().map(String::from),
+        read_bytes,
+        write_bytes,
+        read_speed,
+        write_speed,
+        total_read_bytes: read_bytes,
+        total_write_bytes: write_bytes,
+        total_read_speed: read_speed,
+        total_write_speed: write_speed,
+        children: Vec::new(),
+    };
+    for &child in children.get(&pid).into_iter().flatten() {
+        if visited.contains(&child) {
+            continue;
+        }
+        let child = tree_node(child, processes, accumulators, speeds, children, visited);
+        node.total_read_bytes = node.total_read_bytes.saturating_add(child.total_read_bytes);
+        node.total_write_bytes = node
+            .total_write_bytes
+            .saturating_add(child.total_write_bytes);
+        node.total_read_speed = node.total_read_speed.saturating_add(child.total_read_speed);
+        node.total_write_speed = node
+            .total_write_speed
+            .saturating_add(child.total_write_speed);
+        node.children.push(child);
+    }
+    node.children.sort_by_key(|child| {
+        std::cmp::Reverse(
+            child
+                .total_read_bytes
+                .saturating_add(child.total_write_bytes),
+        )
+    });
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(name: &str, dir: &str, parent_pid: Option<u32>) -> ProcessSnapshot {
+        ProcessSnapshot {
+            name: name.into(),
+            exe_path: Some(format!("{}\\{}", dir, name).into()),
+            parent_pid,
+            ..Default::default()
+        }
+    }
+
+    fn acc(name: &str, write_bytes: u64) -> ProcessIOAccumulator {
+        ProcessIOAccumulator {
+            name: name.into(),
+            exe_path: None,
+            read_bytes: 0,
+            write_bytes,
+        }
+    }
+
+    #[test]
+    fn test_helpers_are_grouped_under_the_app() {
+        let code = "C:\\Apps\\Code";
+        let system = "C:\\Windows\\System32";
+        let processes = HashMap::from([
+            (1, snapshot("explorer.exe", "C:\\Windows", None)),
+            (10, snapshot("Code.exe", code, Some(1))),
+            (11, snapshot("Code.exe", code, Some(10))),
+            (12, snapshot("Code Helper.exe", code, Some(11))),
+            // A shell started from the editor is its own application
+            (13, snapshot("cmd.exe", system, Some(10))),
+            (20, snapshot("services.exe", system, None)),
+            (21, snapshot("svchost.exe", system, Some(20))),
+        ]);
+        let accumulators = HashMap::from([
+            (10, acc("Code.exe", 100)),
+            (11, acc("Code.exe", 0)),
+            (12, acc("Code Helper.exe", 300)),
+            (13, acc("cmd.exe", 50)),
+            (21, acc("svchost.exe", 1000)),
+        ]);
+
+        assert_eq!(application_root(&processes, 12), 10);
+        assert_eq!(application_root(&processes, 21), 21);
+
+        let speeds = HashMap::from([(12, (0, 30)), (10, (0, 10))]);
+        let groups = group_by_application(&processes, &accumulators, &speeds);
+        let summary: Vec<(u32, &str, u64, usize)> = groups
+            .iter()
+            .map(|g| {
+                (
+                    g.root_pid,
+                    g.name.as_str(),
+                    g.write_bytes,
+                    g.processes.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (21, "svchost.exe", 1000, 1),
+                (10, "Code.exe", 400, 2),
+                (13, "cmd.exe", 50, 1),
+            ]
+        );
+        assert_eq!(groups[1].processes[0].pid, 12);
+        assert_eq!(groups[1].write_speed, 40);
+    }
+
+    #[test]
+    fn test_parent_cycles_terminate() {
+        let processes = HashMap::from([
+            (1, snapshot("a.exe", "C:\\A", Some(2))),
+            (2, snapshot("a.exe", "C:\\A", Some(1))),
+        ]);
+        let root = application_root(&processes, 1);
+        assert!(root == 1 || root == 2);
+
+        let tree = process_tree(&processes, &HashMap::new(), &HashMap::new());
+        assert_eq!(tree.len(), 1);
+        assert_eq!((tree[0].pid, tree[0].children[0].pid), (1, 2));
+    }
+
+    #[test]
+    fn test_tree_rolls_up_descendants() {
+        let dir = "C:\\Apps";
+        let processes = HashMap::from([
+            (1, snapshot("explorer.exe", dir, None)),
+            (10, snapshot("game.exe", dir, Some(1))),
+            (11, snapshot("shader.exe", dir, Some(10))),
+            (12, snapshot("crash.exe", dir, Some(10))),
+            // Its parent exited
+            (20, snapshot("orphan.exe", dir, Some(99))),
+        ]);
+        let accumulators = HashMap::from([
+            (10, acc("game.exe", 100)),
+            (11, acc("shader.exe", 500)),
+            (20, acc("orphan.exe", 50)),
+        ]);
+        let speeds = HashMap::from([(11, (0, 7))]);
+
+        let tree = process_tree(&processes, &accumulators, &speeds);
+        let roots: Vec<(u32, u64)> = tree
+            .iter()
+            .map(|node| (node.pid, node.total_write_bytes))
+            .collect();
+        assert_eq!(roots, [(1, 600), (20, 50)]);
+        let game = &tree[0].children[0];
+        assert_eq!((game.write_bytes, game.total_write_bytes), (100, 600));
+        assert_eq!(game.total_write_speed, 7);
+        let children: Vec<u32> = game.children.iter().map(|child| child.pid).collect();
+        assert_eq!(children, [11, 12]);
+    }
+}
@@ -1,72 +1,588 @@
-use std::collections::{HashMap, HashSet};
+use crate::io_priority::{self, IoPriority};
+use crate::known_writers;
+use crate::models::{ProcessIOStat, ProcessInstance};
+use crate::process_filter::{self, SharedProcessFilter};
+use crate::process_groups::{self, ApplicationGroup, ProcessTreeNode};
+use crate::services;
+use crate::top_processes::{self, SharedTopLimit};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
-use sysinfo::{ProcessesToUpdate, System};
-use crate::models::ProcessIOStat;
+use std::time::Instant;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, Uid, UpdateKind, Users};
+
+/// Exited process names kept in memory before the least recently exited are evicted
+pub const MAX_DEAD_HISTORY_ENTRIES: usize = 5000;
+
+/// Ticks averaged into the per-process live speeds
+pub const SPEED_WINDOW_TICKS: usize = 3;
+
+/// Seconds of per-process deltas kept for the activity sparklines
+pub const SPARKLINE_SECS: u64 = 60;
+
+/// Per process name (read, write) bytes of one tick, keyed by the name the
+/// accumulators share, so counting a tick copies no strings
+pub type TickDeltas = HashMap<Arc<str>, (u64, u64)>;
+
+/// Per process name command line of a tick's busiest instance
+pub type TickCommandLines = HashMap<Arc<str>, Arc<str>>;
+
+/// Name of the row collecting the processes outside the top list, and of the
+/// untracked processes in allowlist-only mode
+pub const OTHERS: &str = "Others";
+
+/// One tick of a process's recent activity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SparklinePoint {
+    pub timestamp: f64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Sizes of the per-process maps, reported with the monitor loop stats
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessMapSizes {
+    pub accumulators: usize,
+    pub dead_history: usize,
+    pub snapshots: usize,
+    /// Names evicted from the exited-process history this session
+    pub evicted: u64,
+}
+
+/// A running process with its cumulative I/O counters
+#[derive(Debug, Clone, Default)]
+pub struct ProcessSnapshot {
+    /// Executable name; filters, grouping and history are keyed by it
+    pub name: Arc<str>,
+    /// Shown instead of the name: svchost instances list the services they host
+    pub display_name: Option<Arc<str>>,
+    pub exe_path: Option<Arc<str>>,
+    /// Arguments joined by spaces; None when unreadable (e.g. another user's process)
+    pub command_line: Option<Arc<str>>,
+    /// Account the process runs as; None when it can't be resolved
+    pub user: Option<Arc<str>>,
+    pub parent_pid: Option<u32>,
+    /// Unix time the process started (seconds; 0 when unknown)
+    pub start_time: u64,
+    /// I/O priority hint; None when unreadable (or outside Windows)
+    pub io_priority: Option<IoPriority>,
+    /// Part of Windows rather than the user's apps (see `is_system_process`)
+    pub system: bool,
+    /// Bytes read since the process started
+    pub read_bytes: u64,
+    /// Bytes written since the process started
+    pub written_bytes: u64,
+}
+
+/// Accounts services run as (and root outside Windows)
+const SYSTEM_ACCOUNTS: &[&str] = &["SYSTEM", "LOCAL SERVICE", "NETWORK SERVICE", "root"];
+
+/// Whether a process belongs to the OS rather than the logged-in user: the
+/// kernel pseudo-processes (PID 0 and 4), anything in session 0, where
+/// services run, and anything running as a service account
+pub fn is_system_process(pid: u32, user: Option<&str>, session: Option<u32>) -> bool {
+    pid == 0
+        || pid == 4
+        || session == Some(0)
+        || user.is_some_and(|user| {
+            SYSTEM_ACCOUNTS
+                .iter()
+                .any(|account| user.eq_ignore_ascii_case(account))
+        })
+}
+
+/// Bytes of OS processes versus the user's own this session
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SystemUserSplit {
+    pub system_read_bytes: u64,
+    pub system_write_bytes: u64,
+    pub user_read_bytes: u64,
+    pub user_write_bytes: u64,
+}
+
+/// Where the process list comes from (sysinfo in the app, synthetic churn in soak runs)
+pub trait ProcessSource: Send {
+    /// Refreshes `processes` to every running process by pid (the map is
+    /// reused from scan to scan)
+    fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>);
+}
+
+/// The real process list
+pub struct SysinfoSource {
+    sys: System,
+    users: Users,
+    /// Resolved account names, including ids that didn't resolve
+    user_names: HashMap<Uid, Option<Arc<str>>>,
+    /// Fields that don't change over a process's life (name, path, command
+    /// line, owner, ...), read once per (pid, start time) and shared with every
+    /// snapshot; only the counters are filled in each scan
+    known: HashMap<u32, ProcessSnapshot>,
+    /// Last I/O priority read per pid, with the counters it was read at
+    io_priorities: HashMap<u32, (u64, Option<IoPriority>)>,
+    /// Scans since the services of the svchost instances were last read
+    scans_since_services: u64,
+}
+
+/// Scans between two reads of the services each svchost hosts (services start
+/// and stop inside a running host)
+const SERVICE_REFRESH_SCANS: u64 = 60;
+
+fn is_svchost(snapshot: &ProcessSnapshot) -> bool {
+    snapshot.name.eq_ignore_ascii_case("svchost.exe")
+}
+
+impl SysinfoSource {
+    pub fn new() -> Self {
+        Self {
+            sys: System::new(),
+            users: Users::new(),
+            user_names: HashMap::new(),
+            known: HashMap::new(),
+            io_priorities: HashMap::new(),
+            scans_since_services: 0,
+        }
+    }
+
+    /// I/O priority of `pid`, re-read only while its counters move (idle
+    /// processes keep their last answer instead of being opened every scan)
+    fn io_priority(&mut self, pid: u32, snapshot: &ProcessSnapshot) -> Option<IoPriority> {
+        let counters = snapshot.read_bytes.saturating_add(snapshot.written_bytes);
+        match self.io_priorities.get(&pid) {
+            Some(&(seen, priority)) if seen == counters => priority,
+            _ => {
+                let priority = io_priority::query(pid);
+                self.io_priorities.insert(pid, (counters, priority));
+                priority
+            }
+        }
+    }
+
+    /// Labels svchost instances with the services they host. The service
+    /// manager is asked when a new svchost shows up and every
+    /// `SERVICE_REFRESH_SCANS` scans; the name itself stays "svchost.exe".
+    fn label_svchosts(&mut self, new_svchost: bool) {
+        self.scans_since_services += 1;
+        if !new_svchost && self.scans_since_services < SERVICE_REFRESH_SCANS {
+            return;
+        }
+        if !self.known.values().any(is_svchost) {
+            return;
+        }
+        // A failed query keeps the previous labels
+        let Ok(hosted) = services::service_pids() else {
+            return;
+        };
+        self.scans_since_services = 0;
+        for (pid, snapshot) in self.known.iter_mut().filter(|(_, s)| is_svchost(s)) {
+            snapshot.display_name = hosted
+                .get(pid)
+                .map(|names| services::svchost_name(names).into());
+        }
+    }
+
+    /// Account name of `uid`; the account list is re-read once per unknown id
+    /// (accounts created after the start), never on every scan
+    fn user_name(&mut self, uid: &Uid) -> Option<Arc<str>> {
+        if let Some(name) = self.user_names.get(uid) {
+            return name.clone();
+        }
+        if self.users.get_user_by_id(uid).is_none() {
+            self.users.refresh_list();
+        }
+        let name: Option<Arc<str>> = self
+            .users
+            .get_user_by_id(uid)
+            .map(|user| user.name().into());
+        self.user_names.insert(uid.clone(), name.clone());
+        name
+    }
+}
+
+impl Default for SysinfoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessSource for SysinfoSource {
+    fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>) {
+        // Only the disk counters are read every scan (no CPU or memory); the
+        // exe path, command line and owner once per process
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            ProcessRefreshKind::new()
+                .with_disk_usage()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+
+        // Processes not seen before (or a recycled pid) get their fixed fields read
+        let known = &self.known;
+        let new: Vec<(u32, ProcessSnapshot, Option<Uid>, Option<u32>)> = self
+            .sys
+            .processes()
+            .iter()
+            .filter(|(pid, process)| {
+                known
+                    .get(&pid.as_u32())
+                    .is_none_or(|snapshot| snapshot.start_time != process.start_time())
+            })
+            .map(|(pid, process)| {
+                // Only Windows sessions say anything about services
+                let session = if cfg!(windows) {
+                    process.session_id().map(|s| s.as_u32())
+                } else {
+                    None
+                };
+                (
+                    pid.as_u32(),
+                    ProcessSnapshot {
+                        name: process.name().to_string_lossy().into(),
+                        display_name: None,
+                        exe_path: process.exe().map(|p| p.to_string_lossy().into()),
+                        command_line: join_command_line(process.cmd()),
+                        user: None,
+                        parent_pid: None,
+                        start_time: process.start_time(),
+                        io_priority: None,
+                        // Decided below, once the account name is known
+                        system: false,
+                        read_bytes: 0,
+                        written_bytes: 0,
+                    },
+                    process.user_id().cloned(),
+                    session,
+                )
+            })
+            .collect();
+        let new_svchost = new.iter().any(|(_, snapshot, ..)| is_svchost(snapshot));
+        for (pid, mut snapshot, uid, session) in new {
+            snapshot.user = uid.and_then(|uid| self.user_name(&uid));
+            snapshot.system = is_system_process(pid, snapshot.user.as_deref(), session);
+            self.known.insert(pid, snapshot);
+        }
+
+        let sys = &self.sys;
+        self.known
+            .retain(|pid, _| sys.process(sysinfo::Pid::from_u32(*pid)).is_some());
+        let known = &self.known;
+        self.io_priorities.retain(|pid, _| known.contains_key(pid));
+        self.label_svchosts(new_svchost);
+
+        processes.clear();
+        for (pid, process) in self.sys.processes() {
+            let pid = pid.as_u32();
+            let Some(known) = self.known.get(&pid) else {
+                continue;
+            };
+            let disk_usage = process.disk_usage();
+            let mut snapshot = known.clone();
+            snapshot.parent_pid = process.parent().map(|pid| pid.as_u32());
+            snapshot.read_bytes = disk_usage.read_bytes;
+            snapshot.written_bytes = disk_usage.written_bytes;
+            processes.insert(pid, snapshot);
+        }
+        for (&pid, snapshot) in processes.iter_mut() {
+            snapshot.io_priority = self.io_priority(pid, snapshot);
+        }
+    }
+}
+
+/// For scans that only look at names and exe paths: the name is always
+/// read, the path once per process, and no counters at all
+pub fn name_refresh() -> ProcessRefreshKind {
+    ProcessRefreshKind::new().with_exe(UpdateKind::OnlyIfNotSet)
+}
+
+fn join_command_line(args: &[std::ffi::OsString]) -> Option<Arc<str>> {
+    let joined = args
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!joined.is_empty()).then(|| joined.into())
+}
+
+/// Synthetic process churn: every tick starts `spawn_per_tick` short-lived
+/// processes (names drawn from a fixed pool) that each do some I/O and exit
+/// after `lifetime_ticks`
+pub struct MockProcessSource {
+    pub spawn_per_tick: u32,
+    pub lifetime_ticks: u64,
+    pub name_pool: u32,
+    /// Bytes each process reads and writes per tick
+    pub bytes_per_tick: u64,
+    tick: u64,
+    next_pid: u32,
+    /// pid -> (snapshot, tick it exits at)
+    running: HashMap<u32, (ProcessSnapshot, u64)>,
+}
+
+impl MockProcessSource {
+    pub fn new(
+        spawn_per_tick: u32,
+        lifetime_ticks: u64,
+        name_pool: u32,
+        bytes_per_tick: u64,
+    ) -> Self {
+        Self {
+            spawn_per_tick,
+            lifetime_ticks,
+            name_pool: name_pool.max(1),
+            bytes_per_tick,
+            tick: 0,
+            next_pid: 1,
+            running: HashMap::new(),
+        }
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.running.len()
+    }
+}
+
+impl ProcessSource for MockProcessSource {
+    fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>) {
+        self.tick += 1;
+        let tick = self.tick;
+        self.running.retain(|_, (_, exits_at)| *exits_at > tick);
+
+        for (snapshot, _) in self.running.values_mut() {
+            snapshot.read_bytes = snapshot.read_bytes.saturating_add(self.bytes_per_tick);
+            snapshot.written_bytes = snapshot.written_bytes.saturating_add(self.bytes_per_tick);
+        }
+
+        for _ in 0..self.spawn_per_tick {
+            let pid = self.next_pid;
+            self.next_pid = self.next_pid.wrapping_add(1).max(1);
+            let name = format!("fake-{}.exe", pid % self.name_pool);
+            let snapshot = ProcessSnapshot {
+                exe_path: Some(format!("C:\\Fake\\{}", name).into()),
+                command_line: Some(format!("C:\\Fake\\{} --instance {}", name, pid).into()),
+                user: Some(format!("user{}", pid % 2).into()),
+                start_time: tick,
+                name: name.into(),
+                ..Default::default()
+            };
+            self.running
+                .insert(pid, (snapshot, tick + self.lifetime_ticks));
+        }
+
+        processes.clear();
+        processes.extend(
+            self.running
+                .iter()
+                .map(|(pid, (snapshot, _))| (*pid, snapshot.clone())),
+        );
+    }
+}
 
 #[derive(Clone)]
 pub struct ProcessIOAccumulator {
-    pub name: String,
+    /// Shared with the process's snapshot unless the name was normalized
+    pub name: Arc<str>,
+    /// Executable of the process, kept for its name once it exited
+    pub exe_path: Option<Arc<str>>,
     pub read_bytes: u64,
     pub write_bytes: u64,
 }
 
 pub type ProcessAccumulators = Arc<Mutex<HashMap<u32, ProcessIOAccumulator>>>;
 
+/// Per-name (read, write) bytes of one tick, for the sparklines
+struct RecentTick {
+    at: Instant,
+    /// Wall-clock time, for the chart axis
+    timestamp: f64,
+    deltas: TickDeltas,
+}
+
+/// Per-pid (name, read, write) bytes of one tick, for the live speeds
+struct TickSample {
+    at: Instant,
+    deltas: HashMap<u32, (Arc<str>, u64, u64)>,
+}
+
 pub fn create_accumulators() -> ProcessAccumulators {
     Arc::new(Mutex::new(HashMap::new()))
 }
 
 pub struct ProcessMonitor {
-    sys: System,
+    source: Box<dyn ProcessSource>,
+    /// Process list of the most recent `update`
+    processes: HashMap<u32, ProcessSnapshot>,
     dead_process_history: HashMap<String, (u64, u64)>,
     last_process_snapshot: HashMap<String, (u64, u64)>,
     accumulators: ProcessAccumulators,
-    last_seen_by_pid: HashMap<u32, (u64, u64)>,
+    /// Counter baseline per (pid, start time): a recycled PID is a new key
+    /// and starts from a fresh baseline
+    last_seen_by_pid: HashMap<(u32, u64), (u64, u64)>,
+    /// Names of processes that exited since the last call to `take_exited_deltas`
+    exited_names: HashSet<String>,
+    /// Per-name (read, write) deltas of the most recent `update`
+    tick_deltas: TickDeltas,
+    /// Per-name command line of the busiest instance of the most recent `update`
+    /// (its exe path unless `ProcessFilter::store_command_lines` is set)
+    tick_command_lines: TickCommandLines,
+    /// Per-name (bytes, command line) of the busiest instance, filled during `update`
+    busiest: HashMap<Arc<str>, (u64, Arc<str>)>,
+    /// Update count, used as the recency clock of the exited-process history
+    tick: u64,
+    /// Update count at which each name in `dead_process_history` last exited
+    dead_last_exit: HashMap<String, u64>,
+    /// Executable of the last exited instance of each name in `dead_process_history`
+    dead_exe_paths: HashMap<String, Arc<str>>,
+    /// Unsaved bytes of evicted names, handed out with the next deltas
+    evicted_deltas: HashMap<String, (u64, u64)>,
+    evicted_count: u64,
+    history_cap: usize,
+    /// Set once any process reported non-zero I/O counters
+    counters_seen: bool,
+    /// The last `SPEED_WINDOW_TICKS` ticks plus the one before them (its time
+    /// opens the window)
+    speed_window: VecDeque<TickSample>,
+    /// Ticks of the last `SPARKLINE_SECS`
+    recent_ticks: VecDeque<RecentTick>,
+    /// Processes left out of the accounting
+    filter: SharedProcessFilter,
+    /// Length of the top-processes list built for the live stream
+    top_limit: SharedTopLimit,
+    /// PID of the app itself, left out when the filter excludes it
+    own_pid: u32,
+    /// Session bytes of OS versus user processes
+    split: SystemUserSplit,
 }
 
 impl ProcessMonitor {
     pub fn new(accumulators: ProcessAccumulators) -> Self {
+        Self::with_source(accumulators, Box::new(SysinfoSource::new()))
+    }
+
+    pub fn with_source(accumulators: ProcessAccumulators, source: Box<dyn ProcessSource>) -> Self {
         Self {
-            sys: System::new(),
+            source,
+            processes: HashMap::new(),
             dead_process_history: HashMap::new(),
             last_process_snapshot: HashMap::new(),
             accumulators,
             last_seen_by_pid: HashMap::new(),
+            exited_names: HashSet::new(),
+            tick_deltas: HashMap::new(),
+            tick_command_lines: HashMap::new(),
+            busiest: HashMap::new(),
+            tick: 0,
+            dead_last_exit: HashMap::new(),
+            dead_exe_paths: HashMap::new(),
+            evicted_deltas: HashMap::new(),
+            evicted_count: 0,
+            history_cap: MAX_DEAD_HISTORY_ENTRIES,
+            counters_seen: false,
+            filter: process_filter::create_process_filter(),
+            top_limit: top_processes::create_top_limit(),
+            own_pid: std::process::id(),
+            split: SystemUserSplit::default(),
+            speed_window: VecDeque::new(),
+            recent_ticks: VecDeque::new(),
         }
     }
 
+    /// Uses the app's exclusion list instead of an empty one
+    pub fn with_filter(mut self, filter: SharedProcessFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Follows the app's top-processes limit setting instead of the default
+    pub fn with_top_limit(mut self, top_limit: SharedTopLimit) -> Self {
+        self.top_limit = top_limit;
+        self
+    }
+
+    /// The configured top-processes limit (0 = every process)
+    pub fn top_limit(&self) -> usize {
+        top_processes::current_limit(&self.top_limit)
+    }
+
+    /// Whether per-process I/O counters are readable (false while every
+    /// process reports zero, e.g. without the needed privileges)
+    pub fn counters_available(&self) -> bool {
+        self.counters_seen
+    }
+
     pub fn reset(&mut self) {
         self.dead_process_history.clear();
+        self.dead_last_exit.clear();
+        self.dead_exe_paths.clear();
+        self.evicted_deltas.clear();
         self.last_process_snapshot.clear();
         self.last_seen_by_pid.clear();
+        self.exited_names.clear();
+        self.tick_deltas.clear();
+        self.tick_command_lines.clear();
+        self.speed_window.clear();
+        self.recent_ticks.clear();
+        self.split = SystemUserSplit::default();
         if let Ok(mut acc) = self.accumulators.lock() {
             acc.clear();
         }
     }
 
     pub fn update(&mut self) -> (u64, u64) {
-        self.sys.refresh_processes(ProcessesToUpdate::All);
+        self.update_at(Instant::now())
+    }
+
+    fn update_at(&mut self, now: Instant) -> (u64, u64) {
+        self.source.refresh(&mut self.processes);
+        self.tick += 1;
+        self.counters_seen = self.counters_seen
+            || self
+                .processes
+                .values()
+                .any(|p| p.read_bytes > 0 || p.written_bytes > 0);
         let mut tick_read_delta: u64 = 0;
         let mut tick_write_delta: u64 = 0;
-
-        let active_pids: HashSet<u32> = self
-            .sys
-            .processes()
-            .keys()
-            .map(|p| p.as_u32())
-            .collect();
+        self.tick_deltas.clear();
+        self.tick_command_lines.clear();
+        self.busiest.clear();
+        // The speed sample leaving the window lends its map to this tick's
+        let mut pid_deltas = if self.speed_window.len() > SPEED_WINDOW_TICKS {
+            self.speed_window
+                .pop_front()
+                .map(|sample| sample.deltas)
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        pid_deltas.clear();
 
         if let Ok(mut acc_guard) = self.accumulators.lock() {
-            for (pid, process) in self.sys.processes() {
-                let pid_u32 = pid.as_u32();
-                let disk_usage = process.disk_usage();
-
-                // sysinfo::Process::disk_usage() returns cumulative bytes since the process
+            // Read in place rather than cloned every tick; a panic elsewhere
+            // doesn't leave the filter half-written
+            let filter = self.filter.lock().unwrap_or_else(|e| e.into_inner());
+            // Accumulators of processes whose PID was taken over by a new one
+            let mut recycled: Vec<ProcessIOAccumulator> = Vec::new();
+            for (&pid_u32, process) in &self.processes {
+                // The source reports cumulative bytes since the process
                 // started. We must compute per-tick deltas to avoid double counting.
-                let current_read = disk_usage.read_bytes;
-                let current_write = disk_usage.written_bytes;
+                let current_read = process.read_bytes;
+                let current_write = process.written_bytes;
+                let key = (pid_u32, process.start_time);
+
+                if filter.is_excluded(&process.name, process.exe_path.as_deref())
+                    || (filter.exclude_self && pid_u32 == self.own_pid)
+                {
+                    // Excluded processes keep a current baseline, so removing them
+                    // from the list later doesn't count what they did meanwhile
+                    self.last_seen_by_pid
+                        .insert(key, (current_read, current_write));
+                    acc_guard.remove(&pid_u32);
+                    continue;
+                }
 
-                let (r_delta, w_delta) = match self.last_seen_by_pid.get_mut(&pid_u32) {
+                let (r_delta, w_delta) = match self.last_seen_by_pid.get_mut(&key) {
                     Some((prev_r, prev_w)) => {
                         let r = current_read.saturating_sub(*prev_r);
                         let w = current_write.saturating_sub(*prev_w);
@@ -76,109 +592,648 @@ impl ProcessMonitor {
                     }
                     None => {
                         // New to our monitor session: establish baseline; count 0 for this tick.
-                        self.last_seen_by_pid.insert(pid_u32, (current_read, current_write));
+                        self.last_seen_by_pid
+                            .insert(key, (current_read, current_write));
+                        // An accumulator under this PID belongs to the process that had it before
+                        recycled.extend(acc_guard.remove(&pid_u32));
                         (0, 0)
                     }
                 };
 
-                let acc = acc_guard.entry(pid_u32).or_insert_with(|| ProcessIOAccumulator {
-                    name: process.name().to_string_lossy().to_string(),
-                    read_bytes: 0,
-                    write_bytes: 0,
-                });
+                let tracked = filter.is_tracked(&process.name, process.exe_path.as_deref());
+                let name: Cow<str> = if !tracked {
+                    Cow::Borrowed(OTHERS)
+                } else if filter.names.is_enabled() {
+                    Cow::Owned(
+                        filter
+                            .names
+                            .normalize(&process.name, process.exe_path.as_deref()),
+                    )
+                } else {
+                    Cow::Borrowed(&process.name)
+                };
+                // The snapshot's name is shared as is; only a changed key allocates
+                let intern = |name: Cow<str>| -> Arc<str> {
+                    if *name == *process.name {
+                        Arc::clone(&process.name)
+                    } else {
+                        name.into()
+                    }
+                };
+                let acc = match acc_guard.entry(pid_u32) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(ProcessIOAccumulator {
+                        name: intern(Cow::Borrowed(&name)),
+                        exe_path: process.exe_path.clone(),
+                        read_bytes: 0,
+                        write_bytes: 0,
+                    }),
+                };
 
-                // Keep name fresh (helps with long-running processes that change name/exe)
-                acc.name = process.name().to_string_lossy().to_string();
+                // Keep name fresh (helps with long-running processes that change name/exe).
+                // Bytes already saved under the old name move along, or the next
+                // flush would write them again under the new one
+                if *acc.name != *name {
+                    let renamed = intern(name);
+                    move_saved(
+                        &mut self.last_process_snapshot,
+                        &acc.name,
+                        &renamed,
+                        (acc.read_bytes, acc.write_bytes),
+                    );
+                    acc.name = renamed;
+                }
+                // The exe path of a new process can arrive a scan later
+                if acc.exe_path.is_none() {
+                    acc.exe_path = process.exe_path.clone();
+                }
 
                 if r_delta > 0 || w_delta > 0 {
                     acc.read_bytes = acc.read_bytes.saturating_add(r_delta);
                     acc.write_bytes = acc.write_bytes.saturating_add(w_delta);
                     tick_read_delta = tick_read_delta.saturating_add(r_delta);
                     tick_write_delta = tick_write_delta.saturating_add(w_delta);
+                    let (split_read, split_write) = if process.system {
+                        (
+                            &mut self.split.system_read_bytes,
+                            &mut self.split.system_write_bytes,
+                        )
+                    } else {
+                        (
+                            &mut self.split.user_read_bytes,
+                            &mut self.split.user_write_bytes,
+                        )
+                    };
+                    *split_read = split_read.saturating_add(r_delta);
+                    *split_write = split_write.saturating_add(w_delta);
+
+                    match self.tick_deltas.get_mut(&acc.name) {
+                        Some(tick) => {
+                            tick.0 = tick.0.saturating_add(r_delta);
+                            tick.1 = tick.1.saturating_add(w_delta);
+                        }
+                        None => {
+                            self.tick_deltas
+                                .insert(Arc::clone(&acc.name), (r_delta, w_delta));
+                        }
+                    }
+                    pid_deltas.insert(pid_u32, (Arc::clone(&acc.name), r_delta, w_delta));
+
+                    let stored_command_line = if filter.store_command_lines {
+                        process.command_line.as_ref()
+                    } else {
+                        process.exe_path.as_ref()
+                    };
+                    if let Some(command_line) = stored_command_line.filter(|_| tracked) {
+                        let bytes = r_delta.saturating_add(w_delta);
+                        match self.busiest.get_mut(&acc.name) {
+                            Some(busiest) if bytes <= busiest.0 => {}
+                            Some(busiest) => *busiest = (bytes, Arc::clone(command_line)),
+                            None => {
+                                self.busiest.insert(
+                                    Arc::clone(&acc.name),
+                                    (bytes, Arc::clone(command_line)),
+                                );
+                            }
+                        }
+                    }
                 }
             }
 
             // Handle dead processes (present in our maps but no longer active)
-            let dead_pids: Vec<u32> = self
-                .last_seen_by_pid
+            let mut exited = recycled;
+            let processes = &self.processes;
+            self.last_seen_by_pid.retain(|&(pid, start_time), _| {
+                match processes.get(&pid) {
+                    // A recycled PID's accumulator was already retired above
+                    Some(process) => process.start_time == start_time,
+                    None => {
+                        exited.extend(acc_guard.remove(&pid));
+                        false
+                    }
+                }
+            });
+            for acc in exited {
+                if acc.read_bytes > 0 || acc.write_bytes > 0 {
+                    let name = acc.name.to_string();
+                    self.exited_names.insert(name.clone());
+                    self.dead_last_exit.insert(name.clone(), self.tick);
+                    if let Some(exe_path) = acc.exe_path {
+                        self.dead_exe_paths.insert(name.clone(), exe_path);
+                    }
+                    let entry = self.dead_process_history.entry(name).or_insert((0, 0));
+                    entry.0 = entry.0.saturating_add(acc.read_bytes);
+                    entry.1 = entry.1.saturating_add(acc.write_bytes);
+                }
+            }
+        }
+
+        self.tick_command_lines.extend(
+            self.busiest
+                .drain()
+                .map(|(name, (_, command_line))| (name, command_line)),
+        );
+
+        self.speed_window.push_back(TickSample {
+            at: now,
+            deltas: pid_deltas,
+        });
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        // Ticks that aged out of the sparklines lend their map to this one
+        let keep = std::time::Duration::from_secs(SPARKLINE_SECS);
+        let mut deltas = TickDeltas::new();
+        while self
+            .recent_ticks
+            .front()
+            .is_some_and(|tick| now.saturating_duration_since(tick.at) > keep)
+        {
+            deltas = self
+                .recent_ticks
+                .pop_front()
+                .map(|tick| tick.deltas)
+                .unwrap_or_default();
+        }
+        deltas.clear();
+        deltas.extend(
+            self.tick_deltas
+                .iter()
+                .map(|(name, bytes)| (Arc::clone(name), *bytes)),
+        );
+        self.recent_ticks.push_back(RecentTick {
+            at: now,
+            timestamp,
+            deltas,
+        });
+
+        if self.dead_process_history.len() > self.history_cap
+            || self.last_process_snapshot.len() > self.history_cap * 2
+        {
+            self.evict_dead_history();
+        }
+
+        (tick_read_delta, tick_write_delta)
+    }
+
+    /// Takes the current counters as the new baseline without counting them,
+    /// so I/O done while monitoring was paused is not attributed
+    ///
+    /// Processes that exited in the meantime keep their old baseline and are
+    /// retired by the next `update` as usual, as are those whose PID was
+    /// recycled (the new process gets its baseline there).
+    pub fn rebaseline(&mut self) {
+        self.source.refresh(&mut self.processes);
+        let known: HashSet<u32> = self.last_seen_by_pid.keys().map(|&(pid, _)| pid).collect();
+        for (&pid, process) in &self.processes {
+            let key = (pid, process.start_time);
+            if self.last_seen_by_pid.contains_key(&key) || !known.contains(&pid) {
+                self.last_seen_by_pid
+                    .insert(key, (process.read_bytes, process.written_bytes));
+            }
+        }
+        self.tick_deltas.clear();
+        self.tick_command_lines.clear();
+        // The paused time is not part of any speed
+        self.speed_window.clear();
+        self.speed_window.push_back(TickSample {
+            at: Instant::now(),
+            deltas: HashMap::new(),
+        });
+    }
+
+    /// Per-pid (name, read, write) bytes/s over the speed window (empty
+    /// until two ticks were seen)
+    fn window_speeds(&self) -> HashMap<u32, (Arc<str>, u64, u64)> {
+        let (Some(first), Some(last)) = (self.speed_window.front(), self.speed_window.back())
+        else {
+            return HashMap::new();
+        };
+        let elapsed = last.at.saturating_duration_since(first.at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return HashMap::new();
+        }
+
+        let mut bytes: HashMap<u32, (Arc<str>, u64, u64)> = HashMap::new();
+        for sample in self.speed_window.iter().skip(1) {
+            for (pid, (name, r, w)) in &sample.deltas {
+                let entry = bytes.entry(*pid).or_insert_with(|| (name.clone(), 0, 0));
+                entry.1 = entry.1.saturating_add(*r);
+                entry.2 = entry.2.saturating_add(*w);
+            }
+        }
+        bytes
+            .into_iter()
+            .map(|(pid, (name, r, w))| {
+                let speed = |b: u64| (b as f64 / elapsed) as u64;
+                (pid, (name, speed(r), speed(w)))
+            })
+            .collect()
+    }
+
+    /// Per-tick bytes of `name` over the last `SPARKLINE_SECS`, oldest first
+    /// (ticks without I/O are zero points, so every row shares the time axis)
+    pub fn sparkline(&self, name: &str) -> Vec<SparklinePoint> {
+        self.recent_ticks
+            .iter()
+            .map(|tick| {
+                let (read_bytes, write_bytes) = tick.deltas.get(name).copied().unwrap_or((0, 0));
+                SparklinePoint {
+                    timestamp: tick.timestamp,
+                    read_bytes,
+                    write_bytes,
+                }
+            })
+            .collect()
+    }
+
+    /// Per-pid (read, write) bytes/s over the last few ticks
+    pub fn process_speeds(&self) -> HashMap<u32, (u64, u64)> {
+        self.window_speeds()
+            .into_iter()
+            .map(|(pid, (_, r, w))| (pid, (r, w)))
+            .collect()
+    }
+
+    /// Per process name (read, write) bytes/s over the last few ticks
+    pub fn name_speeds(&self) -> HashMap<String, (u64, u64)> {
+        let mut speeds: HashMap<String, (u64, u64)> = HashMap::new();
+        for (name, r, w) in self.window_speeds().into_values() {
+            let entry = speeds.entry(name.to_string()).or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(r);
+            entry.1 = entry.1.saturating_add(w);
+        }
+        speeds
+    }
+
+    /// Bounds the exited-process history and the persisted-totals snapshot
+    ///
+    /// The least recently exited names go first (down to 90% of the cap so
+    /// this doesn't run every tick). Their unsaved bytes are queued for the
+    /// next DB flush and their snapshot entry is dropped with them, so a
+    /// process that comes back under the same name starts again from zero.
+    fn evict_dead_history(&mut self) {
+        let live_names: HashSet<Arc<str>> = match self.accumulators.lock() {
+            Ok(acc_guard) => acc_guard
+                .values()
+                .map(|acc| Arc::clone(&acc.name))
+                .collect(),
+            Err(_) => return,
+        };
+
+        let target = self.history_cap * 9 / 10;
+        if self.dead_process_history.len() > target {
+            let mut candidates: Vec<(u64, String)> = self
+                .dead_process_history
                 .keys()
-                .filter(|pid| !active_pids.contains(pid))
-                .cloned()
+                .filter(|name| !live_names.contains(name.as_str()))
+                .map(|name| {
+                    (
+                        self.dead_last_exit.get(name).copied().unwrap_or(0),
+                        name.clone(),
+                    )
+                })
                 .collect();
+            candidates.sort_unstable();
 
-            for pid in dead_pids {
-                self.last_seen_by_pid.remove(&pid);
-                if let Some(acc) = acc_guard.remove(&pid) {
-                    if acc.read_bytes > 0 || acc.write_bytes > 0 {
-                        let entry = self.dead_process_history.entry(acc.name).or_insert((0, 0));
-                        entry.0 = entry.0.saturating_add(acc.read_bytes);
-                        entry.1 = entry.1.saturating_add(acc.write_bytes);
-                    }
+            let excess = self.dead_process_history.len() - target;
+            for (_, name) in candidates.into_iter().take(excess) {
+                let Some((total_r, total_w)) = self.dead_process_history.remove(&name) else {
+                    continue;
+                };
+                self.dead_last_exit.remove(&name);
+                self.dead_exe_paths.remove(&name);
+                self.exited_names.remove(&name);
+                let (saved_r, saved_w) = self.last_process_snapshot.remove(&name).unwrap_or((0, 0));
+                let unsaved = (
+                    total_r.saturating_sub(saved_r),
+                    total_w.saturating_sub(saved_w),
+                );
+                if unsaved.0 > 0 || unsaved.1 > 0 {
+                    let entry = self.evicted_deltas.entry(name).or_insert((0, 0));
+                    entry.0 = entry.0.saturating_add(unsaved.0);
+                    entry.1 = entry.1.saturating_add(unsaved.1);
                 }
+                self.evicted_count += 1;
             }
         }
 
-        (tick_read_delta, tick_write_delta)
+        // Snapshot entries of names that are neither running nor remembered hold
+        // totals nothing will be compared against any more
+        let dead = &self.dead_process_history;
+        self.last_process_snapshot
+            .retain(|name, _| dead.contains_key(name) || live_names.contains(name.as_str()));
+    }
+
+    /// Executable of a process name: a running instance's (whether or not it
+    /// did any I/O), else that of the last instance that exited
+    pub fn exe_path(&self, name: &str) -> Option<String> {
+        let names = self
+            .filter
+            .lock()
+            .map(|f| f.names.clone())
+            .unwrap_or_default();
+        let running = self.processes.values().find_map(|process| {
+            let exe_path = process.exe_path.as_deref()?;
+            names
+                .normalize(&process.name, Some(exe_path))
+                .eq_ignore_ascii_case(name)
+                .then_some(exe_path)
+        });
+        running
+            .or_else(|| {
+                self.dead_exe_paths
+                    .iter()
+                    .find(|(dead, _)| dead.eq_ignore_ascii_case(name))
+                    .map(|(_, exe_path)| &**exe_path)
+            })
+            .map(String::from)
     }
 
-    pub fn get_top_processes(&self) -> Vec<ProcessIOStat> {
-        let mut grouped: HashMap<String, (Option<String>, u64, u64)> = HashMap::new();
+    /// pid -> name of the processes seen by the last scan
+    pub fn process_names(&self) -> HashMap<u32, String> {
+        self.processes
+            .iter()
+            .map(|(pid, process)| (*pid, process.name.to_string()))
+            .collect()
+    }
+
+    /// Live processes grouped into application trees
+    pub fn application_groups(&self) -> Vec<ApplicationGroup> {
+        match self.accumulators.lock() {
+            Ok(acc_guard) => process_groups::group_by_application(
+                &self.processes,
+                &acc_guard,
+                &self.process_speeds(),
+            ),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Live processes as a parent/child tree with rolled-up I/O
+    pub fn process_tree(&self) -> Vec<ProcessTreeNode> {
+        match self.accumulators.lock() {
+            Ok(acc_guard) => {
+                process_groups::process_tree(&self.processes, &acc_guard, &self.process_speeds())
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// The running instances of `name` (case-insensitive) with their start
+    /// times, own session counters and speeds, lowest PID first
+    pub fn instances(&self, name: &str) -> Vec<ProcessInstance> {
+        let Ok(acc_guard) = self.accumulators.lock() else {
+            return Vec::new();
+        };
+        let speeds = self.process_speeds();
+        let names = self
+            .filter
+            .lock()
+            .map(|f| f.names.clone())
+            .unwrap_or_default();
+        let mut instances: Vec<ProcessInstance> = self
+            .processes
+            .iter()
+            .filter(|(_, process)| {
+                names
+                    .normalize(&process.name, process.exe_path.as_deref())
+                    .eq_ignore_ascii_case(name)
+            })
+            .map(|(&pid, process)| {
+                let (read_bytes, write_bytes) = acc_guard
+                    .get(&pid)
+                    .map(|acc| (acc.read_bytes, acc.write_bytes))
+                    .unwrap_or((0, 0));
+                let (read_speed, write_speed) = speeds.get(&pid).copied().unwrap_or((0, 0));
+                ProcessInstance {
+                    stat: ProcessIOStat {
+                        pid,
+                        name: process.name.to_string(),
+                        display_name: process.display_name.as_deref().map(String::from),
+                        exe_path: process.exe_path.as_deref().map(String::from),
+                        command_line: process.command_line.as_deref().map(String::from),
+                        user: process.user.as_deref().map(String::from),
+                        system: process.system,
+                        read_bytes,
+                        write_bytes,
+                        total_bytes: read_bytes.saturating_add(write_bytes),
+                        read_speed,
+                        write_speed,
+                        pinned: false,
+                        known_writer: known_writers::identify(&process.name)
+                            .map(|writer| writer.id),
+                        io_priority: process.io_priority,
+                    },
+                    parent_pid: process.parent_pid,
+                    start_time: process.start_time,
+                    lifetime_read_bytes: process.read_bytes,
+                    lifetime_write_bytes: process.written_bytes,
+                }
+            })
+            .collect();
+        instances.sort_by_key(|p| p.stat.pid);
+        instances
+    }
+
+    /// Session bytes of OS processes versus the user's own
+    pub fn system_user_split(&self) -> SystemUserSplit {
+        self.split.clone()
+    }
+
+    pub fn map_sizes(&self) -> ProcessMapSizes {
+        ProcessMapSizes {
+            accumulators: self.accumulators.lock().map(|acc| acc.len()).unwrap_or(0),
+            dead_history: self.dead_process_history.len(),
+            snapshots: self.last_process_snapshot.len(),
+            evicted: self.evicted_count,
+        }
+    }
+
+    /// Per process name (read, write) bytes of the last tick
+    pub fn last_tick_deltas(&self) -> &TickDeltas {
+        &self.tick_deltas
+    }
+
+    /// Per process name command line of the instance that did the most I/O in
+    /// the last tick (names without I/O or a readable command line are missing)
+    pub fn last_tick_command_lines(&self) -> &TickCommandLines {
+        &self.tick_command_lines
+    }
+
+    /// Hands the last tick's deltas and command lines over, leaving them empty
+    pub fn take_last_tick(&mut self) -> (TickDeltas, TickCommandLines) {
+        (
+            std::mem::take(&mut self.tick_deltas),
+            std::mem::take(&mut self.tick_command_lines),
+        )
+    }
+
+    /// Returns true if any running process matches one of the given names (case-insensitive)
+    pub fn is_process_running(&self, names: &[&str]) -> bool {
+        self.processes
+            .values()
+            .any(|process| names.iter().any(|n| process.name.eq_ignore_ascii_case(n)))
+    }
+
+    /// Session totals per name, busiest first, with everything past the first
+    /// `limit` (0 = no limit) summed into an "Others" row
+    pub fn get_top_processes(&self, limit: usize) -> Vec<ProcessIOStat> {
+        #[derive(Default)]
+        struct Group<'a> {
+            exe_path: Option<String>,
+            /// Live instance with the most I/O, and its bytes
+            busiest: Option<(u64, &'a ProcessSnapshot)>,
+            read: u64,
+            write: u64,
+        }
+        let mut grouped: HashMap<String, Group> = HashMap::new();
 
         for (name, (r, w)) in &self.dead_process_history {
-            grouped.insert(name.clone(), (None, *r, *w));
+            grouped.insert(
+                name.clone(),
+                Group {
+                    read: *r,
+                    write: *w,
+                    ..Group::default()
+                },
+            );
         }
 
         if let Ok(acc_guard) = self.accumulators.lock() {
-            for (pid, process) in self.sys.processes() {
-                let pid_u32 = pid.as_u32();
-                if let Some(acc) = acc_guard.get(&pid_u32) {
+            for (pid, process) in &self.processes {
+                if let Some(acc) = acc_guard.get(pid) {
                     if acc.read_bytes == 0 && acc.write_bytes == 0 {
                         continue;
                     }
-                    let name = process.name().to_string_lossy().to_string();
-                    let exe_path = process.exe().map(|p| p.to_string_lossy().to_string());
-                    let entry = grouped.entry(name).or_insert((exe_path, 0, 0));
-                    entry.1 += acc.read_bytes;
-                    entry.2 += acc.write_bytes;
+                    let entry = grouped.entry(acc.name.to_string()).or_default();
+                    entry.read += acc.read_bytes;
+                    entry.write += acc.write_bytes;
+                    if *acc.name == *OTHERS {
+                        continue;
+                    }
+                    if entry.exe_path.is_none() {
+                        entry.exe_path = process.exe_path.as_deref().map(String::from);
+                    }
+                    let bytes = acc.read_bytes.saturating_add(acc.write_bytes);
+                    if entry.busiest.is_none_or(|(most, _)| bytes > most) {
+                        entry.busiest = Some((bytes, process));
+                    }
                 }
             }
         }
 
+        let speeds = self.name_speeds();
         let mut stats: Vec<ProcessIOStat> = grouped
             .into_iter()
-            .map(|(name, (exe_path, r, w))| ProcessIOStat {
-                pid: 0,
-                name,
-                exe_path,
-                read_bytes: r,
-                write_bytes: w,
-                total_bytes: r + w,
+            .map(|(name, group)| {
+                let (read_speed, write_speed) = speeds.get(&name).copied().unwrap_or((0, 0));
+                ProcessIOStat {
+                    pid: 0,
+                    known_writer: known_writers::identify(&name).map(|writer| writer.id),
+                    io_priority: group.busiest.and_then(|(_, process)| process.io_priority),
+                    name,
+                    display_name: group
+                        .busiest
+                        .and_then(|(_, process)| process.display_name.as_deref())
+                        .map(String::from),
+                    exe_path: group.exe_path,
+                    command_line: group
+                        .busiest
+                        .and_then(|(_, process)| process.command_line.as_deref())
+                        .map(String::from),
+                    user: group
+                        .busiest
+                        .and_then(|(_, process)| process.user.as_deref())
+                        .map(String::from),
+                    system: group.busiest.is_some_and(|(_, process)| process.system),
+                    read_bytes: group.read,
+                    write_bytes: group.write,
+                    total_bytes: group.read + group.write,
+                    read_speed,
+                    write_speed,
+                    pinned: false,
+                }
             })
             .collect();
 
-        stats.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes));
+        stats.sort_by_key(|s| std::cmp::Reverse(s.total_bytes));
+
+        // Pinned processes are listed whatever their rank, even without I/O yet
+        let pinned = self
+            .filter
+            .lock()
+            .map(|f| f.pinned.clone())
+            .unwrap_or_default();
+        for stat in &mut stats {
+            stat.pinned = pinned.iter().any(|p| p.eq_ignore_ascii_case(&stat.name));
+        }
+        for name in &pinned {
+            if !stats.iter().any(|s| s.name.eq_ignore_ascii_case(name)) {
+                stats.push(ProcessIOStat {
+                    pid: 0,
+                    name: name.clone(),
+                    display_name: None,
+                    exe_path: None,
+                    command_line: None,
+                    user: None,
+                    system: false,
+                    read_bytes: 0,
+                    write_bytes: 0,
+                    total_bytes: 0,
+                    read_speed: 0,
+                    write_speed: 0,
+                    pinned: true,
+                    known_writer: known_writers::identify(name).map(|writer| writer.id),
+                    io_priority: None,
+                });
+            }
+        }
 
-        // Calculate totals before truncation to handle "Others"
+        // Calculate totals before truncation to handle "Others"; processes
+        // untracked in allowlist-only mode are already in their own row
         let total_read: u64 = stats.iter().map(|s| s.read_bytes).sum();
         let total_write: u64 = stats.iter().map(|s| s.write_bytes).sum();
+        let total_read_speed: u64 = stats.iter().map(|s| s.read_speed).sum();
+        let total_write_speed: u64 = stats.iter().map(|s| s.write_speed).sum();
+        let untracked = stats.iter().position(|s| s.name == OTHERS);
+        if let Some(index) = untracked {
+            stats.remove(index);
+        }
 
-        if stats.len() > 50 {
-            stats.truncate(50);
+        if (limit > 0 && stats.len() > limit) || untracked.is_some() {
+            if limit > 0 && stats.len() > limit {
+                let rest = stats.split_off(limit);
+                stats.extend(rest.into_iter().filter(|s| s.pinned));
+            }
 
             let top_read: u64 = stats.iter().map(|s| s.read_bytes).sum();
             let top_write: u64 = stats.iter().map(|s| s.write_bytes).sum();
 
             let other_read = total_read.saturating_sub(top_read);
             let other_write = total_write.saturating_sub(top_write);
+            let top_read_speed: u64 = stats.iter().map(|s| s.read_speed).sum();
+            let top_write_speed: u64 = stats.iter().map(|s| s.write_speed).sum();
 
             if other_read > 0 || other_write > 0 {
                 stats.push(ProcessIOStat {
                     pid: 0,
-                    name: "Others".to_string(),
+                    name: OTHERS.to_string(),
+                    display_name: None,
                     exe_path: None,
+                    command_line: None,
+                    user: None,
+                    system: false,
                     read_bytes: other_read,
                     write_bytes: other_write,
                     total_bytes: other_read + other_write,
+                    read_speed: total_read_speed.saturating_sub(top_read_speed),
+                    write_speed: total_write_speed.saturating_sub(top_write_speed),
+                    pinned: false,
+                    known_writer: None,
+                    io_priority: None,
                 });
             }
         }
@@ -186,35 +1241,638 @@ impl ProcessMonitor {
         stats
     }
 
+    /// Returns the bytes of every process not handed out yet.
+    ///
+    /// The saved snapshot advances here: the caller queues the deltas in the
+    /// write coalescer, which keeps them (and re-queues them after a failed
+    /// flush) until they are written.
     pub fn get_deltas_for_db(&mut self) -> HashMap<String, (u64, u64)> {
-        let mut deltas: HashMap<String, (u64, u64)> = HashMap::new();
+        self.exited_names.clear();
+        let current_totals = self.current_totals();
+        let deltas = self.diff_against_snapshot(current_totals);
+        self.with_evicted_deltas(deltas)
+    }
 
-        // Aggregate current totals by process name across active + dead processes.
-        // This avoids snapshot collisions when multiple PIDs share the same name.
+    /// Returns the unsaved deltas of processes that exited since the last flush.
+    ///
+    /// Flushing these right away means a crash or forced close cannot lose the
+    /// attribution of short-lived processes that died between periodic flushes.
+    /// As with `get_deltas_for_db`, the write coalescer holds them until written.
+    pub fn take_exited_deltas(&mut self) -> HashMap<String, (u64, u64)> {
+        if self.exited_names.is_empty() {
+            return self.with_evicted_deltas(HashMap::new());
+        }
+
+        // Only the exited names are totalled: copying the whole history for
+        // them would cost a full clone of it on every tick a process exits
+        let exited: HashSet<String> = std::mem::take(&mut self.exited_names);
+        let mut current_totals: HashMap<String, (u64, u64)> = exited
+            .into_iter()
+            .filter_map(|name| {
+                let totals = self.dead_process_history.get(&name).copied()?;
+                Some((name, totals))
+            })
+            .collect();
+        if let Ok(acc_guard) = self.accumulators.lock() {
+            for acc in acc_guard.values() {
+                // A name can still have live instances next to the exited ones
+                if let Some(entry) = current_totals.get_mut(&*acc.name) {
+                    entry.0 = entry.0.saturating_add(acc.read_bytes);
+                    entry.1 = entry.1.saturating_add(acc.write_bytes);
+                }
+            }
+        }
+        let deltas = self.diff_against_snapshot(current_totals);
+        self.with_evicted_deltas(deltas)
+    }
+
+    /// What `get_deltas_for_db` would return, without advancing the snapshot
+    pub fn unsaved_deltas(&self) -> HashMap<String, (u64, u64)> {
+        let mut deltas = self.deltas_against_snapshot(&self.current_totals());
+        for (name, (r, w)) in &self.evicted_deltas {
+            let entry = deltas.entry(name.clone()).or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(*r);
+            entry.1 = entry.1.saturating_add(*w);
+        }
+        deltas
+    }
+
+    /// Adds the unsaved bytes of evicted names to `deltas`
+    fn with_evicted_deltas(
+        &mut self,
+        mut deltas: HashMap<String, (u64, u64)>,
+    ) -> HashMap<String, (u64, u64)> {
+        for (name, (r, w)) in self.evicted_deltas.drain() {
+            let entry = deltas.entry(name).or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(r);
+            entry.1 = entry.1.saturating_add(w);
+        }
+        deltas
+    }
+
+    /// Aggregates current totals by process name across active + dead processes.
+    /// This avoids snapshot collisions when multiple PIDs share the same name.
+    fn current_totals(&self) -> HashMap<String, (u64, u64)> {
         let mut current_totals: HashMap<String, (u64, u64)> = self.dead_process_history.clone();
         if let Ok(acc_guard) = self.accumulators.lock() {
             for acc in acc_guard.values() {
-                let entry = current_totals.entry(acc.name.clone()).or_insert((0, 0));
+                let entry = current_totals.entry(acc.name.to_string()).or_insert((0, 0));
                 entry.0 = entry.0.saturating_add(acc.read_bytes);
                 entry.1 = entry.1.saturating_add(acc.write_bytes);
             }
         }
+        current_totals
+    }
 
-        for (name, (cur_r, cur_w)) in current_totals {
-            let snapshot = self
-                .last_process_snapshot
-                .entry(name.clone())
-                .or_insert((0, 0));
-            let r_delta = cur_r.saturating_sub(snapshot.0);
-            let w_delta = cur_w.saturating_sub(snapshot.1);
+    /// Computes deltas against the last persisted snapshot and advances it.
+    fn diff_against_snapshot(
+        &mut self,
+        current_totals: HashMap<String, (u64, u64)>,
+    ) -> HashMap<String, (u64, u64)> {
+        let deltas = self.deltas_against_snapshot(&current_totals);
+        // Only names that did I/O get a snapshot entry
+        for name in deltas.keys() {
+            self.last_process_snapshot
+                .insert(name.clone(), current_totals[name]);
+        }
+        deltas
+    }
 
+    fn deltas_against_snapshot(
+        &self,
+        current_totals: &HashMap<String, (u64, u64)>,
+    ) -> HashMap<String, (u64, u64)> {
+        let mut deltas: HashMap<String, (u64, u64)> = HashMap::new();
+
+        for (name, &(cur_r, cur_w)) in current_totals {
+            let (saved_r, saved_w) = self
+                .last_process_snapshot
+                .get(name)
+                .copied()
+                .unwrap_or((0, 0));
+            let r_delta = cur_r.saturating_sub(saved_r);
+            let w_delta = cur_w.saturating_sub(saved_w);
             if r_delta > 0 || w_delta > 0 {
-                deltas.insert(name, (r_delta, w_delta));
-                snapshot.0 = cur_r;
-                snapshot.1 = cur_w;
+                deltas.insert(name.clone(), (r_delta, w_delta));
             }
         }
 
         deltas
     }
 }
+
+/// Moves up to `bytes` of the saved totals of `from` over to `to`
+fn move_saved(
+    snapshot: &mut HashMap<String, (u64, u64)>,
+    from: &str,
+    to: &str,
+    (read, write): (u64, u64),
+) {
+    let Some(saved) = snapshot.get_mut(from) else {
+        return;
+    };
+    let moved = (read.min(saved.0), write.min(saved.1));
+    saved.0 -= moved.0;
+    saved.1 -= moved.1;
+    if *saved == (0, 0) {
+        snapshot.remove(from);
+    }
+    if moved != (0, 0) {
+        let entry = snapshot.entry(to.to_string()).or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(moved.0);
+        entry.1 = entry.1.saturating_add(moved.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::top_processes::DEFAULT_TOP_LIMIT;
+
+    #[test]
+    fn test_exited_processes_move_to_history() {
+        let accumulators = create_accumulators();
+        let source = MockProcessSource::new(2, 3, 4, 100);
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+
+        let mut total_write = 0;
+        for _ in 0..20 {
+            total_write += monitor.update().1;
+        }
+
+        // Each process is seen for three ticks: baseline, then two ticks of I/O
+        // (the last two spawn rounds are cut short by the end of the loop)
+        assert_eq!(total_write, 18 * 2 * 200 + 2 * 100);
+        assert!(accumulators.lock().unwrap().len() <= 6);
+        assert!(monitor.map_sizes().dead_history <= 4);
+
+        let deltas = monitor.get_deltas_for_db();
+        let written: u64 = deltas.values().map(|(_, w)| w).sum();
+        assert_eq!(written, total_write);
+    }
+
+    #[test]
+    fn test_rebaseline_skips_paused_io() {
+        let accumulators = create_accumulators();
+        let source = MockProcessSource::new(1, 100, 10, 50);
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+
+        monitor.update();
+        monitor.rebaseline();
+        monitor.rebaseline();
+
+        // Three running processes, each counted for one tick after the pause
+        assert_eq!(monitor.update().1, 3 * 50);
+        let accumulated: u64 = accumulators
+            .lock()
+            .unwrap()
+            .values()
+            .map(|acc| acc.write_bytes)
+            .sum();
+        assert_eq!(accumulated, 3 * 50);
+    }
+
+    #[test]
+    fn test_dead_history_is_capped_without_losing_bytes() {
+        let accumulators = create_accumulators();
+        let source = MockProcessSource::new(5, 2, 1000, 10);
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        monitor.history_cap = 20;
+
+        let mut total_write = 0;
+        let mut saved_write = 0;
+        for _ in 0..100 {
+            total_write += monitor.update().1;
+            saved_write += monitor
+                .take_exited_deltas()
+                .values()
+                .map(|(_, w)| w)
+                .sum::<u64>();
+        }
+        // Peeking leaves the deltas for the next flush
+        let unsaved = monitor.unsaved_deltas();
+        let deltas = monitor.get_deltas_for_db();
+        assert_eq!(unsaved, deltas);
+        saved_write += deltas.values().map(|(_, w)| w).sum::<u64>();
+
+        let sizes = monitor.map_sizes();
+        assert!(sizes.dead_history <= 20);
+        assert!(sizes.snapshots <= 20 + sizes.accumulators);
+        assert!(sizes.evicted > 0);
+        assert_eq!(saved_write, total_write);
+    }
+
+    /// Two node.exe instances; the second writes twice as fast
+    struct NodeSource {
+        tick: u64,
+    }
+
+    impl ProcessSource for NodeSource {
+        fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>) {
+            self.tick += 1;
+            let node = |script: &str, written_bytes: u64| ProcessSnapshot {
+                name: "node.exe".into(),
+                exe_path: Some("C:\\nodejs\\node.exe".into()),
+                command_line: Some(format!("node {}", script).into()),
+                user: Some("builder".into()),
+                start_time: 1_700_000_000,
+                written_bytes,
+                ..Default::default()
+            };
+            *processes = HashMap::from([
+                (1, node("build.js", self.tick * 100)),
+                (2, node("server.js", self.tick * 200)),
+            ]);
+        }
+    }
+
+    #[test]
+    fn test_command_line_of_busiest_instance() {
+        let accumulators = create_accumulators();
+        let source = NodeSource { tick: 0 };
+        let filter = process_filter::create_process_filter();
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source))
+            .with_filter(Arc::clone(&filter));
+
+        monitor.update();
+        assert!(monitor.last_tick_command_lines().is_empty());
+        // Unnormalized names are shared with the snapshot, not copied
+        assert!(Arc::ptr_eq(
+            &accumulators.lock().unwrap()[&1].name,
+            &monitor.processes[&1].name
+        ));
+        // Arguments are only stored when opted in
+        monitor.update();
+        assert_eq!(
+            &*monitor.last_tick_command_lines()["node.exe"],
+            "C:\\nodejs\\node.exe"
+        );
+        filter.lock().unwrap().store_command_lines = true;
+        monitor.update();
+        assert_eq!(
+            &*monitor.last_tick_command_lines()["node.exe"],
+            "node server.js"
+        );
+        // The tick's maps share the accumulator's name and the snapshot's
+        // command line instead of copying them
+        let (name, _) = monitor
+            .last_tick_deltas()
+            .get_key_value("node.exe")
+            .unwrap();
+        let acc_guard = accumulators.lock().unwrap();
+        assert!([1, 2]
+            .iter()
+            .any(|pid| Arc::ptr_eq(name, &acc_guard[pid].name)));
+        drop(acc_guard);
+        assert!(Arc::ptr_eq(
+            &monitor.last_tick_command_lines()["node.exe"],
+            monitor.processes[&2].command_line.as_ref().unwrap()
+        ));
+
+        let top = monitor.get_top_processes(DEFAULT_TOP_LIMIT);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].command_line.as_deref(), Some("node server.js"));
+        assert_eq!(top[0].user.as_deref(), Some("builder"));
+        assert_eq!(top[0].write_bytes, 600);
+    }
+
+    #[test]
+    fn test_speeds_average_the_last_ticks() {
+        let accumulators = create_accumulators();
+        let source = NodeSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        let start = Instant::now();
+
+        monitor.update_at(start);
+        assert!(monitor.name_speeds().is_empty());
+        // 300 bytes every half second
+        for tick in 1..=4 {
+            monitor.update_at(start + std::time::Duration::from_millis(500 * tick));
+        }
+
+        assert_eq!(monitor.name_speeds()["node.exe"], (0, 600));
+        assert_eq!(monitor.process_speeds()[&2], (0, 400));
+        assert_eq!(
+            monitor.get_top_processes(DEFAULT_TOP_LIMIT)[0].write_speed,
+            600
+        );
+    }
+
+    #[test]
+    fn test_excluded_processes_are_not_counted() {
+        let accumulators = create_accumulators();
+        let filter = process_filter::create_process_filter();
+        let source = NodeSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source))
+            .with_filter(Arc::clone(&filter));
+
+        monitor.update();
+        assert_eq!(monitor.update().1, 300);
+        filter.lock().unwrap().excluded = vec!["NODE.EXE".to_string()];
+        assert_eq!(monitor.update().1, 0);
+        assert_eq!(monitor.update().1, 0);
+        assert!(accumulators.lock().unwrap().is_empty());
+
+        // Back in the list: only the bytes from now on count
+        filter.lock().unwrap().excluded.clear();
+        assert_eq!(monitor.update().1, 300);
+    }
+
+    #[test]
+    fn test_system_processes_are_recognized() {
+        assert!(is_system_process(4, None, None));
+        assert!(is_system_process(900, Some("user"), Some(0)));
+        assert!(is_system_process(900, Some("Network Service"), Some(1)));
+        assert!(!is_system_process(900, Some("user"), Some(1)));
+        assert!(!is_system_process(900, None, None));
+    }
+
+    #[test]
+    fn test_io_is_split_between_system_and_user() {
+        struct MixedSource {
+            tick: u64,
+        }
+        impl ProcessSource for MixedSource {
+            fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>) {
+                self.tick += 1;
+                let process = |name: &str, system: bool, written_bytes: u64| ProcessSnapshot {
+                    name: name.into(),
+                    display_name: system.then(|| "svchost (wuauserv)".into()),
+                    system,
+                    written_bytes,
+                    ..Default::default()
+                };
+                *processes = HashMap::from([
+                    (10, process("svchost.exe", true, self.tick * 50)),
+                    (20, process("code.exe", false, self.tick * 200)),
+                ]);
+            }
+        }
+
+        let accumulators = create_accumulators();
+        let mut monitor = ProcessMonitor::with_source(
+            Arc::clone(&accumulators),
+            Box::new(MixedSource { tick: 0 }),
+        );
+        for _ in 0..3 {
+            monitor.update();
+        }
+
+        let split = monitor.system_user_split();
+        assert_eq!(
+            (split.system_write_bytes, split.user_write_bytes),
+            (100, 400)
+        );
+        let top = monitor.get_top_processes(DEFAULT_TOP_LIMIT);
+        assert_eq!((top[0].name.as_str(), top[0].system), ("code.exe", false));
+        assert_eq!((top[1].name.as_str(), top[1].system), ("svchost.exe", true));
+        // Hosted services are only a label; the row stays keyed by the exe name
+        assert_eq!(top[1].display_name.as_deref(), Some("svchost (wuauserv)"));
+    }
+
+    #[test]
+    fn test_own_process_can_be_excluded() {
+        let accumulators = create_accumulators();
+        let filter = process_filter::create_process_filter();
+        let source = NodeSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source))
+            .with_filter(Arc::clone(&filter));
+        monitor.own_pid = 2;
+
+        monitor.update();
+        assert_eq!(monitor.update().1, 300);
+        filter.lock().unwrap().exclude_self = true;
+        assert_eq!(monitor.update().1, 100);
+        assert!(!accumulators.lock().unwrap().contains_key(&2));
+    }
+
+    #[test]
+    fn test_allowlist_only_lumps_the_rest_into_others() {
+        let accumulators = create_accumulators();
+        let filter = process_filter::create_process_filter();
+        {
+            let mut filter = filter.lock().unwrap();
+            filter.allowlist_only = true;
+            filter.allowed = vec!["sqlservr.exe".to_string()];
+        }
+        let source = NodeSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source))
+            .with_filter(filter);
+
+        monitor.update();
+        // Untracked bytes still count towards the totals
+        assert_eq!(monitor.update().1, 300);
+        assert_eq!(
+            monitor
+                .last_tick_deltas()
+                .keys()
+                .map(|name| &**name)
+                .collect::<Vec<_>>(),
+            [OTHERS]
+        );
+        assert!(monitor.last_tick_command_lines().is_empty());
+
+        let top = monitor.get_top_processes(DEFAULT_TOP_LIMIT);
+        assert_eq!(top.len(), 1);
+        assert_eq!((top[0].name.as_str(), top[0].write_bytes), (OTHERS, 300));
+        assert_eq!(top[0].exe_path, None);
+        assert_eq!(monitor.get_deltas_for_db()[OTHERS], (0, 300));
+    }
+
+    /// One process that writes 100 bytes on its second tick, then idles
+    struct IdleAfterWriteSource {
+        tick: u64,
+    }
+
+    impl ProcessSource for IdleAfterWriteSource {
+        fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>) {
+            self.tick += 1;
+            *processes = HashMap::from([(
+                7,
+                ProcessSnapshot {
+                    name: "App-1.2.exe".into(),
+                    start_time: 1_700_000_000,
+                    written_bytes: self.tick.min(2) * 100,
+                    ..Default::default()
+                },
+            )]);
+        }
+    }
+
+    #[test]
+    fn test_renaming_live_processes_saves_nothing_twice() {
+        let accumulators = create_accumulators();
+        let filter = process_filter::create_process_filter();
+        let source = IdleAfterWriteSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source))
+            .with_filter(Arc::clone(&filter));
+
+        monitor.update();
+        monitor.update();
+        assert_eq!(monitor.get_deltas_for_db()["App-1.2.exe"], (0, 100));
+
+        // Normalization changed mid-session
+        filter.lock().unwrap().names.strip_versions = true;
+        monitor.update();
+        assert_eq!(&*accumulators.lock().unwrap()[&7].name, "App.exe");
+        assert!(monitor.get_deltas_for_db().is_empty());
+
+        // Allowlist-only mode moves the process to Others
+        filter.lock().unwrap().allowlist_only = true;
+        monitor.update();
+        assert_eq!(&*accumulators.lock().unwrap()[&7].name, OTHERS);
+        assert!(monitor.get_deltas_for_db().is_empty());
+    }
+
+    #[test]
+    fn test_sparkline_keeps_the_last_minute() {
+        let accumulators = create_accumulators();
+        let source = NodeSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        let start = Instant::now();
+
+        for second in 0..=SPARKLINE_SECS + 10 {
+            monitor.update_at(start + std::time::Duration::from_secs(second));
+        }
+
+        let points = monitor.sparkline("node.exe");
+        assert_eq!(points.len(), SPARKLINE_SECS as usize + 1);
+        assert!(points.iter().all(|p| p.write_bytes == 300));
+        let idle = monitor.sparkline("other.exe");
+        assert_eq!(idle.len(), points.len());
+        assert!(idle.iter().all(|p| p.write_bytes == 0));
+    }
+
+    #[test]
+    fn test_instances_keep_their_own_counters() {
+        let accumulators = create_accumulators();
+        let source = NodeSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        for _ in 0..3 {
+            monitor.update();
+        }
+
+        let instances: Vec<(u32, u64, u64, u64)> = monitor
+            .instances("NODE.EXE")
+            .iter()
+            .map(|p| {
+                (
+                    p.stat.pid,
+                    p.stat.write_bytes,
+                    p.lifetime_write_bytes,
+                    p.start_time,
+                )
+            })
+            .collect();
+        // The first tick is the baseline: the session counts from there
+        assert_eq!(
+            instances,
+            [(1, 200, 300, 1_700_000_000), (2, 400, 600, 1_700_000_000)]
+        );
+        assert!(monitor.instances("other.exe").is_empty());
+    }
+
+    #[test]
+    fn test_exe_path_of_idle_and_exited_processes() {
+        // Idle processes are in no top list but still have a path
+        let source = MockProcessSource::new(1, 100, 100, 0);
+        let mut monitor = ProcessMonitor::with_source(create_accumulators(), Box::new(source));
+        monitor.update();
+        assert_eq!(
+            monitor.exe_path("FAKE-1.EXE").as_deref(),
+            Some("C:\\Fake\\fake-1.exe")
+        );
+
+        // Each process lives two ticks; the first one is gone after the third
+        let source = MockProcessSource::new(1, 2, 100, 50);
+        let mut monitor = ProcessMonitor::with_source(create_accumulators(), Box::new(source));
+        for _ in 0..4 {
+            monitor.update();
+        }
+        assert!(!monitor.processes.contains_key(&1));
+        assert_eq!(
+            monitor.exe_path("fake-1.exe").as_deref(),
+            Some("C:\\Fake\\fake-1.exe")
+        );
+        assert_eq!(monitor.exe_path("other.exe"), None);
+    }
+
+    #[test]
+    fn test_top_list_follows_the_limit() {
+        let accumulators = create_accumulators();
+        let source = MockProcessSource::new(10, 100, 10, 10);
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        for _ in 0..3 {
+            monitor.update();
+        }
+
+        let all = monitor.get_top_processes(0);
+        assert_eq!(all.len(), 10);
+        let top = monitor.get_top_processes(3);
+        assert_eq!(top.len(), 4);
+        assert_eq!(top[3].name, OTHERS);
+        let total = |stats: &[ProcessIOStat]| stats.iter().map(|s| s.total_bytes).sum::<u64>();
+        assert_eq!(total(&top), total(&all));
+    }
+
+    #[test]
+    fn test_pinned_processes_are_always_listed() {
+        let accumulators = create_accumulators();
+        let filter = process_filter::create_process_filter();
+        filter.lock().unwrap().pinned = vec!["FAKE-0.exe".to_string(), "idle.exe".to_string()];
+        let source = MockProcessSource::new(10, 100, 10, 10);
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source))
+            .with_filter(filter);
+        for _ in 0..3 {
+            monitor.update();
+        }
+
+        let top = monitor.get_top_processes(1);
+        let pinned: Vec<&str> = top
+            .iter()
+            .filter(|s| s.pinned)
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(pinned.len(), 2);
+        assert!(pinned.contains(&"fake-0.exe") && pinned.contains(&"idle.exe"));
+        assert_eq!(top.last().unwrap().name, OTHERS);
+        let total = |stats: &[ProcessIOStat]| stats.iter().map(|s| s.total_bytes).sum::<u64>();
+        assert_eq!(total(&top), total(&monitor.get_top_processes(0)));
+    }
+
+    #[test]
+    fn test_recycled_pid_gets_a_fresh_baseline() {
+        /// PID 7 is an updater for two ticks, then a game that has already
+        /// written a lot by the time it is first seen
+        struct RecyclingSource {
+            tick: u64,
+        }
+        impl ProcessSource for RecyclingSource {
+            fn refresh(&mut self, processes: &mut HashMap<u32, ProcessSnapshot>) {
+                self.tick += 1;
+                let (name, start_time, written_bytes) = if self.tick <= 2 {
+                    ("updater.exe", 100, self.tick * 10)
+                } else {
+                    ("game.exe", 200, 5000 + self.tick * 100)
+                };
+                let process = ProcessSnapshot {
+                    name: name.into(),
+                    start_time,
+                    written_bytes,
+                    ..Default::default()
+                };
+                *processes = HashMap::from([(7, process)]);
+            }
+        }
+
+        let accumulators = create_accumulators();
+        let source = RecyclingSource { tick: 0 };
+        let mut monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        let written: Vec<u64> = (0..4).map(|_| monitor.update().1).collect();
+        assert_eq!(written, [0, 10, 0, 100]);
+
+        assert_eq!(monitor.take_exited_deltas()["updater.exe"], (0, 10));
+        let acc = accumulators.lock().unwrap()[&7].clone();
+        assert_eq!((&*acc.name, acc.write_bytes), ("game.exe", 100));
+    }
+}
@@ -0,0 +1,148 @@
+//! How process names are keyed in the stats and the history. By default the
+//! name is kept as reported, so "Code.exe" and "code.exe" or an updater's
+//! "app-1.2.exe" and "app-1.3.exe" end up as separate rows; normalization
+//! folds them into one, optionally keyed by the full exe path instead.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameNormalization {
+    /// "Code.exe" and "code.exe" are the same process (keys are lowercased)
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Drop version suffixes ("app-1.2.3.exe" -> "app.exe"), including those of
+    /// the folders in an exe path ("app-1.0.9\Discord.exe")
+    #[serde(default)]
+    pub strip_versions: bool,
+    /// Key by exe path, telling apart same-named executables in different
+    /// folders (falls back to the name when the path is unreadable)
+    #[serde(default)]
+    pub by_exe_path: bool,
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '_' | ' ')
+}
+
+/// `stem` without one trailing version: "app-1.2", "app v2" and "python3.11"
+/// lose it, "d3d11" (no separator or dot) keeps its digits
+fn strip_version_once(stem: &str) -> &str {
+    let rest = stem.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let version = &stem[rest.len()..];
+    if !version.bytes().any(|b| b.is_ascii_digit()) {
+        return stem;
+    }
+    let rest = match rest.strip_suffix(['v', 'V']) {
+        Some(before) if before.ends_with(is_separator) => before,
+        _ => rest,
+    };
+    if let Some(before) = rest.strip_suffix(is_separator) {
+        if !before.is_empty() {
+            return before;
+        }
+    }
+    if version.trim_matches('.').contains('.') && rest.ends_with(|c: char| c.is_alphabetic()) {
+        return rest;
+    }
+    stem
+}
+
+/// One file or folder name without its version suffixes (the extension stays)
+fn strip_versions(component: &str) -> String {
+    let (stem, extension) = match component.rfind('.') {
+        Some(dot)
+            if dot > 0
+                && component[dot + 1..]
+                    .chars()
+                    .all(|c| c.is_ascii_alphabetic())
+                && dot + 1 < component.len() =>
+        {
+            component.split_at(dot)
+        }
+        _ => (component, ""),
+    };
+    let mut stem = stem;
+    loop {
+        let stripped = strip_version_once(stem);
+        if stripped == stem {
+            break;
+        }
+        stem = stripped;
+    }
+    format!("{}{}", stem, extension)
+}
+
+impl NameNormalization {
+    pub fn is_enabled(&self) -> bool {
+        self.case_insensitive || self.strip_versions || self.by_exe_path
+    }
+
+    /// The key a process is accumulated and recorded under
+    pub fn normalize(&self, name: &str, exe_path: Option<&str>) -> String {
+        let key = exe_path.filter(|_| self.by_exe_path).unwrap_or(name);
+        let key = if self.strip_versions {
+            key.split_inclusive(['\\', '/'])
+                .map(|part| {
+                    let component = part.trim_end_matches(['\\', '/']);
+                    format!("{}{}", strip_versions(component), &part[component.len()..])
+                })
+                .collect()
+        } else {
+            key.to_string()
+        };
+        if self.case_insensitive {
+            key.to_lowercase()
+        } else {
+            key
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_suffixes_are_stripped() {
+        let names = NameNormalization {
+            strip_versions: true,
+            ..NameNormalization::default()
+        };
+        let cases = [
+            ("app-1.2.3.exe", "app.exe"),
+            ("App_2.exe", "App.exe"),
+            ("tool v2.1.exe", "tool.exe"),
+            ("python3.11.exe", "python.exe"),
+            ("app-1.2-3.exe", "app.exe"),
+            ("d3d11.exe", "d3d11.exe"),
+            ("python3.exe", "python3.exe"),
+            ("7z.exe", "7z.exe"),
+            ("svchost (wuauserv)", "svchost (wuauserv)"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(names.normalize(name, None), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_paths_and_case() {
+        let names = NameNormalization {
+            case_insensitive: true,
+            strip_versions: true,
+            by_exe_path: true,
+        };
+        let path = "C:\\Users\\A\\Discord\\app-1.0.9\\Discord.exe";
+        assert_eq!(
+            names.normalize("Discord.exe", Some(path)),
+            "c:\\users\\a\\discord\\app\\discord.exe"
+        );
+        assert_eq!(names.normalize("Code.exe", None), "code.exe");
+        // Normalizing twice changes nothing
+        let once = names.normalize("App-2.0.exe", None);
+        assert_eq!(names.normalize(&once, None), once);
+        assert_eq!(
+            NameNormalization::default().normalize("Code.exe", Some(path)),
+            "Code.exe"
+        );
+    }
+}
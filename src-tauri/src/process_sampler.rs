@@ -0,0 +1,315 @@
+//! Process scanning on a dedicated thread. Walking every process takes tens
+//! of milliseconds on a busy machine, so the monitor loop only sends requests
+//! and awaits the results instead of blocking the async runtime.
+
+use crate::models::{ProcessIOStat, ProcessInstance};
+use crate::process_groups::{ApplicationGroup, ProcessTreeNode};
+use crate::process_monitor::{
+    ProcessMapSizes, ProcessMonitor, SparklinePoint, SystemUserSplit, TickCommandLines, TickDeltas,
+};
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Per process name (read, write) bytes
+pub type ProcessDeltas = HashMap<String, (u64, u64)>;
+
+/// What the monitor loop wants besides the tick deltas
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickRequest {
+    /// Build the top-processes list (only while a view displays it)
+    pub top_processes: bool,
+    /// Check whether any of these processes is running
+    pub running: Option<&'static [&'static str]>,
+}
+
+/// Result of one process scan
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTick {
+    pub read_delta: u64,
+    pub write_delta: u64,
+    /// Deltas of this tick by process name
+    pub processes: TickDeltas,
+    /// Deltas of processes that exited since the last tick
+    pub exited: ProcessDeltas,
+    /// Command lines of this tick's busiest instance by process name
+    pub command_lines: TickCommandLines,
+    pub top_processes: Option<Vec<ProcessIOStat>>,
+    /// Set when `TickRequest::running` was given
+    pub running: Option<bool>,
+    pub map_sizes: ProcessMapSizes,
+    pub counters_available: bool,
+}
+
+enum Command {
+    Sample(TickRequest, oneshot::Sender<ProcessTick>),
+    TakeDeltas(oneshot::Sender<ProcessDeltas>),
+    UnsavedDeltas(oneshot::Sender<ProcessDeltas>),
+    TopProcesses(Option<usize>, oneshot::Sender<Vec<ProcessIOStat>>),
+    ProcessNames(oneshot::Sender<HashMap<u32, String>>),
+    ApplicationGroups(oneshot::Sender<Vec<ApplicationGroup>>),
+    ProcessTree(oneshot::Sender<Vec<ProcessTreeNode>>),
+    Sparkline(String, oneshot::Sender<Vec<SparklinePoint>>),
+    Instances(String, oneshot::Sender<Vec<ProcessInstance>>),
+    ExePath(String, oneshot::Sender<Option<String>>),
+    SystemUserSplit(oneshot::Sender<SystemUserSplit>),
+    Reset,
+    Rebaseline,
+}
+
+/// Handle to the sampling thread; the thread exits when every handle is dropped
+#[derive(Clone)]
+pub struct ProcessSampler {
+    commands: mpsc::Sender<Command>,
+}
+
+/// The monitor's sampler, for commands (None until the monitor loop starts)
+pub type SharedSampler = Arc<Mutex<Option<ProcessSampler>>>;
+
+pub fn create_shared_sampler() -> SharedSampler {
+    Arc::new(Mutex::new(None))
+}
+
+pub fn current(shared: &SharedSampler) -> Option<ProcessSampler> {
+    shared.lock().ok().and_then(|guard| guard.clone())
+}
+
+impl ProcessSampler {
+    pub fn spawn(mut monitor: ProcessMonitor) -> std::io::Result<Self> {
+        let (commands, receiver) = mpsc::channel::<Command>();
+        std::thread::Builder::new()
+            .name("process-sampler".to_string())
+            .spawn(move || {
+                while let Ok(command) = receiver.recv() {
+                    match command {
+                        Command::Sample(request, reply) => {
+                            let _ = reply.send(scan(&mut monitor, request));
+                        }
+                        Command::TakeDeltas(reply) => {
+                            let _ = reply.send(monitor.get_deltas_for_db());
+                        }
+                        Command::UnsavedDeltas(reply) => {
+                            let _ = reply.send(monitor.unsaved_deltas());
+                        }
+                        Command::TopProcesses(limit, reply) => {
+                            let limit = limit.unwrap_or_else(|| monitor.top_limit());
+                            let _ = reply.send(monitor.get_top_processes(limit));
+                        }
+                        Command::ProcessNames(reply) => {
+                            let _ = reply.send(monitor.process_names());
+                        }
+                        Command::ApplicationGroups(reply) => {
+                            let _ = reply.send(monitor.application_groups());
+                        }
+                        Command::ProcessTree(reply) => {
+                            let _ = reply.send(monitor.process_tree());
+                        }
+                        Command::Sparkline(name, reply) => {
+                            let _ = reply.send(monitor.sparkline(&name));
+                        }
+                        Command::Instances(name, reply) => {
+                            let _ = reply.send(monitor.instances(&name));
+                        }
+                        Command::ExePath(name, reply) => {
+                            let _ = reply.send(monitor.exe_path(&name));
+                        }
+                        Command::SystemUserSplit(reply) => {
+                            let _ = reply.send(monitor.system_user_split());
+                        }
+                        Command::Reset => monitor.reset(),
+                        Command::Rebaseline => monitor.rebaseline(),
+                    }
+                }
+            })?;
+        Ok(Self { commands })
+    }
+
+    /// Scans the processes; `None` if the sampling thread is gone
+    pub async fn sample(&self, request: TickRequest) -> Option<ProcessTick> {
+        let (reply, result) = oneshot::channel();
+        self.commands.send(Command::Sample(request, reply)).ok()?;
+        result.await.ok()
+    }
+
+    /// Accumulated per-process deltas not yet written to the database
+    pub async fn take_deltas(&self) -> ProcessDeltas {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::TakeDeltas(reply)).is_err() {
+            return ProcessDeltas::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Per-name bytes the next `take_deltas` would hand out, left in place
+    pub async fn unsaved_deltas(&self) -> ProcessDeltas {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::UnsavedDeltas(reply)).is_err() {
+            return ProcessDeltas::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Session top-process list as of the last scan (no new scan), with
+    /// `limit` rows (0 = every process) or the configured number
+    pub async fn top_processes(&self, limit: Option<usize>) -> Vec<ProcessIOStat> {
+        let (reply, result) = oneshot::channel();
+        if self
+            .commands
+            .send(Command::TopProcesses(limit, reply))
+            .is_err()
+        {
+            return Vec::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// pid -> name as of the last scan
+    pub async fn process_names(&self) -> HashMap<u32, String> {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::ProcessNames(reply)).is_err() {
+            return HashMap::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Live processes grouped into application trees, as of the last scan
+    pub async fn application_groups(&self) -> Vec<ApplicationGroup> {
+        let (reply, result) = oneshot::channel();
+        if self
+            .commands
+            .send(Command::ApplicationGroups(reply))
+            .is_err()
+        {
+            return Vec::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Live processes as a parent/child tree, as of the last scan
+    pub async fn process_tree(&self) -> Vec<ProcessTreeNode> {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::ProcessTree(reply)).is_err() {
+            return Vec::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Recent per-tick bytes of one process name
+    pub async fn sparkline(&self, name: String) -> Vec<SparklinePoint> {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::Sparkline(name, reply)).is_err() {
+            return Vec::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Running instances of one process name, as of the last scan
+    pub async fn instances(&self, name: String) -> Vec<ProcessInstance> {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::Instances(name, reply)).is_err() {
+            return Vec::new();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    /// Executable of a process name, running or exited, as of the last scan
+    pub async fn exe_path(&self, name: String) -> Option<String> {
+        let (reply, result) = oneshot::channel();
+        self.commands.send(Command::ExePath(name, reply)).ok()?;
+        result.await.ok().flatten()
+    }
+
+    /// Session bytes of OS processes versus the user's own
+    pub async fn system_user_split(&self) -> SystemUserSplit {
+        let (reply, result) = oneshot::channel();
+        if self.commands.send(Command::SystemUserSplit(reply)).is_err() {
+            return SystemUserSplit::default();
+        }
+        result.await.unwrap_or_default()
+    }
+
+    pub fn reset(&self) {
+        let _ = self.commands.send(Command::Reset);
+    }
+
+    /// New baselines after a pause or a sleep/resume gap
+    pub fn rebaseline(&self) {
+        let _ = self.commands.send(Command::Rebaseline);
+    }
+}
+
+fn scan(monitor: &mut ProcessMonitor, request: TickRequest) -> ProcessTick {
+    let (read_delta, write_delta) = monitor.update();
+    let (processes, command_lines) = monitor.take_last_tick();
+    ProcessTick {
+        read_delta,
+        write_delta,
+        processes,
+        exited: monitor.take_exited_deltas(),
+        command_lines,
+        top_processes: request
+            .top_processes
+            .then(|| monitor.get_top_processes(monitor.top_limit())),
+        running: request
+            .running
+            .map(|names| monitor.is_process_running(names)),
+        map_sizes: monitor.map_sizes(),
+        counters_available: monitor.counters_available(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_monitor::{create_accumulators, MockProcessSource};
+
+    #[tokio::test]
+    async fn test_sampler_thread_answers_in_order() {
+        let source = MockProcessSource::new(1, 100, 10, 50);
+        let monitor = ProcessMonitor::with_source(create_accumulators(), Box::new(source));
+        let sampler = ProcessSampler::spawn(monitor).unwrap();
+
+        // The first scan only takes baselines
+        let first = sampler.sample(TickRequest::default()).await.unwrap();
+        assert_eq!(first.write_delta, 0);
+        assert!(first.top_processes.is_none());
+        assert!(first.running.is_none());
+
+        let second = sampler
+            .sample(TickRequest {
+                top_processes: true,
+                running: Some(&["fake-1.exe"]),
+            })
+            .await
+            .unwrap();
+        assert_eq!(second.write_delta, 50);
+        assert_eq!(second.running, Some(true));
+        assert!(!second.top_processes.unwrap().is_empty());
+
+        // Commands are handled in order: the rebaseline lands before the next
+        // scan, which counts one tick for each of the three running processes
+        sampler.rebaseline();
+        let third = sampler.sample(TickRequest::default()).await.unwrap();
+        assert_eq!(third.write_delta, 3 * 50);
+
+        let deltas = sampler.take_deltas().await;
+        assert_eq!(deltas.values().map(|(_, w)| w).sum::<u64>(), 50 + 3 * 50);
+        assert!(sampler.take_deltas().await.is_empty());
+        assert!(!sampler.top_processes(None).await.is_empty());
+        assert!(sampler
+            .process_names()
+            .await
+            .values()
+            .any(|name| name == "fake-1.exe"));
+
+        // Commands see the same thread through the shared slot
+        let shared = create_shared_sampler();
+        assert!(current(&shared).is_none());
+        *shared.lock().unwrap() = Some(sampler.clone());
+        assert!(!current(&shared)
+            .unwrap()
+            .top_processes(None)
+            .await
+            .is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::db;
+use sqlx::{Pool, Sqlite};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// app_settings key holding the monitor interval in milliseconds
+pub const SAMPLE_INTERVAL_KEY: &str = "sample_interval_ms";
+
+pub const DEFAULT_INTERVAL_MS: u64 = 1000;
+pub const MIN_INTERVAL_MS: u64 = 250;
+pub const MAX_INTERVAL_MS: u64 = 10_000;
+
+/// Current monitor interval, read by the loop on every tick so changes apply immediately
+pub type SharedInterval = Arc<AtomicU64>;
+
+pub fn create_interval() -> SharedInterval {
+    Arc::new(AtomicU64::new(DEFAULT_INTERVAL_MS))
+}
+
+pub fn clamp_interval_ms(interval_ms: u64) -> u64 {
+    interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS)
+}
+
+pub fn current_ms(interval: &SharedInterval) -> u64 {
+    clamp_interval_ms(interval.load(Ordering::Relaxed))
+}
+
+/// How many ticks make up `period` (at least one), for work done on a fixed cadence
+pub fn ticks_per(period: Duration, interval_ms: u64) -> u64 {
+    (period.as_millis() as u64 / interval_ms.max(1)).max(1)
+}
+
+/// A pause between ticks longer than this many intervals (and at least
+/// `MIN_GAP`) means the machine slept or the process was frozen
+const GAP_INTERVALS: u64 = 5;
+const MIN_GAP: Duration = Duration::from_secs(10);
+
+/// True if `elapsed` since the previous tick is a gap whose counter jump
+/// must not be counted as I/O of a single tick
+pub fn is_time_gap(elapsed: Duration, interval_ms: u64) -> bool {
+    elapsed
+        > MIN_GAP.max(Duration::from_millis(
+            interval_ms.saturating_mul(GAP_INTERVALS),
+        ))
+}
+
+/// Converts the bytes of one tick to bytes/sec
+pub fn per_second(bytes: u64, interval_ms: u64) -> u64 {
+    (bytes as u128 * 1000 / interval_ms.max(1) as u128) as u64
+}
+
+/// Converts bytes counted over a measured (monotonic) duration to bytes/sec
+pub fn per_second_over(bytes: u64, elapsed: Duration) -> u64 {
+    (bytes as u128 * 1_000_000 / elapsed.as_micros().max(1)) as u64
+}
+
+/// Reads the persisted interval (default when never set or invalid)
+pub async fn load_interval_ms(pool: &Pool<Sqlite>) -> Result<u64, sqlx::Error> {
+    Ok(db::get_setting(pool, SAMPLE_INTERVAL_KEY)
+        .await?
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(clamp_interval_ms)
+        .unwrap_or(DEFAULT_INTERVAL_MS))
+}
+
+pub async fn save_interval_ms(pool: &Pool<Sqlite>, interval_ms: u64) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, SAMPLE_INTERVAL_KEY, &interval_ms.to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cadences_follow_interval() {
+        assert_eq!(ticks_per(Duration::from_secs(5), 1000), 5);
+        assert_eq!(ticks_per(Duration::from_secs(5), 250), 20);
+        assert_eq!(ticks_per(Duration::from_secs(5), 10_000), 1);
+    }
+
+    #[test]
+    fn test_per_second_scales_tick_bytes() {
+        assert_eq!(per_second(1000, 1000), 1000);
+        assert_eq!(per_second(1000, 250), 4000);
+        assert_eq!(per_second(1000, 10_000), 100);
+        assert_eq!(per_second_over(1500, Duration::from_millis(1500)), 1000);
+        assert_eq!(clamp_interval_ms(10), MIN_INTERVAL_MS);
+        assert_eq!(clamp_interval_ms(60_000), MAX_INTERVAL_MS);
+    }
+
+    #[test]
+    fn test_time_gap() {
+        assert!(!is_time_gap(Duration::from_secs(3), 1000));
+        assert!(is_time_gap(Duration::from_secs(11), 1000));
+        // On battery (5 s ticks) a slow tick is not a gap
+        assert!(!is_time_gap(Duration::from_secs(20), 5000));
+        assert!(is_time_gap(Duration::from_secs(600), 5000));
+    }
+}
@@ -1,7 +1,11 @@
-use sqlx::{Pool, Sqlite};
-use std::sync::Arc;
+use crate::db::{self, current_pool, SharedPool};
+use crate::db_cleanup::{
+    analyze_database, cleanup_old_data, load_retention_policy, vacuum_database,
+};
+use crate::storage::{self, SharedStorage};
+use crate::volume_monitor;
+use crate::write_coalescer::{self, WriteCoalescer};
 use tokio::time::{interval, Duration};
-use crate::db_cleanup::{cleanup_old_data, vacuum_database, analyze_database, RetentionPolicy};
 
 /// Starts the cleanup scheduler that runs every 24 hours
 ///
@@ -9,17 +13,24 @@ use crate::db_cleanup::{cleanup_old_data, vacuum_database, analyze_database, Ret
 /// and performs VACUUM to reclaim unused space.
 ///
 /// # Arguments
-/// * `pool` - Shared SQLite connection pool wrapped in Arc
-pub async fn start_cleanup_scheduler(pool: Arc<Pool<Sqlite>>) {
+/// * `pool` - Shared pool slot (runs are skipped while the database is unavailable)
+/// * `storage` - Time series backend slot
+pub async fn start_cleanup_scheduler(shared_pool: SharedPool, shared_storage: SharedStorage) {
     // 24 hours interval for cleanup (86400 seconds)
     let mut cleanup_interval = interval(Duration::from_secs(86400));
 
     loop {
         cleanup_interval.tick().await;
+        let (Some(pool), Some(store)) = (
+            current_pool(&shared_pool),
+            storage::current(&shared_storage),
+        ) else {
+            continue;
+        };
 
-        let policy = RetentionPolicy::default();
+        let policy = load_retention_policy(&pool).await;
 
-        match cleanup_old_data(&pool, &policy).await {
+        match cleanup_old_data(&pool, store.as_ref(), &policy).await {
             Ok(count) => {
                 println!(
                     "[Cleanup] Successfully deleted {} old records older than {} days",
@@ -45,13 +56,16 @@ pub async fn start_cleanup_scheduler(pool: Arc<Pool<Sqlite>>) {
 /// query planner make better decisions about query optimization.
 ///
 /// # Arguments
-/// * `pool` - Shared SQLite connection pool wrapped in Arc
-pub async fn start_analyze_scheduler(pool: Arc<Pool<Sqlite>>) {
+/// * `pool` - Shared pool slot (runs are skipped while the database is unavailable)
+pub async fn start_analyze_scheduler(shared_pool: SharedPool) {
     // 7 days interval for ANALYZE (604800 seconds)
     let mut analyze_interval = interval(Duration::from_secs(604800));
 
     loop {
         analyze_interval.tick().await;
+        let Some(pool) = current_pool(&shared_pool) else {
+            continue;
+        };
 
         match analyze_database(&pool).await {
             Ok(_) => println!("[Analyze] Query optimization completed successfully"),
@@ -68,16 +82,19 @@ pub async fn start_analyze_scheduler(pool: Arc<Pool<Sqlite>>) {
 /// The PASSIVE mode is used to avoid blocking readers.
 ///
 /// # Arguments
-/// * `pool` - Shared SQLite connection pool wrapped in Arc
-pub async fn start_wal_checkpoint_scheduler(pool: Arc<Pool<Sqlite>>) {
+/// * `pool` - Shared pool slot (runs are skipped while the database is unavailable)
+pub async fn start_wal_checkpoint_scheduler(shared_pool: SharedPool) {
     // 6 hours interval for WAL checkpoint (21600 seconds)
     let mut checkpoint_interval = interval(Duration::from_secs(21600));
 
     loop {
         checkpoint_interval.tick().await;
+        let Some(pool) = current_pool(&shared_pool) else {
+            continue;
+        };
 
         match sqlx::query("PRAGMA wal_checkpoint(PASSIVE)")
-            .execute(&*pool)
+            .execute(&pool)
             .await
         {
             Ok(_) => {
@@ -88,6 +105,52 @@ pub async fn start_wal_checkpoint_scheduler(pool: Arc<Pool<Sqlite>>) {
     }
 }
 
+/// How long free space samples are kept (trends over months stay visible)
+const FREE_SPACE_RETENTION_DAYS: u64 = 365;
+
+/// Starts the free space sampler that runs every 5 minutes
+///
+/// Records total/free bytes per mounted volume into free_space_history so the
+/// UI can plot how free space changes over weeks. Samples older than a year
+/// are pruned once a day.
+///
+/// # Arguments
+/// * `pool` - Shared pool slot (runs are skipped while the database is unavailable)
+/// * `writes` - Samples are queued here and written with the monitor's next flush
+pub async fn start_free_space_scheduler(shared_pool: SharedPool, writes: WriteCoalescer) {
+    // 5 minutes interval for free space sampling (300 seconds)
+    let mut sample_interval = interval(Duration::from_secs(300));
+    let mut samples_taken: u64 = 0;
+
+    loop {
+        sample_interval.tick().await;
+        let Some(pool) = current_pool(&shared_pool) else {
+            continue;
+        };
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let samples =
+            tokio::task::spawn_blocking(move || volume_monitor::sample_free_space(timestamp))
+                .await
+                .unwrap_or_default();
+
+        write_coalescer::queue_free_space(&writes, samples);
+
+        // 288 samples = 24 hours
+        if samples_taken.is_multiple_of(288) {
+            match db::prune_free_space_history(&pool, FREE_SPACE_RETENTION_DAYS).await {
+                Ok(count) if count > 0 => println!("[FreeSpace] Pruned {} old samples", count),
+                Ok(_) => {}
+                Err(e) => eprintln!("[FreeSpace] Prune failed: {}", e),
+            }
+        }
+        samples_taken += 1;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
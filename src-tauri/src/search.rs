@@ -0,0 +1,127 @@
+use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config, Matcher};
+use serde::{Deserialize, Serialize};
+
+/// What kind of item a search candidate is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    /// Process names (live and all-time history)
+    Processes,
+    /// Executable paths of running processes
+    ExePaths,
+    /// File paths seen by file-level tracing
+    Files,
+    /// Every scope above
+    All,
+}
+
+impl SearchScope {
+    /// Returns true if items of `kind` should be searched under this scope
+    pub fn includes(self, kind: SearchScope) -> bool {
+        self == SearchScope::All || self == kind
+    }
+}
+
+/// A ranked fuzzy match
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub kind: SearchScope,
+    pub value: String,
+    pub score: u32,
+}
+
+/// Ranks candidates against the query (fzf-style scoring), best match first.
+///
+/// Paths are matched with path-aware bonuses so "appdata temp" prefers
+/// segment boundaries. Duplicate values of the same kind are collapsed.
+pub fn fuzzy_search(
+    candidates: Vec<(SearchScope, String)>,
+    query: &str,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let pattern = Pattern::parse(query, CaseMatching::Ignore, Normalization::Smart);
+    let mut name_matcher = Matcher::new(Config::DEFAULT);
+    let mut path_matcher = Matcher::new(Config::DEFAULT.match_paths());
+
+    let mut names: Vec<String> = Vec::new();
+    let mut exe_paths: Vec<String> = Vec::new();
+    let mut files: Vec<String> = Vec::new();
+    for (kind, value) in candidates {
+        match kind {
+            SearchScope::Processes => names.push(value),
+            SearchScope::ExePaths => exe_paths.push(value),
+            SearchScope::Files => files.push(value),
+            SearchScope::All => {}
+        }
+    }
+    for list in [&mut names, &mut exe_paths, &mut files] {
+        list.sort_unstable();
+        list.dedup();
+    }
+
+    let mut results: Vec<SearchResult> = Vec::new();
+    for (kind, list) in [
+        (SearchScope::Processes, names),
+        (SearchScope::ExePaths, exe_paths),
+        (SearchScope::Files, files),
+    ] {
+        let matcher = if kind == SearchScope::Processes {
+            &mut name_matcher
+        } else {
+            &mut path_matcher
+        };
+        results.extend(
+            pattern
+                .match_list(list, matcher)
+                .into_iter()
+                .map(|(value, score)| SearchResult { kind, value, score }),
+        );
+    }
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.value.cmp(&b.value)));
+    results.truncate(limit);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_search_ranks_best_match_first() {
+        let candidates = vec![
+            (SearchScope::Processes, "chrome.exe".to_string()),
+            (SearchScope::Processes, "steam.exe".to_string()),
+            (SearchScope::Processes, "steamwebhelper.exe".to_string()),
+        ];
+        let results = fuzzy_search(candidates, "steam", 10);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].value, "steam.exe");
+        assert!(results.iter().all(|r| r.kind == SearchScope::Processes));
+    }
+
+    #[test]
+    fn test_fuzzy_search_dedups_and_limits() {
+        let candidates = vec![
+            (SearchScope::Processes, "node.exe".to_string()),
+            (SearchScope::Processes, "node.exe".to_string()),
+            (
+                SearchScope::ExePaths,
+                "C:\\Program Files\\nodejs\\node.exe".to_string(),
+            ),
+        ];
+        let results = fuzzy_search(candidates.clone(), "node", 10);
+        assert_eq!(results.len(), 2);
+
+        let limited = fuzzy_search(candidates, "node", 1);
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[test]
+    fn test_scope_includes() {
+        assert!(SearchScope::All.includes(SearchScope::Files));
+        assert!(SearchScope::Processes.includes(SearchScope::Processes));
+        assert!(!SearchScope::Processes.includes(SearchScope::ExePaths));
+    }
+}
@@ -0,0 +1,166 @@
+// Windows Service Control Manager helpers
+// Servis durumunu sorgulamak için (örn. defragsvc)
+
+/// Services listed in a svchost name before the rest is only counted
+pub const MAX_LISTED_SERVICES: usize = 3;
+
+#[cfg(windows)]
+mod windows_impl {
+    use std::collections::HashMap;
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Services::*;
+
+    /// Çalışan Win32 servislerini barındıran PID'e göre listele
+    pub fn service_pids() -> Result<HashMap<u32, Vec<String>>, String> {
+        unsafe {
+            let manager = OpenSCManagerW(
+                PCWSTR::null(),
+                PCWSTR::null(),
+                SC_MANAGER_CONNECT | SC_MANAGER_ENUMERATE_SERVICE,
+            )
+            .map_err(|e| format!("OpenSCManagerW failed: {}", e))?;
+
+            // İlk çağrı gereken tampon boyutunu döndürür
+            let mut bytes_needed: u32 = 0;
+            let mut count: u32 = 0;
+            let _ = EnumServicesStatusExW(
+                manager,
+                SC_ENUM_PROCESS_INFO,
+                SERVICE_WIN32,
+                SERVICE_ACTIVE,
+                None,
+                &mut bytes_needed,
+                &mut count,
+                None,
+                PCWSTR::null(),
+            );
+
+            // u64 tampon: yapılar hizalı okunabilsin
+            let mut buffer = vec![0u64; (bytes_needed as usize).div_ceil(8)];
+            let bytes =
+                std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8);
+            let result = EnumServicesStatusExW(
+                manager,
+                SC_ENUM_PROCESS_INFO,
+                SERVICE_WIN32,
+                SERVICE_ACTIVE,
+                Some(bytes),
+                &mut bytes_needed,
+                &mut count,
+                None,
+                PCWSTR::null(),
+            );
+            let _ = CloseServiceHandle(manager);
+            result.map_err(|e| format!("EnumServicesStatusExW failed: {}", e))?;
+
+            let services = std::slice::from_raw_parts(
+                buffer.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESSW,
+                count as usize,
+            );
+            let mut by_pid: HashMap<u32, Vec<String>> = HashMap::new();
+            for service in services {
+                let pid = service.ServiceStatusProcess.dwProcessId;
+                if pid == 0 {
+                    continue;
+                }
+                if let Ok(name) = service.lpServiceName.to_string() {
+                    by_pid.entry(pid).or_default().push(name);
+                }
+            }
+            Ok(by_pid)
+        }
+    }
+
+    /// Servisin şu anda çalışıp çalışmadığını SCM üzerinden sorgula
+    pub fn is_service_running(service_name: &str) -> Result<bool, String> {
+        unsafe {
+            let manager = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_CONNECT)
+                .map_err(|e| format!("OpenSCManagerW failed: {}", e))?;
+
+            let name: Vec<u16> = service_name
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+            let service = match OpenServiceW(
+                manager,
+                PCWSTR::from_raw(name.as_ptr()),
+                SERVICE_QUERY_STATUS,
+            ) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    let _ = CloseServiceHandle(manager);
+                    return Err(format!("OpenServiceW ({}) failed: {}", service_name, e));
+                }
+            };
+
+            let mut status = SERVICE_STATUS_PROCESS::default();
+            let mut bytes_needed: u32 = 0;
+            let buffer = std::slice::from_raw_parts_mut(
+                &mut status as *mut SERVICE_STATUS_PROCESS as *mut u8,
+                std::mem::size_of::<SERVICE_STATUS_PROCESS>(),
+            );
+            let result = QueryServiceStatusEx(
+                service,
+                SC_STATUS_PROCESS_INFO,
+                Some(buffer),
+                &mut bytes_needed,
+            );
+
+            // Temizlik
+            let _ = CloseServiceHandle(service);
+            let _ = CloseServiceHandle(manager);
+
+            result.map_err(|e| format!("QueryServiceStatusEx ({}) failed: {}", service_name, e))?;
+            Ok(status.dwCurrentState == SERVICE_RUNNING)
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::{is_service_running, service_pids};
+
+/// Windows dışı platformlar için fallback
+#[cfg(not(windows))]
+pub fn is_service_running(_service_name: &str) -> Result<bool, String> {
+    Ok(false)
+}
+
+/// Windows dışı platformlar için fallback
+#[cfg(not(windows))]
+pub fn service_pids() -> Result<std::collections::HashMap<u32, Vec<String>>, String> {
+    Err("Services are only available on Windows".to_string())
+}
+
+/// Display name of a svchost instance from the services it hosts, e.g.
+/// "svchost (wuauserv)" or "svchost (a, b, c +2)"
+pub fn svchost_name(services: &[String]) -> String {
+    let mut services: Vec<&str> = services.iter().map(String::as_str).collect();
+    services.sort_unstable_by_key(|name| name.to_ascii_lowercase());
+    let listed = services[..services.len().min(MAX_LISTED_SERVICES)].join(", ");
+    match services.len().saturating_sub(MAX_LISTED_SERVICES) {
+        0 => format!("svchost ({})", listed),
+        more => format!("svchost ({} +{})", listed, more),
+    }
+}
+
+/// Güvenli wrapper - hata durumunda servis çalışmıyor kabul edilir
+pub fn is_service_running_safe(service_name: &str) -> bool {
+    is_service_running(service_name).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svchost_names_list_the_hosted_services() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(svchost_name(&names(&["wuauserv"])), "svchost (wuauserv)");
+        assert_eq!(
+            svchost_name(&names(&[
+                "Dnscache", "BITS", "wuauserv", "Schedule", "lmhosts"
+            ])),
+            "svchost (BITS, Dnscache, lmhosts +2)"
+        );
+    }
+}
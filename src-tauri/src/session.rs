@@ -0,0 +1,161 @@
+//! Summary of a monitoring session, recorded at shutdown so the next launch
+//! can show what the previous session did.
+
+use crate::models::{ProcessIOStat, TimelineEvent};
+use crate::process_monitor::OTHERS;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Timeline event kind; details hold the serialized `SessionSummary`
+pub const SESSION_EVENT_KIND: &str = "session";
+
+/// Processes listed in a summary
+pub const SUMMARY_PROCESS_COUNT: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionProcess {
+    pub name: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    /// Listed because the user pinned it
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub duration_secs: f64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    /// Busiest processes of the session, by total bytes, then any pinned
+    /// process not among them
+    pub top_processes: Vec<SessionProcess>,
+}
+
+/// Totals of the running session, published by the monitor loop every tick
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionTotals {
+    pub start_time: f64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+pub type SharedSessionTotals = Arc<Mutex<SessionTotals>>;
+
+pub fn create_session_totals() -> SharedSessionTotals {
+    Arc::new(Mutex::new(SessionTotals::default()))
+}
+
+pub fn publish(shared: &SharedSessionTotals, totals: SessionTotals) {
+    if let Ok(mut guard) = shared.lock() {
+        *guard = totals;
+    }
+}
+
+pub fn current(shared: &SharedSessionTotals) -> SessionTotals {
+    shared.lock().map(|guard| guard.clone()).unwrap_or_default()
+}
+
+/// Builds the summary from the session totals and the top-process list
+/// (the aggregated "Others" entry is left out, pinned processes are kept)
+pub fn summarize(
+    start_time: f64,
+    end_time: f64,
+    read_bytes: u64,
+    write_bytes: u64,
+    processes: &[ProcessIOStat],
+) -> SessionSummary {
+    let mut ranked: Vec<&ProcessIOStat> = processes
+        .iter()
+        .filter(|p| p.name != OTHERS && (p.total_bytes > 0 || p.pinned))
+        .collect();
+    ranked.sort_by_key(|p| std::cmp::Reverse(p.total_bytes));
+    let pinned = ranked.split_off(SUMMARY_PROCESS_COUNT.min(ranked.len()));
+    ranked.extend(pinned.into_iter().filter(|p| p.pinned));
+
+    SessionSummary {
+        start_time,
+        end_time,
+        duration_secs: (end_time - start_time).max(0.0),
+        read_bytes,
+        write_bytes,
+        top_processes: ranked
+            .into_iter()
+            .map(|p| SessionProcess {
+                name: p.name.clone(),
+                read_bytes: p.read_bytes,
+                write_bytes: p.write_bytes,
+                pinned: p.pinned,
+            })
+            .collect(),
+    }
+}
+
+/// Decodes the summary stored in a session timeline event
+pub fn from_event(event: &TimelineEvent) -> Option<SessionSummary> {
+    event
+        .details
+        .as_deref()
+        .and_then(|details| serde_json::from_str(details).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, read_bytes: u64, write_bytes: u64) -> ProcessIOStat {
+        ProcessIOStat {
+            name: name.to_string(),
+            read_bytes,
+            write_bytes,
+            total_bytes: read_bytes + write_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_keeps_top_five_without_others() {
+        let mut processes: Vec<ProcessIOStat> = (1..=7)
+            .map(|i| process(&format!("p{}.exe", i), i * 10, i))
+            .collect();
+        processes.push(process(OTHERS, 1_000, 1_000));
+        processes.push(process("idle.exe", 0, 0));
+        processes[0].pinned = true;
+
+        let summary = summarize(100.0, 460.0, 5_000, 6_000, &processes);
+        assert_eq!(summary.duration_secs, 360.0);
+        let names: Vec<&str> = summary
+            .top_processes
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["p7.exe", "p6.exe", "p5.exe", "p4.exe", "p3.exe", "p1.exe"]
+        );
+
+        let event = TimelineEvent {
+            id: 1,
+            kind: SESSION_EVENT_KIND.to_string(),
+            start_time: 100.0,
+            end_time: Some(460.0),
+            details: serde_json::to_string(&summary).ok(),
+        };
+        assert_eq!(from_event(&event), Some(summary));
+    }
+
+    #[test]
+    fn test_published_totals_are_read_back() {
+        let shared = create_session_totals();
+        assert_eq!(current(&shared), SessionTotals::default());
+        let totals = SessionTotals {
+            start_time: 10.0,
+            read_bytes: 1,
+            write_bytes: 2,
+        };
+        publish(&shared, totals.clone());
+        assert_eq!(current(&shared), totals);
+    }
+}
@@ -0,0 +1,391 @@
+//! Moving the configuration between machines: one JSON file with the
+//! portable settings (process filter and exclusions, retention period,
+//! sampling, low-wear and battery modes, ...) and the process categories.
+//! Settings tied to this machine (its label, storage backend, drive
+//! endurance) stay behind. The app has no alert thresholds yet; once it
+//! stores any, their keys belong in `PORTABLE_KEYS`.
+
+use crate::categories;
+use crate::db;
+use crate::process_names::NameNormalization;
+use crate::{
+    activity, channels, db_cleanup, file_io, flush_settings, low_wear, payload, power,
+    process_filter, sampling, top_processes,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Identifies a settings file
+pub const SETTINGS_FORMAT: &str = "driveanalizer-settings";
+
+/// Bumped when the file layout changes
+pub const SETTINGS_FORMAT_VERSION: u32 = 1;
+
+/// Setting keys carried over to another machine (telemetry consent is given
+/// per machine and never travels)
+pub const PORTABLE_KEYS: &[&str] = &[
+    process_filter::EXCLUDED_PROCESSES_KEY,
+    process_filter::ALLOWED_PROCESSES_KEY,
+    process_filter::ALLOWLIST_ONLY_KEY,
+    process_filter::EXCLUDE_SELF_KEY,
+    process_filter::NAME_NORMALIZATION_KEY,
+    process_filter::PINNED_PROCESSES_KEY,
+    top_processes::TOP_LIMIT_KEY,
+    db_cleanup::RETENTION_DAYS_KEY,
+    sampling::SAMPLE_INTERVAL_KEY,
+    payload::PAYLOAD_ENCODING_KEY,
+    channels::LEGACY_DISK_METRICS_EVENT_KEY,
+    activity::ACTIVITY_LED_HZ_KEY,
+    low_wear::LOW_WEAR_ENABLED_KEY,
+    low_wear::LOW_WEAR_FLUSH_MINUTES_KEY,
+    flush_settings::FLUSH_PERIOD_KEY,
+    flush_settings::FLUSH_BUFFER_KEY,
+    power::BATTERY_THROTTLE_ENABLED_KEY,
+    power::BATTERY_INTERVAL_MS_KEY,
+    power::BATTERY_FLUSH_SECS_KEY,
+    file_io::FILE_TRACING_KEY,
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsFile {
+    pub format: String,
+    pub format_version: u32,
+    /// Setting key -> stored value (only keys that were set)
+    pub settings: BTreeMap<String, String>,
+    /// Process name -> category
+    pub categories: BTreeMap<String, String>,
+}
+
+/// What an import changed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportSummary {
+    pub settings: usize,
+    pub categories: usize,
+    /// Keys this version doesn't know or doesn't move between machines
+    pub skipped: Vec<String>,
+}
+
+/// The file's settings that may be imported, and the keys left out
+fn portable_settings(file: &SettingsFile) -> (Vec<(&str, &str)>, Vec<String>) {
+    let mut accepted = Vec::new();
+    let mut skipped = Vec::new();
+    for (key, value) in &file.settings {
+        match PORTABLE_KEYS.iter().find(|portable| **portable == key) {
+            Some(portable) => accepted.push((*portable, value.as_str())),
+            None => skipped.push(key.clone()),
+        }
+    }
+    (accepted, skipped)
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", key, value))
+}
+
+fn in_range<T: FromStr + PartialOrd + Display>(
+    key: &str,
+    value: &str,
+    min: T,
+    max: T,
+) -> Result<String, String> {
+    let parsed: T = parse(key, value)?;
+    if parsed < min || parsed > max {
+        return Err(format!("{} must be between {} and {}", key, min, max));
+    }
+    Ok(parsed.to_string())
+}
+
+/// The value to store for `key`, checked the way its `set_*` command checks
+/// it; loaders fall back to defaults on bad values, which would silently
+/// drop e.g. an exclusion list
+fn checked_value(key: &str, value: &str) -> Result<String, String> {
+    match key {
+        process_filter::EXCLUDED_PROCESSES_KEY
+        | process_filter::ALLOWED_PROCESSES_KEY
+        | process_filter::PINNED_PROCESSES_KEY => {
+            let entries: Vec<String> = serde_json::from_str(value)
+                .map_err(|_| format!("Invalid process list for {}", key))?;
+            let entries = process_filter::normalize(&entries)?;
+            serde_json::to_string(&entries).map_err(|e| e.to_string())
+        }
+        process_filter::ALLOWLIST_ONLY_KEY
+        | process_filter::EXCLUDE_SELF_KEY
+        | channels::LEGACY_DISK_METRICS_EVENT_KEY
+        | low_wear::LOW_WEAR_ENABLED_KEY
+        | power::BATTERY_THROTTLE_ENABLED_KEY
+        | file_io::FILE_TRACING_KEY => parse::<bool>(key, value).map(|flag| flag.to_string()),
+        process_filter::NAME_NORMALIZATION_KEY => {
+            let names: NameNormalization =
+                serde_json::from_str(value).map_err(|_| format!("Invalid value for {}", key))?;
+            serde_json::to_string(&names).map_err(|e| e.to_string())
+        }
+        top_processes::TOP_LIMIT_KEY => {
+            top_processes::validate_limit(parse(key, value)?).map(|limit| limit.to_string())
+        }
+        db_cleanup::RETENTION_DAYS_KEY => {
+            db_cleanup::validate_retention_days(parse(key, value)?).map(|days| days.to_string())
+        }
+        sampling::SAMPLE_INTERVAL_KEY | power::BATTERY_INTERVAL_MS_KEY => in_range(
+            key,
+            value,
+            sampling::MIN_INTERVAL_MS,
+            sampling::MAX_INTERVAL_MS,
+        ),
+        payload::PAYLOAD_ENCODING_KEY => payload::PayloadEncoding::parse(value)
+            .map(|encoding| encoding.as_str().to_string())
+            .ok_or_else(|| format!("Invalid value for {}: {}", key, value)),
+        activity::ACTIVITY_LED_HZ_KEY => {
+            in_range(key, value, activity::MIN_LED_HZ, activity::MAX_LED_HZ)
+        }
+        low_wear::LOW_WEAR_FLUSH_MINUTES_KEY => in_range(
+            key,
+            value,
+            low_wear::MIN_FLUSH_MINUTES,
+            low_wear::MAX_FLUSH_MINUTES,
+        ),
+        flush_settings::FLUSH_PERIOD_KEY => {
+            let settings = flush_settings::FlushSettings {
+                flush_secs: parse(key, value)?,
+                ..Default::default()
+            };
+            settings.validate()?;
+            Ok(settings.flush_secs.to_string())
+        }
+        flush_settings::FLUSH_BUFFER_KEY => {
+            let settings = flush_settings::FlushSettings {
+                buffer_records: parse(key, value)?,
+                ..Default::default()
+            };
+            settings.validate()?;
+            Ok(settings.buffer_records.to_string())
+        }
+        power::BATTERY_FLUSH_SECS_KEY => match parse::<u64>(key, value)? {
+            0 => Err("Flush period must be at least one second".to_string()),
+            secs => Ok(secs.to_string()),
+        },
+        _ => Ok(value.to_string()),
+    }
+}
+
+/// Checks the header and the category names before anything is written
+pub fn validate(file: &SettingsFile) -> Result<(), String> {
+    if file.format != SETTINGS_FORMAT {
+        return Err("Not a settings file".to_string());
+    }
+    if file.format_version > SETTINGS_FORMAT_VERSION {
+        return Err(format!(
+            "Settings file version {} is newer than this app supports ({})",
+            file.format_version, SETTINGS_FORMAT_VERSION
+        ));
+    }
+    for category in file.categories.values() {
+        categories::validate(category)?;
+    }
+    Ok(())
+}
+
+pub async fn export_settings(pool: &Pool<Sqlite>) -> Result<SettingsFile, sqlx::Error> {
+    let mut settings = BTreeMap::new();
+    for key in PORTABLE_KEYS {
+        if let Some(value) = db::get_setting(pool, key).await? {
+            settings.insert(key.to_string(), value);
+        }
+    }
+    Ok(SettingsFile {
+        format: SETTINGS_FORMAT.to_string(),
+        format_version: SETTINGS_FORMAT_VERSION,
+        settings,
+        categories: db::get_process_categories(pool)
+            .await?
+            .into_iter()
+            .collect(),
+    })
+}
+
+/// Stores the file's settings and category assignments over the current ones
+/// (categories of processes the file doesn't mention are kept). Every value
+/// is checked first and everything is written in one transaction: a bad
+/// value or a database error leaves the current configuration untouched.
+pub async fn import_settings(
+    pool: &Pool<Sqlite>,
+    file: &SettingsFile,
+) -> Result<ImportSummary, String> {
+    validate(file)?;
+    let (settings, skipped) = portable_settings(file);
+    let settings: Vec<(&str, String)> = settings
+        .into_iter()
+        .map(|(key, value)| Ok((key, checked_value(key, value)?)))
+        .collect::<Result<_, String>>()?;
+
+    let db_error = |e: sqlx::Error| format!("Database error: {}", e);
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    for (key, value) in &settings {
+        db::set_setting(&mut *tx, key, value)
+            .await
+            .map_err(db_error)?;
+    }
+    for (name, category) in &file.categories {
+        let category = categories::validate(category)?;
+        db::set_process_category(&mut *tx, name, Some(category))
+            .await
+            .map_err(db_error)?;
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok(ImportSummary {
+        settings: settings.len(),
+        categories: file.categories.len(),
+        skipped,
+    })
+}
+
+/// Writes the file to `<data_dir>/exports/settings-<timestamp>.json`
+pub fn write_settings_file(
+    file: &SettingsFile,
+    data_dir: &Path,
+    timestamp: u64,
+) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    let dir = data_dir.join(crate::incident::EXPORTS_DIR);
+    std::fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!("settings-{}.json", timestamp));
+    std::fs::write(&path, serde_json::to_vec_pretty(file)?)?;
+    println!("[Settings] Exported {}", path.display());
+    Ok(path)
+}
+
+pub fn read_settings_file(
+    path: &Path,
+) -> Result<SettingsFile, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        db::create_schema(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_settings_round_trip_between_databases() {
+        let source = test_pool().await;
+        db::set_setting(&source, sampling::SAMPLE_INTERVAL_KEY, "2000")
+            .await
+            .unwrap();
+        db::set_setting(&source, "machine_label", "office-pc")
+            .await
+            .unwrap();
+        db_cleanup::save_retention_days(&source, 14).await.unwrap();
+        db::set_process_category(&source, "steam.exe", Some("Games"))
+            .await
+            .unwrap();
+
+        let mut file = export_settings(&source).await.unwrap();
+        assert_eq!(file.settings.len(), 2);
+        file.settings
+            .insert("unknown_key".to_string(), "x".to_string());
+
+        let target = test_pool().await;
+        let summary = import_settings(&target, &file).await.unwrap();
+        assert_eq!(summary.settings, 2);
+        assert_eq!(summary.skipped, ["unknown_key"]);
+        assert_eq!(
+            db_cleanup::load_retention_policy(&target).await.keep_days,
+            14
+        );
+        assert_eq!(
+            db::get_setting(&target, sampling::SAMPLE_INTERVAL_KEY)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("2000")
+        );
+        assert_eq!(
+            db::get_process_categories(&target).await.unwrap()["steam.exe"],
+            "Games"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_values_are_rejected_before_writing() {
+        let target = test_pool().await;
+        let mut file = SettingsFile {
+            format: SETTINGS_FORMAT.to_string(),
+            format_version: SETTINGS_FORMAT_VERSION,
+            settings: BTreeMap::from([
+                (
+                    sampling::SAMPLE_INTERVAL_KEY.to_string(),
+                    "2000".to_string(),
+                ),
+                (
+                    process_filter::EXCLUDED_PROCESSES_KEY.to_string(),
+                    "backup.exe".to_string(),
+                ),
+            ]),
+            categories: BTreeMap::new(),
+        };
+        assert!(import_settings(&target, &file).await.is_err());
+        assert_eq!(
+            db::get_setting(&target, sampling::SAMPLE_INTERVAL_KEY)
+                .await
+                .unwrap(),
+            None
+        );
+
+        file.settings.insert(
+            process_filter::EXCLUDED_PROCESSES_KEY.to_string(),
+            r#"[" backup.exe", "BACKUP.EXE"]"#.to_string(),
+        );
+        file.settings
+            .insert(sampling::SAMPLE_INTERVAL_KEY.to_string(), "5".to_string());
+        assert!(import_settings(&target, &file).await.is_err());
+
+        file.settings.insert(
+            sampling::SAMPLE_INTERVAL_KEY.to_string(),
+            "2000".to_string(),
+        );
+        file.settings
+            .insert("telemetry_enabled".to_string(), "true".to_string());
+        let summary = import_settings(&target, &file).await.unwrap();
+        assert_eq!(summary.skipped, ["telemetry_enabled"]);
+        assert_eq!(
+            db::get_setting(&target, process_filter::EXCLUDED_PROCESSES_KEY)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(r#"["backup.exe"]"#)
+        );
+    }
+
+    #[test]
+    fn test_foreign_and_newer_files_are_rejected() {
+        let mut file = SettingsFile {
+            format: SETTINGS_FORMAT.to_string(),
+            format_version: SETTINGS_FORMAT_VERSION,
+            settings: BTreeMap::new(),
+            categories: BTreeMap::from([("a.exe".to_string(), " ".to_string())]),
+        };
+        assert!(validate(&file).is_err());
+        file.categories.clear();
+        assert!(validate(&file).is_ok());
+        file.format_version += 1;
+        assert!(validate(&file).is_err());
+        file.format = "incident".to_string();
+        file.format_version = 1;
+        assert!(validate(&file).is_err());
+    }
+}
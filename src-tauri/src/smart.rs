@@ -0,0 +1,441 @@
+// SMART özniteliklerini okumak için
+// ATA/SATA diskler: READ ATTRIBUTES, NVMe diskler: SMART/Health Information log sayfası
+
+use crate::models::TemperatureSample;
+use crate::volume_monitor;
+use serde::Serialize;
+
+/// Bir SMART özniteliği (512 byte'lık READ ATTRIBUTES çıktısından)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmartAttribute {
+    pub id: u8,
+    pub current: u8,
+    pub worst: u8,
+    /// 48-bit ham değer
+    pub raw: u64,
+}
+
+/// Temperature (ham değerin düşük byte'ı santigrat)
+pub const ATTR_TEMPERATURE: u8 = 194;
+/// Airflow Temperature (bazı diskler 194 yerine bunu raporlar)
+pub const ATTR_AIRFLOW_TEMPERATURE: u8 = 190;
+/// Total LBAs Written
+pub const ATTR_TOTAL_LBAS_WRITTEN: u8 = 241;
+/// Total LBAs Read
+pub const ATTR_TOTAL_LBAS_READ: u8 = 242;
+
+const ATTRIBUTE_TABLE_OFFSET: usize = 2;
+const ATTRIBUTE_ENTRY_SIZE: usize = 12;
+const ATTRIBUTE_COUNT: usize = 30;
+
+/// READ ATTRIBUTES verisini ayrıştır: 2 byte revizyon, ardından 30 adet
+/// 12 byte'lık kayıt (id, flags[2], current, worst, raw[6], reserved)
+pub fn parse_attributes(data: &[u8]) -> Vec<SmartAttribute> {
+    let mut attributes = Vec::new();
+    for index in 0..ATTRIBUTE_COUNT {
+        let start = ATTRIBUTE_TABLE_OFFSET + index * ATTRIBUTE_ENTRY_SIZE;
+        let Some(entry) = data.get(start..start + ATTRIBUTE_ENTRY_SIZE) else {
+            break;
+        };
+        if entry[0] == 0 {
+            continue;
+        }
+        let raw = entry[5..11]
+            .iter()
+            .rev()
+            .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+        attributes.push(SmartAttribute {
+            id: entry[0],
+            current: entry[3],
+            worst: entry[4],
+            raw,
+        });
+    }
+    attributes
+}
+
+/// Host tarafından yazılan toplam byte (241 numaralı öznitelik, 512 byte LBA
+/// varsayımıyla; bazı üreticiler farklı birim kullanır)
+pub fn host_writes_bytes(attributes: &[SmartAttribute]) -> Option<u64> {
+    attributes
+        .iter()
+        .find(|a| a.id == ATTR_TOTAL_LBAS_WRITTEN)
+        .map(|a| a.raw.saturating_mul(512))
+}
+
+/// NVMe SMART/Health Information log sayfasının (Log ID 02h) önemli alanları
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NvmeHealth {
+    /// Composite Temperature; None when the drive doesn't report it
+    pub temperature_c: Option<f64>,
+    /// Üreticinin dayanıklılık tahmininin kullanılan yüzdesi (100'ü geçebilir)
+    pub percentage_used: u8,
+    pub data_read_bytes: u64,
+    pub data_written_bytes: u64,
+    pub power_on_hours: u64,
+}
+
+/// NVMe log sayfası boyutu
+pub const NVME_HEALTH_LOG_SIZE: usize = 512;
+/// "Data Units" 1000 adet 512 byte'lık birimdir
+const NVME_DATA_UNIT_BYTES: u64 = 512 * 1000;
+
+fn read_le_u128(data: &[u8], offset: usize) -> u128 {
+    data[offset..offset + 16]
+        .iter()
+        .rev()
+        .fold(0u128, |acc, byte| (acc << 8) | *byte as u128)
+}
+
+/// Health log sayfasını ayrıştır (NVMe 1.4, Figure 194 düzeni)
+pub fn parse_nvme_health(data: &[u8]) -> Option<NvmeHealth> {
+    if data.len() < NVME_HEALTH_LOG_SIZE {
+        return None;
+    }
+
+    // Composite Temperature Kelvin cinsinden; 0 raporlanmıyor demektir
+    let kelvin = u16::from_le_bytes([data[1], data[2]]);
+    let to_bytes = |units: u128| {
+        (units.saturating_mul(NVME_DATA_UNIT_BYTES as u128)).min(u64::MAX as u128) as u64
+    };
+
+    Some(NvmeHealth {
+        temperature_c: (kelvin != 0).then_some(kelvin as f64 - 273.15),
+        percentage_used: data[5],
+        data_read_bytes: to_bytes(read_le_u128(data, 32)),
+        data_written_bytes: to_bytes(read_le_u128(data, 48)),
+        power_on_hours: read_le_u128(data, 128).min(u64::MAX as u128) as u64,
+    })
+}
+
+/// SMART özniteliklerinden sıcaklık (santigrat)
+pub fn temperature_celsius(attributes: &[SmartAttribute]) -> Option<f64> {
+    [ATTR_TEMPERATURE, ATTR_AIRFLOW_TEMPERATURE]
+        .iter()
+        .find_map(|id| attributes.iter().find(|a| a.id == *id))
+        .map(|a| (a.raw & 0xFF) as f64)
+        .filter(|celsius| *celsius > 0.0)
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, GENERIC_READ, GENERIC_WRITE, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ, FILE_SHARE_WRITE,
+        IOCTL_VOLUME_GET_VOLUME_DISK_EXTENTS, OPEN_EXISTING,
+    };
+    use windows::Win32::System::Ioctl::{
+        NVMeDataTypeLogPage, PropertyStandardQuery, ProtocolTypeNvme,
+        StorageDeviceProtocolSpecificProperty, IDEREGS, IOCTL_STORAGE_QUERY_PROPERTY,
+        READ_ATTRIBUTES, READ_ATTRIBUTE_BUFFER_SIZE, SENDCMDINPARAMS, SMART_CMD, SMART_CYL_HI,
+        SMART_CYL_LOW, SMART_RCV_DRIVE_DATA, STORAGE_PROPERTY_QUERY,
+        STORAGE_PROTOCOL_SPECIFIC_DATA, VOLUME_DISK_EXTENTS,
+    };
+    use windows::Win32::System::IO::DeviceIoControl;
+
+    unsafe fn open_device(path: &str, access: u32) -> Result<HANDLE, String> {
+        let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+        CreateFileW(
+            PCWSTR::from_raw(wide.as_ptr()),
+            access,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAGS_AND_ATTRIBUTES(0),
+            HANDLE::default(),
+        )
+        .map_err(|e| format!("CreateFileW ({}) failed: {}", path, e))
+    }
+
+    /// Birimin (örn. "C:") bulunduğu fiziksel disk numarası (ilk extent)
+    pub fn physical_drive_number(volume: &str) -> Result<u32, String> {
+        unsafe {
+            let handle = open_device(&format!("\\\\.\\{}", volume), 0)?;
+            let mut extents = VOLUME_DISK_EXTENTS::default();
+            let mut returned: u32 = 0;
+            let result = DeviceIoControl(
+                handle,
+                IOCTL_VOLUME_GET_VOLUME_DISK_EXTENTS,
+                None,
+                0,
+                Some(&mut extents as *mut VOLUME_DISK_EXTENTS as *mut core::ffi::c_void),
+                std::mem::size_of::<VOLUME_DISK_EXTENTS>() as u32,
+                Some(&mut returned),
+                None,
+            );
+            let _ = CloseHandle(handle);
+
+            // Birden fazla diske yayılan birimlerde (spanned) ERROR_MORE_DATA
+            // döner; ilk extent yine de doldurulmuş olur
+            if result.is_err() && extents.NumberOfDiskExtents == 0 {
+                return Err(format!("Disk extents query ({}) failed", volume));
+            }
+            Ok(extents.Extents[0].DiskNumber)
+        }
+    }
+
+    /// SMART READ ATTRIBUTES (yönetici yetkisi gerektirir)
+    pub fn read_attributes(volume: &str) -> Result<Vec<u8>, String> {
+        let disk = physical_drive_number(volume)?;
+        unsafe {
+            let handle = open_device(
+                &format!("\\\\.\\PhysicalDrive{}", disk),
+                GENERIC_READ.0 | GENERIC_WRITE.0,
+            )?;
+
+            let mut input = SENDCMDINPARAMS::default();
+            input.cBufferSize = READ_ATTRIBUTE_BUFFER_SIZE;
+            input.irDriveRegs = IDEREGS {
+                bFeaturesReg: READ_ATTRIBUTES as u8,
+                bSectorCountReg: 1,
+                bSectorNumberReg: 1,
+                bCylLowReg: SMART_CYL_LOW as u8,
+                bCylHighReg: SMART_CYL_HI as u8,
+                bDriveHeadReg: 0xA0,
+                bCommandReg: SMART_CMD as u8,
+                bReserved: 0,
+            };
+
+            // SENDCMDOUTPARAMS başlığı (16 byte) + 512 byte veri
+            const HEADER: usize = 16;
+            let mut output = vec![0u8; HEADER + READ_ATTRIBUTE_BUFFER_SIZE as usize];
+            let mut returned: u32 = 0;
+            let result = DeviceIoControl(
+                handle,
+                SMART_RCV_DRIVE_DATA,
+                Some(&input as *const SENDCMDINPARAMS as *const core::ffi::c_void),
+                (std::mem::size_of::<SENDCMDINPARAMS>() - 1) as u32,
+                Some(output.as_mut_ptr() as *mut core::ffi::c_void),
+                output.len() as u32,
+                Some(&mut returned),
+                None,
+            );
+            let _ = CloseHandle(handle);
+
+            result.map_err(|e| format!("SMART_RCV_DRIVE_DATA (disk {}) failed: {}", disk, e))?;
+            Ok(output.split_off(HEADER))
+        }
+    }
+
+    /// NVMe SMART/Health log sayfası, IOCTL_STORAGE_QUERY_PROPERTY ile.
+    /// StorNVMe sürücüsü bu sorguyu erişim hakkı 0 ile de kabul eder.
+    pub fn read_nvme_health_log(volume: &str) -> Result<Vec<u8>, String> {
+        const NVME_LOG_PAGE_HEALTH_INFO: u32 = 0x02;
+        // STORAGE_PROPERTY_QUERY başlığı (PropertyId + QueryType)
+        const QUERY_HEADER: usize = 8;
+        let specific_size = std::mem::size_of::<STORAGE_PROTOCOL_SPECIFIC_DATA>();
+
+        let disk = physical_drive_number(volume)?;
+        unsafe {
+            let handle = open_device(&format!("\\\\.\\PhysicalDrive{}", disk), 0)?;
+
+            // Girdi ve çıktı aynı tampon: sorgu + protokol verisi + log sayfası
+            let mut buffer = vec![0u8; QUERY_HEADER + specific_size + super::NVME_HEALTH_LOG_SIZE];
+            let query = STORAGE_PROPERTY_QUERY {
+                PropertyId: StorageDeviceProtocolSpecificProperty,
+                QueryType: PropertyStandardQuery,
+                AdditionalParameters: [0],
+            };
+            let specific = STORAGE_PROTOCOL_SPECIFIC_DATA {
+                ProtocolType: ProtocolTypeNvme,
+                DataType: NVMeDataTypeLogPage.0 as u32,
+                ProtocolDataRequestValue: NVME_LOG_PAGE_HEALTH_INFO,
+                ProtocolDataOffset: specific_size as u32,
+                ProtocolDataLength: super::NVME_HEALTH_LOG_SIZE as u32,
+                ..Default::default()
+            };
+            // Tampon byte hizalı olduğu için unaligned yazılır
+            std::ptr::copy_nonoverlapping(
+                &query as *const STORAGE_PROPERTY_QUERY as *const u8,
+                buffer.as_mut_ptr(),
+                QUERY_HEADER,
+            );
+            std::ptr::write_unaligned(
+                buffer.as_mut_ptr().add(QUERY_HEADER) as *mut STORAGE_PROTOCOL_SPECIFIC_DATA,
+                specific,
+            );
+
+            let mut returned: u32 = 0;
+            let result = DeviceIoControl(
+                handle,
+                IOCTL_STORAGE_QUERY_PROPERTY,
+                Some(buffer.as_ptr() as *const core::ffi::c_void),
+                buffer.len() as u32,
+                Some(buffer.as_mut_ptr() as *mut core::ffi::c_void),
+                buffer.len() as u32,
+                Some(&mut returned),
+                None,
+            );
+            let _ = CloseHandle(handle);
+
+            result.map_err(|e| format!("NVMe health log (disk {}) failed: {}", disk, e))?;
+
+            // Çıktı: STORAGE_PROTOCOL_DATA_DESCRIPTOR (Version, Size, ProtocolSpecificData)
+            let descriptor = std::ptr::read_unaligned(
+                buffer.as_ptr().add(QUERY_HEADER) as *const STORAGE_PROTOCOL_SPECIFIC_DATA
+            );
+            let start = QUERY_HEADER + descriptor.ProtocolDataOffset as usize;
+            let length = (descriptor.ProtocolDataLength as usize).min(super::NVME_HEALTH_LOG_SIZE);
+            buffer
+                .get(start..start + length)
+                .map(|data| data.to_vec())
+                .ok_or_else(|| format!("Short NVMe health log for disk {}", disk))
+        }
+    }
+}
+
+#[cfg(windows)]
+pub use windows_impl::{physical_drive_number, read_attributes, read_nvme_health_log};
+
+/// Windows dışı platformlar için fallback - fiziksel disk eşlemesi yok
+#[cfg(not(windows))]
+pub fn physical_drive_number(_volume: &str) -> Result<u32, String> {
+    Err("Disk extent queries are only implemented on Windows".to_string())
+}
+
+/// Windows dışı platformlar için fallback - SMART okunamıyor
+#[cfg(not(windows))]
+pub fn read_attributes(_volume: &str) -> Result<Vec<u8>, String> {
+    Err("SMART queries are only implemented on Windows".to_string())
+}
+
+/// Windows dışı platformlar için fallback - NVMe log sayfası okunamıyor
+#[cfg(not(windows))]
+pub fn read_nvme_health_log(_volume: &str) -> Result<Vec<u8>, String> {
+    Err("NVMe queries are only implemented on Windows".to_string())
+}
+
+/// Güvenli wrapper - NVMe olmayan ya da sorgulanamayan disklerde None
+pub fn read_nvme_health_safe(volume: &str) -> Option<NvmeHealth> {
+    match read_nvme_health_log(volume) {
+        Ok(data) => parse_nvme_health(&data),
+        Err(e) => {
+            eprintln!("[SMART] {}", e);
+            None
+        }
+    }
+}
+
+/// Güvenli wrapper - hata durumunda öznitelik yok
+pub fn read_attributes_safe(volume: &str) -> Vec<SmartAttribute> {
+    match read_attributes(volume) {
+        Ok(data) => parse_attributes(&data),
+        Err(e) => {
+            eprintln!("[SMART] {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Sürücü sıcaklığı: önce NVMe log sayfası, sonra SMART öznitelikleri.
+/// Periyodik örneklemede kullanıldığı için hatalar loglanmaz.
+pub fn read_temperature(volume: &str) -> Option<f64> {
+    read_nvme_health_log(volume)
+        .ok()
+        .and_then(|data| parse_nvme_health(&data))
+        .and_then(|health| health.temperature_c)
+        .or_else(|| {
+            read_attributes(volume)
+                .ok()
+                .and_then(|data| temperature_celsius(&parse_attributes(&data)))
+        })
+}
+
+/// Bağlı birimler üzerinden fiziksel disk başına bir sıcaklık örneği
+pub fn sample_temperatures(timestamp: f64) -> Vec<TemperatureSample> {
+    let mut seen_disks: Vec<u32> = Vec::new();
+    let mut samples = Vec::new();
+
+    for mounted in volume_monitor::list_mounted_volumes() {
+        // Aynı diskteki ikinci bölümü atla (eşleme yoksa birim başına örnek)
+        if let Ok(disk) = physical_drive_number(&mounted.volume) {
+            if seen_disks.contains(&disk) {
+                continue;
+            }
+            seen_disks.push(disk);
+        }
+
+        if let Some(temperature_c) = read_temperature(&mounted.volume) {
+            samples.push(TemperatureSample {
+                timestamp,
+                disk: mounted.volume,
+                temperature_c,
+            });
+        }
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_attributes() {
+        let mut data = vec![0u8; 512];
+        // Entry 0: id 241, current 100, worst 99, raw = 0x010203
+        let entry = &mut data[2..14];
+        entry[0] = ATTR_TOTAL_LBAS_WRITTEN;
+        entry[3] = 100;
+        entry[4] = 99;
+        entry[5] = 0x03;
+        entry[6] = 0x02;
+        entry[7] = 0x01;
+
+        let attributes = parse_attributes(&data);
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].raw, 0x010203);
+        assert_eq!(attributes[0].worst, 99);
+        assert_eq!(host_writes_bytes(&attributes), Some(0x010203 * 512));
+    }
+
+    #[test]
+    fn test_parse_nvme_health() {
+        let mut data = vec![0u8; NVME_HEALTH_LOG_SIZE];
+        // 310 K = 36.85 C
+        data[1..3].copy_from_slice(&310u16.to_le_bytes());
+        data[5] = 7;
+        data[32..48].copy_from_slice(&2u128.to_le_bytes());
+        data[48..64].copy_from_slice(&1000u128.to_le_bytes());
+        data[128..144].copy_from_slice(&1234u128.to_le_bytes());
+
+        let health = parse_nvme_health(&data).unwrap();
+        assert!((health.temperature_c.unwrap() - 36.85).abs() < 1e-9);
+        assert_eq!(health.percentage_used, 7);
+        assert_eq!(health.data_read_bytes, 2 * 512_000);
+        assert_eq!(health.data_written_bytes, 512_000_000);
+        assert_eq!(health.power_on_hours, 1234);
+        assert!(parse_nvme_health(&data[..100]).is_none());
+
+        // 0 K means the drive doesn't report a temperature
+        data[1..3].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(parse_nvme_health(&data).unwrap().temperature_c, None);
+    }
+
+    #[test]
+    fn test_temperature_celsius() {
+        let attribute = |id: u8, raw: u64| SmartAttribute {
+            id,
+            current: 0,
+            worst: 0,
+            raw,
+        };
+        // Upper bytes hold min/max on many drives
+        assert_eq!(
+            temperature_celsius(&[attribute(ATTR_TEMPERATURE, 0x0014_0037_002A)]),
+            Some(42.0)
+        );
+        assert_eq!(
+            temperature_celsius(&[attribute(ATTR_AIRFLOW_TEMPERATURE, 30)]),
+            Some(30.0)
+        );
+        assert_eq!(temperature_celsius(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_attributes_short_buffer() {
+        assert!(parse_attributes(&[0u8; 10]).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::models::{DiskStat, SmoothedSpeeds};
+
+/// Time constant of the short moving average
+pub const SHORT_WINDOW_SECS: f64 = 5.0;
+/// Time constant of the long moving average
+pub const LONG_WINDOW_SECS: f64 = 30.0;
+
+/// History lead fetched before the requested range so the averages start warmed up
+pub const WARMUP_SECS: f64 = LONG_WINDOW_SECS * 3.0;
+
+/// Exponential moving average over time (not over samples)
+///
+/// The weight of a sample depends on the time since the previous one, so
+/// the result does not change with the sampling interval and gaps decay
+/// the old value instead of being averaged in.
+#[derive(Debug, Clone, Copy)]
+struct Ema {
+    window_secs: f64,
+    value: Option<f64>,
+    last_time: f64,
+}
+
+impl Ema {
+    fn new(window_secs: f64) -> Self {
+        Self {
+            window_secs,
+            value: None,
+            last_time: 0.0,
+        }
+    }
+
+    fn update(&mut self, sample: f64, now: f64) -> f64 {
+        let value = match self.value {
+            Some(previous) => {
+                let dt = (now - self.last_time).max(0.0);
+                let alpha = 1.0 - (-dt / self.window_secs).exp();
+                previous + alpha * (sample - previous)
+            }
+            None => sample,
+        };
+        self.value = Some(value);
+        self.last_time = now;
+        value
+    }
+}
+
+/// 5s and 30s averages of the read and write speeds
+#[derive(Debug, Clone, Copy)]
+pub struct SpeedSmoother {
+    read_short: Ema,
+    write_short: Ema,
+    read_long: Ema,
+    write_long: Ema,
+}
+
+impl Default for SpeedSmoother {
+    fn default() -> Self {
+        Self {
+            read_short: Ema::new(SHORT_WINDOW_SECS),
+            write_short: Ema::new(SHORT_WINDOW_SECS),
+            read_long: Ema::new(LONG_WINDOW_SECS),
+            write_long: Ema::new(LONG_WINDOW_SECS),
+        }
+    }
+}
+
+impl SpeedSmoother {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, read_speed: u64, write_speed: u64, now: f64) -> SmoothedSpeeds {
+        let (read, write) = (read_speed as f64, write_speed as f64);
+        SmoothedSpeeds {
+            timestamp: now,
+            read_speed_5s: self.read_short.update(read, now).round() as u64,
+            write_speed_5s: self.write_short.update(write, now).round() as u64,
+            read_speed_30s: self.read_long.update(read, now).round() as u64,
+            write_speed_30s: self.write_long.update(write, now).round() as u64,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Smoothed series of recorded samples; points before `start` only warm up the averages
+pub fn smooth_history(stats: &[DiskStat], start: f64) -> Vec<SmoothedSpeeds> {
+    let mut smoother = SpeedSmoother::new();
+    stats
+        .iter()
+        .map(|stat| smoother.observe(stat.read_speed, stat.write_speed, stat.timestamp))
+        .filter(|point| point.timestamp >= start)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_average_reacts_faster() {
+        let mut smoother = SpeedSmoother::new();
+        smoother.observe(0, 0, 0.0);
+        let mut point = smoother.observe(1000, 0, 1.0);
+        for t in 2..5 {
+            point = smoother.observe(1000, 0, t as f64);
+        }
+        assert!(point.read_speed_5s > point.read_speed_30s);
+        assert!(point.read_speed_5s < 1000);
+        assert_eq!(point.write_speed_5s, 0);
+    }
+
+    #[test]
+    fn test_average_independent_of_interval() {
+        let mut coarse = SpeedSmoother::new();
+        let mut fine = SpeedSmoother::new();
+        coarse.observe(0, 0, 0.0);
+        fine.observe(0, 0, 0.0);
+        let coarse_point = coarse.observe(100, 0, 2.0);
+        fine.observe(100, 0, 1.0);
+        let fine_point = fine.observe(100, 0, 2.0);
+        assert_eq!(coarse_point.read_speed_30s, fine_point.read_speed_30s);
+    }
+}
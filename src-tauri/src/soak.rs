@@ -0,0 +1,247 @@
+//! Hidden `--soak` mode: runs the monitor loop (sampler thread, process
+//! accounting, burst/peak/smoothing state, live payload encoding, write
+//! coalescer, SQLite storage) over synthetic process churn for hours and
+//! fails when memory or database growth is not bounded. Usage:
+//! `driveanalizer --soak [hours]`.
+
+use crate::core::{LivePayload, LoopHandles, MonitorEmitter, MonitorEvent, MonitorLoop};
+use crate::db;
+use crate::db_reader;
+use crate::payload::PayloadEncoder;
+use crate::process_monitor::{self, MockProcessSource, ProcessMonitor};
+use crate::storage::{SqliteStorage, Storage};
+use crate::subscriptions::MetricStream;
+use crate::write_coalescer;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessesToUpdate, System};
+
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+    pub duration: Duration,
+    /// Real time between ticks (much shorter than the app's sampling interval)
+    pub tick: Duration,
+    pub spawn_per_tick: u32,
+    pub lifetime_ticks: u64,
+    pub name_pool: u32,
+    /// Ticks between bound checks
+    pub check_ticks: u64,
+    /// Allowed resident memory growth after the first check
+    pub max_rss_growth_bytes: u64,
+    /// Allowed database growth per simulated second
+    pub max_db_bytes_per_tick: u64,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(2 * 3600),
+            tick: Duration::from_millis(20),
+            spawn_per_tick: 20,
+            lifetime_ticks: 5,
+            name_pool: 2000,
+            check_ticks: 3000,
+            max_rss_growth_bytes: 64 * 1024 * 1024,
+            max_db_bytes_per_tick: 8192,
+        }
+    }
+}
+
+/// Entry point for `main`: returns the process exit code
+pub fn run_from_args(args: &[String]) -> i32 {
+    let mut config = SoakConfig::default();
+    if let Some(hours) = args
+        .iter()
+        .skip_while(|arg| *arg != "--soak")
+        .nth(1)
+        .and_then(|arg| arg.parse::<f64>().ok())
+    {
+        config.duration = Duration::from_secs_f64(hours * 3600.0);
+    }
+
+    match tauri::async_runtime::block_on(run(config)) {
+        Ok(()) => {
+            println!("[Soak] Passed");
+            0
+        }
+        Err(e) => {
+            eprintln!("[Soak] Failed: {}", e);
+            1
+        }
+    }
+}
+
+fn resident_memory(sys: &mut System) -> u64 {
+    let Ok(pid) = sysinfo::get_current_pid() else {
+        return 0;
+    };
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+    sys.process(pid).map(|p| p.memory()).unwrap_or(0)
+}
+
+pub async fn run(config: SoakConfig) -> Result<(), String> {
+    let dir = std::env::temp_dir().join(format!("driveanalizer-soak-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let db_path = dir.join("soak.db");
+    let result = run_in(&config, &db_path).await;
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+/// Stands in for a window subscribed to every stream: payloads are encoded
+/// like channel frames and dropped
+#[derive(Default)]
+struct SoakEmitter {
+    encoder: PayloadEncoder,
+}
+
+impl MonitorEmitter for SoakEmitter {
+    fn wants(&self, _stream: MetricStream) -> bool {
+        true
+    }
+
+    fn publish(&mut self, payload: LivePayload<'_>) -> Result<(), String> {
+        self.encoder.msgpack(&payload).map(|_| ())
+    }
+
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    fn emit(&mut self, _event: MonitorEvent<'_>) {}
+}
+
+async fn run_in(config: &SoakConfig, db_path: &std::path::Path) -> Result<(), String> {
+    let pool = db::open_pool(db_path).await.map_err(|e| e.to_string())?;
+    let shared_pool = Arc::new(Mutex::new(Some(pool.clone())));
+    let contention = db_reader::create_contention();
+    let store: Arc<dyn Storage> = Arc::new(SqliteStorage::new(
+        Arc::clone(&shared_pool),
+        Arc::clone(&contention),
+    ));
+    let handles = LoopHandles::new(shared_pool, Arc::new(Mutex::new(Some(store))), contention);
+    // Ticks come much faster than the flush period; flush every ten of them
+    if let Ok(mut flush) = handles.flush_settings.lock() {
+        flush.buffer_records = 10;
+    }
+    let writes = Arc::clone(&handles.writes);
+    let stats = Arc::clone(&handles.stats);
+
+    let accumulators = process_monitor::create_accumulators();
+    let source = MockProcessSource::new(
+        config.spawn_per_tick,
+        config.lifetime_ticks,
+        config.name_pool,
+        64 * 1024,
+    );
+    let max_live = (config.spawn_per_tick as u64 * (config.lifetime_ticks + 1)) as usize;
+    let mut monitor_loop = MonitorLoop::new(
+        handles,
+        ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source)),
+        SoakEmitter::default(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut sys = System::new();
+    let mut baseline: Option<(u64, u64, u64)> = None;
+    let (wal_path, shm_path) = (
+        db_path.with_extension("db-wal"),
+        db_path.with_extension("db-shm"),
+    );
+
+    let started = Instant::now();
+    let mut tick: u64 = 0;
+
+    println!(
+        "[Soak] Running for {:.1} h ({} fake processes per tick)",
+        config.duration.as_secs_f64() / 3600.0,
+        config.spawn_per_tick
+    );
+
+    while started.elapsed() < config.duration {
+        tick += 1;
+        monitor_loop.tick().await;
+
+        if tick.is_multiple_of(config.check_ticks) {
+            let live = accumulators.lock().map(|acc| acc.len()).unwrap_or(0);
+            if live > max_live {
+                return Err(format!(
+                    "{} accumulators for at most {} live processes",
+                    live, max_live
+                ));
+            }
+            let sizes = stats
+                .lock()
+                .map(|stats| stats.process_maps.clone())
+                .unwrap_or_default();
+            let dead = sizes.dead_history;
+            let max_dead = process_monitor::MAX_DEAD_HISTORY_ENTRIES.min(config.name_pool as usize);
+            if dead > max_dead {
+                return Err(format!(
+                    "{} exited-process entries (at most {})",
+                    dead, max_dead
+                ));
+            }
+            if sizes.snapshots > max_dead + live {
+                return Err(format!("{} snapshot entries", sizes.snapshots));
+            }
+            let pending = write_coalescer::pending_disk_stats(&writes);
+            if pending > 10 {
+                return Err(format!(
+                    "{} disk stats still queued after flushing",
+                    pending
+                ));
+            }
+
+            let rss = resident_memory(&mut sys);
+            let db_size =
+                db::get_db_total_size(db_path, &wal_path, &shm_path).map_err(|e| e.to_string())?;
+            println!(
+                "[Soak] tick {}: rss {} KiB, db {} KiB, live {}, dead {}",
+                tick,
+                rss / 1024,
+                db_size / 1024,
+                live,
+                dead
+            );
+
+            // The first check is the baseline: caches and the schema are warmed up by then
+            let Some((base_tick, base_rss, base_db)) = baseline else {
+                baseline = Some((tick, rss, db_size));
+                continue;
+            };
+            let rss_growth = rss.saturating_sub(base_rss);
+            if rss_growth > config.max_rss_growth_bytes {
+                return Err(format!("resident memory grew by {} KiB", rss_growth / 1024));
+            }
+            let db_per_tick = db_size.saturating_sub(base_db) / (tick - base_tick).max(1);
+            if db_per_tick > config.max_db_bytes_per_tick {
+                return Err(format!("database grows by {} bytes per tick", db_per_tick));
+            }
+        }
+
+        tokio::time::sleep(config.tick).await;
+    }
+
+    monitor_loop.shutdown().await;
+    pool.close().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_short_soak_stays_bounded() {
+        let config = SoakConfig {
+            duration: Duration::from_secs(1),
+            tick: Duration::from_millis(1),
+            spawn_per_tick: 5,
+            name_pool: 20,
+            check_ticks: 25,
+            ..SoakConfig::default()
+        };
+        run(config).await.unwrap();
+    }
+}
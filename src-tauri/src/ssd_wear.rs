@@ -0,0 +1,147 @@
+// SSD aşınma / TBW tahmini
+// Öncelik: NVMe health log > SMART "host writes" > uygulamanın ölçtüğü toplam yazma
+
+use crate::models::SsdWear;
+use crate::smart::NvmeHealth;
+
+/// Kullanıcının girdiği nominal TBW değeri için ayar anahtarı (birim başına)
+pub fn rated_tbw_key(volume: &str) -> String {
+    format!(
+        "ssd_rated_tbw:{}",
+        volume.trim_end_matches(['\\', '/']).to_uppercase()
+    )
+}
+
+const BYTES_PER_TB: f64 = 1_000_000_000_000.0;
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Aşınma tahmini için girdiler
+#[derive(Debug, Clone, Default)]
+pub struct WearInputs {
+    pub volume: String,
+    /// Kullanıcının girdiği değer (NVMe'de zorunlu değil)
+    pub rated_tbw: Option<f64>,
+    /// NVMe SMART/Health log sayfası (None: NVMe değil ya da okunamadı)
+    pub nvme: Option<NvmeHealth>,
+    /// SMART 241 özniteliğinden (None: okunamadı)
+    pub host_writes_bytes: Option<u64>,
+    /// Uygulamanın kaydettiği tüm zamanların yazma toplamı (tüm diskler)
+    pub app_written_bytes: u64,
+    /// Son günlerin ortalama yazma hızı (byte/s, tüm diskler)
+    pub avg_write_bytes_per_sec: f64,
+    /// Şu an (unix saniye)
+    pub now: f64,
+}
+
+pub fn estimate(inputs: &WearInputs) -> SsdWear {
+    let (written, source) = match (&inputs.nvme, inputs.host_writes_bytes) {
+        (Some(nvme), _) => (nvme.data_written_bytes, "nvme"),
+        (None, Some(bytes)) => (bytes, "smart"),
+        (None, None) => (inputs.app_written_bytes, "app"),
+    };
+    let drive_percent_used = inputs.nvme.map(|nvme| nvme.percentage_used);
+
+    // Nominal TBW girilmemişse sürücünün kendi "percentage used" değerinden
+    // türetilir (yazılan / kullanılan oran)
+    let rated_bytes = match (inputs.rated_tbw, drive_percent_used) {
+        (Some(tbw), _) if tbw > 0.0 => tbw * BYTES_PER_TB,
+        (_, Some(percent)) if percent > 0 => written as f64 * 100.0 / percent as f64,
+        _ => 0.0,
+    };
+    let percent_used = match (inputs.rated_tbw, drive_percent_used) {
+        (None, Some(percent)) => percent as f64,
+        _ if rated_bytes > 0.0 => written as f64 / rated_bytes * 100.0,
+        _ => 0.0,
+    };
+
+    // Mevcut yazma hızıyla kalan dayanıklılığın biteceği tarih
+    let daily_writes = inputs.avg_write_bytes_per_sec * SECS_PER_DAY;
+    let remaining = (rated_bytes - written as f64).max(0.0);
+    let projected_end_of_life = (rated_bytes > 0.0 && daily_writes > 0.0)
+        .then(|| inputs.now + remaining / daily_writes * SECS_PER_DAY);
+
+    SsdWear {
+        volume: inputs.volume.clone(),
+        rated_tbw: inputs.rated_tbw,
+        host_writes_bytes: inputs.host_writes_bytes,
+        drive_percent_used,
+        temperature_c: inputs.nvme.and_then(|nvme| nvme.temperature_c),
+        app_written_bytes: inputs.app_written_bytes,
+        percent_used,
+        projected_end_of_life,
+        source: source.to_string(),
+        system_wide: source == "app",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_prefers_smart() {
+        let wear = estimate(&WearInputs {
+            volume: "C:".to_string(),
+            rated_tbw: Some(600.0),
+            nvme: None,
+            host_writes_bytes: Some(150 * 1_000_000_000_000),
+            app_written_bytes: 1_000,
+            avg_write_bytes_per_sec: 0.0,
+            now: 0.0,
+        });
+        assert_eq!(wear.source, "smart");
+        assert!(!wear.system_wide);
+        assert!((wear.percent_used - 25.0).abs() < 1e-9);
+        assert!(wear.projected_end_of_life.is_none());
+    }
+
+    #[test]
+    fn test_estimate_projection_from_app_totals() {
+        // 1 TBW, 0.5 TB written, 0.1 TB/day -> 5 days left
+        let wear = estimate(&WearInputs {
+            volume: "D:".to_string(),
+            rated_tbw: Some(1.0),
+            nvme: None,
+            host_writes_bytes: None,
+            app_written_bytes: 500_000_000_000,
+            avg_write_bytes_per_sec: 100_000_000_000.0 / SECS_PER_DAY,
+            now: 1_000.0,
+        });
+        assert_eq!(wear.source, "app");
+        assert!(wear.system_wide);
+        assert!((wear.percent_used - 50.0).abs() < 1e-9);
+        let eol = wear.projected_end_of_life.unwrap();
+        assert!((eol - (1_000.0 + 5.0 * SECS_PER_DAY)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_estimate_from_nvme_without_rated_tbw() {
+        // 10% used after 60 TB -> implied 600 TBW
+        let wear = estimate(&WearInputs {
+            volume: "C:".to_string(),
+            rated_tbw: None,
+            nvme: Some(NvmeHealth {
+                temperature_c: Some(40.0),
+                percentage_used: 10,
+                data_read_bytes: 0,
+                data_written_bytes: 60 * 1_000_000_000_000,
+                power_on_hours: 100,
+            }),
+            host_writes_bytes: Some(1),
+            app_written_bytes: 1,
+            avg_write_bytes_per_sec: 1_000_000_000_000.0 / SECS_PER_DAY,
+            now: 0.0,
+        });
+        assert_eq!(wear.source, "nvme");
+        assert_eq!(wear.percent_used, 10.0);
+        assert_eq!(wear.drive_percent_used, Some(10));
+        // 540 TB left at 1 TB/day
+        let eol = wear.projected_end_of_life.unwrap();
+        assert!((eol - 540.0 * SECS_PER_DAY).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_rated_tbw_key_normalizes_volume() {
+        assert_eq!(rated_tbw_key("c:\\"), "ssd_rated_tbw:C:");
+    }
+}
@@ -0,0 +1,299 @@
+//! Persistence backend for the high-volume time series (per-second disk
+//! samples and the per-disk breakdown).
+//!
+//! SQLite is the default and always holds settings, process totals and
+//! timeline events; the time series can instead go to the Parquet backend
+//! (`parquet-storage` feature) for users who analyze them with external tools.
+
+use crate::db::{self, SharedPool};
+use crate::db_reader::{ReadSession, SharedContention};
+use crate::models::{DiskStat, PhysicalDiskStat, RangeAggregate};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Settings key selecting the backend (applied at startup)
+pub const STORAGE_BACKEND_KEY: &str = "storage_backend";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    Sqlite,
+    Parquet,
+}
+
+impl StorageBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StorageBackend::Sqlite => "sqlite",
+            StorageBackend::Parquet => "parquet",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sqlite" => Some(StorageBackend::Sqlite),
+            "parquet" => Some(StorageBackend::Parquet),
+            _ => None,
+        }
+    }
+
+    /// Whether this build can use the backend
+    pub fn is_compiled_in(self) -> bool {
+        match self {
+            StorageBackend::Sqlite => true,
+            StorageBackend::Parquet => cfg!(feature = "parquet-storage"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Database not initialized")]
+    Unavailable,
+    #[error("Database error: {0}")]
+    Sqlite(#[from] sqlx::Error),
+    #[error("File storage error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Storage error: {0}")]
+    Other(String),
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    fn backend(&self) -> StorageBackend;
+
+    /// False while the database is being migrated; callers keep buffering
+    fn is_available(&self) -> bool;
+
+    async fn insert_disk_stats(&self, stats: &[DiskStat]) -> StorageResult<()>;
+
+    async fn insert_physical_disk_stats(&self, stats: &[PhysicalDiskStat]) -> StorageResult<()>;
+
+    async fn disk_stats_history(&self, start: f64, end: f64) -> StorageResult<Vec<DiskStat>>;
+
+    async fn physical_disk_stats(
+        &self,
+        start: f64,
+        end: f64,
+    ) -> StorageResult<Vec<PhysicalDiskStat>>;
+
+    /// Averages and peaks over a range; backends with a query engine override this
+    async fn range_aggregate(&self, start: f64, end: f64) -> StorageResult<RangeAggregate> {
+        Ok(aggregate_stats(&self.disk_stats_history(start, end).await?))
+    }
+
+    /// Timestamp of the oldest per-second sample (None when empty)
+    async fn oldest_disk_stat(&self) -> StorageResult<Option<f64>>;
+
+    /// Deletes samples older than `days`, returns the number removed
+    async fn prune_older_than(&self, days: u64) -> StorageResult<u64>;
+
+    /// Deletes every sample (database reset)
+    async fn clear(&self) -> StorageResult<()>;
+
+    /// Persists anything held in memory (called at shutdown)
+    async fn flush(&self) -> StorageResult<()> {
+        Ok(())
+    }
+}
+
+/// Backend slot shared by the monitor and the commands (empty until setup)
+pub type SharedStorage = Arc<Mutex<Option<Arc<dyn Storage>>>>;
+
+pub fn create_shared_storage() -> SharedStorage {
+    Arc::new(Mutex::new(None))
+}
+
+/// Returns the configured backend if it can currently be written to
+pub fn current(shared: &SharedStorage) -> Option<Arc<dyn Storage>> {
+    shared
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .filter(|storage| storage.is_available())
+}
+
+pub(crate) fn aggregate_stats(stats: &[DiskStat]) -> RangeAggregate {
+    if stats.is_empty() {
+        return RangeAggregate {
+            avg_idle_time: 100.0,
+            ..Default::default()
+        };
+    }
+    let count = stats.len() as f64;
+    RangeAggregate {
+        sample_count: stats.len() as u64,
+        avg_read_speed: stats.iter().map(|s| s.read_speed as f64).sum::<f64>() / count,
+        avg_write_speed: stats.iter().map(|s| s.write_speed as f64).sum::<f64>() / count,
+        max_read_speed: stats.iter().map(|s| s.read_speed).max().unwrap_or(0),
+        max_write_speed: stats.iter().map(|s| s.write_speed).max().unwrap_or(0),
+        avg_idle_time: stats.iter().map(|s| s.idle_time).sum::<f64>() / count,
+    }
+}
+
+/// Default backend: the app's SQLite database (follows pool swaps). Range
+/// reads run on their own read-only connection so they never hold up the writer.
+pub struct SqliteStorage {
+    shared_pool: SharedPool,
+    contention: SharedContention,
+}
+
+impl SqliteStorage {
+    pub fn new(shared_pool: SharedPool, contention: SharedContention) -> Self {
+        Self {
+            shared_pool,
+            contention,
+        }
+    }
+
+    fn pool(&self) -> StorageResult<sqlx::Pool<sqlx::Sqlite>> {
+        db::current_pool(&self.shared_pool).ok_or(StorageError::Unavailable)
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Sqlite
+    }
+
+    fn is_available(&self) -> bool {
+        db::current_pool(&self.shared_pool).is_some()
+    }
+
+    async fn insert_disk_stats(&self, stats: &[DiskStat]) -> StorageResult<()> {
+        Ok(db::insert_stats_batch(&self.pool()?, stats).await?)
+    }
+
+    async fn insert_physical_disk_stats(&self, stats: &[PhysicalDiskStat]) -> StorageResult<()> {
+        Ok(db::insert_physical_disk_stats_batch(&self.pool()?, stats).await?)
+    }
+
+    async fn disk_stats_history(&self, start: f64, end: f64) -> StorageResult<Vec<DiskStat>> {
+        let mut read = ReadSession::begin(&self.pool()?, &self.contention).await?;
+        let result = db::get_disk_stats_history(read.conn(), start, end).await;
+        read.finish(&result).await;
+        Ok(result?)
+    }
+
+    async fn physical_disk_stats(
+        &self,
+        start: f64,
+        end: f64,
+    ) -> StorageResult<Vec<PhysicalDiskStat>> {
+        let mut read = ReadSession::begin(&self.pool()?, &self.contention).await?;
+        let result = db::get_physical_disk_stats(read.conn(), start, end).await;
+        read.finish(&result).await;
+        Ok(result?)
+    }
+
+    async fn range_aggregate(&self, start: f64, end: f64) -> StorageResult<RangeAggregate> {
+        let mut read = ReadSession::begin(&self.pool()?, &self.contention).await?;
+        let result = db::get_range_aggregate(read.conn(), start, end).await;
+        read.finish(&result).await;
+        Ok(result?)
+    }
+
+    async fn oldest_disk_stat(&self) -> StorageResult<Option<f64>> {
+        Ok(db::get_oldest_disk_stat(&self.pool()?).await?)
+    }
+
+    async fn prune_older_than(&self, days: u64) -> StorageResult<u64> {
+        Ok(db::cleanup_old_data(&self.pool()?, days).await?)
+    }
+
+    async fn clear(&self) -> StorageResult<()> {
+        Ok(db::clear_disk_stats(&self.pool()?).await?)
+    }
+}
+
+/// Reads the configured backend from settings (SQLite when unset, unknown or
+/// not compiled into this build)
+pub async fn configured_backend(pool: &sqlx::Pool<sqlx::Sqlite>) -> StorageBackend {
+    let requested = db::get_setting(pool, STORAGE_BACKEND_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|value| StorageBackend::parse(&value))
+        .unwrap_or(StorageBackend::Sqlite);
+
+    if requested.is_compiled_in() {
+        requested
+    } else {
+        eprintln!(
+            "[Storage] Backend '{}' is not available in this build, using SQLite",
+            requested.as_str()
+        );
+        StorageBackend::Sqlite
+    }
+}
+
+/// Builds the backend; `resolve_data_dir` is called on every file operation so
+/// file backends follow data directory migrations.
+pub fn open_storage(
+    backend: StorageBackend,
+    shared_pool: SharedPool,
+    contention: SharedContention,
+    #[allow(unused_variables)] resolve_data_dir: impl Fn() -> Option<std::path::PathBuf>
+        + Send
+        + Sync
+        + 'static,
+) -> Arc<dyn Storage> {
+    match backend {
+        #[cfg(feature = "parquet-storage")]
+        StorageBackend::Parquet => Arc::new(crate::storage_parquet::ParquetStorage::new(
+            shared_pool,
+            resolve_data_dir,
+        )),
+        _ => Arc::new(SqliteStorage::new(shared_pool, contention)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(read_speed: u64, write_speed: u64, idle_time: f64) -> DiskStat {
+        DiskStat {
+            read_speed,
+            write_speed,
+            idle_time,
+            ..DiskStat::idle(0.0)
+        }
+    }
+
+    #[test]
+    fn test_aggregate_stats_matches_sql_semantics() {
+        let aggregate = aggregate_stats(&[stat(100, 0, 90.0), stat(300, 50, 70.0)]);
+        assert_eq!(aggregate.sample_count, 2);
+        assert_eq!(aggregate.avg_read_speed, 200.0);
+        assert_eq!(aggregate.max_write_speed, 50);
+        assert_eq!(aggregate.avg_idle_time, 80.0);
+
+        assert_eq!(aggregate_stats(&[]).avg_idle_time, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_unavailable_without_pool() {
+        let storage = SqliteStorage::new(
+            Arc::new(Mutex::new(None)),
+            crate::db_reader::create_contention(),
+        );
+        assert!(!storage.is_available());
+        assert!(matches!(
+            storage.insert_disk_stats(&[]).await,
+            Err(StorageError::Unavailable)
+        ));
+    }
+
+    #[test]
+    fn test_backend_parse_roundtrip() {
+        for backend in [StorageBackend::Sqlite, StorageBackend::Parquet] {
+            assert_eq!(StorageBackend::parse(backend.as_str()), Some(backend));
+        }
+        assert_eq!(StorageBackend::parse("duckdb"), None);
+    }
+}
@@ -0,0 +1,521 @@
+//! Flat-file Parquet backend for the time series (`parquet-storage` feature).
+//!
+//! Samples are buffered in memory and written as immutable files of up to
+//! `ROWS_PER_FILE` rows under `<data_dir>/parquet/<series>/`, named
+//! `<first_ms>_<last_ms>.parquet` so range queries and pruning only open the
+//! files they need. The files can be read directly by DuckDB, pandas, etc.
+
+use crate::db::{self, SharedPool};
+use crate::models::{DiskStat, PhysicalDiskStat};
+use crate::storage::{Storage, StorageBackend, StorageError, StorageResult};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, UInt64Type};
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use async_trait::async_trait;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Sub-directory of the data dir (listed in data_dir::DATA_SUBDIRS)
+pub const PARQUET_DIR: &str = "parquet";
+
+/// One hour of per-second samples per file
+const ROWS_PER_FILE: usize = 3600;
+
+impl From<parquet::errors::ParquetError> for StorageError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        StorageError::Other(e.to_string())
+    }
+}
+
+impl From<arrow_schema::ArrowError> for StorageError {
+    fn from(e: arrow_schema::ArrowError) -> Self {
+        StorageError::Other(e.to_string())
+    }
+}
+
+/// A row type stored as one Parquet series
+trait ParquetRow: Clone + Send + Sync + 'static {
+    const SERIES: &'static str;
+
+    fn timestamp(&self) -> f64;
+    fn schema() -> Arc<Schema>;
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, arrow_schema::ArrowError>;
+    fn from_batch(batch: &RecordBatch) -> Vec<Self>;
+}
+
+fn f64_column(batch: &RecordBatch, index: usize) -> &Float64Array {
+    batch.column(index).as_primitive::<Float64Type>()
+}
+
+fn u64_column(batch: &RecordBatch, index: usize) -> &UInt64Array {
+    batch.column(index).as_primitive::<UInt64Type>()
+}
+
+impl ParquetRow for DiskStat {
+    const SERIES: &'static str = "disk_stats";
+
+    fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("timestamp", DataType::Float64, false),
+            Field::new("read_bytes", DataType::UInt64, false),
+            Field::new("write_bytes", DataType::UInt64, false),
+            Field::new("read_speed", DataType::UInt64, false),
+            Field::new("write_speed", DataType::UInt64, false),
+            Field::new("idle_time", DataType::Float64, false),
+            Field::new("queue_depth", DataType::Float64, false),
+            Field::new("read_iops", DataType::Float64, false),
+            Field::new("write_iops", DataType::Float64, false),
+            Field::new("read_latency_ms", DataType::Float64, false),
+            Field::new("write_latency_ms", DataType::Float64, false),
+            Field::new("monotonic_offset", DataType::Float64, false),
+        ]))
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, arrow_schema::ArrowError> {
+        let f64s = |f: fn(&DiskStat) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(f)))
+        };
+        let u64s = |f: fn(&DiskStat) -> u64| -> ArrayRef {
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(f)))
+        };
+        RecordBatch::try_new(
+            Self::schema(),
+            vec![
+                f64s(|s| s.timestamp),
+                u64s(|s| s.read_bytes),
+                u64s(|s| s.write_bytes),
+                u64s(|s| s.read_speed),
+                u64s(|s| s.write_speed),
+                f64s(|s| s.idle_time),
+                f64s(|s| s.queue_depth),
+                f64s(|s| s.read_iops),
+                f64s(|s| s.write_iops),
+                f64s(|s| s.read_latency_ms),
+                f64s(|s| s.write_latency_ms),
+                f64s(|s| s.monotonic_offset),
+            ],
+        )
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Vec<Self> {
+        // Files written before the offset was stored have 11 columns
+        let offsets = (batch.num_columns() > 11).then(|| f64_column(batch, 11));
+        (0..batch.num_rows())
+            .map(|i| DiskStat {
+                timestamp: f64_column(batch, 0).value(i),
+                read_bytes: u64_column(batch, 1).value(i),
+                write_bytes: u64_column(batch, 2).value(i),
+                read_speed: u64_column(batch, 3).value(i),
+                write_speed: u64_column(batch, 4).value(i),
+                idle_time: f64_column(batch, 5).value(i),
+                queue_depth: f64_column(batch, 6).value(i),
+                read_iops: f64_column(batch, 7).value(i),
+                write_iops: f64_column(batch, 8).value(i),
+                read_latency_ms: f64_column(batch, 9).value(i),
+                write_latency_ms: f64_column(batch, 10).value(i),
+                monotonic_offset: offsets.map_or(0.0, |column| column.value(i)),
+            })
+            .collect()
+    }
+}
+
+impl ParquetRow for PhysicalDiskStat {
+    const SERIES: &'static str = "physical_disk_stats";
+
+    fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("timestamp", DataType::Float64, false),
+            Field::new("disk", DataType::Utf8, false),
+            Field::new("read_speed", DataType::UInt64, false),
+            Field::new("write_speed", DataType::UInt64, false),
+            Field::new("idle_time", DataType::Float64, false),
+            Field::new("queue_depth", DataType::Float64, false),
+        ]))
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch, arrow_schema::ArrowError> {
+        RecordBatch::try_new(
+            Self::schema(),
+            vec![
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|s| s.timestamp),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|s| s.disk.as_str()),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|s| s.read_speed),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|s| s.write_speed),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|s| s.idle_time),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|s| s.queue_depth),
+                )),
+            ],
+        )
+    }
+
+    fn from_batch(batch: &RecordBatch) -> Vec<Self> {
+        let disks = batch.column(1).as_string::<i32>();
+        (0..batch.num_rows())
+            .map(|i| PhysicalDiskStat {
+                timestamp: f64_column(batch, 0).value(i),
+                disk: disks.value(i).to_string(),
+                read_speed: u64_column(batch, 2).value(i),
+                write_speed: u64_column(batch, 3).value(i),
+                idle_time: f64_column(batch, 4).value(i),
+                queue_depth: f64_column(batch, 5).value(i),
+            })
+            .collect()
+    }
+}
+
+/// Time range encoded in a series file name
+fn file_range(path: &Path) -> Option<(f64, f64)> {
+    let stem = path.file_name()?.to_str()?.strip_suffix(".parquet")?;
+    let (first, last) = stem.split_once('_')?;
+    Some((
+        first.parse::<i64>().ok()? as f64 / 1000.0,
+        last.parse::<i64>().ok()? as f64 / 1000.0,
+    ))
+}
+
+fn series_files(dir: &Path) -> StorageResult<Vec<(PathBuf, f64, f64)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some((first, last)) = file_range(&path) {
+            files.push((path, first, last));
+        }
+    }
+    files.sort_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(files)
+}
+
+fn write_file<T: ParquetRow>(dir: &Path, rows: &[T]) -> StorageResult<()> {
+    let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+        return Ok(());
+    };
+    fs::create_dir_all(dir)?;
+    let name = format!(
+        "{}_{}.parquet",
+        (first.timestamp() * 1000.0) as i64,
+        (last.timestamp() * 1000.0) as i64
+    );
+    let tmp_path = dir.join(format!("{}.tmp", name));
+
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(fs::File::create(&tmp_path)?, T::schema(), Some(props))?;
+    writer.write(&T::to_batch(rows)?)?;
+    writer.close()?;
+
+    fs::rename(tmp_path, dir.join(name))?;
+    Ok(())
+}
+
+fn read_range<T: ParquetRow>(dir: &Path, start: f64, end: f64) -> StorageResult<Vec<T>> {
+    let mut rows = Vec::new();
+    for (path, first, last) in series_files(dir)? {
+        if last < start || first > end {
+            continue;
+        }
+        let reader = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(path)?)?.build()?;
+        for batch in reader {
+            rows.extend(
+                T::from_batch(&batch?)
+                    .into_iter()
+                    .filter(|row| row.timestamp() >= start && row.timestamp() <= end),
+            );
+        }
+    }
+    Ok(rows)
+}
+
+/// Deletes files whose newest sample is older than `cutoff`; returns the rows removed
+fn prune_files(dir: &Path, cutoff: f64) -> StorageResult<u64> {
+    let mut removed = 0;
+    for (path, _, last) in series_files(dir)? {
+        if last >= cutoff {
+            continue;
+        }
+        let rows = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&path)?)?
+            .metadata()
+            .file_metadata()
+            .num_rows();
+        fs::remove_file(path)?;
+        removed += rows.max(0) as u64;
+    }
+    Ok(removed)
+}
+
+/// In-memory tail of one series
+struct Series<T> {
+    pending: Mutex<Vec<T>>,
+}
+
+impl<T: ParquetRow> Series<T> {
+    fn new() -> Self {
+        Self {
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn lock(&self) -> StorageResult<std::sync::MutexGuard<'_, Vec<T>>> {
+        self.pending
+            .lock()
+            .map_err(|e| StorageError::Other(format!("Lock error: {}", e)))
+    }
+
+    /// Appends rows and returns a full file's worth once the buffer is big enough
+    fn push(&self, rows: &[T], force: bool) -> StorageResult<Option<Vec<T>>> {
+        let mut pending = self.lock()?;
+        pending.extend_from_slice(rows);
+        if pending.is_empty() || (!force && pending.len() < ROWS_PER_FILE) {
+            return Ok(None);
+        }
+        Ok(Some(std::mem::take(&mut *pending)))
+    }
+
+    fn pending_in_range(&self, start: f64, end: f64) -> StorageResult<Vec<T>> {
+        Ok(self
+            .lock()?
+            .iter()
+            .filter(|row| row.timestamp() >= start && row.timestamp() <= end)
+            .cloned()
+            .collect())
+    }
+}
+
+type DataDirResolver = Box<dyn Fn() -> Option<PathBuf> + Send + Sync>;
+
+pub struct ParquetStorage {
+    shared_pool: SharedPool,
+    resolve_data_dir: DataDirResolver,
+    disk_stats: Series<DiskStat>,
+    physical_disk_stats: Series<PhysicalDiskStat>,
+}
+
+impl ParquetStorage {
+    /// The SQLite pool is only used as the availability signal: while it is
+    /// out of its slot the data directory is being migrated.
+    pub fn new(
+        shared_pool: SharedPool,
+        resolve_data_dir: impl Fn() -> Option<PathBuf> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            shared_pool,
+            resolve_data_dir: Box::new(resolve_data_dir),
+            disk_stats: Series::new(),
+            physical_disk_stats: Series::new(),
+        }
+    }
+
+    fn series_dir<T: ParquetRow>(&self) -> StorageResult<PathBuf> {
+        (self.resolve_data_dir)()
+            .map(|dir| dir.join(PARQUET_DIR).join(T::SERIES))
+            .ok_or(StorageError::Unavailable)
+    }
+
+    async fn append<T: ParquetRow>(
+        &self,
+        series: &Series<T>,
+        rows: &[T],
+        force: bool,
+    ) -> StorageResult<()> {
+        let Some(full) = series.push(rows, force)? else {
+            return Ok(());
+        };
+        let dir = self.series_dir::<T>()?;
+        let result = tokio::task::spawn_blocking({
+            let full = full.clone();
+            move || write_file(&dir, &full)
+        })
+        .await
+        .map_err(|e| StorageError::Other(e.to_string()))?;
+
+        if result.is_err() {
+            // Keep the rows for the next attempt
+            series.lock()?.splice(0..0, full);
+        }
+        result
+    }
+
+    async fn query<T: ParquetRow>(
+        &self,
+        series: &Series<T>,
+        start: f64,
+        end: f64,
+    ) -> StorageResult<Vec<T>> {
+        let dir = self.series_dir::<T>()?;
+        let mut rows = tokio::task::spawn_blocking(move || read_range::<T>(&dir, start, end))
+            .await
+            .map_err(|e| StorageError::Other(e.to_string()))??;
+        rows.extend(series.pending_in_range(start, end)?);
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl Storage for ParquetStorage {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Parquet
+    }
+
+    fn is_available(&self) -> bool {
+        db::current_pool(&self.shared_pool).is_some()
+    }
+
+    async fn insert_disk_stats(&self, stats: &[DiskStat]) -> StorageResult<()> {
+        self.append(&self.disk_stats, stats, false).await
+    }
+
+    async fn insert_physical_disk_stats(&self, stats: &[PhysicalDiskStat]) -> StorageResult<()> {
+        self.append(&self.physical_disk_stats, stats, false).await
+    }
+
+    async fn disk_stats_history(&self, start: f64, end: f64) -> StorageResult<Vec<DiskStat>> {
+        self.query(&self.disk_stats, start, end).await
+    }
+
+    async fn physical_disk_stats(
+        &self,
+        start: f64,
+        end: f64,
+    ) -> StorageResult<Vec<PhysicalDiskStat>> {
+        self.query(&self.physical_disk_stats, start, end).await
+    }
+
+    async fn oldest_disk_stat(&self) -> StorageResult<Option<f64>> {
+        let dir = self.series_dir::<DiskStat>()?;
+        let files = tokio::task::spawn_blocking(move || series_files(&dir))
+            .await
+            .map_err(|e| StorageError::Other(e.to_string()))??;
+        let pending = self.disk_stats.lock()?.first().map(|stat| stat.timestamp);
+        Ok(files
+            .first()
+            .map(|(_, first, _)| *first)
+            .into_iter()
+            .chain(pending)
+            .reduce(f64::min))
+    }
+
+    async fn prune_older_than(&self, days: u64) -> StorageResult<u64> {
+        let cutoff = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+            - (days as f64 * 86400.0);
+        let disk_dir = self.series_dir::<DiskStat>()?;
+        let physical_dir = self.series_dir::<PhysicalDiskStat>()?;
+        tokio::task::spawn_blocking(move || {
+            Ok(prune_files(&disk_dir, cutoff)? + prune_files(&physical_dir, cutoff)?)
+        })
+        .await
+        .map_err(|e| StorageError::Other(e.to_string()))?
+    }
+
+    async fn clear(&self) -> StorageResult<()> {
+        self.disk_stats.lock()?.clear();
+        self.physical_disk_stats.lock()?.clear();
+        for dir in [
+            self.series_dir::<DiskStat>()?,
+            self.series_dir::<PhysicalDiskStat>()?,
+        ] {
+            if dir.is_dir() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn flush(&self) -> StorageResult<()> {
+        self.append(&self.disk_stats, &[], true).await?;
+        self.append(&self.physical_disk_stats, &[], true).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(timestamp: f64) -> DiskStat {
+        DiskStat {
+            read_bytes: 1,
+            write_bytes: 2,
+            read_speed: 3,
+            write_speed: 4,
+            idle_time: 95.0,
+            queue_depth: 0.1,
+            read_iops: 5.0,
+            write_iops: 6.0,
+            read_latency_ms: 0.5,
+            write_latency_ms: 0.7,
+            ..DiskStat::idle(timestamp)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parquet_roundtrip_and_prune() {
+        let dir =
+            std::env::temp_dir().join(format!("driveanalizer-parquet-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let shared_pool: SharedPool = Arc::new(Mutex::new(Some(pool)));
+
+        let root = dir.clone();
+        let storage = ParquetStorage::new(shared_pool, move || Some(root.clone()));
+
+        storage
+            .insert_disk_stats(&[stat(10.0), stat(20.0)])
+            .await
+            .unwrap();
+        // Pending rows are visible before they reach a file
+        assert_eq!(
+            storage.disk_stats_history(0.0, 100.0).await.unwrap().len(),
+            2
+        );
+
+        storage.flush().await.unwrap();
+        storage.insert_disk_stats(&[stat(30.0)]).await.unwrap();
+        let history = storage.disk_stats_history(15.0, 100.0).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].write_latency_ms, 0.7);
+        assert_eq!(storage.oldest_disk_stat().await.unwrap(), Some(10.0));
+
+        // Samples from 1970 are older than any retention window
+        assert_eq!(storage.prune_older_than(1).await.unwrap(), 2);
+        assert_eq!(storage.oldest_disk_stat().await.unwrap(), Some(30.0));
+        assert_eq!(
+            storage.disk_stats_history(0.0, 100.0).await.unwrap().len(),
+            1
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Event streams produced by the monitor loop that the frontend can opt into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetricStream {
+    /// `disk-metrics` - aggregate speeds and session totals
+    DiskMetrics,
+    /// `top-processes` - per-process I/O list (the most expensive payload)
+    TopProcesses,
+    /// `disk-breakdown` - per physical disk samples
+    DiskBreakdown,
+    /// `smoothed-speeds` - 5s/30s moving averages of the speeds (opt-in)
+    SmoothedSpeeds,
+    /// `drive-activity` - idle/read/write LED state, sent on change (opt-in)
+    DriveActivity,
+    /// `top-files` - busiest files of the session, while file tracing runs (opt-in)
+    TopFiles,
+    /// `top-processes-diff` - the top-processes list as full/delta frames (opt-in)
+    TopProcessesDiff,
+}
+
+impl MetricStream {
+    pub const ALL: [MetricStream; 7] = [
+        MetricStream::DiskMetrics,
+        MetricStream::TopProcesses,
+        MetricStream::DiskBreakdown,
+        MetricStream::SmoothedSpeeds,
+        MetricStream::DriveActivity,
+        MetricStream::TopFiles,
+        MetricStream::TopProcessesDiff,
+    ];
+
+    /// Name of the window event carrying the stream
+    pub fn event_name(self) -> &'static str {
+        match self {
+            MetricStream::DiskMetrics => "disk-metrics",
+            MetricStream::TopProcesses => "top-processes",
+            MetricStream::DiskBreakdown => "disk-breakdown",
+            MetricStream::SmoothedSpeeds => "smoothed-speeds",
+            MetricStream::DriveActivity => "drive-activity",
+            MetricStream::TopFiles => "top-files",
+            MetricStream::TopProcessesDiff => "top-processes-diff",
+        }
+    }
+
+    /// Streams that existed before subscriptions could be declared; newer
+    /// streams have to be requested explicitly
+    pub const DEFAULT: [MetricStream; 3] = [
+        MetricStream::DiskMetrics,
+        MetricStream::TopProcesses,
+        MetricStream::DiskBreakdown,
+    ];
+}
+
+/// Streams currently displayed by the frontend.
+///
+/// The original streams are subscribed by default so a frontend that never
+/// declares its needs keeps receiving every event it knows about. Recording to the database is never
+/// affected; only computing and emitting the live payloads is skipped.
+#[derive(Debug, Clone)]
+pub struct SubscriptionSet {
+    /// Streams declared for the whole frontend (`set_metric_subscriptions`)
+    shared: HashSet<MetricStream>,
+    /// Streams each window asked for (`subscribe_metrics`), by window label
+    windows: HashMap<String, HashSet<MetricStream>>,
+    /// `shared` still holds the defaults nobody asked for
+    defaults: bool,
+}
+
+impl Default for SubscriptionSet {
+    fn default() -> Self {
+        Self {
+            shared: MetricStream::DEFAULT.into_iter().collect(),
+            windows: HashMap::new(),
+            defaults: true,
+        }
+    }
+}
+
+impl SubscriptionSet {
+    pub fn contains(&self, stream: MetricStream) -> bool {
+        self.shared.contains(&stream) || self.windows.values().any(|s| s.contains(&stream))
+    }
+
+    /// Wanted streams in `MetricStream::ALL` order
+    pub fn streams(&self) -> Vec<MetricStream> {
+        MetricStream::ALL
+            .into_iter()
+            .filter(|stream| self.contains(*stream))
+            .collect()
+    }
+
+    /// Replaces the frontend-wide declaration
+    pub fn set_shared(&mut self, streams: impl IntoIterator<Item = MetricStream>) {
+        self.shared = streams.into_iter().collect();
+        self.defaults = false;
+    }
+
+    /// Adds streams for one window; the first explicit declaration drops the defaults
+    pub fn subscribe(&mut self, window: &str, streams: impl IntoIterator<Item = MetricStream>) {
+        self.drop_defaults();
+        self.windows
+            .entry(window.to_string())
+            .or_default()
+            .extend(streams);
+    }
+
+    pub fn unsubscribe(&mut self, window: &str, streams: &[MetricStream]) {
+        self.drop_defaults();
+        if let Some(subscribed) = self.windows.get_mut(window) {
+            subscribed.retain(|stream| !streams.contains(stream));
+            if subscribed.is_empty() {
+                self.windows.remove(window);
+            }
+        }
+    }
+
+    /// Forgets a closed window
+    pub fn remove_window(&mut self, window: &str) {
+        self.windows.remove(window);
+    }
+
+    fn drop_defaults(&mut self) {
+        if self.defaults {
+            self.shared.clear();
+            self.defaults = false;
+        }
+    }
+}
+
+pub type MetricSubscriptions = Arc<Mutex<SubscriptionSet>>;
+
+pub fn create_subscriptions() -> MetricSubscriptions {
+    Arc::new(Mutex::new(SubscriptionSet::default()))
+}
+
+/// Returns true if the stream is wanted (a poisoned lock counts as subscribed)
+pub fn is_subscribed(subscriptions: &MetricSubscriptions, stream: MetricStream) -> bool {
+    subscriptions
+        .lock()
+        .map(|set| set.contains(stream))
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_subscribes_original_streams() {
+        let subscriptions = create_subscriptions();
+        for stream in MetricStream::DEFAULT {
+            assert!(is_subscribed(&subscriptions, stream));
+        }
+        assert!(!is_subscribed(&subscriptions, MetricStream::SmoothedSpeeds));
+    }
+
+    #[test]
+    fn test_unsubscribed_stream() {
+        let subscriptions = create_subscriptions();
+        subscriptions
+            .lock()
+            .unwrap()
+            .set_shared([MetricStream::DiskMetrics, MetricStream::DiskBreakdown]);
+        assert!(!is_subscribed(&subscriptions, MetricStream::TopProcesses));
+        assert!(is_subscribed(&subscriptions, MetricStream::DiskMetrics));
+    }
+
+    #[test]
+    fn test_window_subscriptions_are_combined() {
+        let subscriptions = create_subscriptions();
+        let mut set = subscriptions.lock().unwrap();
+        set.subscribe("main", [MetricStream::DiskMetrics]);
+        // Declaring per window drops the implicit defaults
+        assert!(!set.contains(MetricStream::TopProcesses));
+
+        set.subscribe(
+            "widget",
+            [MetricStream::DriveActivity, MetricStream::DiskMetrics],
+        );
+        set.unsubscribe("main", &[MetricStream::DiskMetrics]);
+        assert_eq!(
+            set.streams(),
+            [MetricStream::DiskMetrics, MetricStream::DriveActivity]
+        );
+
+        set.remove_window("widget");
+        assert!(set.streams().is_empty());
+    }
+
+    #[test]
+    fn test_stream_names() {
+        let stream: MetricStream = serde_json::from_str("\"top-processes\"").unwrap();
+        assert_eq!(stream, MetricStream::TopProcesses);
+        for stream in MetricStream::ALL {
+            assert_eq!(
+                serde_json::to_string(&stream).unwrap(),
+                format!("\"{}\"", stream.event_name())
+            );
+        }
+    }
+}
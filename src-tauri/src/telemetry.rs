@@ -0,0 +1,202 @@
+// Opt-in anonymous telemetry.
+//
+// Only aggregate counters are ever reported: which features were used (and
+// how often), how many times the app panicked, app version and OS family.
+// No process names, paths, drive names or machine identifiers are collected.
+// Nothing is sent unless the user opted in AND the build has an endpoint.
+
+use crate::data_dir;
+use crate::db::{self, SharedPool};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::AppHandle;
+
+/// Settings key holding the user's consent ("true" / "false")
+pub const TELEMETRY_ENABLED_KEY: &str = "telemetry_enabled";
+
+/// Collector endpoint, baked in at build time; unset builds never send anything
+pub const TELEMETRY_ENDPOINT: Option<&str> = option_env!("DRIVEANALIZER_TELEMETRY_URL");
+
+/// Bumped whenever the payload shape changes
+const PAYLOAD_VERSION: u32 = 1;
+
+const CRASH_COUNT_FILE: &str = "crash_count";
+
+/// In-memory usage counters since the last successful report
+pub struct TelemetryCounters {
+    features: Mutex<BTreeMap<String, u64>>,
+    started: Instant,
+}
+
+pub type Telemetry = Arc<TelemetryCounters>;
+
+pub fn create_telemetry() -> Telemetry {
+    Arc::new(TelemetryCounters {
+        features: Mutex::new(BTreeMap::new()),
+        started: Instant::now(),
+    })
+}
+
+/// Exactly what would be sent - also what preview_telemetry_payload returns
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryPayload {
+    pub payload_version: u32,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub features_used: BTreeMap<String, u64>,
+    pub crash_count: u64,
+    pub uptime_hours: u64,
+}
+
+/// Telemetry status for the settings screen
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryStatus {
+    pub enabled: bool,
+    /// False for builds without an endpoint: enabling has no effect there
+    pub endpoint_configured: bool,
+}
+
+/// Counts one use of a feature (command name)
+pub fn record_feature(telemetry: &Telemetry, feature: &str) {
+    if let Ok(mut features) = telemetry.features.lock() {
+        *features.entry(feature.to_string()).or_insert(0) += 1;
+    }
+}
+
+fn crash_count_path(app: &AppHandle) -> Option<PathBuf> {
+    data_dir::resolve_data_dir(app)
+        .ok()
+        .map(|dir| dir.join("logs").join(CRASH_COUNT_FILE))
+}
+
+fn read_crash_count(app: &AppHandle) -> u64 {
+    crash_count_path(app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| content.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Counts panics in a file so crashes that take the process down are still
+/// reported on the next run. Chains to the previously installed hook.
+pub fn install_panic_hook(app: AppHandle) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(path) = crash_count_path(&app) {
+            let count = read_crash_count(&app) + 1;
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let _ = fs::write(&path, count.to_string());
+        }
+        previous(info);
+    }));
+}
+
+pub fn build_payload(app: &AppHandle, telemetry: &Telemetry) -> TelemetryPayload {
+    let features_used = telemetry
+        .features
+        .lock()
+        .map(|features| features.clone())
+        .unwrap_or_default();
+
+    TelemetryPayload {
+        payload_version: PAYLOAD_VERSION,
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        features_used,
+        crash_count: read_crash_count(app),
+        // Rounded to whole hours so it can't be used to correlate sessions
+        uptime_hours: telemetry.started.elapsed().as_secs() / 3600,
+    }
+}
+
+pub async fn is_enabled(pool: &Pool<Sqlite>) -> Result<bool, sqlx::Error> {
+    Ok(db::get_setting(pool, TELEMETRY_ENABLED_KEY)
+        .await?
+        .is_some_and(|value| value == "true"))
+}
+
+pub async fn set_enabled(pool: &Pool<Sqlite>, enabled: bool) -> Result<(), sqlx::Error> {
+    db::set_setting(
+        pool,
+        TELEMETRY_ENABLED_KEY,
+        if enabled { "true" } else { "false" },
+    )
+    .await
+}
+
+async fn send_payload(endpoint: &str, payload: &TelemetryPayload) -> Result<(), reqwest::Error> {
+    reqwest::Client::new()
+        .post(endpoint)
+        .timeout(std::time::Duration::from_secs(30))
+        .json(payload)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Sends one report a day while the user has opted in. Counters are only
+/// reset after a successful send.
+pub async fn start_telemetry_scheduler(
+    app: AppHandle,
+    shared_pool: SharedPool,
+    telemetry: Telemetry,
+) {
+    let Some(endpoint) = TELEMETRY_ENDPOINT else {
+        println!("[Telemetry] No endpoint configured in this build, reporting disabled");
+        return;
+    };
+
+    let mut report_interval = tokio::time::interval(std::time::Duration::from_secs(86400));
+    // The first tick fires immediately; report after a full day instead
+    report_interval.tick().await;
+
+    loop {
+        report_interval.tick().await;
+        let Some(pool) = db::current_pool(&shared_pool) else {
+            continue;
+        };
+        if !is_enabled(&pool).await.unwrap_or(false) {
+            continue;
+        }
+
+        let payload = build_payload(&app, &telemetry);
+        match send_payload(endpoint, &payload).await {
+            Ok(()) => {
+                if let Ok(mut features) = telemetry.features.lock() {
+                    features.clear();
+                }
+                if let Some(path) = crash_count_path(&app) {
+                    let _ = fs::remove_file(path);
+                }
+                println!("[Telemetry] Report sent");
+            }
+            Err(e) => eprintln!("[Telemetry] Failed to send report: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_feature_counts_uses() {
+        let telemetry = create_telemetry();
+        record_feature(&telemetry, "create_backup");
+        record_feature(&telemetry, "create_backup");
+        record_feature(&telemetry, "fuzzy_search");
+
+        let features = telemetry.features.lock().unwrap();
+        assert_eq!(features["create_backup"], 2);
+        assert_eq!(features["fuzzy_search"], 1);
+    }
+}
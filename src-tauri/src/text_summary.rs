@@ -0,0 +1,191 @@
+use crate::models::RangeAggregate;
+use serde::{Deserialize, Serialize};
+
+/// Time window a summary covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryRange {
+    LastMinute,
+    LastHour,
+    LastDay,
+    LastWeek,
+}
+
+impl SummaryRange {
+    pub fn duration_secs(self) -> f64 {
+        match self {
+            SummaryRange::LastMinute => 60.0,
+            SummaryRange::LastHour => 3600.0,
+            SummaryRange::LastDay => 86400.0,
+            SummaryRange::LastWeek => 604800.0,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SummaryRange::LastMinute => "the last minute",
+            SummaryRange::LastHour => "the last hour",
+            SummaryRange::LastDay => "the last 24 hours",
+            SummaryRange::LastWeek => "the last 7 days",
+        }
+    }
+}
+
+/// Plain-language description of the dashboard for screen readers and tooltips
+#[derive(Debug, Clone, Serialize)]
+pub struct TextSummary {
+    /// Full summary, one or more sentences
+    pub text: String,
+    /// One short line for the tray tooltip
+    pub short: String,
+}
+
+/// The process with the largest share of this session's I/O
+#[derive(Debug, Clone)]
+pub struct DominantProcess {
+    pub name: String,
+    /// "reads" or "writes"
+    pub direction: &'static str,
+    /// 0-100
+    pub share_percent: f64,
+}
+
+/// Formats a byte rate as B/s, KB/s, MB/s or GB/s (1024 based, like the UI)
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec.max(0.0);
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 100.0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Picks the process dominating whichever direction (reads/writes) had more bytes
+pub fn dominant_process(session_totals: &[(String, u64, u64)]) -> Option<DominantProcess> {
+    let total_read: u64 = session_totals.iter().map(|(_, r, _)| *r).sum();
+    let total_write: u64 = session_totals.iter().map(|(_, _, w)| *w).sum();
+
+    let (direction, total, top) = if total_write >= total_read {
+        (
+            "writes",
+            total_write,
+            session_totals.iter().max_by_key(|(_, _, w)| *w)?,
+        )
+    } else {
+        (
+            "reads",
+            total_read,
+            session_totals.iter().max_by_key(|(_, r, _)| *r)?,
+        )
+    };
+    if total == 0 {
+        return None;
+    }
+
+    let top_bytes = if direction == "writes" { top.2 } else { top.1 };
+    Some(DominantProcess {
+        name: top.0.clone(),
+        direction,
+        share_percent: top_bytes as f64 / total as f64 * 100.0,
+    })
+}
+
+pub fn summarize(
+    range: SummaryRange,
+    aggregate: &RangeAggregate,
+    dominant: Option<&DominantProcess>,
+) -> TextSummary {
+    if aggregate.sample_count == 0 {
+        let text = format!("No disk activity was recorded over {}.", range.label());
+        return TextSummary {
+            short: "No disk activity recorded".to_string(),
+            text,
+        };
+    }
+
+    let mut sentences = vec![
+        format!(
+            "Writes averaged {} over {} (peak {}), reads averaged {} (peak {}).",
+            format_rate(aggregate.avg_write_speed),
+            range.label(),
+            format_rate(aggregate.max_write_speed as f64),
+            format_rate(aggregate.avg_read_speed),
+            format_rate(aggregate.max_read_speed as f64),
+        ),
+        format!(
+            "The disk was busy {:.0}% of the time.",
+            (100.0 - aggregate.avg_idle_time).clamp(0.0, 100.0)
+        ),
+    ];
+
+    if let Some(dominant) = dominant {
+        if dominant.share_percent >= 1.0 {
+            sentences.push(format!(
+                "This session, {} were dominated by {} at {:.0}%.",
+                dominant.direction, dominant.name, dominant.share_percent
+            ));
+        }
+    }
+
+    TextSummary {
+        text: sentences.join(" "),
+        short: format!(
+            "R {} / W {}",
+            format_rate(aggregate.avg_read_speed),
+            format_rate(aggregate.avg_write_speed)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(512.0), "512 B/s");
+        assert_eq!(format_rate(12.0 * 1024.0 * 1024.0), "12.0 MB/s");
+        assert_eq!(format_rate(150.0 * 1024.0), "150 KB/s");
+    }
+
+    #[test]
+    fn test_summary_mentions_dominant_process() {
+        let aggregate = RangeAggregate {
+            sample_count: 3600,
+            avg_read_speed: 1024.0,
+            avg_write_speed: 12.0 * 1024.0 * 1024.0,
+            max_read_speed: 4096,
+            max_write_speed: 80 * 1024 * 1024,
+            avg_idle_time: 85.0,
+        };
+        let dominant = dominant_process(&[
+            ("steam.exe".to_string(), 0, 800),
+            ("chrome.exe".to_string(), 50, 200),
+        ])
+        .unwrap();
+        let summary = summarize(SummaryRange::LastHour, &aggregate, Some(&dominant));
+
+        assert!(summary
+            .text
+            .starts_with("Writes averaged 12.0 MB/s over the last hour"));
+        assert!(summary.text.contains("busy 15%"));
+        assert!(summary
+            .text
+            .contains("writes were dominated by steam.exe at 80%"));
+    }
+
+    #[test]
+    fn test_summary_without_samples() {
+        let summary = summarize(SummaryRange::LastMinute, &RangeAggregate::default(), None);
+        assert_eq!(
+            summary.text,
+            "No disk activity was recorded over the last minute."
+        );
+    }
+}
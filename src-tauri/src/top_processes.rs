@@ -0,0 +1,183 @@
+//! Diff-based `top-processes-diff` payloads: the list barely changes while the
+//! disk is idle, so only changed entries are sent, with a full snapshot
+//! every `FULL_SNAPSHOT_EVERY` frames and whenever a new subscriber joins.
+//! How many processes the list holds before the rest is folded into "Others"
+//! is a setting.
+
+use crate::db;
+use crate::models::ProcessIOStat;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Frames between full snapshots (late listeners catch up within this many ticks)
+pub const FULL_SNAPSHOT_EVERY: u32 = 30;
+
+pub const TOP_LIMIT_KEY: &str = "top_processes_limit";
+
+/// Processes listed by default
+pub const DEFAULT_TOP_LIMIT: usize = 50;
+
+/// Largest limit accepted; 0 lists every process
+pub const MAX_TOP_LIMIT: usize = 10_000;
+
+pub type SharedTopLimit = Arc<AtomicUsize>;
+
+pub fn create_top_limit() -> SharedTopLimit {
+    Arc::new(AtomicUsize::new(DEFAULT_TOP_LIMIT))
+}
+
+pub fn current_limit(limit: &SharedTopLimit) -> usize {
+    limit.load(Ordering::Relaxed)
+}
+
+pub fn validate_limit(limit: usize) -> Result<usize, String> {
+    if limit > MAX_TOP_LIMIT {
+        return Err(format!(
+            "Limit must be at most {} (0 lists every process)",
+            MAX_TOP_LIMIT
+        ));
+    }
+    Ok(limit)
+}
+
+pub async fn load_top_limit(pool: &Pool<Sqlite>) -> Result<usize, sqlx::Error> {
+    Ok(db::get_setting(pool, TOP_LIMIT_KEY)
+        .await?
+        .and_then(|value| value.parse::<usize>().ok())
+        .map(|limit| limit.min(MAX_TOP_LIMIT))
+        .unwrap_or(DEFAULT_TOP_LIMIT))
+}
+
+pub async fn save_top_limit(pool: &Pool<Sqlite>, limit: usize) -> Result<(), sqlx::Error> {
+    db::set_setting(pool, TOP_LIMIT_KEY, &limit.to_string()).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TopProcessesFrame {
+    /// The whole list in display order
+    Full { processes: Vec<ProcessIOStat> },
+    /// New or changed entries (by name) and names no longer listed; the
+    /// receiver re-sorts by `total_bytes`
+    Delta {
+        changed: Vec<ProcessIOStat>,
+        removed: Vec<String>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct TopProcessesDiff {
+    previous: HashMap<String, ProcessIOStat>,
+    /// Frames sent since the last full snapshot (None before the first one)
+    since_full: Option<u32>,
+}
+
+impl TopProcessesDiff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Next frame to send, or None when nothing changed
+    pub fn observe(
+        &mut self,
+        processes: &[ProcessIOStat],
+        force_full: bool,
+    ) -> Option<TopProcessesFrame> {
+        let full_due = force_full
+            || self
+                .since_full
+                .is_none_or(|since| since + 1 >= FULL_SNAPSHOT_EVERY);
+
+        let changed: Vec<ProcessIOStat> = processes
+            .iter()
+            .filter(|p| self.previous.get(&p.name) != Some(*p))
+            .cloned()
+            .collect();
+        let removed: Vec<String> = self
+            .previous
+            .keys()
+            .filter(|name| !processes.iter().any(|p| &p.name == *name))
+            .cloned()
+            .collect();
+
+        self.previous = processes
+            .iter()
+            .map(|p| (p.name.clone(), p.clone()))
+            .collect();
+
+        if full_due {
+            self.since_full = Some(0);
+            return Some(TopProcessesFrame::Full {
+                processes: processes.to_vec(),
+            });
+        }
+        self.since_full = self.since_full.map(|since| since + 1);
+        if changed.is_empty() && removed.is_empty() {
+            return None;
+        }
+        Some(TopProcessesFrame::Delta { changed, removed })
+    }
+
+    /// Next frame is a full snapshot (after a reset or sleep/resume)
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, write_bytes: u64) -> ProcessIOStat {
+        ProcessIOStat {
+            name: name.to_string(),
+            write_bytes,
+            total_bytes: write_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_only_changes_are_sent_between_snapshots() {
+        let mut diff = TopProcessesDiff::new();
+        let list = vec![process("a.exe", 10), process("b.exe", 5)];
+        assert!(matches!(
+            diff.observe(&list, false),
+            Some(TopProcessesFrame::Full { .. })
+        ));
+
+        // Idle: nothing to send
+        assert!(diff.observe(&list, false).is_none());
+
+        let next = vec![process("a.exe", 20), process("c.exe", 1)];
+        match diff.observe(&next, false) {
+            Some(TopProcessesFrame::Delta { changed, removed }) => {
+                let names: Vec<&str> = changed.iter().map(|p| p.name.as_str()).collect();
+                assert_eq!(names, ["a.exe", "c.exe"]);
+                assert_eq!(removed, ["b.exe"]);
+            }
+            other => panic!("expected a delta, got {:?}", other),
+        }
+
+        // A new subscriber gets the whole list right away
+        assert!(matches!(
+            diff.observe(&next, true),
+            Some(TopProcessesFrame::Full { .. })
+        ));
+    }
+
+    #[test]
+    fn test_full_snapshot_is_periodic() {
+        let mut diff = TopProcessesDiff::new();
+        let list = vec![process("a.exe", 10)];
+        diff.observe(&list, false);
+        let fulls = (1..=FULL_SNAPSHOT_EVERY * 2)
+            .filter_map(|_| diff.observe(&list, false))
+            .filter(|frame| matches!(frame, TopProcessesFrame::Full { .. }))
+            .count();
+        assert_eq!(fulls, 2);
+    }
+}
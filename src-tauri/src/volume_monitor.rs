@@ -0,0 +1,296 @@
+use crate::models::{FreeSpaceSample, MountedVolume, VolumeStat};
+use crate::perf_counters::{LogicalDiskMetrics, PhysicalDiskMetrics};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use sysinfo::Disks;
+
+/// Latest speeds and session totals per mounted volume, keyed by volume name
+pub type VolumeStats = Arc<Mutex<HashMap<String, VolumeStat>>>;
+
+pub fn create_volume_stats() -> VolumeStats {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+/// Applies a LogicalDisk sample to the per-volume state.
+///
+/// Counters report rates, so session totals are integrated as
+/// `speed * elapsed_secs` since the previous sample. Volumes that
+/// are no longer reported (unmounted) are dropped.
+pub fn apply_sample(
+    volumes: &mut HashMap<String, VolumeStat>,
+    metrics: &[LogicalDiskMetrics],
+    elapsed_secs: f64,
+) {
+    volumes.retain(|name, _| metrics.iter().any(|m| &m.volume == name));
+
+    for metric in metrics {
+        let read_speed = metric.read_bytes_per_sec.max(0.0);
+        let write_speed = metric.write_bytes_per_sec.max(0.0);
+
+        let entry = volumes
+            .entry(metric.volume.clone())
+            .or_insert_with(|| VolumeStat {
+                volume: metric.volume.clone(),
+                read_speed: 0,
+                write_speed: 0,
+                session_read_bytes: 0,
+                session_write_bytes: 0,
+                disk: None,
+                disk_idle_time: None,
+                disk_queue_depth: None,
+            });
+
+        entry.read_speed = read_speed as u64;
+        entry.write_speed = write_speed as u64;
+        entry.session_read_bytes = entry
+            .session_read_bytes
+            .saturating_add((read_speed * elapsed_secs) as u64);
+        entry.session_write_bytes = entry
+            .session_write_bytes
+            .saturating_add((write_speed * elapsed_secs) as u64);
+    }
+}
+
+/// Copies each physical disk's idle time and queue depth to the volumes on
+/// it. Load is a property of the disk, so volumes sharing one report the same
+/// values; volumes that can't be mapped keep None.
+pub fn apply_disk_load(volumes: &mut HashMap<String, VolumeStat>, disks: &[PhysicalDiskMetrics]) {
+    for (name, stat) in volumes.iter_mut() {
+        let disk = disks.iter().find(|disk| {
+            disk.volumes()
+                .any(|volume| volume.eq_ignore_ascii_case(name))
+        });
+        stat.disk = disk.map(|d| d.instance.clone());
+        stat.disk_idle_time = disk.map(|d| d.idle_time);
+        stat.disk_queue_depth = disk.map(|d| d.queue_depth);
+    }
+}
+
+/// Returns the volumes sorted by name (C:, D:, ...)
+pub fn snapshot(volumes: &VolumeStats) -> Vec<VolumeStat> {
+    let mut stats: Vec<VolumeStat> = match volumes.lock() {
+        Ok(guard) => guard.values().cloned().collect(),
+        Err(_) => Vec::new(),
+    };
+    stats.sort_by(|a, b| a.volume.cmp(&b.volume));
+    stats
+}
+
+/// Maps a mount point to the volume name used by the perf counters
+/// ("C:\\" -> "C:"), leaving root mounts like "/" untouched.
+pub fn volume_name(mount_point: &str) -> String {
+    let trimmed = mount_point.trim_end_matches(['\\', '/']);
+    if trimmed.is_empty() {
+        mount_point.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Reads total and free space of every mounted volume
+pub fn sample_free_space(timestamp: f64) -> Vec<FreeSpaceSample> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut samples: Vec<FreeSpaceSample> = Vec::new();
+
+    for disk in disks.list() {
+        let volume = volume_name(&disk.mount_point().to_string_lossy());
+        // The same volume can be listed more than once (bind mounts)
+        if samples.iter().any(|s| s.volume == volume) {
+            continue;
+        }
+        samples.push(FreeSpaceSample {
+            timestamp,
+            volume,
+            total_bytes: disk.total_space(),
+            free_bytes: disk.available_space(),
+        });
+    }
+
+    samples
+}
+
+/// Lists currently mounted volumes (deduplicated by volume name)
+pub fn list_mounted_volumes() -> Vec<MountedVolume> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut volumes: Vec<MountedVolume> = Vec::new();
+
+    for disk in disks.list() {
+        let volume = volume_name(&disk.mount_point().to_string_lossy());
+        if volumes.iter().any(|v| v.volume == volume) {
+            continue;
+        }
+        volumes.push(MountedVolume {
+            volume,
+            label: disk.name().to_string_lossy().to_string(),
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            total_bytes: disk.total_space(),
+            removable: disk.is_removable(),
+        });
+    }
+
+    volumes
+}
+
+/// Volumes that appeared or disappeared since the previous poll
+#[derive(Debug, Default)]
+pub struct DriveChanges {
+    pub added: Vec<MountedVolume>,
+    pub removed: Vec<MountedVolume>,
+}
+
+/// Detects drive attach/removal by diffing successive volume lists
+#[derive(Default)]
+pub struct DriveWatcher {
+    known: Option<BTreeMap<String, MountedVolume>>,
+}
+
+impl DriveWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The first poll only records the baseline (drives present at startup
+    /// are not reported as added).
+    pub fn update(&mut self, current: Vec<MountedVolume>) -> DriveChanges {
+        let current: BTreeMap<String, MountedVolume> =
+            current.into_iter().map(|v| (v.volume.clone(), v)).collect();
+
+        let Some(known) = self.known.replace(current.clone()) else {
+            return DriveChanges::default();
+        };
+
+        DriveChanges {
+            added: current
+                .iter()
+                .filter(|(name, _)| !known.contains_key(*name))
+                .map(|(_, v)| v.clone())
+                .collect(),
+            removed: known
+                .into_iter()
+                .filter(|(name, _)| !current.contains_key(name))
+                .map(|(_, v)| v)
+                .collect(),
+        }
+    }
+}
+
+/// Starts/stops per-volume counters for attached/removed drives right away
+/// instead of waiting for the next LogicalDisk sample.
+pub fn apply_drive_changes(volumes: &VolumeStats, changes: &DriveChanges) {
+    let Ok(mut guard) = volumes.lock() else {
+        return;
+    };
+    for removed in &changes.removed {
+        guard.remove(&removed.volume);
+    }
+    for added in &changes.added {
+        guard
+            .entry(added.volume.clone())
+            .or_insert_with(|| VolumeStat {
+                volume: added.volume.clone(),
+                read_speed: 0,
+                write_speed: 0,
+                session_read_bytes: 0,
+                session_write_bytes: 0,
+                disk: None,
+                disk_idle_time: None,
+                disk_queue_depth: None,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(volume: &str, read: f64, write: f64) -> LogicalDiskMetrics {
+        LogicalDiskMetrics {
+            volume: volume.to_string(),
+            read_bytes_per_sec: read,
+            write_bytes_per_sec: write,
+        }
+    }
+
+    #[test]
+    fn test_apply_sample_integrates_totals() {
+        let mut volumes = HashMap::new();
+        apply_sample(&mut volumes, &[metric("C:", 100.0, 50.0)], 5.0);
+        apply_sample(&mut volumes, &[metric("C:", 200.0, 0.0)], 5.0);
+
+        let c = &volumes["C:"];
+        assert_eq!(c.read_speed, 200);
+        assert_eq!(c.write_speed, 0);
+        assert_eq!(c.session_read_bytes, 1500);
+        assert_eq!(c.session_write_bytes, 250);
+    }
+
+    #[test]
+    fn test_apply_sample_drops_unmounted_volumes() {
+        let mut volumes = HashMap::new();
+        apply_sample(
+            &mut volumes,
+            &[metric("C:", 1.0, 1.0), metric("E:", 1.0, 1.0)],
+            1.0,
+        );
+        apply_sample(&mut volumes, &[metric("C:", 1.0, 1.0)], 1.0);
+
+        assert!(volumes.contains_key("C:"));
+        assert!(!volumes.contains_key("E:"));
+    }
+
+    #[test]
+    fn test_apply_disk_load_maps_instances() {
+        let mut volumes = HashMap::new();
+        apply_sample(
+            &mut volumes,
+            &[metric("C:", 1.0, 1.0), metric("E:", 1.0, 1.0)],
+            1.0,
+        );
+        let disk = PhysicalDiskMetrics {
+            instance: "0 C: D:".to_string(),
+            read_bytes_per_sec: 0.0,
+            write_bytes_per_sec: 0.0,
+            idle_time: 40.0,
+            queue_depth: 2.5,
+        };
+        apply_disk_load(&mut volumes, &[disk]);
+
+        assert_eq!(volumes["C:"].disk.as_deref(), Some("0 C: D:"));
+        assert_eq!(volumes["C:"].disk_idle_time, Some(40.0));
+        assert_eq!(volumes["C:"].disk_queue_depth, Some(2.5));
+        assert_eq!(volumes["E:"].disk_idle_time, None);
+    }
+
+    #[test]
+    fn test_volume_name() {
+        assert_eq!(volume_name("C:\\"), "C:");
+        assert_eq!(volume_name("D:"), "D:");
+        assert_eq!(volume_name("/mnt/data/"), "/mnt/data");
+        assert_eq!(volume_name("/"), "/");
+    }
+
+    fn mounted(volume: &str) -> MountedVolume {
+        MountedVolume {
+            volume: volume.to_string(),
+            label: String::new(),
+            file_system: "NTFS".to_string(),
+            total_bytes: 1000,
+            removable: true,
+        }
+    }
+
+    #[test]
+    fn test_drive_watcher_reports_changes_after_baseline() {
+        let mut watcher = DriveWatcher::new();
+        let baseline = watcher.update(vec![mounted("C:")]);
+        assert!(baseline.added.is_empty() && baseline.removed.is_empty());
+
+        let changes = watcher.update(vec![mounted("C:"), mounted("E:")]);
+        assert_eq!(changes.added, vec![mounted("E:")]);
+        assert!(changes.removed.is_empty());
+
+        let changes = watcher.update(vec![mounted("C:")]);
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.removed, vec![mounted("E:")]);
+    }
+}
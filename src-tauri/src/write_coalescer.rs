@@ -0,0 +1,510 @@
+//! Central write coalescer: subsystems (disk stats, process deltas, free
+//! space and temperature samples) queue their writes, and every flush period
+//! writes them all in a single transaction, which cuts fsyncs and WAL growth.
+//!
+//! Timeline events are written directly, since callers need their ids.
+
+use crate::coverage;
+use crate::db;
+use crate::db_reader::{self, SharedContention};
+use crate::models::{DiskStat, FreeSpaceSample, PhysicalDiskStat, SpeedPeak, TemperatureSample};
+use crate::process_monitor::TickCommandLines;
+use crate::storage::{Storage, StorageBackend};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Rows kept per series while flushes keep failing or no storage backend is
+/// set (the oldest are dropped)
+pub const MAX_RETAINED_ROWS: usize = 50_000;
+
+/// Writes waiting for the next flush
+#[derive(Debug, Default)]
+pub struct PendingWrites {
+    pub disk_stats: Vec<DiskStat>,
+    pub physical_disk_stats: Vec<PhysicalDiskStat>,
+    /// Process name -> (read, write) delta (summed per process)
+    pub process_deltas: HashMap<String, (u64, u64)>,
+    pub free_space: Vec<FreeSpaceSample>,
+    pub temperatures: Vec<TemperatureSample>,
+    pub peaks: Vec<SpeedPeak>,
+    /// Day -> collected seconds (monitoring coverage)
+    pub coverage: HashMap<i64, f64>,
+    /// Process name -> latest command line (the newest wins)
+    pub command_lines: TickCommandLines,
+}
+
+impl PendingWrites {
+    pub fn is_empty(&self) -> bool {
+        self.disk_stats.is_empty()
+            && self.physical_disk_stats.is_empty()
+            && self.process_deltas.is_empty()
+            && self.free_space.is_empty()
+            && self.temperatures.is_empty()
+            && self.peaks.is_empty()
+            && self.coverage.is_empty()
+            && self.command_lines.is_empty()
+    }
+
+    /// Number of rows the next flush will write
+    pub fn len(&self) -> usize {
+        self.disk_stats.len()
+            + self.physical_disk_stats.len()
+            + self.process_deltas.len()
+            + self.free_space.len()
+            + self.temperatures.len()
+            + self.peaks.len()
+            + self.coverage.len()
+            + self.command_lines.len()
+    }
+
+    pub fn merge_process_deltas(&mut self, deltas: HashMap<String, (u64, u64)>) {
+        for (name, (read, write)) in deltas {
+            let entry = self.process_deltas.entry(name).or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(read);
+            entry.1 = entry.1.saturating_add(write);
+        }
+    }
+
+    /// Puts the rows of a failed flush back ahead of those queued since
+    fn restore(&mut self, failed: PendingWrites) {
+        let newer = std::mem::replace(self, failed);
+        self.disk_stats.extend(newer.disk_stats);
+        self.physical_disk_stats.extend(newer.physical_disk_stats);
+        self.merge_process_deltas(newer.process_deltas);
+        self.free_space.extend(newer.free_space);
+        self.temperatures.extend(newer.temperatures);
+        self.peaks.extend(newer.peaks);
+        for (day, seconds) in newer.coverage {
+            *self.coverage.entry(day).or_insert(0.0) += seconds;
+        }
+        self.command_lines.extend(newer.command_lines);
+
+        keep_newest(&mut self.disk_stats);
+        keep_newest(&mut self.physical_disk_stats);
+        keep_newest(&mut self.free_space);
+        keep_newest(&mut self.temperatures);
+        keep_newest(&mut self.peaks);
+    }
+}
+
+fn keep_newest<T>(rows: &mut Vec<T>) {
+    if rows.len() > MAX_RETAINED_ROWS {
+        rows.drain(..rows.len() - MAX_RETAINED_ROWS);
+    }
+}
+
+/// Re-queues rows that could not be written
+fn restore(coalescer: &WriteCoalescer, failed: PendingWrites) {
+    if failed.is_empty() {
+        return;
+    }
+    let mut pending = coalescer.lock().unwrap_or_else(|e| e.into_inner());
+    pending.restore(failed);
+}
+
+pub type WriteCoalescer = Arc<Mutex<PendingWrites>>;
+
+pub fn create_write_coalescer() -> WriteCoalescer {
+    Arc::new(Mutex::new(PendingWrites::default()))
+}
+
+pub fn queue_disk_stat(coalescer: &WriteCoalescer, stat: DiskStat) {
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.disk_stats.push(stat);
+        keep_newest(&mut pending.disk_stats);
+    }
+}
+
+pub fn queue_physical_disk_stats(coalescer: &WriteCoalescer, stats: Vec<PhysicalDiskStat>) {
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.physical_disk_stats.extend(stats);
+        keep_newest(&mut pending.physical_disk_stats);
+    }
+}
+
+pub fn queue_process_deltas(coalescer: &WriteCoalescer, deltas: HashMap<String, (u64, u64)>) {
+    if deltas.is_empty() {
+        return;
+    }
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.merge_process_deltas(deltas);
+    }
+}
+
+/// Queues the latest command line per process name (newer replace older)
+pub fn queue_command_lines(coalescer: &WriteCoalescer, command_lines: TickCommandLines) {
+    if command_lines.is_empty() {
+        return;
+    }
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.command_lines.extend(command_lines);
+    }
+}
+
+pub fn queue_free_space(coalescer: &WriteCoalescer, samples: Vec<FreeSpaceSample>) {
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.free_space.extend(samples);
+        keep_newest(&mut pending.free_space);
+    }
+}
+
+pub fn queue_temperatures(coalescer: &WriteCoalescer, samples: Vec<TemperatureSample>) {
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.temperatures.extend(samples);
+        keep_newest(&mut pending.temperatures);
+    }
+}
+
+pub fn queue_peaks(coalescer: &WriteCoalescer, peaks: Vec<SpeedPeak>) {
+    if let Ok(mut pending) = coalescer.lock() {
+        pending.peaks.extend(peaks);
+        keep_newest(&mut pending.peaks);
+    }
+}
+
+/// Counts `start..end` as collected time
+pub fn queue_coverage(coalescer: &WriteCoalescer, start: f64, end: f64) {
+    if let Ok(mut pending) = coalescer.lock() {
+        coverage::add_interval(&mut pending.coverage, start, end);
+    }
+}
+
+/// Number of rows of any kind waiting for the next flush
+pub fn pending_rows(coalescer: &WriteCoalescer) -> usize {
+    coalescer.lock().map(|p| p.len()).unwrap_or(0)
+}
+
+/// Number of disk_stats rows waiting (the monitor flushes early when it grows)
+pub fn pending_disk_stats(coalescer: &WriteCoalescer) -> usize {
+    coalescer.lock().map(|p| p.disk_stats.len()).unwrap_or(0)
+}
+
+/// Queued disk samples between `start` and `end` and the queued process
+/// bytes: what the database doesn't have yet
+pub fn pending_between(coalescer: &WriteCoalescer, start: f64, end: f64) -> PendingWrites {
+    let Ok(pending) = coalescer.lock() else {
+        return PendingWrites::default();
+    };
+    let in_range = |timestamp: f64| (start..=end).contains(&timestamp);
+    PendingWrites {
+        disk_stats: pending
+            .disk_stats
+            .iter()
+            .filter(|s| in_range(s.timestamp))
+            .cloned()
+            .collect(),
+        physical_disk_stats: pending
+            .physical_disk_stats
+            .iter()
+            .filter(|s| in_range(s.timestamp))
+            .cloned()
+            .collect(),
+        process_deltas: pending.process_deltas.clone(),
+        ..Default::default()
+    }
+}
+
+/// Drops everything pending (used on database reset)
+pub fn clear(coalescer: &WriteCoalescer) {
+    if let Ok(mut pending) = coalescer.lock() {
+        *pending = PendingWrites::default();
+    }
+}
+
+/// Writes all pending data in a single transaction
+///
+/// Disk stats go into the same transaction when the time series backend is
+/// SQLite; other backends receive them after the commit. Without a storage
+/// backend, disk stats stay queued until one is available. Rows that fail to
+/// be written are queued again for the next flush.
+///
+/// # Returns
+/// Number of rows written
+pub async fn flush(
+    coalescer: &WriteCoalescer,
+    pool: &Pool<Sqlite>,
+    store: Option<&Arc<dyn Storage>>,
+    contention: &SharedContention,
+) -> Result<usize, String> {
+    let pending = {
+        let mut guard = coalescer.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut taken = std::mem::take(&mut *guard);
+        if store.is_none() {
+            guard.disk_stats = std::mem::take(&mut taken.disk_stats);
+            guard.physical_disk_stats = std::mem::take(&mut taken.physical_disk_stats);
+        }
+        taken
+    };
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let sqlite_series = store.is_some_and(|s| s.backend() == StorageBackend::Sqlite);
+    let row_count = pending.len();
+
+    // Connection acquisition is where the writer waits on other pool users
+    let wait_started = std::time::Instant::now();
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            db_reader::record_error(contention, &e);
+            restore(coalescer, pending);
+            return Err(format!("Database error: {}", e));
+        }
+    };
+    let began = std::time::Instant::now();
+
+    let result = write_pending(&mut tx, &pending, sqlite_series).await;
+    let result = match result {
+        Ok(()) => tx.commit().await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        db_reader::record_error(contention, &e);
+        restore(coalescer, pending);
+        return Err(format!("Database error: {}", e));
+    }
+    db_reader::record_write(contention, wait_started, began);
+
+    if let (Some(store), false) = (store, sqlite_series) {
+        // The SQLite part is committed; only the time series is retried
+        if let Err(e) = store.insert_disk_stats(&pending.disk_stats).await {
+            restore(
+                coalescer,
+                PendingWrites {
+                    disk_stats: pending.disk_stats,
+                    physical_disk_stats: pending.physical_disk_stats,
+                    ..Default::default()
+                },
+            );
+            return Err(e.to_string());
+        }
+        if let Err(e) = store
+            .insert_physical_disk_stats(&pending.physical_disk_stats)
+            .await
+        {
+            restore(
+                coalescer,
+                PendingWrites {
+                    physical_disk_stats: pending.physical_disk_stats,
+                    ..Default::default()
+                },
+            );
+            return Err(e.to_string());
+        }
+    }
+
+    Ok(row_count)
+}
+
+async fn write_pending(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    pending: &PendingWrites,
+    sqlite_series: bool,
+) -> Result<(), sqlx::Error> {
+    if sqlite_series {
+        db::insert_stats_batch(&mut **tx, &pending.disk_stats).await?;
+        db::insert_physical_disk_stats_batch(&mut **tx, &pending.physical_disk_stats).await?;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    db::update_process_history(&mut **tx, now, &pending.process_deltas).await?;
+    db::update_process_command_lines(&mut **tx, &pending.command_lines).await?;
+    // Each flush interval also becomes a point of the per-process time series
+    db::insert_process_samples(&mut **tx, now, &pending.process_deltas).await?;
+    db::add_process_rollup(&mut **tx, now, &pending.process_deltas).await?;
+    db::insert_free_space_samples(&mut **tx, &pending.free_space).await?;
+    db::insert_temperature_samples(&mut **tx, &pending.temperatures).await?;
+    // Several records for one direction can be queued; the upsert keeps the highest
+    db::upsert_speed_peaks(&mut **tx, &pending.peaks).await?;
+    db::add_coverage(&mut **tx, &pending.coverage).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SqliteStorage;
+
+    async fn test_pool() -> Pool<Sqlite> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        db::create_schema(&pool).await.unwrap();
+        pool
+    }
+
+    fn stat(timestamp: f64) -> DiskStat {
+        DiskStat {
+            read_speed: 1,
+            write_speed: 2,
+            idle_time: 0.0,
+            monotonic_offset: 0.0,
+            ..DiskStat::idle(timestamp)
+        }
+    }
+
+    #[test]
+    fn test_process_deltas_are_merged() {
+        let coalescer = create_write_coalescer();
+        queue_process_deltas(&coalescer, HashMap::from([("a.exe".to_string(), (1, 2))]));
+        queue_process_deltas(&coalescer, HashMap::from([("a.exe".to_string(), (10, 20))]));
+
+        let pending = coalescer.lock().unwrap();
+        assert_eq!(pending.process_deltas["a.exe"], (11, 22));
+    }
+
+    #[tokio::test]
+    async fn test_flush_writes_everything_in_one_transaction() {
+        let pool = test_pool().await;
+        let contention = db_reader::create_contention();
+        let store: Arc<dyn Storage> = Arc::new(SqliteStorage::new(
+            Arc::new(Mutex::new(Some(pool.clone()))),
+            contention.clone(),
+        ));
+
+        let coalescer = create_write_coalescer();
+        queue_disk_stat(&coalescer, stat(1.0));
+        queue_disk_stat(&coalescer, stat(2.0));
+        queue_process_deltas(&coalescer, HashMap::from([("a.exe".to_string(), (5, 6))]));
+        queue_coverage(&coalescer, 0.0, 1.0);
+        queue_coverage(&coalescer, 1.0, 2.0);
+
+        let written = flush(&coalescer, &pool, Some(&store), &contention)
+            .await
+            .unwrap();
+        assert_eq!(written, 4);
+        assert!(coalescer.lock().unwrap().is_empty());
+
+        let (_, write_total) = db::get_alltime_totals(&pool).await.unwrap();
+        assert_eq!(write_total, 6);
+        let samples = db::get_process_samples(&pool, 0.0, f64::MAX).await.unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].write_bytes, 6);
+        assert_eq!(
+            db::get_disk_stats_history(&pool, 0.0, 10.0)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(db::get_coverage(&pool, 0, 0).await.unwrap(), vec![(0, 2.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_rows_queued() {
+        let pool = test_pool().await;
+        let contention = db_reader::create_contention();
+        let store: Arc<dyn Storage> = Arc::new(SqliteStorage::new(
+            Arc::new(Mutex::new(Some(pool.clone()))),
+            contention.clone(),
+        ));
+        sqlx::query("DROP TABLE process_history")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let coalescer = create_write_coalescer();
+        queue_disk_stat(&coalescer, stat(1.0));
+        queue_process_deltas(&coalescer, HashMap::from([("a.exe".to_string(), (5, 6))]));
+        assert!(flush(&coalescer, &pool, Some(&store), &contention)
+            .await
+            .is_err());
+
+        // Rows queued after the failure land behind the restored ones
+        queue_disk_stat(&coalescer, stat(2.0));
+        queue_process_deltas(&coalescer, HashMap::from([("a.exe".to_string(), (1, 1))]));
+        assert!(db::get_disk_stats_history(&pool, 0.0, 10.0)
+            .await
+            .unwrap()
+            .is_empty());
+        let pending = coalescer.lock().unwrap();
+        let timestamps: Vec<f64> = pending.disk_stats.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, [1.0, 2.0]);
+        assert_eq!(pending.process_deltas["a.exe"], (6, 7));
+    }
+
+    #[tokio::test]
+    async fn test_process_deltas_survive_a_failed_flush() {
+        use crate::process_monitor::{self, MockProcessSource, ProcessMonitor};
+
+        let pool = test_pool().await;
+        let contention = db_reader::create_contention();
+        let coalescer = create_write_coalescer();
+        let mut monitor = ProcessMonitor::with_source(
+            process_monitor::create_accumulators(),
+            Box::new(MockProcessSource::new(1, 3, 2, 100)),
+        );
+
+        let mut written = 0;
+        for _ in 0..5 {
+            written += monitor.update().1;
+            queue_process_deltas(&coalescer, monitor.take_exited_deltas());
+        }
+        queue_process_deltas(&coalescer, monitor.get_deltas_for_db());
+        sqlx::query("DROP TABLE process_history")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(flush(&coalescer, &pool, None, &contention).await.is_err());
+
+        db::create_schema(&pool).await.unwrap();
+        for _ in 0..5 {
+            written += monitor.update().1;
+            queue_process_deltas(&coalescer, monitor.take_exited_deltas());
+        }
+        queue_process_deltas(&coalescer, monitor.get_deltas_for_db());
+        flush(&coalescer, &pool, None, &contention).await.unwrap();
+
+        assert!(written > 0);
+        assert_eq!(db::get_alltime_totals(&pool).await.unwrap().1, written);
+    }
+
+    #[test]
+    fn test_restored_rows_are_capped() {
+        let mut pending = PendingWrites {
+            disk_stats: (0..MAX_RETAINED_ROWS).map(|i| stat(i as f64)).collect(),
+            ..Default::default()
+        };
+        pending.restore(PendingWrites {
+            disk_stats: vec![stat(-1.0)],
+            ..Default::default()
+        });
+
+        assert_eq!(pending.disk_stats.len(), MAX_RETAINED_ROWS);
+        assert_eq!(pending.disk_stats[0].timestamp, 0.0);
+    }
+
+    #[test]
+    fn test_queued_rows_are_capped() {
+        let coalescer = create_write_coalescer();
+        for i in 0..=MAX_RETAINED_ROWS {
+            queue_disk_stat(&coalescer, stat(i as f64));
+        }
+
+        let pending = coalescer.lock().unwrap();
+        assert_eq!(pending.disk_stats.len(), MAX_RETAINED_ROWS);
+        assert_eq!(pending.disk_stats[0].timestamp, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_flush_keeps_disk_stats_without_storage() {
+        let pool = test_pool().await;
+        let contention = db_reader::create_contention();
+
+        let coalescer = create_write_coalescer();
+        queue_disk_stat(&coalescer, stat(1.0));
+        queue_process_deltas(&coalescer, HashMap::from([("a.exe".to_string(), (1, 1))]));
+
+        assert_eq!(
+            flush(&coalescer, &pool, None, &contention).await.unwrap(),
+            1
+        );
+        assert_eq!(pending_disk_stats(&coalescer), 1);
+    }
+}
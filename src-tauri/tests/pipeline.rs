@@ -0,0 +1,333 @@
+//! End-to-end checks of the monitoring pipeline without a window: synthetic
+//! processes go through the monitor loop (sampler thread, write coalescer)
+//! into a real SQLite file, and are read back through the `core` API the
+//! commands use.
+
+use driveanalizer_lib::core::{
+    self, CoreError, DiskStat, LivePayload, LoopHandles, MonitorEmitter, MonitorEvent, MonitorLoop,
+};
+use driveanalizer_lib::db_cleanup;
+use driveanalizer_lib::db_reader;
+use driveanalizer_lib::payload::PayloadEncoder;
+use driveanalizer_lib::process_monitor::{
+    self, MockProcessSource, ProcessAccumulators, ProcessMonitor,
+};
+use driveanalizer_lib::session::{self, SessionTotals, SharedSessionTotals};
+use driveanalizer_lib::storage::{SqliteStorage, Storage};
+use driveanalizer_lib::subscriptions::MetricStream;
+use driveanalizer_lib::write_coalescer::{self, WriteCoalescer};
+use sqlx::{Pool, Sqlite};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+type SharedPool = Arc<Mutex<Option<Pool<Sqlite>>>>;
+
+/// A window showing the top-processes list: keeps the latest frame
+#[derive(Default)]
+struct TopProcessesView {
+    encoder: PayloadEncoder,
+    frame: Vec<u8>,
+}
+
+impl MonitorEmitter for TopProcessesView {
+    fn wants(&self, stream: MetricStream) -> bool {
+        stream == MetricStream::TopProcesses
+    }
+
+    fn publish(&mut self, payload: LivePayload<'_>) -> Result<(), String> {
+        if let LivePayload::TopProcesses(_) = payload {
+            self.frame = self.encoder.msgpack(&payload)?.to_vec();
+        }
+        Ok(())
+    }
+
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    fn emit(&mut self, _event: MonitorEvent<'_>) {}
+}
+
+/// Temp database plus the monitor loop the app runs, with the handles the
+/// commands read
+struct Harness {
+    dir: PathBuf,
+    shared_pool: SharedPool,
+    store: Arc<dyn Storage>,
+    writes: WriteCoalescer,
+    accumulators: ProcessAccumulators,
+    session_totals: SharedSessionTotals,
+    reset_signal: Arc<AtomicBool>,
+    monitor: MonitorLoop<TopProcessesView>,
+}
+
+impl Harness {
+    async fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("driveanalizer-it-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = core::open_database(&dir.join("test.db")).await.unwrap();
+        Self::with_pool(dir, pool)
+    }
+
+    fn with_pool(dir: PathBuf, pool: Pool<Sqlite>) -> Self {
+        let shared_pool: SharedPool = Arc::new(Mutex::new(Some(pool)));
+        let contention = db_reader::create_contention();
+        let store: Arc<dyn Storage> = Arc::new(SqliteStorage::new(
+            Arc::clone(&shared_pool),
+            Arc::clone(&contention),
+        ));
+        let handles = LoopHandles::new(
+            Arc::clone(&shared_pool),
+            Arc::new(Mutex::new(Some(Arc::clone(&store)))),
+            contention,
+        );
+        let writes = Arc::clone(&handles.writes);
+        let session_totals = Arc::clone(&handles.session_totals);
+        let reset_signal = Arc::clone(&handles.reset_signal);
+
+        let accumulators = process_monitor::create_accumulators();
+        // Two new processes per tick, each living ten ticks and doing 1000 B/tick
+        let source = MockProcessSource::new(2, 10, 8, 1000);
+        let monitor = ProcessMonitor::with_source(Arc::clone(&accumulators), Box::new(source));
+        Self {
+            dir,
+            shared_pool,
+            store,
+            writes,
+            accumulators,
+            session_totals,
+            reset_signal,
+            monitor: MonitorLoop::new(handles, monitor, TopProcessesView::default()).unwrap(),
+        }
+    }
+
+    fn db_path(&self) -> PathBuf {
+        self.dir.join("test.db")
+    }
+
+    fn pool(&self) -> Pool<Sqlite> {
+        core::pool(&self.shared_pool).unwrap()
+    }
+
+    fn session(&self) -> SessionTotals {
+        session::current(&self.session_totals)
+    }
+
+    /// One monitor tick; returns the decoded top-processes payload
+    async fn tick(&mut self) -> serde_json::Value {
+        self.monitor.tick().await;
+        rmp_serde::from_slice(&self.monitor.emitter().frame).unwrap()
+    }
+
+    async fn close(self) -> PathBuf {
+        drop(self.monitor);
+        let pool = self.shared_pool.lock().unwrap().take();
+        if let Some(pool) = pool {
+            pool.close().await;
+        }
+        self.dir
+    }
+}
+
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
+
+/// A recorded sample with nothing going on
+fn stat(timestamp: f64) -> DiskStat {
+    DiskStat {
+        timestamp,
+        read_bytes: 0,
+        write_bytes: 0,
+        read_speed: 0,
+        write_speed: 0,
+        idle_time: 100.0,
+        queue_depth: 0.0,
+        read_iops: 0.0,
+        write_iops: 0.0,
+        read_latency_ms: 0.0,
+        write_latency_ms: 0.0,
+        monotonic_offset: 0.0,
+    }
+}
+
+fn remove(dir: &Path) {
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_flush_persists_ticks_and_payloads() {
+    let mut harness = Harness::new("flush").await;
+
+    let mut payload = serde_json::Value::Null;
+    for _ in 0..5 {
+        payload = harness.tick().await;
+    }
+    let top = payload.as_array().unwrap();
+    assert!(!top.is_empty());
+    assert!(top[0]["name"].as_str().unwrap().starts_with("fake-"));
+    assert!(top[0]["write_bytes"].as_u64().unwrap() > 0);
+
+    assert!(harness.monitor.flush().await.unwrap() > 0);
+    assert_eq!(write_coalescer::pending_rows(&harness.writes), 0);
+
+    let pool = harness.pool();
+    let written = harness.session().write_bytes;
+    assert!(written > 0);
+    let stats = core::disk_stats_history(harness.store.as_ref(), 0.0, f64::MAX)
+        .await
+        .unwrap();
+    assert_eq!(stats.len(), 5);
+    assert_eq!(stats.last().unwrap().write_bytes, written);
+
+    // Everything written this session ends up in the process history
+    let totals = core::alltime_totals(&pool).await.unwrap();
+    assert_eq!(totals.write_bytes, written);
+    assert_eq!(
+        core::process_history_totals(&pool)
+            .await
+            .unwrap()
+            .write_bytes,
+        written
+    );
+
+    remove(&harness.close().await);
+}
+
+#[tokio::test]
+async fn test_reset_clears_database_and_baselines() {
+    let mut harness = Harness::new("reset").await;
+    for _ in 0..4 {
+        harness.tick().await;
+    }
+    harness.monitor.flush().await.unwrap();
+    harness.tick().await;
+    let now = unix_now();
+    sqlx::query("INSERT INTO timeline_events (kind, start_time, end_time) VALUES ('burst', ?, ?)")
+        .bind(now - 1.0)
+        .bind(now)
+        .execute(&harness.pool())
+        .await
+        .unwrap();
+
+    // What reset_database does; the next tick resets the loop's baselines
+    core::clear_recorded_data(&harness.pool()).await.unwrap();
+    harness.reset_signal.store(true, Ordering::Relaxed);
+    harness.tick().await;
+    assert!(!harness.reset_signal.load(Ordering::Relaxed));
+
+    let pool = harness.pool();
+    assert!(core::process_history(&pool).await.unwrap().is_empty());
+    assert_eq!(core::alltime_totals(&pool).await.unwrap().write_bytes, 0);
+    assert!(
+        core::disk_stats_history(harness.store.as_ref(), 0.0, f64::MAX)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    // The tick queued before the reset is dropped, only the one after it waits
+    assert_eq!(write_coalescer::pending_disk_stats(&harness.writes), 1);
+    // Bursts, gaps and the last session described the cleared data
+    assert!(core::timeline_events(&pool, 0.0, f64::MAX)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Session totals restart with the reset; the first tick after it only
+    // takes the process baselines
+    harness.tick().await;
+    let since_reset = harness.session().write_bytes;
+    assert!(since_reset > 0);
+    let by_name = core::session_totals_by_name(&harness.accumulators).unwrap();
+    assert_eq!(by_name.iter().map(|(_, _, w)| w).sum::<u64>(), since_reset);
+
+    remove(&harness.close().await);
+}
+
+#[tokio::test]
+async fn test_records_survive_missing_pool_and_restart() {
+    let mut harness = Harness::new("recovery").await;
+    harness.tick().await;
+
+    // The pool is taken out while the data directory is migrated
+    let pool = harness.shared_pool.lock().unwrap().take().unwrap();
+    for _ in 0..3 {
+        harness.tick().await;
+    }
+    assert!(matches!(
+        core::pool(&harness.shared_pool),
+        Err(CoreError::NotInitialized)
+    ));
+    assert_eq!(
+        harness.monitor.flush().await.unwrap_err(),
+        "Database not initialized"
+    );
+    assert_eq!(write_coalescer::pending_disk_stats(&harness.writes), 4);
+
+    // Once it is back, the buffered records are written in one flush
+    *harness.shared_pool.lock().unwrap() = Some(pool);
+    harness.monitor.flush().await.unwrap();
+    let written = harness.session().write_bytes;
+    let db_path = harness.db_path();
+    let dir = harness.close().await;
+
+    // A restart reopens the same file with everything in place
+    let pool = core::open_database(&db_path).await.unwrap();
+    let harness = Harness::with_pool(dir, pool);
+    let pool = harness.pool();
+    assert_eq!(
+        core::alltime_totals(&pool).await.unwrap().write_bytes,
+        written
+    );
+    assert_eq!(
+        core::disk_stats_history(harness.store.as_ref(), 0.0, f64::MAX)
+            .await
+            .unwrap()
+            .len(),
+        4
+    );
+
+    remove(&harness.close().await);
+}
+
+#[tokio::test]
+async fn test_cleanup_prunes_only_old_samples() {
+    let mut harness = Harness::new("cleanup").await;
+    let old = unix_now() - 10.0 * 86400.0;
+    write_coalescer::queue_disk_stat(&harness.writes, stat(old));
+    for _ in 0..3 {
+        harness.tick().await;
+    }
+    harness.monitor.flush().await.unwrap();
+
+    // The monitor's hourly prune keeps the stored retention period: thirty
+    // days by default, so the ten-day-old sample stays until it is shortened
+    let pool = harness.pool();
+    let store = harness.store.as_ref();
+    assert_eq!(
+        db_cleanup::prune_time_series(&pool, store).await.unwrap(),
+        0
+    );
+    db_cleanup::save_retention_days(&pool, 7).await.unwrap();
+    assert_eq!(db_cleanup::load_retention_policy(&pool).await.keep_days, 7);
+    assert_eq!(
+        db_cleanup::prune_time_series(&pool, store).await.unwrap(),
+        1
+    );
+    let remaining = core::disk_stats_history(harness.store.as_ref(), 0.0, f64::MAX)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 3);
+    assert!(remaining.iter().all(|stat| stat.timestamp > old));
+
+    // Process history is not subject to the time series retention
+    assert!(!core::process_history(&pool).await.unwrap().is_empty());
+
+    remove(&harness.close().await);
+}
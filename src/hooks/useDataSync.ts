@@ -1,23 +1,50 @@
 import { useEffect } from 'react';
 import { listen } from '@tauri-apps/api/event';
-import { invoke } from '@tauri-apps/api/core';
-import { useStore, AllTimeTotals, ProcessInfo } from '../store/useStore';
+import { Channel, invoke } from '@tauri-apps/api/core';
+import { useStore, AllTimeTotals, ProcessInfo, SessionTotals } from '../store/useStore';
+import { decodeMessagePack } from '../utils/msgpack';
+
+type TopProcessesFrame =
+    | { kind: 'full'; processes: ProcessInfo[] }
+    | { kind: 'delta'; changed: ProcessInfo[]; removed: string[] };
 
 export function useDataSync() {
     const { 
         setTopProcesses, 
         setAllTimeTotals, 
+        setSessionTotals,
         setProcessHistory
     } = useStore();
 
-    // Top processes event listener
+    // Top processes arrive as MessagePack frames on a channel owned by this view
     useEffect(() => {
-        const unlistenPromise = listen<ProcessInfo[]>('top-processes', (event) => {
-            setTopProcesses(event.payload);
-        });
+        // Full snapshots replace the list; deltas only carry changed entries
+        const current = new Map<string, ProcessInfo>();
+        const channel = new Channel<ArrayBuffer>();
+        channel.onmessage = (buffer) => {
+            let frame: TopProcessesFrame;
+            try {
+                frame = decodeMessagePack<TopProcessesFrame>(buffer);
+            } catch (error) {
+                console.warn('Undecodable top-processes frame', error);
+                return;
+            }
+
+            if (frame.kind === 'full') {
+                current.clear();
+                frame.processes.forEach((process) => current.set(process.name, process));
+                setTopProcesses(frame.processes);
+                return;
+            }
+            frame.removed.forEach((name) => current.delete(name));
+            frame.changed.forEach((process) => current.set(process.name, process));
+            setTopProcesses([...current.values()].sort((a, b) => b.total_bytes - a.total_bytes));
+        };
+
+        const subscription = invoke<number>('subscribe_metric_channel', { stream: 'top-processes-diff', channel });
 
         return () => {
-            unlistenPromise.then((unlisten) => unlisten());
+            subscription.then((id) => invoke('unsubscribe_metric_channel', { id }));
         };
     }, [setTopProcesses]);
 
@@ -48,7 +75,7 @@ export function useDataSync() {
         };
     }, [setAllTimeTotals, setProcessHistory]);
 
-    // Initial fetch of all-time totals and process history
+    // Initial fetch of all-time totals, session totals and process history
     useEffect(() => {
         const fetchData = async () => {
             try {
@@ -58,6 +85,14 @@ export function useDataSync() {
                 console.error('Failed to fetch initial totals:', error);
             }
 
+            // A reloaded view shows the session totals before the next disk-metrics frame
+            try {
+                const session = await invoke<SessionTotals>('get_current_session_totals');
+                setSessionTotals(session);
+            } catch (error) {
+                console.error('Failed to fetch session totals:', error);
+            }
+
             try {
                 const history = await invoke<Record<string, [number, number]>>('get_process_history');
                 const formattedHistory: Record<string, { read_bytes: number, write_bytes: number }> = {};
@@ -70,5 +105,5 @@ export function useDataSync() {
             }
         };
         fetchData();
-    }, [setAllTimeTotals, setProcessHistory]);
+    }, [setAllTimeTotals, setSessionTotals, setProcessHistory]);
 }
@@ -1,6 +1,7 @@
 import { useEffect, useRef } from 'react';
-import { listen } from '@tauri-apps/api/event';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { useStore, DiskStat } from '../store/useStore';
+import { decodeMessagePack } from '../utils/msgpack';
 
 const isValidDiskStat = (payload: unknown): payload is DiskStat => {
     if (!payload || typeof payload !== 'object') return false;
@@ -22,24 +23,36 @@ export function useDiskMetrics() {
     const THROTTLE_MS = 100;
 
     useEffect(() => {
-        const unlistenPromise = listen<DiskStat>('disk-metrics', (event) => {
+        // disk-metrics arrives as MessagePack frames on a channel owned by this view
+        const channel = new Channel<ArrayBuffer>();
+        channel.onmessage = (frame) => {
             const now = Date.now();
 
             if (now - lastUpdateRef.current < THROTTLE_MS) {
                 return;
             }
 
-            if (!isValidDiskStat(event.payload)) {
+            let payload: unknown;
+            try {
+                payload = decodeMessagePack(frame);
+            } catch (error) {
+                console.warn('Undecodable disk-metrics frame', error);
+                return;
+            }
+
+            if (!isValidDiskStat(payload)) {
                 console.warn('Invalid disk-metrics payload received');
                 return;
             }
 
             lastUpdateRef.current = now;
-            updateStats(event.payload);
-        });
+            updateStats(payload);
+        };
+
+        const subscription = invoke<number>('subscribe_disk_metrics', { channel });
 
         return () => {
-            unlistenPromise.then((unlisten) => unlisten());
+            subscription.then((id) => invoke('unsubscribe_metric_channel', { id }));
         };
     }, [updateStats]);
 }
@@ -1,4 +1,5 @@
-import { useState, useMemo } from 'react';
+import { useState, useMemo, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { useStore, ProcessInfo } from '../store/useStore';
 import { formatBytes } from '../utils/format';
 import './TopProcesses.css';
@@ -26,7 +27,61 @@ function stringToColor(str: string): string {
     return '#' + '00000'.substring(0, 6 - c.length) + c;
 }
 
-type SortKey = 'name' | 'read_bytes' | 'write_bytes' | 'total_bytes';
+// Executable icon from the backend, initials while loading or when there is none
+function ProcessIcon({ name, exePath }: { name: string; exePath: string | null }) {
+    const [icon, setIcon] = useState<string | null>(null);
+
+    useEffect(() => {
+        setIcon(null);
+        if (!exePath) return;
+        let cancelled = false;
+        invoke<string>('get_process_icon', { exePath })
+            .then(png => { if (!cancelled) setIcon(png); })
+            .catch(() => {});
+        return () => { cancelled = true; };
+    }, [exePath]);
+
+    if (icon) {
+        return <img className="process-icon" src={`data:image/png;base64,${icon}`} alt="" />;
+    }
+    return (
+        <div className="process-icon" style={{ backgroundColor: stringToColor(name) }}>
+            {getInitials(name)}
+        </div>
+    );
+}
+
+interface KillPlan {
+    token: string;
+    processes: { pid: number; name: string }[];
+}
+
+interface KillResult {
+    pid: number;
+    success: boolean;
+    error: string | null;
+}
+
+// Asks the backend what would be stopped, confirms with the user, then kills
+async function killProcess(name: string) {
+    try {
+        const plan = await invoke<KillPlan>('prepare_kill_process', { target: name });
+        const pids = plan.processes.map(p => p.pid).join(', ');
+        if (!window.confirm(`${name} sonlandırılsın mı? (PID: ${pids})`)) return;
+        const results = await invoke<KillResult[]>('kill_process', {
+            target: name,
+            confirmationToken: plan.token
+        });
+        const failed = results.filter(r => !r.success);
+        if (failed.length > 0) {
+            window.alert(failed.map(r => `PID ${r.pid}: ${r.error}`).join('\n'));
+        }
+    } catch (e) {
+        window.alert(String(e));
+    }
+}
+
+type SortKey = 'name' | 'read_bytes' | 'write_bytes' | 'total_bytes' | 'read_speed' | 'write_speed';
 type SortOrder = 'asc' | 'desc';
 
 function TopProcesses() {
@@ -56,7 +111,9 @@ function TopProcesses() {
                     exe_path: null,
                     read_bytes: stats.read_bytes,
                     write_bytes: stats.write_bytes,
-                    total_bytes: stats.read_bytes + stats.write_bytes
+                    total_bytes: stats.read_bytes + stats.write_bytes,
+                    read_speed: 0,
+                    write_speed: 0
                 });
             });
 
@@ -66,7 +123,12 @@ function TopProcesses() {
                     existing.read_bytes += p.read_bytes;
                     existing.write_bytes += p.write_bytes;
                     existing.total_bytes += p.total_bytes;
+                    existing.read_speed = p.read_speed;
+                    existing.write_speed = p.write_speed;
                     if (p.exe_path) existing.exe_path = p.exe_path;
+                    if (p.command_line) existing.command_line = p.command_line;
+                    if (p.user) existing.user = p.user;
+                    if (p.system) existing.system = p.system;
                 } else {
                     mergedMap.set(p.name, { ...p });
                 }
@@ -134,6 +196,13 @@ function TopProcesses() {
                                 <th onClick={() => handleSort('total_bytes')} className="sortable">
                                     Toplam {getSortIndicator('total_bytes')}
                                 </th>
+                                <th onClick={() => handleSort('read_speed')} className="sortable">
+                                    Okuma/s {getSortIndicator('read_speed')}
+                                </th>
+                                <th onClick={() => handleSort('write_speed')} className="sortable">
+                                    Yazma/s {getSortIndicator('write_speed')}
+                                </th>
+                                <th></th>
                             </tr>
                         </thead>
                         <tbody>
@@ -146,15 +215,25 @@ function TopProcesses() {
                                     </td>
                                     <td>
                                         <div className="process-name-cell">
-                                            <div className="process-icon" style={{ backgroundColor: stringToColor(process.name) }}>
-                                                {getInitials(process.name)}
-                                            </div>
+                                            <ProcessIcon name={process.name} exePath={process.exe_path} />
                                             <div className="process-info">
-                                                <span className="process-name" title={process.exe_path || process.name}>
-                                                    {process.name}
+                                                <span className="process-name" title={process.command_line || process.exe_path || process.name}>
+                                                    {process.display_name || process.name}
+                                                    {process.system && <span className="system-badge">Sistem</span>}
+                                                    {process.pinned && <span className="pinned-badge" title="Sabitlenmiş">📌</span>}
+                                                    {process.known_writer && <span className="system-badge" title={process.known_writer}>Bilinen yazıcı</span>}
                                                 </span>
                                                 {process.exe_path && (
-                                                    <span className="process-path">{process.exe_path}</span>
+                                                    <span
+                                                        className="process-path clickable"
+                                                        title="Dosya konumunu aç"
+                                                        onClick={() => invoke('open_process_location', { name: process.name }).catch(e => window.alert(String(e)))}
+                                                    >
+                                                        {process.exe_path}
+                                                    </span>
+                                                )}
+                                                {process.user && (
+                                                    <span className="process-path">{process.user}</span>
                                                 )}
                                             </div>
                                         </div>
@@ -162,6 +241,19 @@ function TopProcesses() {
                                     <td>{formatBytes(process.read_bytes)}</td>
                                     <td>{formatBytes(process.write_bytes)}</td>
                                     <td className="highlight">{formatBytes(process.total_bytes)}</td>
+                                    <td>{formatBytes(process.read_speed)}/s</td>
+                                    <td>{formatBytes(process.write_speed)}/s</td>
+                                    <td>
+                                        {process.name !== 'Others' && topProcesses.some(p => p.name === process.name) && (
+                                            <button
+                                                className="kill-button"
+                                                title="Sonlandır"
+                                                onClick={() => killProcess(process.name)}
+                                            >
+                                                ✖
+                                            </button>
+                                        )}
+                                    </td>
                                 </tr>
                             ))}
                         </tbody>
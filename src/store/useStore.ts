@@ -9,15 +9,25 @@ export interface DiskStat {
     write_bytes: number; // total bytes written
     idle_time: number;   // %
     queue_depth: number; // count
+    monotonic_offset?: number; // seconds since the monitoring session started
 }
 
 export interface ProcessInfo {
     pid: number;
     name: string;
+    display_name?: string | null; // e.g. "svchost (wuauserv)": shown, never used as a key
     exe_path: string | null;
+    command_line?: string | null;
+    user?: string | null;
+    system?: boolean;    // part of Windows (session 0 / service account)
     read_bytes: number;
     write_bytes: number;
     total_bytes: number;
+    read_speed: number;  // bytes/sec over the last few ticks
+    write_speed: number; // bytes/sec over the last few ticks
+    pinned?: boolean;    // pinned by the user: listed whatever its rank
+    known_writer?: string | null; // id from get_known_writers (Defender, indexer, ...)
+    io_priority?: 'background' | 'normal' | 'high' | null; // Windows I/O priority hint
 }
 
 export type DataDisplayMode = 'session' | 'alltime';
@@ -27,6 +37,12 @@ export interface AllTimeTotals {
     write_bytes: number;
 }
 
+export interface SessionTotals {
+    start_time: number;
+    read_bytes: number;
+    write_bytes: number;
+}
+
 export interface AppMetrics {
     total_disk_size: number;
     ram_usage: number;
@@ -52,6 +68,7 @@ interface AppState {
     setWindowSize: (width: number, height: number) => void;
     setDataDisplayMode: (mode: DataDisplayMode) => void;
     setAllTimeTotals: (totals: AllTimeTotals) => void;
+    setSessionTotals: (totals: SessionTotals) => void;
     setProcessHistory: (history: Record<string, { read_bytes: number, write_bytes: number }>) => void;
     setAppMetrics: (metrics: AppMetrics) => void;
     resetSessionData: () => void;
@@ -107,6 +124,13 @@ export const useStore = create<AppState>()(
             setWindowSize: (width: number, height: number) => set({ windowSize: { width, height } }),
             setDataDisplayMode: (mode: DataDisplayMode) => set({ dataDisplayMode: mode }),
             setAllTimeTotals: (totals: AllTimeTotals) => set({ allTimeTotals: totals }),
+            setSessionTotals: (totals: SessionTotals) => set((state) => ({
+                currentStats: {
+                    ...state.currentStats,
+                    read_bytes: totals.read_bytes,
+                    write_bytes: totals.write_bytes,
+                },
+            })),
             setProcessHistory: (history: Record<string, { read_bytes: number, write_bytes: number }>) => set({ processHistory: history }),
             setAppMetrics: (metrics: AppMetrics) => set({ appMetrics: metrics }),
             resetSessionData: () => set({
@@ -0,0 +1,98 @@
+// Minimal MessagePack decoder for the live frames returned by `get_live_frame`.
+// Covers what the backend produces: maps, arrays, strings, integers, floats,
+// booleans and nil.
+
+import { invoke } from '@tauri-apps/api/core';
+
+class Reader {
+    private offset = 0;
+    private readonly view: DataView;
+    private readonly bytes: Uint8Array;
+    private static readonly textDecoder = new TextDecoder();
+
+    constructor(buffer: ArrayBuffer | Uint8Array) {
+        this.bytes = buffer instanceof Uint8Array ? buffer : new Uint8Array(buffer);
+        this.view = new DataView(this.bytes.buffer, this.bytes.byteOffset, this.bytes.byteLength);
+    }
+
+    read(): unknown {
+        const type = this.u8();
+
+        if (type <= 0x7f) return type;
+        if (type >= 0xe0) return type - 0x100;
+        if ((type & 0xf0) === 0x80) return this.map(type & 0x0f);
+        if ((type & 0xf0) === 0x90) return this.array(type & 0x0f);
+        if ((type & 0xe0) === 0xa0) return this.str(type & 0x1f);
+
+        switch (type) {
+            case 0xc0: return null;
+            case 0xc2: return false;
+            case 0xc3: return true;
+            case 0xca: return this.take(4, (o) => this.view.getFloat32(o));
+            case 0xcb: return this.take(8, (o) => this.view.getFloat64(o));
+            case 0xcc: return this.u8();
+            case 0xcd: return this.take(2, (o) => this.view.getUint16(o));
+            case 0xce: return this.take(4, (o) => this.view.getUint32(o));
+            case 0xcf: return Number(this.take(8, (o) => this.view.getBigUint64(o)));
+            case 0xd0: return this.take(1, (o) => this.view.getInt8(o));
+            case 0xd1: return this.take(2, (o) => this.view.getInt16(o));
+            case 0xd2: return this.take(4, (o) => this.view.getInt32(o));
+            case 0xd3: return Number(this.take(8, (o) => this.view.getBigInt64(o)));
+            case 0xd9: return this.str(this.u8());
+            case 0xda: return this.str(this.take(2, (o) => this.view.getUint16(o)));
+            case 0xdb: return this.str(this.take(4, (o) => this.view.getUint32(o)));
+            case 0xdc: return this.array(this.take(2, (o) => this.view.getUint16(o)));
+            case 0xdd: return this.array(this.take(4, (o) => this.view.getUint32(o)));
+            case 0xde: return this.map(this.take(2, (o) => this.view.getUint16(o)));
+            case 0xdf: return this.map(this.take(4, (o) => this.view.getUint32(o)));
+            default:
+                throw new Error(`Unsupported MessagePack type 0x${type.toString(16)}`);
+        }
+    }
+
+    private u8(): number {
+        return this.take(1, (o) => this.view.getUint8(o));
+    }
+
+    private take<T>(size: number, get: (offset: number) => T): T {
+        if (this.offset + size > this.bytes.byteLength) {
+            throw new Error('Truncated MessagePack frame');
+        }
+        const value = get(this.offset);
+        this.offset += size;
+        return value;
+    }
+
+    private str(length: number): string {
+        const start = this.offset;
+        this.take(length, () => undefined);
+        return Reader.textDecoder.decode(this.bytes.subarray(start, start + length));
+    }
+
+    private array(length: number): unknown[] {
+        const items = new Array(length);
+        for (let i = 0; i < length; i++) items[i] = this.read();
+        return items;
+    }
+
+    private map(length: number): Record<string, unknown> {
+        const result: Record<string, unknown> = {};
+        for (let i = 0; i < length; i++) {
+            const key = String(this.read());
+            result[key] = this.read();
+        }
+        return result;
+    }
+}
+
+export function decodeMessagePack<T = unknown>(buffer: ArrayBuffer | Uint8Array): T {
+    return new Reader(buffer).read() as T;
+}
+
+export type MetricStream = 'disk-metrics' | 'top-processes' | 'disk-breakdown' | 'smoothed-speeds' | 'drive-activity' | 'top-files' | 'top-processes-diff';
+
+/** Fetches and decodes the latest binary frame of a live stream */
+export async function fetchLiveFrame<T>(stream: MetricStream): Promise<T> {
+    const buffer = await invoke<ArrayBuffer>('get_live_frame', { stream });
+    return decodeMessagePack<T>(buffer);
+}